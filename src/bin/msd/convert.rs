@@ -0,0 +1,262 @@
+//! The `convert` subcommand, which converts between MSD and JSON or TOML using
+//! [`msd::convert`].
+
+use crate::{files, usage_error, FAILURE, SUCCESS};
+use msd::de::Options;
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+usage: msd convert [--from <format>] [--to <format>] [--lenient] [<input>] [-o <output>]
+
+Converts between MSD and JSON or TOML. Each tag is represented by its name and its parameter
+lists, preserving the order of the tags and any duplicates. With no input, standard input is
+read; with no output, standard output is written.
+
+formats:
+    msd, json, toml
+
+options:
+    --from <format>        The format of the input, inferred from its extension by default,
+                           or MSD if there is none
+    --to <format>          The format of the output, inferred from its extension by default,
+                           or JSON when converting from MSD and MSD otherwise
+    --lenient              Skip content that is not within a tag when reading MSD
+    -o, --output <path>    Write the output to the given path";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Msd,
+    Json,
+    Toml,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "msd" => Ok(Format::Msd),
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            _ => Err(format!("unknown format `{}`", name)),
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Some(Format::Json),
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Some(Format::Toml),
+            _ if files::is_msd(path) => Some(Format::Msd),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Args {
+    from: Option<Format>,
+    to: Option<Format>,
+    lenient: bool,
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+impl Args {
+    /// Returns the formats to convert from and to.
+    fn formats(&self) -> (Format, Format) {
+        let from = self
+            .from
+            .or_else(|| self.input.as_deref().and_then(Format::from_path))
+            .unwrap_or(Format::Msd);
+        let to = self
+            .to
+            .or_else(|| self.output.as_deref().and_then(Format::from_path))
+            .unwrap_or(if from == Format::Msd {
+                Format::Json
+            } else {
+                Format::Msd
+            });
+        (from, to)
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("option `{}` requires a value", arg))
+        };
+        match arg.as_str() {
+            "--from" => parsed.from = Some(Format::from_name(value()?)?),
+            "--to" => parsed.to = Some(Format::from_name(value()?)?),
+            "--lenient" => parsed.lenient = true,
+            "-o" | "--output" => parsed.output = Some(value()?.into()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ if parsed.input.is_some() => return Err(format!("unexpected argument `{}`", arg)),
+            _ => parsed.input = Some(arg.into()),
+        }
+    }
+    Ok(parsed)
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+    let (from, to) = args.formats();
+    if from == to {
+        return usage_error("cannot convert a format to itself", USAGE);
+    }
+
+    match convert(&args, from, to) {
+        Ok(()) => SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            FAILURE
+        }
+    }
+}
+
+fn convert(args: &Args, from: Format, to: Format) -> Result<(), String> {
+    let input = match &args.input {
+        Some(path) => fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))?,
+        None => {
+            let mut input = Vec::new();
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|error| error.to_string())?;
+            input
+        }
+    };
+    let name = args
+        .input
+        .as_deref()
+        .map_or_else(|| "<stdin>".to_owned(), |path| path.display().to_string());
+
+    let options = if args.lenient {
+        Options::lenient()
+    } else {
+        Options::new()
+    };
+    // Conversions between JSON and TOML pass through MSD.
+    let msd = match from {
+        Format::Msd => Ok(input),
+        Format::Json => msd::convert::from_json(&text(input, &name)?),
+        Format::Toml => msd::convert::from_toml(&text(input, &name)?),
+    }
+    .map_err(|error| format!("{}: {}", name, error))?;
+    let output = match to {
+        Format::Msd => Ok(msd),
+        Format::Json => msd::convert::to_json(&msd, options).map(String::into_bytes),
+        Format::Toml => msd::convert::to_toml(&msd, options).map(String::into_bytes),
+    }
+    .map_err(|error| format!("{}: {}", name, error))?;
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, output).map_err(|error| format!("{}: {}", path.display(), error))
+        }
+        None => io::stdout()
+            .write_all(&output)
+            .map_err(|error| error.to_string()),
+    }
+}
+
+fn text(input: Vec<u8>, name: &str) -> Result<String, String> {
+    String::from_utf8(input).map_err(|_| format!("{}: input is not valid UTF-8", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Args, Format};
+    use crate::test_util::args;
+
+    #[test]
+    fn parse_defaults() {
+        let args = parse_args(&args(&[])).unwrap();
+
+        assert_eq!(args, Args::default());
+        assert_eq!(args.formats(), (Format::Msd, Format::Json));
+    }
+
+    #[test]
+    fn parse_options() {
+        assert_eq!(
+            parse_args(&args(&[
+                "--from",
+                "toml",
+                "--to",
+                "json",
+                "--lenient",
+                "in",
+                "-o",
+                "out"
+            ])),
+            Ok(Args {
+                from: Some(Format::Toml),
+                to: Some(Format::Json),
+                lenient: true,
+                input: Some("in".into()),
+                output: Some("out".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn infer_formats_from_extensions() {
+        assert_eq!(
+            parse_args(&args(&["song.json", "-o", "song.sm"]))
+                .unwrap()
+                .formats(),
+            (Format::Json, Format::Msd)
+        );
+        assert_eq!(
+            parse_args(&args(&["song.SSC", "--output", "song.toml"]))
+                .unwrap()
+                .formats(),
+            (Format::Msd, Format::Toml)
+        );
+    }
+
+    #[test]
+    fn infer_msd_output() {
+        assert_eq!(
+            parse_args(&args(&["song.toml"])).unwrap().formats(),
+            (Format::Toml, Format::Msd)
+        );
+    }
+
+    #[test]
+    fn parse_unknown_format() {
+        assert_eq!(
+            parse_args(&args(&["--to", "yaml"])),
+            Err("unknown format `yaml`".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_missing_value() {
+        assert_eq!(
+            parse_args(&args(&["-o"])),
+            Err("option `-o` requires a value".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_two_inputs() {
+        assert_eq!(
+            parse_args(&args(&["a.sm", "b.sm"])),
+            Err("unexpected argument `b.sm`".to_owned())
+        );
+    }
+}
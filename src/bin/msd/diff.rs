@@ -0,0 +1,299 @@
+//! The `diff` subcommand, which compares MSD files using [`msd::diff::diff()`].
+
+use crate::{usage_error, FAILURE, SUCCESS};
+use msd::{
+    de::{Options, Position},
+    diff::{Difference, Document, Line, ParameterDifference, Tag},
+};
+use std::{fmt::Write as _, fs};
+
+const USAGE: &str = "\
+usage: msd diff [--lenient] <old> <new>
+
+Compares two MSD files tag by tag, listing the tags that were added, removed, moved, or changed
+along with the parameters that changed within them. Differences in whitespace, comments, and the
+spelling of escape sequences are ignored. Exits with a nonzero status if there are any
+differences.
+
+options:
+    --lenient    Skip content that is not within a tag";
+
+/// The number of unchanged lines shown around each changed line of a parameter.
+const CONTEXT: usize = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    lenient: bool,
+    old: String,
+    new: String,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut lenient = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--lenient" => lenient = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => paths.push(arg.clone()),
+        }
+    }
+    match <[String; 2]>::try_from(paths) {
+        Ok([old, new]) => Ok(Args { lenient, old, new }),
+        Err(_) => Err("expected two paths".to_owned()),
+    }
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+    let options = if args.lenient {
+        Options::lenient()
+    } else {
+        Options::new()
+    };
+
+    let read = |path: &str| -> Result<Document, String> {
+        let input = fs::read(path).map_err(|error| format!("{}: {}", path, error))?;
+        Document::parse(&input, options.clone()).map_err(|error| format!("{}: {}", path, error))
+    };
+    let (old, new) = match (read(&args.old), read(&args.new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(message), _) | (_, Err(message)) => {
+            eprintln!("error: {}", message);
+            return FAILURE;
+        }
+    };
+
+    let differences = msd::diff::diff(&old, &new);
+    print!("{}", render(&differences, &args.old, &args.new));
+    if differences.is_empty() {
+        SUCCESS
+    } else {
+        FAILURE
+    }
+}
+
+fn location(path: &str, position: Position) -> String {
+    format!("{}:{}", path, position.line() + 1)
+}
+
+fn name(tag: &Tag) -> String {
+    format!("#{}", String::from_utf8_lossy(tag.name()))
+}
+
+/// Writes each line of `value`, prefixed with `marker`.
+fn write_lines(value: &[u8], marker: char, output: &mut String) {
+    for line in String::from_utf8_lossy(value).split('\n') {
+        let _ = writeln!(output, "  {} {}", marker, line);
+    }
+}
+
+/// Writes every parameter of `tag`, prefixed with `marker`.
+fn write_parameters(tag: &Tag, marker: char, output: &mut String) {
+    for parameter in tag.parameter_lists().iter().flatten() {
+        write_lines(parameter, marker, output);
+    }
+}
+
+fn write_parameter_difference(difference: &ParameterDifference, output: &mut String) {
+    if difference.list() == 0 {
+        let _ = writeln!(output, "  parameter {}:", difference.index());
+    } else {
+        let _ = writeln!(
+            output,
+            "  parameter {} of list {}:",
+            difference.index(),
+            difference.list()
+        );
+    }
+    let lines = difference.lines();
+    // Unchanged lines are only shown near changed lines.
+    let shown = |index: usize| {
+        lines[index.saturating_sub(CONTEXT)..(index + CONTEXT + 1).min(lines.len())]
+            .iter()
+            .any(|line| !matches!(line, Line::Unchanged(_)))
+    };
+    let mut skipped = false;
+    for (index, line) in lines.iter().enumerate() {
+        let (marker, line) = match line {
+            Line::Unchanged(line) => {
+                if !shown(index) {
+                    skipped = true;
+                    continue;
+                }
+                (' ', line)
+            }
+            Line::Removed(line) => ('-', line),
+            Line::Added(line) => ('+', line),
+        };
+        if skipped {
+            skipped = false;
+            output.push_str("    ...\n");
+        }
+        let _ = writeln!(output, "  {} {}", marker, String::from_utf8_lossy(line));
+    }
+    if skipped {
+        output.push_str("    ...\n");
+    }
+}
+
+fn render(differences: &[Difference], old_path: &str, new_path: &str) -> String {
+    let mut output = String::new();
+    for difference in differences {
+        match difference {
+            Difference::Added(tag) => {
+                let _ = writeln!(
+                    output,
+                    "added {} ({})",
+                    name(tag),
+                    location(new_path, tag.position())
+                );
+                write_parameters(tag, '+', &mut output);
+            }
+            Difference::Removed(tag) => {
+                let _ = writeln!(
+                    output,
+                    "removed {} ({})",
+                    name(tag),
+                    location(old_path, tag.position())
+                );
+                write_parameters(tag, '-', &mut output);
+            }
+            Difference::Moved { old, new } => {
+                let _ = writeln!(
+                    output,
+                    "moved {} ({} -> {})",
+                    name(new),
+                    location(old_path, old.position()),
+                    location(new_path, new.position())
+                );
+            }
+            Difference::Changed {
+                old,
+                new,
+                parameters,
+            } => {
+                let _ = writeln!(
+                    output,
+                    "changed {} ({} -> {})",
+                    name(new),
+                    location(old_path, old.position()),
+                    location(new_path, new.position())
+                );
+                for parameter in parameters {
+                    write_parameter_difference(parameter, &mut output);
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, render, Args};
+    use crate::test_util::args;
+    use msd::{de::Options, diff::Document};
+
+    fn diff(old: &[u8], new: &[u8]) -> String {
+        let old = Document::parse(old, Options::new()).unwrap();
+        let new = Document::parse(new, Options::new()).unwrap();
+        render(&msd::diff::diff(&old, &new), "a.sm", "b.sm")
+    }
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_args(&args(&["a.sm", "--lenient", "b.sm"])),
+            Ok(Args {
+                lenient: true,
+                old: "a.sm".to_owned(),
+                new: "b.sm".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_one_path() {
+        assert_eq!(
+            parse_args(&args(&["a.sm"])),
+            Err("expected two paths".to_owned())
+        );
+    }
+
+    #[test]
+    fn no_differences() {
+        assert_eq!(diff(b"#TITLE:Foo;\n", b"#TITLE:  Foo ;"), "");
+    }
+
+    #[test]
+    fn added_removed_and_moved() {
+        assert_eq!(
+            diff(
+                b"#TITLE:Foo;\n#SUBTITLE:Bar;\n#BPMS:0=120;\n",
+                b"#BPMS:0=120;\n#TITLE:Foo;\n#ARTIST:Baz;\n"
+            ),
+            "\
+removed #SUBTITLE (a.sm:2)
+  - Bar
+moved #TITLE (a.sm:1 -> b.sm:2)
+added #ARTIST (b.sm:3)
+  + Baz
+"
+        );
+    }
+
+    #[test]
+    fn changed_parameters() {
+        assert_eq!(
+            diff(b"#TITLE:Foo:Bar;\n", b"#TITLE:Baz:Bar:Qux;\n"),
+            "\
+changed #TITLE (a.sm:1 -> b.sm:1)
+  parameter 0:
+  - Foo
+  + Baz
+  parameter 2:
+  + Qux
+"
+        );
+    }
+
+    #[test]
+    fn changed_lines_with_context() {
+        assert_eq!(
+            diff(
+                b"#NOTES:\n1\n2\n3\n4\n5\n6\n7\n8\n9\n;\n",
+                b"#NOTES:\n1\n2\n3\n4\n5\n6\n7\n8\nX\n;\n"
+            ),
+            "\
+changed #NOTES (a.sm:1 -> b.sm:1)
+  parameter 0:
+    ...
+    7
+    8
+  - 9
+  + X
+"
+        );
+    }
+
+    #[test]
+    fn changed_parameter_in_later_list() {
+        assert_eq!(
+            diff(b"#BPMS:0:120;\n4:60;\n", b"#BPMS:0:120;\n4:90;\n"),
+            "\
+changed #BPMS (a.sm:1 -> b.sm:1)
+  parameter 1 of list 1:
+  - 60
+  + 90
+"
+        );
+    }
+}
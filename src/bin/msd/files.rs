@@ -0,0 +1,97 @@
+//! Finding the MSD files named on the command line.
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The extensions of files using the MSD format, which are searched for within directories.
+const EXTENSIONS: [&str; 4] = ["msd", "sm", "ssc", "dwi"];
+
+/// Returns whether `path` has the extension of an MSD file.
+pub(crate) fn is_msd(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |extension| {
+            EXTENSIONS
+                .iter()
+                .any(|msd| msd.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Expands `paths` into the files they name.
+///
+/// Files are returned as they are given, regardless of their extension. Directories are searched
+/// recursively for files with the extension of an MSD file, which are returned in sorted order.
+pub(crate) fn collect<P>(paths: &[P]) -> io::Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            search(path, &mut files)?;
+        } else {
+            files.push(path.to_owned());
+        }
+    }
+    Ok(files)
+}
+
+fn search(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            search(&entry, files)?;
+        } else if is_msd(&entry) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect, is_msd};
+    use std::{env, fs, path::Path, process};
+
+    #[test]
+    fn is_msd_extensions() {
+        assert!(is_msd(Path::new("song.sm")));
+        assert!(is_msd(Path::new("dir/song.SSC")));
+        assert!(is_msd(Path::new("song.dwi")));
+        assert!(is_msd(Path::new("song.msd")));
+    }
+
+    #[test]
+    fn is_not_msd() {
+        assert!(!is_msd(Path::new("song.ogg")));
+        assert!(!is_msd(Path::new("sm")));
+    }
+
+    #[test]
+    fn collect_directory() {
+        let root = env::temp_dir().join(format!("msd-files-{}", process::id()));
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("b/song.sm"), b"").unwrap();
+        fs::write(root.join("a.ssc"), b"").unwrap();
+        fs::write(root.join("song.ogg"), b"").unwrap();
+
+        let files = collect(&[&root, &root.join("song.ogg")]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            files.unwrap(),
+            vec![
+                root.join("a.ssc"),
+                root.join("b/song.sm"),
+                root.join("song.ogg")
+            ]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! The `fmt` subcommand, which formats MSD files using [`msd::format::format()`].
+
+use crate::{files, usage_error, FAILURE, SUCCESS};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+usage: msd fmt [--check] [<path>...]
+
+Formats the given MSD files in place. Directories are searched for .msd, .sm, .ssc, and .dwi
+files. With no paths, standard input is formatted to standard output.
+
+options:
+    --check    Do not write any files, but list the files that are not formatted and exit with
+               a nonzero status if there are any";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Args {
+    check: bool,
+    paths: Vec<PathBuf>,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    for arg in args {
+        match arg.as_str() {
+            "--check" => parsed.check = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => parsed.paths.push(arg.into()),
+        }
+    }
+    Ok(parsed)
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+
+    if args.paths.is_empty() {
+        return run_stdin(args.check);
+    }
+
+    let files = match files::collect(&args.paths) {
+        Ok(files) => files,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    };
+    let mut code = SUCCESS;
+    for file in files {
+        match format_file(&file, args.check) {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("{}", file.display());
+                code = FAILURE;
+            }
+            Err(message) => {
+                eprintln!("error: {}: {}", file.display(), message);
+                code = FAILURE;
+            }
+        }
+    }
+    code
+}
+
+/// Formats the file at `path`, returning whether it was already formatted.
+///
+/// When checking, the file is left as it is.
+fn format_file(path: &Path, check: bool) -> Result<bool, String> {
+    let input = fs::read(path).map_err(|error| error.to_string())?;
+    let formatted = msd::format::format(&input).map_err(|error| error.to_string())?;
+    if formatted == input {
+        Ok(true)
+    } else if check {
+        Ok(false)
+    } else {
+        fs::write(path, formatted).map_err(|error| error.to_string())?;
+        Ok(true)
+    }
+}
+
+fn run_stdin(check: bool) -> i32 {
+    let mut input = Vec::new();
+    if let Err(error) = io::stdin().read_to_end(&mut input) {
+        eprintln!("error: {}", error);
+        return FAILURE;
+    }
+    let formatted = match msd::format::format(&input) {
+        Ok(formatted) => formatted,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    };
+    if check {
+        return if formatted == input { SUCCESS } else { FAILURE };
+    }
+    match io::stdout().write_all(&formatted) {
+        Ok(()) => SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Args};
+    use crate::test_util::args;
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_args(&args(&["a.sm", "songs"])),
+            Ok(Args {
+                check: false,
+                paths: vec!["a.sm".into(), "songs".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_check() {
+        assert_eq!(
+            parse_args(&args(&["songs", "--check"])),
+            Ok(Args {
+                check: true,
+                paths: vec!["songs".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_unknown_option() {
+        assert_eq!(
+            parse_args(&args(&["--chekc"])),
+            Err("unknown option `--chekc`".to_owned())
+        );
+    }
+}
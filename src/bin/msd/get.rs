@@ -0,0 +1,448 @@
+//! The `get` subcommand, which prints selected tags using [`msd::query::select()`].
+
+use crate::{files, usage_error, FAILURE, SUCCESS};
+use msd::{
+    de::Options,
+    query::{Selection, Selector},
+};
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+const USAGE: &str = "\
+usage: msd get [--format <format>] [--lenient] <selectors> [<path>...]
+
+Prints the tags selected from the given MSD files. Directories are searched for .msd, .sm, .ssc,
+and .dwi files. With no paths, standard input is read.
+
+Selectors are separated by commas. Each is a tag name, optionally followed by `:` and the
+zero-indexed position of a single parameter to print. Names are matched ignoring case, and may
+use `*` to match any sequence of characters and `?` to match any single character. For example,
+`TITLE,BPMS` selects the title and BPMs, and `NOTES:2` selects the difficulty of each chart.
+
+formats:
+    text    Print each tag's parameters separated by `:`, prefixed with the tag's name and, when
+            reading multiple files, the file's path (the default)
+    tsv     Print the path, name, and each parameter separated by tabs, escaping tabs,
+            newlines, and backslashes
+    json    Print an array of objects with the path, name, and parameters
+
+Text and TSV output trim whitespace surrounding each parameter.
+
+options:
+    -f, --format <format>    The output format
+    --lenient                Skip content that is not within a tag";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Text,
+    Tsv,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    format: Format,
+    lenient: bool,
+    selectors: Vec<Selector>,
+    paths: Vec<PathBuf>,
+}
+
+fn parse_selector(selector: &str) -> Result<Selector, String> {
+    let (name, parameter) = match selector.rsplit_once(':') {
+        Some((name, index)) => (
+            name,
+            Some(
+                index
+                    .parse()
+                    .map_err(|_| format!("invalid parameter index `{}`", index))?,
+            ),
+        ),
+        None => (selector, None),
+    };
+    if name.is_empty() {
+        return Err(format!("invalid selector `{}`", selector));
+    }
+    let selector = Selector::new(name);
+    Ok(match parameter {
+        Some(index) => selector.parameter(index),
+        None => selector,
+    })
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut format = Format::Text;
+    let mut lenient = false;
+    let mut selectors = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("text") => Format::Text,
+                    Some("tsv") => Format::Tsv,
+                    Some("json") => Format::Json,
+                    Some(name) => return Err(format!("unknown format `{}`", name)),
+                    None => return Err(format!("option `{}` requires a value", arg)),
+                }
+            }
+            "--lenient" => lenient = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ if selectors.is_none() => {
+                selectors = Some(
+                    arg.split(',')
+                        .map(parse_selector)
+                        .collect::<Result<_, _>>()?,
+                );
+            }
+            _ => paths.push(arg.into()),
+        }
+    }
+    Ok(Args {
+        format,
+        lenient,
+        selectors: selectors.ok_or("no selectors given")?,
+        paths,
+    })
+}
+
+/// Writes selections in a given format.
+struct Output<W> {
+    writer: W,
+    format: Format,
+    /// Whether the path should be written in text output.
+    show_path: bool,
+    /// Whether any selections have been written, which JSON output needs to separate them.
+    written: bool,
+}
+
+impl<W> Output<W>
+where
+    W: Write,
+{
+    fn start(&mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            self.writer.write_all(b"[")?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, path: &str, selection: &Selection) -> io::Result<()> {
+        let name = String::from_utf8_lossy(selection.name());
+        let parameters = selection
+            .parameters()
+            .iter()
+            .map(|parameter| String::from_utf8_lossy(parameter));
+        let mut line = String::new();
+        match self.format {
+            Format::Text => {
+                if self.show_path {
+                    let _ = write!(line, "{}: ", path);
+                }
+                let _ = write!(line, "{}:", name);
+                for (index, parameter) in parameters.enumerate() {
+                    line.push_str(if index == 0 { " " } else { ":" });
+                    line.push_str(parameter.trim());
+                }
+                line.push('\n');
+            }
+            Format::Tsv => {
+                tsv_field(path, &mut line);
+                line.push('\t');
+                tsv_field(&name, &mut line);
+                for parameter in parameters {
+                    line.push('\t');
+                    tsv_field(parameter.trim(), &mut line);
+                }
+                line.push('\n');
+            }
+            Format::Json => {
+                line.push_str(if self.written { ",\n  " } else { "\n  " });
+                line.push_str("{\"path\": ");
+                json_string(path, &mut line);
+                line.push_str(", \"name\": ");
+                json_string(&name, &mut line);
+                line.push_str(", \"parameters\": [");
+                for (index, parameter) in parameters.enumerate() {
+                    if index > 0 {
+                        line.push_str(", ");
+                    }
+                    json_string(&parameter, &mut line);
+                }
+                line.push_str("]}");
+            }
+        }
+        self.written = true;
+        self.writer.write_all(line.as_bytes())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            self.writer
+                .write_all(if self.written { b"\n]\n" } else { b"]\n" })?;
+        }
+        self.writer.flush()
+    }
+}
+
+fn tsv_field(field: &str, output: &mut String) {
+    for character in field.chars() {
+        match character {
+            '\\' => output.push_str("\\\\"),
+            '\t' => output.push_str("\\t"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            _ => output.push(character),
+        }
+    }
+}
+
+fn json_string(string: &str, output: &mut String) {
+    output.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\0'..='\u{1f}' => {
+                let _ = write!(output, "\\u{:04x}", character as u32);
+            }
+            _ => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+    let options = if args.lenient {
+        Options::lenient()
+    } else {
+        Options::new()
+    };
+
+    let files = match files::collect(&args.paths) {
+        Ok(files) => files,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    };
+    let stdout = io::stdout();
+    let mut output = Output {
+        writer: BufWriter::new(stdout.lock()),
+        format: args.format,
+        show_path: files.len() > 1,
+        written: false,
+    };
+    let mut code = SUCCESS;
+    let result = (|| {
+        output.start()?;
+        if args.paths.is_empty() {
+            let stdin = io::stdin();
+            let reader = BufReader::new(stdin.lock());
+            if !get(reader, "-", &args.selectors, &options, &mut output)? {
+                code = FAILURE;
+            }
+        }
+        for file in &files {
+            let path = file.display().to_string();
+            match File::open(file) {
+                Ok(reader) => {
+                    if !get(
+                        BufReader::new(reader),
+                        &path,
+                        &args.selectors,
+                        &options,
+                        &mut output,
+                    )? {
+                        code = FAILURE;
+                    }
+                }
+                Err(error) => {
+                    eprintln!("error: {}: {}", path, error);
+                    code = FAILURE;
+                }
+            }
+        }
+        output.finish()
+    })();
+    match result {
+        Ok(()) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            FAILURE
+        }
+    }
+}
+
+/// Writes the selections from a single input, returning whether it was read without error.
+///
+/// Errors in the input are reported, while errors writing the output are returned.
+fn get<R, W>(
+    reader: R,
+    path: &str,
+    selectors: &[Selector],
+    options: &Options,
+    output: &mut Output<W>,
+) -> io::Result<bool>
+where
+    R: Read,
+    W: Write,
+{
+    for selection in msd::query::select(reader, selectors, options.clone()) {
+        match selection {
+            Ok(selection) => output.write(path, &selection)?,
+            Err(error) => {
+                eprintln!("error: {}: {}", path, error);
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, parse_args, Args, Format, Output};
+    use crate::test_util::args;
+    use msd::{de::Options, query::Selector};
+
+    fn output(format: Format, show_path: bool, input: &[u8], selectors: &[Selector]) -> String {
+        let mut output = Output {
+            writer: Vec::new(),
+            format,
+            show_path,
+            written: false,
+        };
+        output.start().unwrap();
+        assert!(get(input, "a.sm", selectors, &Options::new(), &mut output).unwrap());
+        output.finish().unwrap();
+        String::from_utf8(output.writer).unwrap()
+    }
+
+    const INPUT: &[u8] = b"#TITLE:Foo;\n#BPMS:0.000=120.000,\n4.000=60.000;\n#NOTES:dance-single:\n     desc:Easy:3;\n";
+
+    #[test]
+    fn parse_selectors_and_paths() {
+        assert_eq!(
+            parse_args(&args(&["TITLE,notes:2", "a.sm", "songs"])),
+            Ok(Args {
+                format: Format::Text,
+                lenient: false,
+                selectors: vec![Selector::new("TITLE"), Selector::new("notes").parameter(2)],
+                paths: vec!["a.sm".into(), "songs".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(
+            parse_args(&args(&["--format", "tsv", "--lenient", "TITLE"])),
+            Ok(Args {
+                format: Format::Tsv,
+                lenient: true,
+                selectors: vec![Selector::new("TITLE")],
+                paths: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_no_selectors() {
+        assert_eq!(
+            parse_args(&args(&["-f", "json"])),
+            Err("no selectors given".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_invalid_index() {
+        assert_eq!(
+            parse_args(&args(&["NOTES:x"])),
+            Err("invalid parameter index `x`".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_unknown_format() {
+        assert_eq!(
+            parse_args(&args(&["-f", "csv", "TITLE"])),
+            Err("unknown format `csv`".to_owned())
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            output(
+                Format::Text,
+                false,
+                INPUT,
+                &[Selector::new("TITLE"), Selector::new("BPMS")]
+            ),
+            "TITLE: Foo\nBPMS: 0.000=120.000,\n4.000=60.000\n"
+        );
+    }
+
+    #[test]
+    fn text_with_path() {
+        assert_eq!(
+            output(
+                Format::Text,
+                true,
+                INPUT,
+                &[Selector::new("NOTES").parameter(1)]
+            ),
+            "a.sm: NOTES: desc\n"
+        );
+    }
+
+    #[test]
+    fn tsv() {
+        assert_eq!(
+            output(
+                Format::Tsv,
+                false,
+                INPUT,
+                &[Selector::new("b*"), Selector::new("NOTES")]
+            ),
+            "a.sm\tBPMS\t0.000=120.000,\\n4.000=60.000\na.sm\tNOTES\tdance-single\tdesc\tEasy\t3\n"
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            output(
+                Format::Json,
+                false,
+                INPUT,
+                &[Selector::new("TITLE"), Selector::new("NOTES").parameter(1)]
+            ),
+            "[\n  {\"path\": \"a.sm\", \"name\": \"TITLE\", \"parameters\": [\"Foo\"]},\n  {\"path\": \"a.sm\", \"name\": \"NOTES\", \"parameters\": [\"\\n     desc\"]}\n]\n"
+        );
+    }
+
+    #[test]
+    fn json_empty() {
+        assert_eq!(
+            output(Format::Json, false, INPUT, &[Selector::new("ARTIST")]),
+            "[]\n"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! The `lint` subcommand, which checks MSD files using [`msd::lint::lint()`].
+
+use crate::{files, usage_error, FAILURE, SUCCESS};
+use msd::lint::{Config, Lint, Rule, Severity};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+const USAGE: &str = "\
+usage: msd lint [<options>] [<path>...]
+
+Checks the given MSD files for likely mistakes, printing each problem found. Directories are
+searched for .msd, .sm, .ssc, and .dwi files. With no paths, standard input is read. Exits with a
+nonzero status if any errors are found.
+
+Rules and tag names are separated by commas, and tag names are matched ignoring case.
+
+rules:
+    empty-required-tag    A required tag is missing or empty (error)
+    duplicate-tag         A tag is repeated within a section (warning)
+    unescaped-comment     An unescaped `//` directly follows a value (error)
+    stray-whitespace      A parameter begins or ends with spaces or tabs (warning)
+    mixed-line-endings    LF and CRLF line endings are mixed (warning)
+    round-trip            A tag would change when written by the serializer (error)
+
+options:
+    --enable <rules>              Enable the given rules
+    --disable <rules>             Disable the given rules
+    --require <tags>              Require the given tags to be present and not empty
+    --allow-duplicates <tags>     Allow the given tags to be repeated, in addition to NOTES
+    --section <tags>              Begin a new section at the given tags, in addition to NOTEDATA";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    config: Config,
+    paths: Vec<PathBuf>,
+}
+
+fn parse_rules(rules: &str) -> Result<Vec<Rule>, String> {
+    rules
+        .split(',')
+        .map(|id| Rule::from_id(id).ok_or_else(|| format!("unknown rule `{}`", id)))
+        .collect()
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut config = Config::new();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let option = arg.as_str();
+        if !option.starts_with('-') {
+            paths.push(arg.into());
+            continue;
+        }
+        let value = match option {
+            "--enable" | "--disable" | "--require" | "--allow-duplicates" | "--section" => args
+                .next()
+                .ok_or_else(|| format!("option `{}` requires a value", option))?,
+            _ => return Err(format!("unknown option `{}`", option)),
+        };
+        config = match option {
+            "--enable" => parse_rules(value)?.into_iter().fold(config, Config::enable),
+            "--disable" => parse_rules(value)?
+                .into_iter()
+                .fold(config, Config::disable),
+            "--require" => value.split(',').fold(config, Config::require),
+            "--allow-duplicates" => value.split(',').fold(config, Config::allow_duplicates),
+            _ => value.split(',').fold(config, Config::section),
+        };
+    }
+    Ok(Args { config, paths })
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+
+    let mut inputs = Vec::new();
+    if args.paths.is_empty() {
+        let mut input = Vec::new();
+        if let Err(error) = io::stdin().read_to_end(&mut input) {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+        inputs.push(("<stdin>".to_owned(), input));
+    } else {
+        let files = match files::collect(&args.paths) {
+            Ok(files) => files,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return FAILURE;
+            }
+        };
+        for file in files {
+            match fs::read(&file) {
+                Ok(input) => inputs.push((file.display().to_string(), input)),
+                Err(error) => {
+                    eprintln!("error: {}: {}", file.display(), error);
+                    return FAILURE;
+                }
+            }
+        }
+    }
+
+    let mut code = SUCCESS;
+    for (path, input) in inputs {
+        let lints = msd::lint::lint(&input, &args.config);
+        if lints.iter().any(|lint| lint.severity() == Severity::Error) {
+            code = FAILURE;
+        }
+        print!("{}", report(&lints, &path, &input));
+    }
+    code
+}
+
+/// Describes each lint found in `input`, read from `path`, with one-indexed lines and columns.
+fn report(lints: &[Lint], path: &str, input: &[u8]) -> String {
+    let mut report = String::new();
+    for lint in lints {
+        let _ = writeln!(
+            report,
+            "{}:{}:{}: {}[{}]: {}",
+            path,
+            lint.position().line() + 1,
+            lint.position().char_column(input) + 1,
+            lint.severity(),
+            lint.rule(),
+            lint.message()
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, report, Args};
+    use crate::test_util::args;
+    use msd::lint::{Config, Rule};
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_args(&args(&["a.sm", "songs"])),
+            Ok(Args {
+                config: Config::new(),
+                paths: vec!["a.sm".into(), "songs".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_config() {
+        assert_eq!(
+            parse_args(&args(&[
+                "--disable",
+                "duplicate-tag,round-trip",
+                "--enable",
+                "round-trip",
+                "--require",
+                "TITLE,ARTIST",
+                "--allow-duplicates",
+                "BGCHANGES",
+                "--section",
+                "CHART",
+                "a.sm",
+            ])),
+            Ok(Args {
+                config: Config::new()
+                    .disable(Rule::DuplicateTag)
+                    .require("TITLE")
+                    .require("ARTIST")
+                    .allow_duplicates("BGCHANGES")
+                    .section("CHART"),
+                paths: vec!["a.sm".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_unknown_rule() {
+        assert_eq!(
+            parse_args(&args(&["--disable", "duplicate-tags"])),
+            Err("unknown rule `duplicate-tags`".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_missing_value() {
+        assert_eq!(
+            parse_args(&args(&["--require"])),
+            Err("option `--require` requires a value".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_unknown_option() {
+        assert_eq!(
+            parse_args(&args(&["--fix"])),
+            Err("unknown option `--fix`".to_owned())
+        );
+    }
+
+    #[test]
+    fn report_lints() {
+        let input = "#TITLE:Foo;\n#BANNER:éhttp://example.com;\n#TITLE:Bar;\n".as_bytes();
+        let lints = msd::lint::lint(input, &Config::new());
+
+        assert_eq!(
+            report(&lints, "a.sm", input),
+            "a.sm:2:15: error[unescaped-comment]: unescaped `//` in `#BANNER` begins a comment, discarding the rest of the line\n\
+             a.sm:3:1: warning[duplicate-tag]: duplicate tag `#TITLE`\n"
+        );
+    }
+}
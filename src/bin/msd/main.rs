@@ -0,0 +1,64 @@
+//! The `msd` command-line tool.
+//!
+//! Each subcommand is implemented in its own module, exposing a `run()` function that is given
+//! the subcommand's arguments and returns the process's exit code.
+
+mod convert;
+mod diff;
+mod files;
+mod fmt;
+mod get;
+mod lint;
+mod merge;
+#[cfg(test)]
+mod test_util;
+
+use std::{env, process};
+
+const USAGE: &str = "\
+usage: msd <command> [<args>]
+
+commands:
+    convert    Convert between MSD and JSON or TOML
+    diff       Compare MSD files tag by tag
+    fmt        Format MSD files
+    get        Print selected tags from MSD files
+    lint       Check MSD files for likely mistakes
+    merge      Merge MSD files tag by tag
+    help       Print this message";
+
+/// The exit code for a successful run.
+const SUCCESS: i32 = 0;
+/// The exit code for a run that completed, but found a problem with its input.
+const FAILURE: i32 = 1;
+/// The exit code for invalid arguments.
+const USAGE_ERROR: i32 = 2;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let code = match args.first().map(String::as_str) {
+        Some("convert") => convert::run(&args[1..]),
+        Some("diff") => diff::run(&args[1..]),
+        Some("fmt") => fmt::run(&args[1..]),
+        Some("get") => get::run(&args[1..]),
+        Some("lint") => lint::run(&args[1..]),
+        Some("merge") => merge::run(&args[1..]),
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            SUCCESS
+        }
+        Some("-V" | "--version") => {
+            println!("msd {}", env!("CARGO_PKG_VERSION"));
+            SUCCESS
+        }
+        Some(command) => usage_error(&format!("unknown command `{}`", command), USAGE),
+        None => usage_error("no command given", USAGE),
+    };
+    process::exit(code);
+}
+
+/// Reports invalid arguments, returning the exit code for doing so.
+fn usage_error(message: &str, usage: &str) -> i32 {
+    eprintln!("error: {}\n\n{}", message, usage);
+    USAGE_ERROR
+}
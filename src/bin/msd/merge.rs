@@ -0,0 +1,192 @@
+//! The `merge` subcommand, which merges MSD files using [`msd::merge::merge()`].
+
+use crate::{usage_error, FAILURE, SUCCESS};
+use msd::{
+    de::Options,
+    merge::{Document, Merged},
+};
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+const USAGE: &str = "\
+usage: msd merge [--lenient] [-o <output>] <base> <ours> <theirs>
+
+Merges the changes made to <base> in both <ours> and <theirs> tag by tag, writing the result to
+<ours>. Tags changed on only one side are merged automatically, while tags changed differently on
+both sides are written with both versions surrounded by conflict markers. Exits with a nonzero
+status if there are any conflicts.
+
+To use as a git merge driver, add the following to your git configuration:
+
+    [merge \"msd\"]
+        name = MSD merge driver
+        driver = msd merge %O %A %B
+
+and assign it to MSD files in .gitattributes:
+
+    *.sm merge=msd
+    *.ssc merge=msd
+
+options:
+    --lenient              Skip content that is not within a tag
+    -o, --output <path>    Write the result to the given path instead of <ours>";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    lenient: bool,
+    output: Option<PathBuf>,
+    base: PathBuf,
+    ours: PathBuf,
+    theirs: PathBuf,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut lenient = false;
+    let mut output = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lenient" => lenient = true,
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = Some(path.into()),
+                None => return Err(format!("option `{}` requires a value", arg)),
+            },
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    match <[PathBuf; 3]>::try_from(paths) {
+        Ok([base, ours, theirs]) => Ok(Args {
+            lenient,
+            output,
+            base,
+            ours,
+            theirs,
+        }),
+        Err(_) => Err("expected three paths".to_owned()),
+    }
+}
+
+pub(crate) fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return SUCCESS;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+    let options = if args.lenient {
+        Options::lenient()
+    } else {
+        Options::new()
+    };
+
+    let read = |path: &PathBuf| -> Result<Document, String> {
+        let input = fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        Document::parse(&input, options.clone())
+            .map_err(|error| format!("{}: {}", path.display(), error))
+    };
+    let (base, ours, theirs) = match (read(&args.base), read(&args.ours), read(&args.theirs)) {
+        (Ok(base), Ok(ours), Ok(theirs)) => (base, ours, theirs),
+        (Err(message), ..) | (_, Err(message), _) | (.., Err(message)) => {
+            eprintln!("error: {}", message);
+            return FAILURE;
+        }
+    };
+
+    let merged = msd::merge::merge(&base, &ours, &theirs);
+    let output = args.output.as_ref().unwrap_or(&args.ours);
+    eprint!("{}", report(&merged, &output.display().to_string()));
+    if let Err(error) = fs::write(output, merged.bytes()) {
+        eprintln!("error: {}: {}", output.display(), error);
+        return FAILURE;
+    }
+    if merged.is_clean() {
+        SUCCESS
+    } else {
+        FAILURE
+    }
+}
+
+/// Describes each conflict within the merged document written to `path`.
+fn report(merged: &Merged, path: &str) -> String {
+    let mut report = String::new();
+    for conflict in merged.conflicts() {
+        let _ = writeln!(
+            report,
+            "conflict in #{} ({}:{})",
+            String::from_utf8_lossy(conflict.name()),
+            path,
+            conflict.position().line() + 1
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, report, Args};
+    use crate::test_util::{args, document};
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_args(&args(&["base.sm", "ours.sm", "--lenient", "theirs.sm"])),
+            Ok(Args {
+                lenient: true,
+                output: None,
+                base: "base.sm".into(),
+                ours: "ours.sm".into(),
+                theirs: "theirs.sm".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_output() {
+        assert_eq!(
+            parse_args(&args(&["-o", "out.sm", "base.sm", "ours.sm", "theirs.sm"])),
+            Ok(Args {
+                lenient: false,
+                output: Some("out.sm".into()),
+                base: "base.sm".into(),
+                ours: "ours.sm".into(),
+                theirs: "theirs.sm".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_two_paths() {
+        assert_eq!(
+            parse_args(&args(&["base.sm", "ours.sm"])),
+            Err("expected three paths".to_owned())
+        );
+    }
+
+    #[test]
+    fn report_conflicts() {
+        let merged = msd::merge::merge(
+            &document(b"#TITLE:Foo;\n#ARTIST:Bar;\n"),
+            &document(b"#TITLE:Baz;\n#ARTIST:Qux;\n"),
+            &document(b"#TITLE:Quux;\n#ARTIST:Corge;\n"),
+        );
+
+        assert_eq!(
+            report(&merged, "a.sm"),
+            "conflict in #TITLE (a.sm:1)\nconflict in #ARTIST (a.sm:6)\n"
+        );
+    }
+
+    #[test]
+    fn report_clean() {
+        let merged = msd::merge::merge(
+            &document(b"#TITLE:Foo;\n"),
+            &document(b"#TITLE:Bar;\n"),
+            &document(b"#TITLE:Foo;\n"),
+        );
+
+        assert_eq!(report(&merged, "a.sm"), "");
+    }
+}
@@ -0,0 +1,13 @@
+//! Helpers shared by the subcommands' tests.
+
+use msd::{de::Options, merge::Document};
+
+/// Converts `args` into owned arguments, as they would be given to a subcommand.
+pub(crate) fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|&arg| arg.to_owned()).collect()
+}
+
+/// Parses `input` as a document to be merged.
+pub(crate) fn document(input: &[u8]) -> Document {
+    Document::parse(input, Options::new()).unwrap()
+}
@@ -0,0 +1,39 @@
+//! A minimal executor for driving futures to completion within tests.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+fn noop_raw_waker() -> RawWaker {
+    fn clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    RawWaker::new(ptr::null(), &VTABLE)
+}
+
+/// Polls `future` until it completes.
+///
+/// This busy-loops, so it is only suitable for futures that make progress without needing to be
+/// woken, such as those reading from in-memory sources.
+pub(crate) fn block_on<F>(mut future: F) -> F::Output
+where
+    F: Future,
+{
+    // SAFETY: The waker's vtable functions are all no-ops, which trivially uphold the
+    // `RawWaker` contract.
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    // SAFETY: `future` is shadowed and therefore never moved again.
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
@@ -0,0 +1,1046 @@
+use super::{Problem, ProblemKind};
+use crate::{path::Segment as PathSegment, raw};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+use serde::{
+    de,
+    de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    forward_to_deserialize_any, Deserialize,
+};
+
+/// The depth past which containers are traced as empty, to end the trace of recursive types.
+const EMPTY_DEPTH: usize = 32;
+/// The depth at which a trace is abandoned, which is only reached by recursive `enum`s.
+const MAX_DEPTH: usize = 64;
+
+/// Traces `T`, once for each variant of its most varied `enum`, collecting every problem found.
+pub(super) fn trace<T>() -> Vec<Problem>
+where
+    T: Deserialize<'static>,
+{
+    let mut tracer = Tracer {
+        problems: Vec::new(),
+        variants: BTreeMap::new(),
+    };
+    loop {
+        if let Err(error) = T::deserialize(Deserializer::new(&mut tracer)) {
+            if !error.reported {
+                tracer.report(Problem::new(
+                    ProblemKind::Untraceable(error.message),
+                    None,
+                    error.path,
+                ));
+            }
+        }
+        if !tracer.next_variants() {
+            return tracer.problems;
+        }
+    }
+}
+
+/// The position of the variant visited during the current trace of an `enum`.
+struct Variant {
+    index: usize,
+    count: usize,
+}
+
+struct Tracer {
+    problems: Vec<Problem>,
+    variants: BTreeMap<&'static str, Variant>,
+}
+
+impl Tracer {
+    fn report(&mut self, problem: Problem) {
+        // Problems are found again on every trace.
+        if !self.problems.contains(&problem) {
+            self.problems.push(problem);
+        }
+    }
+
+    /// Returns the index of the variant of `name` to visit during the current trace.
+    fn variant(&mut self, name: &'static str, count: usize) -> usize {
+        self.variants
+            .entry(name)
+            .or_insert(Variant { index: 0, count })
+            .index
+    }
+
+    /// Advances every `enum` to its next variant, returning whether any remain to be visited.
+    fn next_variants(&mut self) -> bool {
+        let mut remaining = false;
+        for variant in self.variants.values_mut() {
+            if variant.index + 1 < variant.count {
+                variant.index += 1;
+                remaining = true;
+            }
+        }
+        remaining
+    }
+}
+
+/// An error that ends a trace.
+#[derive(Debug)]
+struct Error {
+    message: String,
+    path: Vec<PathSegment>,
+    // Whether the problem ending the trace has already been reported.
+    reported: bool,
+}
+
+impl Error {
+    fn reported() -> Self {
+        Self {
+            message: String::new(),
+            path: Vec::new(),
+            reported: true,
+        }
+    }
+
+    fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self {
+            message: msg.to_string(),
+            path: Vec::new(),
+            reported: false,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.message)
+    }
+}
+
+impl de::StdError for Error {}
+
+type Result<T> = core::result::Result<T, Error>;
+
+/// Where a value is located, mirroring the deserializers of the [`de`](crate::de) module.
+#[derive(Clone, Copy)]
+enum Context {
+    /// The entire input.
+    Root,
+    /// The value of a `struct` field.
+    Field,
+    /// An element of a `seq`, which is a tag of its own.
+    Element,
+    /// A parameter, such as a tuple element or map key or value.
+    Parameter,
+}
+
+struct Deserializer<'a> {
+    tracer: &'a mut Tracer,
+    context: Context,
+    path: Vec<PathSegment>,
+    depth: usize,
+    // Whether problems are reported. This is disabled within shapes that have already been
+    // reported.
+    report: bool,
+}
+
+impl<'a> Deserializer<'a> {
+    fn new(tracer: &'a mut Tracer) -> Self {
+        Self {
+            tracer,
+            context: Context::Root,
+            path: Vec::new(),
+            depth: 0,
+            report: true,
+        }
+    }
+
+    fn child(&mut self, context: Context, segment: Option<PathSegment>) -> Deserializer<'_> {
+        let mut path = self.path.clone();
+        path.extend(segment);
+        Deserializer {
+            tracer: self.tracer,
+            context,
+            path,
+            depth: self.depth + 1,
+            report: self.report,
+        }
+    }
+
+    fn unrepresentable(&mut self, kind: ProblemKind, type_name: Option<&'static str>) {
+        if self.report {
+            self.tracer
+                .report(Problem::new(kind, type_name, self.path.clone()));
+        }
+    }
+
+    /// Returns whether the contents of containers should be traced.
+    fn traces_contents(&self) -> Result<bool> {
+        if self.depth >= MAX_DEPTH {
+            Err(de::Error::custom("recursion limit exceeded"))
+        } else {
+            Ok(self.report && self.depth < EMPTY_DEPTH)
+        }
+    }
+
+    fn visit_struct<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.traces_contents()?;
+        visitor.visit_map(Struct {
+            deserializer: self,
+            fields: fields.iter(),
+            field: None,
+        })
+    }
+}
+
+impl<'a> de::Deserializer<'static> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.unrepresentable(ProblemKind::SelfDescribing, None);
+        Err(Error::reported())
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_i8(0)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_i16(0)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_i32(0)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_i64(0)
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_i128(0)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_u8(0)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_u16(0)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_u32(0)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_u64(0)
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_u128(0)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_f32(0.0)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_str("")
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_str("")
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_bytes(b"")
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_bytes(b"")
+    }
+
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        match self.context {
+            Context::Root | Context::Field => {
+                if self.traces_contents()? {
+                    visitor.visit_some(self)
+                } else {
+                    visitor.visit_none()
+                }
+            }
+            Context::Element => {
+                self.unrepresentable(ProblemKind::OptionInSeq, None);
+                visitor.visit_none()
+            }
+            Context::Parameter => {
+                self.unrepresentable(ProblemKind::OptionInTuple, None);
+                visitor.visit_none()
+            }
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        if name == raw::TOKEN {
+            return visitor.visit_byte_buf(Vec::new());
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        match self.context {
+            Context::Root | Context::Field => {
+                let remaining = usize::from(self.traces_contents()?);
+                visitor.visit_seq(Seq {
+                    deserializer: self,
+                    remaining,
+                })
+            }
+            Context::Element => {
+                self.unrepresentable(ProblemKind::NestedSeq, None);
+                visitor.visit_seq(Seq {
+                    deserializer: self,
+                    remaining: 0,
+                })
+            }
+            Context::Parameter => {
+                self.unrepresentable(ProblemKind::SeqInTuple, None);
+                visitor.visit_seq(Seq {
+                    deserializer: self,
+                    remaining: 0,
+                })
+            }
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_seq(Tuple {
+            deserializer: self,
+            len,
+            index: 0,
+        })
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_seq(Tuple {
+            deserializer: self,
+            len,
+            index: 0,
+        })
+    }
+
+    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        let segment = match self.context {
+            Context::Root => None,
+            Context::Field | Context::Element => Some(PathSegment::ParameterList(0)),
+            Context::Parameter => {
+                self.unrepresentable(ProblemKind::MapInTuple, None);
+                return visitor.visit_map(Map {
+                    deserializer: self,
+                    segment: None,
+                    remaining: 0,
+                });
+            }
+        };
+        let remaining = usize::from(self.traces_contents()?);
+        visitor.visit_map(Map {
+            deserializer: self,
+            segment,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        match self.context {
+            Context::Root | Context::Element => {}
+            Context::Field => {
+                self.unrepresentable(ProblemKind::NestedStruct, Some(name));
+                self.report = false;
+            }
+            Context::Parameter => {
+                self.unrepresentable(ProblemKind::StructInTuple, Some(name));
+                self.report = false;
+            }
+        }
+        self.visit_struct(fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.traces_contents()?;
+        let variant = match variants.get(self.tracer.variant(name, variants.len())) {
+            Some(variant) => variant,
+            None => return Err(de::Error::custom("enum has no variants")),
+        };
+        visitor.visit_enum(Enum {
+            deserializer: self,
+            name,
+            variant,
+        })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_str("")
+    }
+
+    fn deserialize_ignored_any<V>(mut self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.unrepresentable(ProblemKind::SelfDescribing, None);
+        Err(Error::reported())
+    }
+}
+
+/// Provides a single element, or none if contents are not being traced.
+struct Seq<'a> {
+    deserializer: Deserializer<'a>,
+    remaining: usize,
+}
+
+impl<'a> SeqAccess<'static> for Seq<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'static>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(
+            self.deserializer
+                .child(Context::Element, Some(PathSegment::Element(0))),
+        )
+        .map(Some)
+        .map_err(|error| error.within(PathSegment::Element(0)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct Tuple<'a> {
+    deserializer: Deserializer<'a>,
+    len: usize,
+    index: usize,
+}
+
+impl<'a> SeqAccess<'static> for Tuple<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'static>,
+    {
+        if self.index == self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(
+            self.deserializer
+                .child(Context::Parameter, Some(PathSegment::Parameter(index))),
+        )
+        .map(Some)
+        .map_err(|error| error.within(PathSegment::Parameter(index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/// Provides a single entry, or none if contents are not being traced.
+struct Map<'a> {
+    deserializer: Deserializer<'a>,
+    segment: Option<PathSegment>,
+    remaining: usize,
+}
+
+impl<'a> Map<'a> {
+    fn parameter<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'static>,
+    {
+        let segment = self.segment.clone();
+        seed.deserialize(self.deserializer.child(Context::Parameter, segment))
+            .map_err(|error| match self.segment.clone() {
+                Some(segment) => error.within(segment),
+                None => error,
+            })
+    }
+}
+
+impl<'a> MapAccess<'static> for Map<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'static>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        self.parameter(seed).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'static>,
+    {
+        self.parameter(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Provides every field of a `struct`, in order.
+struct Struct<'a> {
+    deserializer: Deserializer<'a>,
+    fields: core::slice::Iter<'static, &'static str>,
+    field: Option<&'static str>,
+}
+
+impl<'a> MapAccess<'static> for Struct<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'static>,
+    {
+        let field = match self.fields.next() {
+            Some(field) => *field,
+            None => return Ok(None),
+        };
+        self.field = Some(field);
+        seed.deserialize(Identifier {
+            deserializer: self.deserializer.child(Context::Field, None),
+            name: field,
+            kind: ProblemKind::StructFieldNotIdentifier,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'static>,
+    {
+        let field = self
+            .field
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+        let segment = PathSegment::Tag(field.into());
+        seed.deserialize(
+            self.deserializer
+                .child(Context::Field, Some(segment.clone())),
+        )
+        .map_err(|error| error.within(segment))
+    }
+}
+
+struct Enum<'a> {
+    deserializer: Deserializer<'a>,
+    name: &'static str,
+    variant: &'static str,
+}
+
+impl<'a> EnumAccess<'static> for Enum<'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'static>,
+    {
+        let value = seed.deserialize(Identifier {
+            deserializer: self.deserializer.child(Context::Parameter, None),
+            name: self.variant,
+            kind: ProblemKind::EnumVariantNotIdentifier,
+        })?;
+        Ok((value, self))
+    }
+}
+
+impl<'a> VariantAccess<'static> for Enum<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'static>,
+    {
+        seed.deserialize(self.deserializer.child(Context::Parameter, None))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_seq(Tuple {
+            deserializer: self.deserializer,
+            len,
+            index: 0,
+        })
+    }
+
+    fn struct_variant<V>(mut self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.deserializer
+            .unrepresentable(ProblemKind::StructVariant, Some(self.name));
+        self.deserializer.report = false;
+        self.deserializer.visit_struct(fields, visitor)
+    }
+}
+
+/// Provides the name of a `struct` field or `enum` variant, which must be deserialized as an
+/// identifier.
+struct Identifier<'a> {
+    deserializer: Deserializer<'a>,
+    name: &'static str,
+    kind: ProblemKind,
+}
+
+impl<'a> de::Deserializer<'static> for Identifier<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        self.deserializer.unrepresentable(self.kind, None);
+        Err(Error::reported())
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'static>,
+    {
+        visitor.visit_borrowed_str(self.name)
+    }
+
+    forward_to_deserialize_any! {
+        <W: Visitor<'static>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trace;
+    use crate::{
+        check::{Problem, ProblemKind},
+        path::Segment as PathSegment,
+        RawValue, UnrecognizedTags,
+    };
+    use alloc::collections::BTreeMap;
+    use serde_derive::Deserialize;
+
+    #[test]
+    fn representable() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        enum Kind {
+            Unit,
+            Newtype(u8),
+            Tuple(u8, String),
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            notes: String,
+            meter: u8,
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Song {
+            title: String,
+            subtitle: Option<String>,
+            bpms: Vec<(f32, f32)>,
+            kind: Kind,
+            attacks: BTreeMap<String, f32>,
+            charts: Vec<Chart>,
+            raw: RawValue,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+
+        assert_eq!(trace::<Song>(), Vec::new());
+    }
+
+    #[test]
+    fn option_in_tuple() {
+        assert_eq!(
+            trace::<(u8, Option<u8>)>(),
+            vec![Problem::new(
+                ProblemKind::OptionInTuple,
+                None,
+                vec![PathSegment::Parameter(1)]
+            )]
+        );
+    }
+
+    #[test]
+    fn seq_in_tuple() {
+        assert_eq!(
+            trace::<(Vec<u8>,)>(),
+            vec![Problem::new(
+                ProblemKind::SeqInTuple,
+                None,
+                vec![PathSegment::Parameter(0)]
+            )]
+        );
+    }
+
+    #[test]
+    fn map_in_tuple() {
+        assert_eq!(
+            trace::<(u8, BTreeMap<u8, u8>)>(),
+            vec![Problem::new(
+                ProblemKind::MapInTuple,
+                None,
+                vec![PathSegment::Parameter(1)]
+            )]
+        );
+    }
+
+    #[test]
+    fn struct_in_tuple() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: Option<(u8, Option<u8>)>,
+        }
+
+        assert_eq!(
+            trace::<(Foo,)>(),
+            vec![Problem::new(
+                ProblemKind::StructInTuple,
+                Some("Foo"),
+                vec![PathSegment::Parameter(0)]
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_struct() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Inner {
+            foo: u8,
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Outer {
+            inner: Option<Inner>,
+        }
+
+        assert_eq!(
+            trace::<Outer>(),
+            vec![Problem::new(
+                ProblemKind::NestedStruct,
+                Some("Inner"),
+                vec![PathSegment::Tag("inner".to_owned())]
+            )]
+        );
+    }
+
+    #[test]
+    fn option_in_seq() {
+        assert_eq!(
+            trace::<Vec<Option<u8>>>(),
+            vec![Problem::new(
+                ProblemKind::OptionInSeq,
+                None,
+                vec![PathSegment::Element(0)]
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_seq() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: Vec<Vec<u8>>,
+        }
+
+        assert_eq!(
+            trace::<Foo>(),
+            vec![Problem::new(
+                ProblemKind::NestedSeq,
+                None,
+                vec![PathSegment::Tag("bar".to_owned()), PathSegment::Element(0)]
+            )]
+        );
+    }
+
+    #[test]
+    fn map_value() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: BTreeMap<String, Option<u8>>,
+        }
+
+        assert_eq!(
+            trace::<Foo>(),
+            vec![Problem::new(
+                ProblemKind::OptionInTuple,
+                None,
+                vec![
+                    PathSegment::Tag("bar".to_owned()),
+                    PathSegment::ParameterList(0)
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn every_variant() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        enum Foo {
+            A(u8),
+            B(Vec<u8>),
+            C(u8, Option<u8>),
+        }
+
+        assert_eq!(
+            trace::<Foo>(),
+            vec![
+                Problem::new(ProblemKind::SeqInTuple, None, Vec::new()),
+                Problem::new(
+                    ProblemKind::OptionInTuple,
+                    None,
+                    vec![PathSegment::Parameter(1)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_variant() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        enum Foo {
+            A { bar: u8 },
+        }
+
+        assert_eq!(
+            trace::<Foo>(),
+            vec![Problem::new(
+                ProblemKind::StructVariant,
+                Some("Foo"),
+                Vec::new()
+            )]
+        );
+    }
+
+    #[test]
+    fn self_describing() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        #[serde(untagged)]
+        enum Untagged {
+            A(u8),
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: Untagged,
+            baz: (u8, Option<u8>),
+        }
+
+        // The trace cannot continue past a self-describing type.
+        assert_eq!(
+            trace::<Foo>(),
+            vec![Problem::new(
+                ProblemKind::SelfDescribing,
+                None,
+                vec![PathSegment::Tag("bar".to_owned())]
+            )]
+        );
+    }
+
+    #[test]
+    fn untraceable() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: core::num::NonZeroU8,
+        }
+
+        assert_eq!(
+            trace::<Foo>(),
+            vec![Problem::new(
+                ProblemKind::Untraceable(
+                    "invalid value: integer `0`, expected a nonzero u8".to_owned()
+                ),
+                None,
+                vec![PathSegment::Tag("bar".to_owned())]
+            )]
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Tree {
+            value: u8,
+            children: Vec<Tree>,
+        }
+
+        assert_eq!(trace::<Tree>(), Vec::new());
+    }
+}
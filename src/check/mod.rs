@@ -0,0 +1,84 @@
+//! Checking whether a type can be represented in MSD.
+//!
+//! Some combinations of `serde` types cannot be represented in MSD (see the [crate README] for a
+//! full list). Normally these are only discovered when a value containing one is serialized or
+//! deserialized, which may never happen in tests if, for example, every sampled `Vec` is empty or
+//! every `Option` is `None`.
+//!
+//! [`deserialize()`] instead traces the shape of a [`Deserialize`] type itself, visiting every
+//! field, every element type, and every variant of every `enum`, and reports each location whose
+//! shape cannot be represented. [`serialize()`] does the same for a [`Serialize`] value, reporting
+//! every problem rather than stopping at the first.
+//!
+//! # Example
+//! ```
+//! use msd::{check, check::ProblemKind, de::PathSegment};
+//! use serde_derive::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Bpm {
+//!     beat: f32,
+//!     bpm: f32,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! #[serde(rename_all = "UPPERCASE")]
+//! struct Song {
+//!     title: String,
+//!     offset: (f32, Option<f32>),
+//!     bpms: Bpm,
+//! }
+//!
+//! let problems = check::deserialize::<Song>();
+//!
+//! assert_eq!(problems.len(), 2);
+//! assert_eq!(problems[0].kind(), &ProblemKind::OptionInTuple);
+//! assert_eq!(
+//!     problems[0].path(),
+//!     [PathSegment::Tag("OFFSET".to_owned()), PathSegment::Parameter(1)]
+//! );
+//! assert_eq!(problems[1].kind(), &ProblemKind::NestedStruct);
+//! assert_eq!(problems[1].type_name(), Some("Bpm"));
+//! ```
+//!
+//! [crate README]: https://github.com/Anders429/msd#unrepresentable-types
+//! [`Deserialize`]: serde::Deserialize
+//! [`Serialize`]: serde::Serialize
+
+mod de;
+mod problem;
+mod ser;
+
+pub use problem::{Kind as ProblemKind, Problem};
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Traces the shape of the type `T` as it would be deserialized, returning every location at
+/// which the shape cannot be represented in MSD.
+///
+/// Every variant of each `enum` is visited, and every `Option` is visited as `Some`. Sequences and
+/// maps are visited as though they contain a single element. The contents of a shape that has
+/// already been reported are not checked further.
+///
+/// If `T`'s `Deserialize` implementation rejects the placeholder values provided while tracing,
+/// the trace cannot continue, and a [`ProblemKind::Untraceable`] problem is reported.
+pub fn deserialize<T>() -> Vec<Problem>
+where
+    T: Deserialize<'static>,
+{
+    de::trace::<T>()
+}
+
+/// Traces the shape of `value` as it would be serialized, returning every location at which the
+/// shape cannot be represented in MSD.
+///
+/// Unlike [`deserialize()`], only the shapes actually present in `value` are visited. Elements of
+/// empty sequences, the contents of `None` values, and variants other than the ones present are
+/// not checked.
+pub fn serialize<T>(value: &T) -> Vec<Problem>
+where
+    T: ?Sized + Serialize,
+{
+    ser::trace(value)
+}
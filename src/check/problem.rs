@@ -0,0 +1,162 @@
+use crate::path::Segment as PathSegment;
+use alloc::{string::String, vec::Vec};
+use core::{fmt, fmt::Display};
+
+/// The kind of a [`Problem`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // Unrepresentable shapes.
+    OptionInTuple,
+    SeqInTuple,
+    MapInTuple,
+    StructInTuple,
+    NestedStruct,
+    OptionInSeq,
+    NestedSeq,
+    MapInSeq,
+    StructInSeq,
+    StructVariant,
+
+    // Deserialization requirements.
+    SelfDescribing,
+    StructFieldNotIdentifier,
+    EnumVariantNotIdentifier,
+
+    // The type could not be traced any further, with the error provided by its implementation.
+    Untraceable(String),
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::OptionInTuple => formatter.write_str("option inside tuple"),
+            Kind::SeqInTuple => formatter.write_str("seq inside tuple"),
+            Kind::MapInTuple => formatter.write_str("map inside tuple"),
+            Kind::StructInTuple => formatter.write_str("struct inside tuple"),
+            Kind::NestedStruct => formatter.write_str("nested struct"),
+            Kind::OptionInSeq => formatter.write_str("option inside seq"),
+            Kind::NestedSeq => formatter.write_str("nested seq"),
+            Kind::MapInSeq => formatter.write_str("map inside seq"),
+            Kind::StructInSeq => formatter.write_str("struct inside seq"),
+            Kind::StructVariant => formatter.write_str("struct variant"),
+            Kind::SelfDescribing => formatter.write_str("self-describing type"),
+            Kind::StructFieldNotIdentifier => {
+                formatter.write_str("struct field not deserialized as identifier")
+            }
+            Kind::EnumVariantNotIdentifier => {
+                formatter.write_str("enum variant not deserialized as identifier")
+            }
+            Kind::Untraceable(msg) => write!(formatter, "could not trace type: {}", msg),
+        }
+    }
+}
+
+/// A location within a type whose shape cannot be represented in MSD.
+///
+/// Map keys and values are written as parameters, so they are subject to the same restrictions as
+/// the elements of a tuple, and are reported as such.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    kind: Kind,
+    type_name: Option<&'static str>,
+    path: Vec<PathSegment>,
+}
+
+impl Problem {
+    pub(super) fn new(kind: Kind, type_name: Option<&'static str>, path: Vec<PathSegment>) -> Self {
+        Self {
+            kind,
+            type_name,
+            path,
+        }
+    }
+
+    /// Returns the kind of problem found.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the name of the type whose shape cannot be represented, as provided to serde.
+    ///
+    /// Only named types, such as `struct`s and `enum`s, have names. This is `None` for all other
+    /// types.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    /// Returns the path from the top level of the type to the location of the problem.
+    ///
+    /// The path begins with the outermost segment. Entries of maps are identified only by the
+    /// parameter list they occupy, since their keys are not known.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.kind.fmt(formatter)?;
+        if let Some(type_name) = self.type_name {
+            write!(formatter, " (type `{}`)", type_name)?;
+        }
+        for (index, segment) in self.path.iter().enumerate() {
+            formatter.write_str(if index == 0 { " in " } else { ", " })?;
+            segment.fmt(formatter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kind, Problem};
+    use crate::path::Segment as PathSegment;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!("{}", Problem::new(Kind::NestedSeq, None, Vec::new())),
+            "nested seq"
+        );
+    }
+
+    #[test]
+    fn display_type_name() {
+        assert_eq!(
+            format!(
+                "{}",
+                Problem::new(Kind::StructInTuple, Some("Foo"), Vec::new())
+            ),
+            "struct inside tuple (type `Foo`)"
+        );
+    }
+
+    #[test]
+    fn display_path() {
+        assert_eq!(
+            format!(
+                "{}",
+                Problem::new(
+                    Kind::OptionInTuple,
+                    None,
+                    vec![
+                        PathSegment::Tag("foo".to_owned()),
+                        PathSegment::Parameter(1)
+                    ]
+                )
+            ),
+            "option inside tuple in tag `foo`, parameter 1"
+        );
+    }
+
+    #[test]
+    fn display_untraceable() {
+        assert_eq!(
+            format!(
+                "{}",
+                Problem::new(Kind::Untraceable("bad value".to_owned()), None, Vec::new())
+            ),
+            "could not trace type: bad value"
+        );
+    }
+}
@@ -0,0 +1,775 @@
+use super::{Problem, ProblemKind};
+use crate::{path::Segment as PathSegment, raw, unrecognized};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+use serde::{
+    ser,
+    ser::{
+        SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize,
+};
+
+/// Traces `value`, collecting every problem found.
+pub(super) fn trace<T>(value: &T) -> Vec<Problem>
+where
+    T: ?Sized + Serialize,
+{
+    let mut problems = Vec::new();
+    if let Err(error) = value.serialize(Serializer::new(&mut problems)) {
+        report(
+            &mut problems,
+            Problem::new(ProblemKind::Untraceable(error.message), None, error.path),
+        );
+    }
+    problems
+}
+
+fn report(problems: &mut Vec<Problem>, problem: Problem) {
+    // Every element of a sequence has the same path, so their problems are only reported once.
+    if !problems.contains(&problem) {
+        problems.push(problem);
+    }
+}
+
+/// An error raised by the `Serialize` implementation of the traced value.
+#[derive(Debug)]
+struct Error {
+    message: String,
+    path: Vec<PathSegment>,
+}
+
+impl Error {
+    fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self {
+            message: msg.to_string(),
+            path: Vec::new(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.message)
+    }
+}
+
+impl ser::StdError for Error {}
+
+type Result<T> = core::result::Result<T, Error>;
+
+/// Where a value is located, mirroring the serializers of the [`ser`](crate::ser) module.
+#[derive(Clone, Copy)]
+enum Context {
+    /// The entire output.
+    Root,
+    /// The value of a `struct` field.
+    Field,
+    /// An element of a `seq` that is the value of a `struct` field.
+    FieldElement,
+    /// An element of a top-level `seq`, which is written as tags of its own.
+    RootElement,
+    /// A parameter, such as a tuple element or map key or value.
+    Parameter,
+}
+
+struct Serializer<'a> {
+    problems: &'a mut Vec<Problem>,
+    context: Context,
+    path: Vec<PathSegment>,
+}
+
+impl<'a> Serializer<'a> {
+    fn new(problems: &'a mut Vec<Problem>) -> Self {
+        Self {
+            problems,
+            context: Context::Root,
+            path: Vec::new(),
+        }
+    }
+
+    fn with_context(self, context: Context) -> Self {
+        Self { context, ..self }
+    }
+
+    fn unrepresentable(&mut self, kind: ProblemKind, type_name: Option<&'static str>) {
+        report(
+            self.problems,
+            Problem::new(kind, type_name, self.path.clone()),
+        );
+    }
+
+    /// Returns a compound whose contents are traced as `context`.
+    fn compound(self, context: Context, segment: fn(usize) -> Option<PathSegment>) -> Compound<'a> {
+        Compound {
+            problems: Some(self.problems),
+            context,
+            path: self.path,
+            segment,
+            index: 0,
+        }
+    }
+
+    /// Returns a compound whose contents are not traced, after reporting its shape.
+    fn unrepresentable_compound(
+        mut self,
+        kind: ProblemKind,
+        type_name: Option<&'static str>,
+    ) -> Compound<'a> {
+        self.unrepresentable(kind, type_name);
+        Compound {
+            problems: None,
+            context: self.context,
+            path: self.path,
+            segment: no_segment,
+            index: 0,
+        }
+    }
+
+    fn struct_compound(self) -> Compound<'a> {
+        self.compound(Context::Field, no_segment)
+    }
+}
+
+fn no_segment(_index: usize) -> Option<PathSegment> {
+    None
+}
+
+fn element(index: usize) -> Option<PathSegment> {
+    Some(PathSegment::Element(index))
+}
+
+fn parameter(index: usize) -> Option<PathSegment> {
+    Some(PathSegment::Parameter(index))
+}
+
+fn parameter_list(index: usize) -> Option<PathSegment> {
+    Some(PathSegment::ParameterList(index))
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, _v: bool) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_i128(self, _v: i128) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_u128(self, _v: u128) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_none(mut self) -> Result<()> {
+        match self.context {
+            Context::Root | Context::Field => {}
+            Context::FieldElement | Context::RootElement => {
+                self.unrepresentable(ProblemKind::OptionInSeq, None)
+            }
+            Context::Parameter => self.unrepresentable(ProblemKind::OptionInTuple, None),
+        }
+        Ok(())
+    }
+
+    fn serialize_some<T>(mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self.context {
+            Context::Root | Context::Field => value.serialize(self),
+            Context::FieldElement | Context::RootElement => {
+                self.unrepresentable(ProblemKind::OptionInSeq, None);
+                Ok(())
+            }
+            Context::Parameter => {
+                self.unrepresentable(ProblemKind::OptionInTuple, None);
+                Ok(())
+            }
+        }
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if name == raw::TOKEN {
+            // Raw values are written verbatim.
+            return Ok(());
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self.context {
+            Context::Root => {
+                let segment = PathSegment::Tag(variant.into());
+                let mut serializer = self.with_context(Context::Field);
+                serializer.path.push(segment.clone());
+                value
+                    .serialize(serializer)
+                    .map_err(|error| error.within(segment))
+            }
+            Context::RootElement => value.serialize(self.with_context(Context::FieldElement)),
+            Context::Field | Context::FieldElement | Context::Parameter => value.serialize(self),
+        }
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        match self.context {
+            Context::Root => Ok(self.compound(Context::RootElement, element)),
+            Context::Field => Ok(self.compound(Context::FieldElement, element)),
+            Context::FieldElement | Context::RootElement => {
+                Ok(self.unrepresentable_compound(ProblemKind::NestedSeq, None))
+            }
+            Context::Parameter => Ok(self.unrepresentable_compound(ProblemKind::SeqInTuple, None)),
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound(Context::Parameter, parameter))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound(Context::Parameter, parameter))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        Ok(self.compound(Context::Parameter, parameter))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        match self.context {
+            Context::Root => Ok(self.compound(Context::Parameter, no_segment)),
+            Context::Field | Context::FieldElement => {
+                Ok(self.compound(Context::Parameter, parameter_list))
+            }
+            Context::RootElement => Ok(self.unrepresentable_compound(ProblemKind::MapInSeq, None)),
+            Context::Parameter => Ok(self.unrepresentable_compound(ProblemKind::MapInTuple, None)),
+        }
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        match self.context {
+            Context::Root | Context::FieldElement => Ok(self.struct_compound()),
+            Context::Field => {
+                Ok(self.unrepresentable_compound(ProblemKind::NestedStruct, Some(name)))
+            }
+            Context::RootElement => {
+                Ok(self.unrepresentable_compound(ProblemKind::StructInSeq, Some(name)))
+            }
+            Context::Parameter => {
+                Ok(self.unrepresentable_compound(ProblemKind::StructInTuple, Some(name)))
+            }
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        match self.context {
+            Context::Root | Context::FieldElement | Context::RootElement => {
+                Ok(self.struct_compound())
+            }
+            Context::Field => {
+                Ok(self.unrepresentable_compound(ProblemKind::NestedStruct, Some(name)))
+            }
+            Context::Parameter => {
+                Ok(self.unrepresentable_compound(ProblemKind::StructInTuple, Some(name)))
+            }
+        }
+    }
+}
+
+/// Traces the contents of a compound type.
+///
+/// If the compound itself cannot be represented, `problems` is `None` and its contents are not
+/// traced.
+struct Compound<'a> {
+    problems: Option<&'a mut Vec<Problem>>,
+    context: Context,
+    path: Vec<PathSegment>,
+    // The path segment locating each entry within the compound.
+    segment: fn(usize) -> Option<PathSegment>,
+    index: usize,
+}
+
+impl<'a> Compound<'a> {
+    fn trace<T>(&mut self, context: Context, segment: Option<PathSegment>, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let problems = match &mut self.problems {
+            Some(problems) => problems,
+            None => return Ok(()),
+        };
+        let mut path = self.path.clone();
+        path.extend(segment.clone());
+        value
+            .serialize(Serializer {
+                problems,
+                context,
+                path,
+            })
+            .map_err(|error| match segment {
+                Some(segment) => error.within(segment),
+                None => error,
+            })
+    }
+
+    fn entry<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let segment = (self.segment)(self.index);
+        self.trace(self.context, segment, value)
+    }
+
+    fn next(&mut self) {
+        self.index += 1;
+    }
+
+    fn field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if key == unrecognized::FIELD {
+            // Unrecognized tags are written as entire tags, as they would be at the top level.
+            return self.trace(Context::Root, None, value);
+        }
+        self.trace(Context::Field, Some(PathSegment::Tag(key.into())), value)
+    }
+}
+
+impl<'a> SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.entry(value)?;
+        self.next();
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.entry(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.entry(value)?;
+        self.next();
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trace;
+    use crate::{
+        check::{Problem, ProblemKind},
+        path::Segment as PathSegment,
+        RawValue, UnrecognizedTags,
+    };
+    use alloc::collections::BTreeMap;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn representable() {
+        #[derive(Serialize)]
+        struct Chart {
+            notes: String,
+            meter: u8,
+        }
+        #[derive(Serialize)]
+        struct Song {
+            title: String,
+            subtitle: Option<String>,
+            bpms: Vec<(f32, f32)>,
+            attacks: BTreeMap<String, f32>,
+            charts: Vec<Chart>,
+            raw: RawValue,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+
+        let mut attacks = BTreeMap::new();
+        attacks.insert("foo".to_owned(), 1.0);
+
+        assert_eq!(
+            trace(&Song {
+                title: "foo".to_owned(),
+                subtitle: Some("bar".to_owned()),
+                bpms: vec![(0.0, 120.0), (4.0, 60.0)],
+                attacks,
+                charts: vec![Chart {
+                    notes: "0000".to_owned(),
+                    meter: 1,
+                }],
+                raw: RawValue::new(b"baz".to_vec()),
+                unrecognized: UnrecognizedTags::new(),
+            }),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn option_in_tuple() {
+        assert_eq!(
+            trace(&(1, None::<u8>)),
+            vec![Problem::new(
+                ProblemKind::OptionInTuple,
+                None,
+                vec![PathSegment::Parameter(1)]
+            )]
+        );
+    }
+
+    #[test]
+    fn struct_in_seq() {
+        #[derive(Serialize)]
+        struct Foo {
+            bar: u8,
+        }
+
+        assert_eq!(
+            trace(&vec![Foo { bar: 1 }, Foo { bar: 2 }]),
+            vec![
+                Problem::new(
+                    ProblemKind::StructInSeq,
+                    Some("Foo"),
+                    vec![PathSegment::Element(0)]
+                ),
+                Problem::new(
+                    ProblemKind::StructInSeq,
+                    Some("Foo"),
+                    vec![PathSegment::Element(1)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn map_in_seq() {
+        assert_eq!(
+            trace(&vec![BTreeMap::<u8, u8>::new()]),
+            vec![Problem::new(
+                ProblemKind::MapInSeq,
+                None,
+                vec![PathSegment::Element(0)]
+            )]
+        );
+    }
+
+    #[test]
+    fn field_map_value() {
+        #[derive(Serialize)]
+        struct Foo {
+            bar: BTreeMap<u8, Vec<u8>>,
+        }
+
+        let mut bar = BTreeMap::new();
+        bar.insert(1, Vec::new());
+
+        assert_eq!(
+            trace(&Foo { bar }),
+            vec![Problem::new(
+                ProblemKind::SeqInTuple,
+                None,
+                vec![
+                    PathSegment::Tag("bar".to_owned()),
+                    PathSegment::ParameterList(0)
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn every_problem() {
+        #[derive(Serialize)]
+        struct Inner {
+            foo: u8,
+        }
+        #[derive(Serialize)]
+        struct Outer {
+            foo: Vec<Option<u8>>,
+            bar: Inner,
+            baz: (u8, Vec<u8>),
+        }
+
+        assert_eq!(
+            trace(&Outer {
+                foo: vec![None],
+                bar: Inner { foo: 1 },
+                baz: (1, Vec::new()),
+            }),
+            vec![
+                Problem::new(
+                    ProblemKind::OptionInSeq,
+                    None,
+                    vec![PathSegment::Tag("foo".to_owned()), PathSegment::Element(0)]
+                ),
+                Problem::new(
+                    ProblemKind::NestedStruct,
+                    Some("Inner"),
+                    vec![PathSegment::Tag("bar".to_owned())]
+                ),
+                Problem::new(
+                    ProblemKind::SeqInTuple,
+                    None,
+                    vec![
+                        PathSegment::Tag("baz".to_owned()),
+                        PathSegment::Parameter(1)
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_present_shapes() {
+        #[derive(Serialize)]
+        struct Foo {
+            bar: Vec<Option<u8>>,
+        }
+
+        assert_eq!(trace(&Foo { bar: Vec::new() }), Vec::new());
+    }
+
+    #[test]
+    fn untraceable() {
+        struct Fails;
+
+        impl serde::Serialize for Fails {
+            fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                Err(serde::ser::Error::custom("foo"))
+            }
+        }
+
+        assert_eq!(
+            trace(&(1, Fails)),
+            vec![Problem::new(
+                ProblemKind::Untraceable("foo".to_owned()),
+                None,
+                vec![PathSegment::Parameter(1)]
+            )]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::de::{self, Position};
+use alloc::string::String;
+use core::{fmt, fmt::Display};
+use serde::de::StdError;
+
+/// The kind of an [`Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // MSD errors.
+    Msd(de::ErrorKind),
+    InvalidUtf8,
+
+    // JSON and TOML syntax errors.
+    EndOfInput,
+    UnexpectedCharacter(char),
+    InvalidEscape,
+    DuplicateKey(String),
+
+    // Document structure errors.
+    UnknownKey(String),
+    MissingKey(&'static str),
+    InvalidType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    MissingParameterList,
+    EmptyParameterList,
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Msd(kind) => kind.fmt(formatter),
+            Kind::InvalidUtf8 => formatter.write_str("tag is not valid UTF-8"),
+            Kind::EndOfInput => formatter.write_str("unexpected end of input"),
+            Kind::UnexpectedCharacter(character) => {
+                write!(formatter, "unexpected character {:?}", character)
+            }
+            Kind::InvalidEscape => formatter.write_str("invalid escape sequence"),
+            Kind::DuplicateKey(key) => write!(formatter, "duplicate key `{}`", key),
+            Kind::UnknownKey(key) => write!(formatter, "unknown key `{}`", key),
+            Kind::MissingKey(key) => write!(formatter, "missing key `{}`", key),
+            Kind::InvalidType { expected, found } => {
+                write!(formatter, "expected {}, found {}", expected, found)
+            }
+            Kind::MissingParameterList => {
+                formatter.write_str("a tag must have at least one parameter list")
+            }
+            Kind::EmptyParameterList => {
+                formatter.write_str("only the first parameter list of a tag may be empty")
+            }
+        }
+    }
+}
+
+/// An error that may occur during conversion.
+///
+/// The error's position is within the input being converted, whether that is MSD, JSON, or TOML.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    kind: Kind,
+    position: Position,
+}
+
+impl Error {
+    pub(super) fn new(kind: Kind, position: Position) -> Self {
+        Self { kind, position }
+    }
+
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the position in the input at which the error occurred.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl From<de::Error> for Error {
+    fn from(error: de::Error) -> Self {
+        Self::new(Kind::Msd(error.kind().clone()), error.position())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} at line {} column {}",
+            self.kind,
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+impl StdError for Error {}
+
+/// An alias for a [`Result`] with the error type [`Error`].
+///
+/// [`Result`]: core::result::Result
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Kind};
+    use crate::de::{self, Position};
+
+    #[test]
+    fn display_msd() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::Msd(de::ErrorKind::Io), Position::new(1, 2))
+            ),
+            "io error at line 1 column 2"
+        );
+    }
+
+    #[test]
+    fn display_unexpected_character() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::UnexpectedCharacter('}'), Position::new(3, 4))
+            ),
+            "unexpected character '}' at line 3 column 4"
+        );
+    }
+
+    #[test]
+    fn display_invalid_type() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(
+                    Kind::InvalidType {
+                        expected: "string",
+                        found: "number"
+                    },
+                    Position::new(0, 7)
+                )
+            ),
+            "expected string, found number at line 0 column 7"
+        );
+    }
+
+    #[test]
+    fn display_empty_parameter_list() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::EmptyParameterList, Position::new(5, 6))
+            ),
+            "only the first parameter list of a tag may be empty at line 5 column 6"
+        );
+    }
+}
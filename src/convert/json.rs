@@ -0,0 +1,425 @@
+//! Reading and writing JSON.
+
+use crate::{
+    convert::{
+        value::{insert, Reader, Spanned, Value},
+        Error, ErrorKind, Result, Tag,
+    },
+    de::Position,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Writes `string` as a quoted JSON string.
+fn write_string(string: &str, output: &mut String) {
+    output.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\0'..='\u{1f}' => {
+                // Writing to a `String` cannot fail.
+                let _ = write!(output, "\\u{:04x}", character as u32);
+            }
+            _ => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+/// Writes tags as a JSON document, one tag per line.
+pub(super) fn write(tags: &[Tag]) -> String {
+    let mut output = String::from("{\n  \"tags\": [");
+    for (index, tag) in tags.iter().enumerate() {
+        output.push_str(if index == 0 { "\n    " } else { ",\n    " });
+        output.push_str("{\"name\": ");
+        write_string(&tag.name, &mut output);
+        output.push_str(", \"parameter_lists\": [");
+        for (index, parameters) in tag.parameter_lists.iter().enumerate() {
+            if index > 0 {
+                output.push_str(", ");
+            }
+            output.push('[');
+            for (index, parameter) in parameters.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_string(parameter, &mut output);
+            }
+            output.push(']');
+        }
+        output.push_str("]}");
+    }
+    if !tags.is_empty() {
+        output.push_str("\n  ");
+    }
+    output.push_str("]\n}\n");
+    output
+}
+
+struct Parser<'a> {
+    reader: Reader<'a>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.reader.peek() {
+            self.reader.next();
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Spanned<Value>> {
+        self.skip_whitespace();
+        let position = self.reader.position();
+        let value = match self.reader.peek() {
+            Some('"') => Value::String(self.parse_string()?),
+            Some('[') => self.parse_array()?,
+            Some('{') => self.parse_object()?,
+            Some('-' | '0'..='9') => self.parse_number()?,
+            Some('t') => self.parse_literal("true", Value::Bool)?,
+            Some('f') => self.parse_literal("false", Value::Bool)?,
+            Some('n') => self.parse_literal("null", Value::Null)?,
+            _ => return Err(self.reader.unexpected()),
+        };
+        Ok(Spanned::new(value, position))
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Value> {
+        for expected in literal.chars() {
+            self.reader.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn parse_digits(&mut self) -> Result<()> {
+        if !matches!(self.reader.peek(), Some('0'..='9')) {
+            return Err(self.reader.unexpected());
+        }
+        while let Some('0'..='9') = self.reader.peek() {
+            self.reader.next();
+        }
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<Value> {
+        if self.reader.peek() == Some('-') {
+            self.reader.next();
+        }
+        if self.reader.peek() == Some('0') {
+            self.reader.next();
+        } else {
+            self.parse_digits()?;
+        }
+        if self.reader.peek() == Some('.') {
+            self.reader.next();
+            self.parse_digits()?;
+        }
+        if let Some('e' | 'E') = self.reader.peek() {
+            self.reader.next();
+            if let Some('+' | '-') = self.reader.peek() {
+                self.reader.next();
+            }
+            self.parse_digits()?;
+        }
+        Ok(Value::Number)
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        self.reader.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let position = self.reader.position();
+            match self.reader.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.parse_escape(position)?),
+                Some(character @ '\0'..='\u{1f}') => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedCharacter(character),
+                        position,
+                    ))
+                }
+                Some(character) => string.push(character),
+                None => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    /// Parses the remainder of an escape sequence, whose `\` was at `start`.
+    fn parse_escape(&mut self, start: Position) -> Result<char> {
+        let invalid = || Error::new(ErrorKind::InvalidEscape, start);
+        Ok(match self.reader.next().ok_or_else(invalid)? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let high = self.reader.hex4(start)?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    // A high surrogate must be followed by an escaped low surrogate.
+                    if !self.reader.starts_with("\\u") {
+                        return Err(invalid());
+                    }
+                    self.reader.next();
+                    self.reader.next();
+                    let low = self.reader.hex4(start)?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(invalid());
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        })
+    }
+
+    fn parse_array(&mut self) -> Result<Value> {
+        self.reader.expect('[')?;
+        let mut array = Vec::new();
+        self.skip_whitespace();
+        if self.reader.peek() == Some(']') {
+            self.reader.next();
+            return Ok(Value::Array(array));
+        }
+        loop {
+            array.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.reader.peek() {
+                Some(',') => {
+                    self.reader.next();
+                }
+                Some(']') => {
+                    self.reader.next();
+                    return Ok(Value::Array(array));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value> {
+        self.reader.expect('{')?;
+        let mut table = Vec::new();
+        self.skip_whitespace();
+        if self.reader.peek() == Some('}') {
+            self.reader.next();
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_whitespace();
+            let position = self.reader.position();
+            let key = Spanned::new(self.parse_string()?, position);
+            self.skip_whitespace();
+            self.reader.expect(':')?;
+            let value = self.parse_value()?;
+            insert(&mut table, key, value)?;
+            self.skip_whitespace();
+            match self.reader.peek() {
+                Some(',') => {
+                    self.reader.next();
+                }
+                Some('}') => {
+                    self.reader.next();
+                    return Ok(Value::Table(table));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+}
+
+/// Parses a JSON document.
+pub(super) fn parse(input: &str) -> Result<Spanned<Value>> {
+    let mut parser = Parser {
+        reader: Reader::new(input),
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    match parser.reader.peek() {
+        Some(_) => Err(parser.reader.unexpected()),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write, write_string};
+    use crate::{
+        convert::{
+            value::{Spanned, Value},
+            Error, ErrorKind, Tag,
+        },
+        de::Position,
+    };
+    use alloc::{borrow::ToOwned, string::String, vec};
+    use claims::{assert_err_eq, assert_ok_eq};
+
+    fn string(string: &str, line: usize, column: usize) -> Spanned<Value> {
+        Spanned::new(
+            Value::String(string.to_owned()),
+            Position::new(line, column),
+        )
+    }
+
+    #[test]
+    fn write_string_escapes() {
+        let mut output = String::new();
+        write_string("a\"b\\c\nd\te\u{1}f\u{e9}", &mut output);
+
+        assert_eq!(output, "\"a\\\"b\\\\c\\nd\\te\\u0001f\u{e9}\"");
+    }
+
+    #[test]
+    fn write_empty() {
+        assert_eq!(write(&[]), "{\n  \"tags\": []\n}\n");
+    }
+
+    #[test]
+    fn write_tags() {
+        assert_eq!(
+            write(&[
+                Tag {
+                    name: "TITLE".to_owned(),
+                    parameter_lists: vec![vec!["Foo".to_owned()]],
+                },
+                Tag {
+                    name: "BPMS".to_owned(),
+                    parameter_lists: vec![
+                        vec!["\n   0".to_owned(), "120".to_owned()],
+                        vec!["\n   4".to_owned(), "60".to_owned()]
+                    ],
+                },
+                Tag {
+                    name: "EMPTY".to_owned(),
+                    parameter_lists: vec![vec![]],
+                },
+            ]),
+            "{
+  \"tags\": [
+    {\"name\": \"TITLE\", \"parameter_lists\": [[\"Foo\"]]},
+    {\"name\": \"BPMS\", \"parameter_lists\": [[\"\\n   0\", \"120\"], [\"\\n   4\", \"60\"]]},
+    {\"name\": \"EMPTY\", \"parameter_lists\": [[]]}
+  ]
+}
+"
+        );
+    }
+
+    #[test]
+    fn parse_nested() {
+        assert_ok_eq!(
+            parse("{\"a\": [\"b\", {}],\n \"c\": \"d\"}"),
+            Spanned::new(
+                Value::Table(vec![
+                    (
+                        Spanned::new("a".to_owned(), Position::new(0, 1)),
+                        Spanned::new(
+                            Value::Array(vec![
+                                string("b", 0, 7),
+                                Spanned::new(Value::Table(vec![]), Position::new(0, 12))
+                            ]),
+                            Position::new(0, 6)
+                        )
+                    ),
+                    (
+                        Spanned::new("c".to_owned(), Position::new(1, 1)),
+                        string("d", 1, 6)
+                    ),
+                ]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_scalars() {
+        assert_ok_eq!(
+            parse("[1, -0.5e+3, true, false, null]"),
+            Spanned::new(
+                Value::Array(vec![
+                    Spanned::new(Value::Number, Position::new(0, 1)),
+                    Spanned::new(Value::Number, Position::new(0, 4)),
+                    Spanned::new(Value::Bool, Position::new(0, 13)),
+                    Spanned::new(Value::Bool, Position::new(0, 19)),
+                    Spanned::new(Value::Null, Position::new(0, 26)),
+                ]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_ok_eq!(
+            parse("\"\\\"\\\\\\/\\b\\f\\n\\r\\t\\u00e9\\ud83c\\udfb5\""),
+            string("\"\\/\u{8}\u{c}\n\r\t\u{e9}\u{1f3b5}", 0, 0)
+        );
+    }
+
+    #[test]
+    fn parse_invalid_escape() {
+        assert_err_eq!(
+            parse("\"ab\\x\""),
+            Error::new(ErrorKind::InvalidEscape, Position::new(0, 3))
+        );
+    }
+
+    #[test]
+    fn parse_unpaired_surrogate() {
+        assert_err_eq!(
+            parse("\"\\ud83c\""),
+            Error::new(ErrorKind::InvalidEscape, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn parse_control_character() {
+        assert_err_eq!(
+            parse("\"a\nb\""),
+            Error::new(ErrorKind::UnexpectedCharacter('\n'), Position::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn parse_trailing_comma() {
+        assert_err_eq!(
+            parse("[\"a\",\n]"),
+            Error::new(ErrorKind::UnexpectedCharacter(']'), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn parse_duplicate_key() {
+        assert_err_eq!(
+            parse("{\"a\": 1, \"a\": 2}"),
+            Error::new(ErrorKind::DuplicateKey("a".to_owned()), Position::new(0, 9))
+        );
+    }
+
+    #[test]
+    fn parse_end_of_input() {
+        assert_err_eq!(
+            parse("{\"a\": [\"b\""),
+            Error::new(ErrorKind::EndOfInput, Position::new(0, 10))
+        );
+    }
+
+    #[test]
+    fn parse_trailing_characters() {
+        assert_err_eq!(
+            parse("{} {}"),
+            Error::new(ErrorKind::UnexpectedCharacter('{'), Position::new(0, 3))
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Conversion between MSD and JSON or TOML.
+//!
+//! A document is represented as a list of its tags, in order and including duplicates. Each tag
+//! has a `name` and its `parameter_lists`, which are the lists of parameters ended by each of the
+//! tag's `;` characters. The name is not included in the first parameter list. In JSON, the
+//! document
+//!
+//! ``` text
+//! #TITLE:Foo;
+//! #BPMS:
+//!    0.0:120.0;
+//!    4.0:60.0;
+//! ```
+//!
+//! is converted to
+//!
+//! ``` json
+//! {
+//!   "tags": [
+//!     {"name": "TITLE", "parameter_lists": [["Foo"]]},
+//!     {"name": "BPMS", "parameter_lists": [["\n   0.0", "120.0"], ["\n   4.0", "60.0"]]}
+//!   ]
+//! }
+//! ```
+//!
+//! TOML represents the same structure with an array of tables named `tags`.
+//!
+//! Parameters are converted as they are read by the parser: escape sequences are resolved,
+//! comments are removed, and surrounding whitespace is retained. Converting back to MSD escapes
+//! each parameter again, so converting a document to JSON or TOML and back preserves its tags,
+//! although not necessarily its formatting.
+
+mod error;
+mod json;
+mod toml;
+mod value;
+
+pub use error::{Error, Kind as ErrorKind, Result};
+
+use crate::{
+    de::{Options, ParsedTags},
+    ser::escaped::Escaped,
+};
+use alloc::{string::String, vec::Vec};
+use value::{Spanned, Value};
+
+/// A tag, with each of its parameters as text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Tag {
+    name: String,
+    parameter_lists: Vec<Vec<String>>,
+}
+
+/// Reads the tags of MSD input.
+fn read(input: &[u8], options: Options) -> Result<Vec<Tag>> {
+    ParsedTags::with_options(input, options)
+        .map(|tag| {
+            let tag = tag?;
+            let position = tag.position;
+            let text = |bytes| {
+                String::from_utf8(bytes).map_err(|_| Error::new(ErrorKind::InvalidUtf8, position))
+            };
+            Ok(Tag {
+                name: text(tag.name)?,
+                parameter_lists: tag
+                    .parameter_lists
+                    .into_iter()
+                    .map(|parameters| parameters.into_iter().map(text).collect())
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect()
+}
+
+/// Writes tags as MSD, one tag per line.
+fn write(tags: &[Tag]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for tag in tags {
+        output.push(b'#');
+        output.extend(Escaped::new(tag.name.as_bytes()));
+        for (index, parameters) in tag.parameter_lists.iter().enumerate() {
+            for (parameter_index, parameter) in parameters.iter().enumerate() {
+                // The first parameter list continues on from the name.
+                if index == 0 || parameter_index > 0 {
+                    output.push(b':');
+                }
+                output.extend(Escaped::new(parameter.as_bytes()));
+            }
+            output.push(b';');
+        }
+        output.push(b'\n');
+    }
+    output
+}
+
+/// Interprets a JSON or TOML document as a list of tags.
+fn from_value(document: Spanned<Value>) -> Result<Vec<Tag>> {
+    let position = document.position;
+    let mut tags = None;
+    for (key, value) in document.into_table()? {
+        match key.value.as_str() {
+            "tags" => tags = Some(value.into_array()?),
+            _ => return Err(Error::new(ErrorKind::UnknownKey(key.value), key.position)),
+        }
+    }
+    tags.ok_or_else(|| Error::new(ErrorKind::MissingKey("tags"), position))?
+        .into_iter()
+        .map(tag_from_value)
+        .collect()
+}
+
+fn tag_from_value(tag: Spanned<Value>) -> Result<Tag> {
+    let position = tag.position;
+    let mut name = None;
+    let mut parameter_lists = None;
+    for (key, value) in tag.into_table()? {
+        match key.value.as_str() {
+            "name" => name = Some(value.into_string()?),
+            "parameter_lists" => parameter_lists = Some(parameter_lists_from_value(value)?),
+            _ => return Err(Error::new(ErrorKind::UnknownKey(key.value), key.position)),
+        }
+    }
+    Ok(Tag {
+        name: name.ok_or_else(|| Error::new(ErrorKind::MissingKey("name"), position))?,
+        parameter_lists: parameter_lists
+            .ok_or_else(|| Error::new(ErrorKind::MissingKey("parameter_lists"), position))?,
+    })
+}
+
+fn parameter_lists_from_value(parameter_lists: Spanned<Value>) -> Result<Vec<Vec<String>>> {
+    let position = parameter_lists.position;
+    let parameter_lists = parameter_lists
+        .into_array()?
+        .into_iter()
+        .enumerate()
+        .map(|(index, parameters)| {
+            let position = parameters.position;
+            let parameters = parameters
+                .into_array()?
+                .into_iter()
+                .map(Spanned::into_string)
+                .collect::<Result<Vec<_>>>()?;
+            // Only the first parameter list is preceded by the name, so only it can be written
+            // without any parameters.
+            if index > 0 && parameters.is_empty() {
+                Err(Error::new(ErrorKind::EmptyParameterList, position))
+            } else {
+                Ok(parameters)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // Every tag has at least the parameter list containing its name.
+    if parameter_lists.is_empty() {
+        return Err(Error::new(ErrorKind::MissingParameterList, position));
+    }
+    Ok(parameter_lists)
+}
+
+/// Converts MSD input to JSON.
+///
+/// The input is parsed according to `options`. Every parameter must be valid UTF-8.
+///
+/// # Example
+/// ```
+/// use msd::de::Options;
+///
+/// let json = msd::convert::to_json(b"#TITLE:Foo;\n", Options::new()).unwrap();
+///
+/// assert_eq!(
+///     json,
+///     "{\n  \"tags\": [\n    {\"name\": \"TITLE\", \"parameter_lists\": [[\"Foo\"]]}\n  ]\n}\n"
+/// );
+/// ```
+pub fn to_json(input: &[u8], options: Options) -> Result<String> {
+    Ok(json::write(&read(input, options)?))
+}
+
+/// Converts JSON input to MSD.
+///
+/// The input must have the structure produced by [`to_json()`].
+///
+/// # Example
+/// ```
+/// let msd = msd::convert::from_json(
+///     r#"{"tags": [{"name": "TITLE", "parameter_lists": [["Foo"]]}]}"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(msd, b"#TITLE:Foo;\n");
+/// ```
+pub fn from_json(input: &str) -> Result<Vec<u8>> {
+    Ok(write(&from_value(json::parse(input)?)?))
+}
+
+/// Converts MSD input to TOML.
+///
+/// The input is parsed according to `options`. Every parameter must be valid UTF-8.
+///
+/// # Example
+/// ```
+/// use msd::de::Options;
+///
+/// let toml = msd::convert::to_toml(b"#TITLE:Foo;\n", Options::new()).unwrap();
+///
+/// assert_eq!(
+///     toml,
+///     "[[tags]]\nname = \"TITLE\"\nparameter_lists = [[\"Foo\"]]\n"
+/// );
+/// ```
+pub fn to_toml(input: &[u8], options: Options) -> Result<String> {
+    Ok(toml::write(&read(input, options)?))
+}
+
+/// Converts TOML input to MSD.
+///
+/// The input must have the structure produced by [`to_toml()`].
+///
+/// # Example
+/// ```
+/// let msd = msd::convert::from_toml(
+///     "[[tags]]\nname = \"TITLE\"\nparameter_lists = [[\"Foo\"]]\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(msd, b"#TITLE:Foo;\n");
+/// ```
+pub fn from_toml(input: &str) -> Result<Vec<u8>> {
+    Ok(write(&from_value(toml::parse(input)?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json, from_toml, read, to_json, to_toml, write, Error, ErrorKind, Tag};
+    use crate::de::{Options, Position};
+    use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+
+    fn tag(name: &str, parameter_lists: &[&[&str]]) -> Tag {
+        Tag {
+            name: name.to_owned(),
+            parameter_lists: parameter_lists
+                .iter()
+                .map(|parameters| parameters.iter().map(|&p| p.to_owned()).collect())
+                .collect(),
+        }
+    }
+
+    /// Asserts that writing `tags` as MSD and reading them back gives the same tags.
+    fn assert_round_trip(tags: &[Tag]) {
+        assert_ok_eq!(read(&write(tags), Options::new()), tags.to_vec());
+    }
+
+    #[test]
+    fn read_tags() {
+        assert_ok_eq!(
+            read(
+                b"#TITLE:Foo // comment\n;\n#EMPTY;\n#BPMS:\n   0:120;\n   4:60;\n",
+                Options::new()
+            ),
+            vec![
+                tag("TITLE", &[&["Foo \n"]]),
+                tag("EMPTY", &[&[]]),
+                tag("BPMS", &[&["\n   0", "120"], &["\n   4", "60"]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_invalid_utf8() {
+        assert_err_eq!(
+            read(b"#A:b;\n#C:\xff;\n", Options::new()),
+            Error::new(ErrorKind::InvalidUtf8, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn read_msd_error() {
+        assert_err_eq!(
+            read(b"foo\n#A:b;\n", Options::new()),
+            Error::new(
+                ErrorKind::Msd(crate::de::ErrorKind::ExpectedTag),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn read_lenient() {
+        assert_ok_eq!(
+            read(b"foo\n#A:b;\n", Options::lenient()),
+            vec![tag("A", &[&["b"]])]
+        );
+    }
+
+    #[test]
+    fn write_tags() {
+        assert_eq!(
+            write(&[
+                tag("TITLE", &[&["Foo"]]),
+                tag("EMPTY", &[&[]]),
+                tag("BLANK", &[&[""]]),
+                tag("BPMS", &[&["\n   0", "120"], &["\n   4", "60"]]),
+            ]),
+            b"#TITLE:Foo;\n#EMPTY;\n#BLANK:;\n#BPMS:\n   0:120;\n   4:60;\n"
+        );
+    }
+
+    #[test]
+    fn write_escapes() {
+        assert_eq!(
+            write(&[tag("A#", &[&["b:c;d\\e//f#g", "h/"], &["/i"]])]),
+            b"#A\\#:b\\:c\\;d\\\\e\\/\\/f\\#g:h/;/i;\n".as_slice()
+        );
+    }
+
+    #[test]
+    fn round_trip_escapes() {
+        assert_round_trip(&[tag("A#", &[&["b:c;d\\e//f#g", "h/"], &["/i"]])]);
+    }
+
+    #[test]
+    fn round_trip_whitespace() {
+        assert_round_trip(&[
+            tag("A", &[&[" b ", "\n"], &["\n   c", "d\n"], &["   "]]),
+            tag("", &[&[""]]),
+        ]);
+    }
+
+    #[test]
+    fn round_trip_duplicates() {
+        assert_round_trip(&[
+            tag("NOTES", &[&["a"]]),
+            tag("TITLE", &[&["b"]]),
+            tag("NOTES", &[&["c"]]),
+        ]);
+    }
+
+    #[test]
+    fn msd_json_msd() {
+        let input = b"#TITLE:F\\:oo;\n#BPMS:\n   0:120;\n   4:60;\n#NOTES:a:b:\\\\c;\n";
+
+        assert_ok_eq!(
+            from_json(&assert_ok!(to_json(input, Options::new()))),
+            input.to_vec()
+        );
+    }
+
+    #[test]
+    fn msd_toml_msd() {
+        let input = b"#TITLE:F\\:oo;\n#BPMS:\n   0:120;\n   4:60;\n#NOTES:a:b:\\\\c;\n";
+
+        assert_ok_eq!(
+            from_toml(&assert_ok!(to_toml(input, Options::new()))),
+            input.to_vec()
+        );
+    }
+
+    #[test]
+    fn empty_document() {
+        assert_ok_eq!(
+            from_json(&assert_ok!(to_json(b"", Options::new()))),
+            Vec::new()
+        );
+        assert_ok_eq!(
+            from_toml(&assert_ok!(to_toml(b"", Options::new()))),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn from_json_missing_tags() {
+        assert_err_eq!(
+            from_json("{}"),
+            Error::new(ErrorKind::MissingKey("tags"), Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn from_json_unknown_key() {
+        assert_err_eq!(
+            from_json("{\"tags\": [], \"extra\": 1}"),
+            Error::new(
+                ErrorKind::UnknownKey("extra".to_owned()),
+                Position::new(0, 13)
+            )
+        );
+    }
+
+    #[test]
+    fn from_json_missing_name() {
+        assert_err_eq!(
+            from_json("{\"tags\": [{\"parameter_lists\": [[]]}]}"),
+            Error::new(ErrorKind::MissingKey("name"), Position::new(0, 10))
+        );
+    }
+
+    #[test]
+    fn from_json_invalid_type() {
+        assert_err_eq!(
+            from_json("{\"tags\": [{\"name\": \"A\", \"parameter_lists\": [[1]]}]}"),
+            Error::new(
+                ErrorKind::InvalidType {
+                    expected: "string",
+                    found: "number"
+                },
+                Position::new(0, 45)
+            )
+        );
+    }
+
+    #[test]
+    fn from_json_empty_parameter_list() {
+        assert_err_eq!(
+            from_json("{\"tags\": [{\"name\": \"A\", \"parameter_lists\": [[], []]}]}"),
+            Error::new(ErrorKind::EmptyParameterList, Position::new(0, 48))
+        );
+    }
+
+    #[test]
+    fn from_json_missing_parameter_list() {
+        assert_err_eq!(
+            from_json("{\"tags\": [{\"name\": \"A\", \"parameter_lists\": []}]}"),
+            Error::new(ErrorKind::MissingParameterList, Position::new(0, 43))
+        );
+    }
+
+    #[test]
+    fn from_toml_unknown_key() {
+        assert_err_eq!(
+            from_toml("[[tags]]\nname = \"A\"\nparameter_lists = [[]]\nvalue = \"b\"\n"),
+            Error::new(
+                ErrorKind::UnknownKey("value".to_owned()),
+                Position::new(3, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn from_json_non_ascii() {
+        assert_ok_eq!(
+            from_json(
+                "{\"tags\": [{\"name\": \"T\u{e9}\", \"parameter_lists\": [[\"\\u00e9\"]]}]}"
+            ),
+            String::from("#T\u{e9}:\u{e9};\n").into_bytes()
+        );
+    }
+}
@@ -0,0 +1,725 @@
+//! Reading and writing TOML.
+//!
+//! Only the subset of TOML needed to describe documents is parsed: key/value pairs with bare or
+//! quoted keys, `[table]` and `[[array of tables]]` headers naming a single key, strings of every
+//! kind, arrays, and inline tables. Numbers, dates, and booleans are recognized only so that they
+//! can be reported as having the wrong type. Dotted keys are not supported.
+
+use crate::{
+    convert::{
+        value::{insert, Reader, Spanned, Value},
+        Error, ErrorKind, Result, Tag,
+    },
+    de::Position,
+};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+/// Writes `string` as a TOML basic string.
+fn write_string(string: &str, output: &mut String) {
+    output.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\0'..='\u{1f}' | '\u{7f}' => {
+                // Writing to a `String` cannot fail.
+                let _ = write!(output, "\\u{:04X}", character as u32);
+            }
+            _ => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+/// Writes tags as a TOML document, with a `[[tags]]` table for each tag.
+pub(super) fn write(tags: &[Tag]) -> String {
+    if tags.is_empty() {
+        return String::from("tags = []\n");
+    }
+    let mut output = String::new();
+    for (index, tag) in tags.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str("[[tags]]\nname = ");
+        write_string(&tag.name, &mut output);
+        output.push_str("\nparameter_lists = [");
+        for (index, parameters) in tag.parameter_lists.iter().enumerate() {
+            if index > 0 {
+                output.push_str(", ");
+            }
+            output.push('[');
+            for (index, parameter) in parameters.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_string(parameter, &mut output);
+            }
+            output.push(']');
+        }
+        output.push_str("]\n");
+    }
+    output
+}
+
+fn is_bare_key_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_' || character == '-'
+}
+
+/// The table that key/value pairs are currently being added to.
+enum Section {
+    Root,
+    /// A `[table]`, at the given index of the root table.
+    Table(usize),
+    /// The last table of an `[[array of tables]]`, at the given index of the root table.
+    ArrayTable(usize),
+}
+
+struct Parser<'a> {
+    reader: Reader<'a>,
+}
+
+impl Parser<'_> {
+    /// Skips spaces and tabs.
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t') = self.reader.peek() {
+            self.reader.next();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.reader.peek() == Some('#') {
+            while !matches!(self.reader.peek(), None | Some('\n')) {
+                self.reader.next();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines, and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            if self.reader.starts_with("\r\n") {
+                self.reader.next();
+            }
+            if self.reader.peek() == Some('\n') {
+                self.reader.next();
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Consumes the end of a line, allowing trailing whitespace and a comment.
+    fn parse_line_end(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        self.skip_comment();
+        if self.reader.starts_with("\r\n") {
+            self.reader.next();
+        }
+        match self.reader.peek() {
+            Some('\n') => {
+                self.reader.next();
+                Ok(())
+            }
+            None => Ok(()),
+            _ => Err(self.reader.unexpected()),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<Spanned<String>> {
+        let position = self.reader.position();
+        let key = match self.reader.peek() {
+            Some('"') => self.parse_basic_string()?,
+            Some('\'') => self.parse_literal_string()?,
+            Some(character) if is_bare_key_character(character) => {
+                let mut key = String::new();
+                while let Some(character) = self.reader.peek() {
+                    if !is_bare_key_character(character) {
+                        break;
+                    }
+                    key.push(character);
+                    self.reader.next();
+                }
+                key
+            }
+            _ => return Err(self.reader.unexpected()),
+        };
+        Ok(Spanned::new(key, position))
+    }
+
+    fn parse_key_value(&mut self) -> Result<(Spanned<String>, Spanned<Value>)> {
+        let key = self.parse_key()?;
+        self.skip_whitespace();
+        self.reader.expect('=')?;
+        self.skip_whitespace();
+        Ok((key, self.parse_value()?))
+    }
+
+    fn parse_value(&mut self) -> Result<Spanned<Value>> {
+        let position = self.reader.position();
+        let value = match self.reader.peek() {
+            Some('"') if self.reader.starts_with("\"\"\"") => {
+                Value::String(self.parse_multiline_basic_string()?)
+            }
+            Some('"') => Value::String(self.parse_basic_string()?),
+            Some('\'') if self.reader.starts_with("'''") => {
+                Value::String(self.parse_multiline_literal_string()?)
+            }
+            Some('\'') => Value::String(self.parse_literal_string()?),
+            Some('[') => self.parse_array()?,
+            Some('{') => self.parse_inline_table()?,
+            Some('t') => self.parse_literal("true", Value::Bool)?,
+            Some('f') => self.parse_literal("false", Value::Bool)?,
+            Some('i') => self.parse_literal("inf", Value::Number)?,
+            Some('n') => self.parse_literal("nan", Value::Number)?,
+            Some('+' | '-' | '0'..='9') => {
+                // Numbers and dates are not validated, since they are always the wrong type.
+                while let Some('0'..='9' | 'a'..='z' | 'A'..='Z' | '_' | '+' | '-' | '.' | ':') =
+                    self.reader.peek()
+                {
+                    self.reader.next();
+                }
+                Value::Number
+            }
+            _ => return Err(self.reader.unexpected()),
+        };
+        Ok(Spanned::new(value, position))
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Value> {
+        for expected in literal.chars() {
+            self.reader.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    /// Parses the remainder of an escape sequence, whose `\` was at `start`.
+    fn parse_escape(&mut self, start: Position) -> Result<char> {
+        let invalid = || Error::new(ErrorKind::InvalidEscape, start);
+        Ok(match self.reader.next().ok_or_else(invalid)? {
+            '"' => '"',
+            '\\' => '\\',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => char::from_u32(self.reader.hex4(start)?).ok_or_else(invalid)?,
+            'U' => {
+                let high = self.reader.hex4(start)?;
+                let low = self.reader.hex4(start)?;
+                char::from_u32(high << 16 | low).ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        })
+    }
+
+    /// Fails if `character`, found at `position`, may not appear within a string.
+    fn check_string_character(character: char, position: Position) -> Result<()> {
+        match character {
+            '\0'..='\u{8}' | '\n'..='\u{1f}' | '\u{7f}' => Err(Error::new(
+                ErrorKind::UnexpectedCharacter(character),
+                position,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String> {
+        self.reader.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let position = self.reader.position();
+            match self.reader.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.parse_escape(position)?),
+                Some(character) => {
+                    Self::check_string_character(character, position)?;
+                    string.push(character);
+                }
+                None => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String> {
+        self.reader.expect('\'')?;
+        let mut string = String::new();
+        loop {
+            let position = self.reader.position();
+            match self.reader.next() {
+                Some('\'') => return Ok(string),
+                Some(character) => {
+                    Self::check_string_character(character, position)?;
+                    string.push(character);
+                }
+                None => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    /// Skips a newline immediately following the opening delimiter of a multi-line string.
+    fn skip_leading_newline(&mut self) {
+        if self.reader.starts_with("\r\n") {
+            self.reader.next();
+        }
+        if self.reader.peek() == Some('\n') {
+            self.reader.next();
+        }
+    }
+
+    /// Consumes the closing delimiter of a multi-line string, along with up to two quotes
+    /// immediately preceding it that belong to the string's contents.
+    fn parse_multiline_end(&mut self, quote: char, string: &mut String) -> bool {
+        let delimiter = if quote == '"' { "\"\"\"" } else { "'''" };
+        if !self.reader.starts_with(delimiter) {
+            return false;
+        }
+        for _ in 0..3 {
+            self.reader.next();
+        }
+        for _ in 0..2 {
+            if self.reader.peek() != Some(quote) {
+                break;
+            }
+            self.reader.next();
+            string.push(quote);
+        }
+        true
+    }
+
+    fn parse_multiline_basic_string(&mut self) -> Result<String> {
+        for _ in 0..3 {
+            self.reader.next();
+        }
+        self.skip_leading_newline();
+        let mut string = String::new();
+        loop {
+            if self.parse_multiline_end('"', &mut string) {
+                return Ok(string);
+            }
+            let position = self.reader.position();
+            match self.reader.next() {
+                Some('\\') => {
+                    // A backslash at the end of a line trims all whitespace up to the next
+                    // non-whitespace character.
+                    let mut line_ending = false;
+                    let mut lookahead = self.reader.clone();
+                    while let Some(' ' | '\t' | '\r' | '\n') = lookahead.peek() {
+                        line_ending |= lookahead.next() == Some('\n');
+                    }
+                    if line_ending {
+                        self.reader = lookahead;
+                    } else {
+                        string.push(self.parse_escape(position)?);
+                    }
+                }
+                Some(character @ ('\n' | '\r')) => string.push(character),
+                Some(character) => {
+                    Self::check_string_character(character, position)?;
+                    string.push(character);
+                }
+                None => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    fn parse_multiline_literal_string(&mut self) -> Result<String> {
+        for _ in 0..3 {
+            self.reader.next();
+        }
+        self.skip_leading_newline();
+        let mut string = String::new();
+        loop {
+            if self.parse_multiline_end('\'', &mut string) {
+                return Ok(string);
+            }
+            let position = self.reader.position();
+            match self.reader.next() {
+                Some(character @ ('\n' | '\r')) => string.push(character),
+                Some(character) => {
+                    Self::check_string_character(character, position)?;
+                    string.push(character);
+                }
+                None => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value> {
+        self.reader.expect('[')?;
+        let mut array = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.reader.peek() == Some(']') {
+                self.reader.next();
+                return Ok(Value::Array(array));
+            }
+            array.push(self.parse_value()?);
+            self.skip_trivia();
+            match self.reader.peek() {
+                Some(',') => {
+                    self.reader.next();
+                }
+                Some(']') => {
+                    self.reader.next();
+                    return Ok(Value::Array(array));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    fn parse_inline_table(&mut self) -> Result<Value> {
+        self.reader.expect('{')?;
+        let mut table = Vec::new();
+        self.skip_whitespace();
+        if self.reader.peek() == Some('}') {
+            self.reader.next();
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_whitespace();
+            let (key, value) = self.parse_key_value()?;
+            insert(&mut table, key, value)?;
+            self.skip_whitespace();
+            match self.reader.peek() {
+                Some(',') => {
+                    self.reader.next();
+                }
+                Some('}') => {
+                    self.reader.next();
+                    return Ok(Value::Table(table));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+        }
+    }
+
+    /// Parses a table header, returning its key and whether it names an array of tables.
+    fn parse_header(&mut self) -> Result<(Spanned<String>, bool)> {
+        self.reader.expect('[')?;
+        let array = self.reader.peek() == Some('[');
+        if array {
+            self.reader.next();
+        }
+        self.skip_whitespace();
+        let key = self.parse_key()?;
+        self.skip_whitespace();
+        self.reader.expect(']')?;
+        if array {
+            self.reader.expect(']')?;
+        }
+        Ok((key, array))
+    }
+}
+
+/// Returns the table that `section` refers to.
+fn section_table<'a>(
+    root: &'a mut Vec<(Spanned<String>, Spanned<Value>)>,
+    section: &Section,
+) -> &'a mut Vec<(Spanned<String>, Spanned<Value>)> {
+    let value = match *section {
+        Section::Root => return root,
+        Section::Table(index) => &mut root[index].1.value,
+        Section::ArrayTable(index) => match &mut root[index].1.value {
+            Value::Array(array) => &mut array.last_mut().expect("array of tables is empty").value,
+            _ => unreachable!("array of tables is not an array"),
+        },
+    };
+    match value {
+        Value::Table(table) => table,
+        _ => unreachable!("section is not a table"),
+    }
+}
+
+/// Parses a TOML document.
+pub(super) fn parse(input: &str) -> Result<Spanned<Value>> {
+    let mut parser = Parser {
+        reader: Reader::new(input),
+    };
+    let mut root = Vec::new();
+    // The keys of the root table that were defined by `[[array of tables]]` headers, and so may
+    // be appended to by further headers.
+    let mut array_tables = Vec::<String>::new();
+    let mut section = Section::Root;
+    loop {
+        parser.skip_trivia();
+        let position = parser.reader.position();
+        match parser.reader.peek() {
+            None => return Ok(Spanned::new(Value::Table(root), Position::new(0, 0))),
+            Some('[') => {
+                let (key, array) = parser.parse_header()?;
+                let table = Spanned::new(Value::Table(Vec::new()), position);
+                let existing = root
+                    .iter()
+                    .position(|(existing, _)| existing.value == key.value);
+                section = match existing {
+                    Some(index) if array && array_tables.contains(&key.value) => {
+                        if let Value::Array(array) = &mut root[index].1.value {
+                            array.push(table);
+                        }
+                        Section::ArrayTable(index)
+                    }
+                    Some(_) => {
+                        return Err(Error::new(ErrorKind::DuplicateKey(key.value), key.position))
+                    }
+                    None if array => {
+                        array_tables.push(key.value.clone());
+                        root.push((key, Spanned::new(Value::Array(vec![table]), position)));
+                        Section::ArrayTable(root.len() - 1)
+                    }
+                    None => {
+                        root.push((key, table));
+                        Section::Table(root.len() - 1)
+                    }
+                };
+            }
+            Some(_) => {
+                let (key, value) = parser.parse_key_value()?;
+                insert(section_table(&mut root, &section), key, value)?;
+            }
+        }
+        parser.parse_line_end()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write, write_string};
+    use crate::{
+        convert::{
+            value::{Spanned, Value},
+            Error, ErrorKind, Tag,
+        },
+        de::Position,
+    };
+    use alloc::{borrow::ToOwned, string::String, vec};
+    use claims::{assert_err_eq, assert_ok_eq};
+
+    fn string(string: &str, line: usize, column: usize) -> Spanned<Value> {
+        Spanned::new(
+            Value::String(string.to_owned()),
+            Position::new(line, column),
+        )
+    }
+
+    fn key(key: &str, line: usize, column: usize) -> Spanned<String> {
+        Spanned::new(key.to_owned(), Position::new(line, column))
+    }
+
+    #[test]
+    fn write_string_escapes() {
+        let mut output = String::new();
+        write_string("a\"b\\c\nd\te\u{1}f\u{7f}\u{e9}", &mut output);
+
+        assert_eq!(output, "\"a\\\"b\\\\c\\nd\\te\\u0001f\\u007F\u{e9}\"");
+    }
+
+    #[test]
+    fn write_empty() {
+        assert_eq!(write(&[]), "tags = []\n");
+    }
+
+    #[test]
+    fn write_tags() {
+        assert_eq!(
+            write(&[
+                Tag {
+                    name: "TITLE".to_owned(),
+                    parameter_lists: vec![vec!["Foo".to_owned()]],
+                },
+                Tag {
+                    name: "BPMS".to_owned(),
+                    parameter_lists: vec![
+                        vec!["\n   0".to_owned(), "120".to_owned()],
+                        vec!["\n   4".to_owned(), "60".to_owned()]
+                    ],
+                },
+            ]),
+            "[[tags]]
+name = \"TITLE\"
+parameter_lists = [[\"Foo\"]]
+
+[[tags]]
+name = \"BPMS\"
+parameter_lists = [[\"\\n   0\", \"120\"], [\"\\n   4\", \"60\"]]
+"
+        );
+    }
+
+    #[test]
+    fn parse_array_of_tables() {
+        assert_ok_eq!(
+            parse("# comment\n[[tags]]\nname = \"a\" # comment\n\n[[ tags ]]\n'name' = 'b'\n"),
+            Spanned::new(
+                Value::Table(vec![(
+                    key("tags", 1, 2),
+                    Spanned::new(
+                        Value::Array(vec![
+                            Spanned::new(
+                                Value::Table(vec![(key("name", 2, 0), string("a", 2, 7))]),
+                                Position::new(1, 0)
+                            ),
+                            Spanned::new(
+                                Value::Table(vec![(key("name", 5, 0), string("b", 5, 9))]),
+                                Position::new(4, 0)
+                            ),
+                        ]),
+                        Position::new(1, 0)
+                    )
+                )]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_table() {
+        assert_ok_eq!(
+            parse("a = 1\n[b]\nc = true\n"),
+            Spanned::new(
+                Value::Table(vec![
+                    (
+                        key("a", 0, 0),
+                        Spanned::new(Value::Number, Position::new(0, 4))
+                    ),
+                    (
+                        key("b", 1, 1),
+                        Spanned::new(
+                            Value::Table(vec![(
+                                key("c", 2, 0),
+                                Spanned::new(Value::Bool, Position::new(2, 4))
+                            )]),
+                            Position::new(1, 0)
+                        )
+                    ),
+                ]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_multiline_array() {
+        assert_ok_eq!(
+            parse("a = [\n  [\"b\"], # comment\n  [],\n]"),
+            Spanned::new(
+                Value::Table(vec![(
+                    key("a", 0, 0),
+                    Spanned::new(
+                        Value::Array(vec![
+                            Spanned::new(
+                                Value::Array(vec![string("b", 1, 3)]),
+                                Position::new(1, 2)
+                            ),
+                            Spanned::new(Value::Array(vec![]), Position::new(2, 2)),
+                        ]),
+                        Position::new(0, 4)
+                    )
+                )]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_inline_table() {
+        assert_ok_eq!(
+            parse("a = {b = 'c', \"d\" = []}"),
+            Spanned::new(
+                Value::Table(vec![(
+                    key("a", 0, 0),
+                    Spanned::new(
+                        Value::Table(vec![
+                            (key("b", 0, 5), string("c", 0, 9)),
+                            (
+                                key("d", 0, 14),
+                                Spanned::new(Value::Array(vec![]), Position::new(0, 20))
+                            ),
+                        ]),
+                        Position::new(0, 4)
+                    )
+                )]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_strings() {
+        assert_ok_eq!(
+            parse(
+                "a = \"\\u00e9\\U0001F3B5\\t\"\nb = '\\n'\nc = \"\"\"\nd\\\n   e\"\"\"\"\nf = '''\ng\n'''"
+            ),
+            Spanned::new(
+                Value::Table(vec![
+                    (key("a", 0, 0), string("\u{e9}\u{1f3b5}\t", 0, 4)),
+                    (key("b", 1, 0), string("\\n", 1, 4)),
+                    (key("c", 2, 0), string("de\"", 2, 4)),
+                    (key("f", 5, 0), string("g\n", 5, 4)),
+                ]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_invalid_escape() {
+        assert_err_eq!(
+            parse("a = \"b\\x\""),
+            Error::new(ErrorKind::InvalidEscape, Position::new(0, 6))
+        );
+    }
+
+    #[test]
+    fn parse_duplicate_key() {
+        assert_err_eq!(
+            parse("a = 1\na = 2\n"),
+            Error::new(ErrorKind::DuplicateKey("a".to_owned()), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn parse_array_of_tables_after_array() {
+        assert_err_eq!(
+            parse("tags = []\n[[tags]]\n"),
+            Error::new(
+                ErrorKind::DuplicateKey("tags".to_owned()),
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_dotted_key() {
+        assert_err_eq!(
+            parse("a.b = 1\n"),
+            Error::new(ErrorKind::UnexpectedCharacter('.'), Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn parse_two_values_on_one_line() {
+        assert_err_eq!(
+            parse("a = 1 b = 2\n"),
+            Error::new(ErrorKind::UnexpectedCharacter('b'), Position::new(0, 6))
+        );
+    }
+
+    #[test]
+    fn parse_unterminated_string() {
+        assert_err_eq!(
+            parse("a = \"b\n"),
+            Error::new(ErrorKind::UnexpectedCharacter('\n'), Position::new(0, 6))
+        );
+    }
+}
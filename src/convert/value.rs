@@ -0,0 +1,263 @@
+//! The values shared by the JSON and TOML parsers.
+
+use crate::{
+    convert::{Error, ErrorKind, Result},
+    de::Position,
+};
+use alloc::{string::String, vec::Vec};
+
+/// A value along with the position at which it starts in its input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Spanned<T> {
+    pub(super) value: T,
+    pub(super) position: Position,
+}
+
+impl<T> Spanned<T> {
+    pub(super) fn new(value: T, position: Position) -> Self {
+        Self { value, position }
+    }
+}
+
+/// A value parsed from JSON or TOML.
+///
+/// Only strings, arrays, and tables are meaningful when converting to MSD. The remaining types are
+/// parsed only so that they can be reported as having the wrong type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Value {
+    String(String),
+    Array(Vec<Spanned<Value>>),
+    Table(Vec<(Spanned<String>, Spanned<Value>)>),
+    Number,
+    Bool,
+    Null,
+}
+
+impl Value {
+    /// Returns the name of the value's type, for use in error messages.
+    pub(super) fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Table(_) => "table",
+            Value::Number => "number",
+            Value::Bool => "boolean",
+            Value::Null => "null",
+        }
+    }
+}
+
+impl Spanned<Value> {
+    pub(super) fn into_string(self) -> Result<String> {
+        match self.value {
+            Value::String(string) => Ok(string),
+            value => Err(invalid_type("string", &value, self.position)),
+        }
+    }
+
+    pub(super) fn into_array(self) -> Result<Vec<Spanned<Value>>> {
+        match self.value {
+            Value::Array(array) => Ok(array),
+            value => Err(invalid_type("array", &value, self.position)),
+        }
+    }
+
+    pub(super) fn into_table(self) -> Result<Vec<(Spanned<String>, Spanned<Value>)>> {
+        match self.value {
+            Value::Table(table) => Ok(table),
+            value => Err(invalid_type("table", &value, self.position)),
+        }
+    }
+}
+
+fn invalid_type(expected: &'static str, found: &Value, position: Position) -> Error {
+    Error::new(
+        ErrorKind::InvalidType {
+            expected,
+            found: found.type_name(),
+        },
+        position,
+    )
+}
+
+/// Inserts an entry into a table, failing if the key is already present.
+pub(super) fn insert(
+    table: &mut Vec<(Spanned<String>, Spanned<Value>)>,
+    key: Spanned<String>,
+    value: Spanned<Value>,
+) -> Result<()> {
+    if table
+        .iter()
+        .any(|(existing, _)| existing.value == key.value)
+    {
+        return Err(Error::new(ErrorKind::DuplicateKey(key.value), key.position));
+    }
+    table.push((key, value));
+    Ok(())
+}
+
+/// A cursor over text input, tracking the position of each character.
+#[derive(Clone)]
+pub(super) struct Reader<'a> {
+    input: &'a str,
+    offset: usize,
+    position: Position,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            offset: 0,
+            position: Position::new(0, 0),
+        }
+    }
+
+    pub(super) fn position(&self) -> Position {
+        self.position
+    }
+
+    pub(super) fn peek(&self) -> Option<char> {
+        self.input[self.offset..].chars().next()
+    }
+
+    pub(super) fn starts_with(&self, pattern: &str) -> bool {
+        self.input[self.offset..].starts_with(pattern)
+    }
+
+    pub(super) fn next(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.offset += character.len_utf8();
+        self.position = if character == '\n' {
+            Position::new(self.position.line() + 1, 0)
+        } else {
+            Position::new(
+                self.position.line(),
+                self.position.column() + character.len_utf8(),
+            )
+        };
+        Some(character)
+    }
+
+    /// Consumes the next character, failing unless it is `expected`.
+    pub(super) fn expect(&mut self, expected: char) -> Result<()> {
+        match self.peek() {
+            Some(character) if character == expected => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Returns an error for the next character, which the caller did not expect.
+    pub(super) fn unexpected(&self) -> Error {
+        Error::new(
+            match self.peek() {
+                Some(character) => ErrorKind::UnexpectedCharacter(character),
+                None => ErrorKind::EndOfInput,
+            },
+            self.position,
+        )
+    }
+
+    /// Reads the four hexadecimal digits of a `\u` escape, returning the code unit they encode.
+    ///
+    /// `start` is the position of the escape's `\`, which errors are reported at.
+    pub(super) fn hex4(&mut self, start: Position) -> Result<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|character| character.to_digit(16))
+                .ok_or_else(|| Error::new(ErrorKind::InvalidEscape, start))?;
+            self.next();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{insert, Reader, Spanned, Value};
+    use crate::{
+        convert::{Error, ErrorKind},
+        de::Position,
+    };
+    use alloc::vec::Vec;
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+
+    #[test]
+    fn reader_positions() {
+        let mut reader = Reader::new("aé\nb");
+
+        assert_some_eq!(reader.next(), 'a');
+        assert_some_eq!(reader.next(), 'é');
+        assert_eq!(reader.position(), Position::new(0, 3));
+        assert_some_eq!(reader.next(), '\n');
+        assert_eq!(reader.position(), Position::new(1, 0));
+        assert_some_eq!(reader.next(), 'b');
+        assert_none!(reader.next());
+    }
+
+    #[test]
+    fn reader_expect_end_of_input() {
+        let mut reader = Reader::new("");
+
+        assert_err_eq!(
+            reader.expect('a'),
+            Error::new(ErrorKind::EndOfInput, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn reader_hex4() {
+        let mut reader = Reader::new("00e9");
+
+        assert_ok_eq!(reader.hex4(Position::new(0, 0)), 0xe9);
+    }
+
+    #[test]
+    fn reader_hex4_invalid() {
+        let mut reader = Reader::new("00g9");
+
+        assert_err_eq!(
+            reader.hex4(Position::new(0, 0)),
+            Error::new(ErrorKind::InvalidEscape, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn invalid_type() {
+        assert_err_eq!(
+            Spanned::new(Value::Number, Position::new(1, 2)).into_string(),
+            Error::new(
+                ErrorKind::InvalidType {
+                    expected: "string",
+                    found: "number"
+                },
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    #[test]
+    fn insert_duplicate() {
+        let mut table = Vec::new();
+        assert_ok!(insert(
+            &mut table,
+            Spanned::new("a".into(), Position::new(0, 0)),
+            Spanned::new(Value::Null, Position::new(0, 1))
+        ));
+
+        assert_err_eq!(
+            insert(
+                &mut table,
+                Spanned::new("a".into(), Position::new(0, 2)),
+                Spanned::new(Value::Null, Position::new(0, 3))
+            ),
+            Error::new(ErrorKind::DuplicateKey("a".into()), Position::new(0, 2))
+        );
+    }
+}
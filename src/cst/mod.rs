@@ -0,0 +1,356 @@
+//! A lossless concrete syntax tree for editing MSD documents in place.
+//!
+//! Where the [`de`] and [`ser`] modules convert between MSD and Rust types, discarding formatting
+//! along the way, a [`Document`] keeps every byte of its input. Whitespace, line endings,
+//! comments, the spelling of escape sequences, and malformed input such as tags missing their `;`
+//! are all preserved, and any region of the document that is not edited is written back out
+//! exactly as it was read.
+//!
+//! Input is split into tags the same way the deserializer splits it when recovering from every
+//! deviation from the format, so parsing a `Document` never fails.
+//!
+//! # Example
+//! ```
+//! use msd::cst::{Document, Tag};
+//!
+//! let mut document = Document::parse(b"#TITLE:foo; // comment\r\n#BPMS:0.000=120.000\r\n");
+//!
+//! document.tag_mut(0).unwrap().set_parameter(0, b"b:r");
+//! document.push_tag(Tag::new(b"ARTIST", &[b"baz"]));
+//!
+//! assert_eq!(
+//!     document.to_bytes(),
+//!     b"#TITLE:b\\:r; // comment\r\n#BPMS:0.000=120.000\r\n#ARTIST:baz;\r\n"
+//! );
+//! ```
+//!
+//! [`de`]: crate::de
+//! [`ser`]: crate::ser
+
+mod scan;
+mod tag;
+
+pub use tag::Tag;
+
+use alloc::vec::Vec;
+
+/// A lossless representation of an MSD document.
+///
+/// A document is made up of any content preceding its first tag, followed by its [`Tag`]s, each
+/// of which owns the trivia following it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Document {
+    leading: Vec<u8>,
+    tags: Vec<Tag>,
+
+    // Empty for a default document, in which case `\n` is used.
+    line_ending: &'static [u8],
+}
+
+impl Document {
+    /// Parses a document from bytes.
+    pub fn parse(bytes: &[u8]) -> Self {
+        let (leading, mut rest) = match scan::leading(bytes) {
+            Some(start) => bytes.split_at(start),
+            None => (bytes, &[][..]),
+        };
+
+        let mut tags = Vec::new();
+        while !rest.is_empty() {
+            let (tag, remaining) = rest.split_at(scan::tag(rest).end);
+            tags.push(Tag::from_source(tag.to_vec()));
+            rest = remaining;
+        }
+
+        Self {
+            leading: leading.to_vec(),
+            tags,
+
+            line_ending: match bytes.iter().position(|&byte| byte == b'\n') {
+                Some(index) if index > 0 && bytes[index - 1] == b'\r' => b"\r\n",
+                _ => b"\n",
+            },
+        }
+    }
+
+    /// Returns the content preceding the first tag.
+    pub fn leading(&self) -> &[u8] {
+        &self.leading
+    }
+
+    /// Returns the document's tags, in order.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Returns the tag at `index`, if it exists.
+    pub fn tag(&self, index: usize) -> Option<&Tag> {
+        self.tags.get(index)
+    }
+
+    /// Returns a mutable reference to the tag at `index`, if it exists.
+    pub fn tag_mut(&mut self, index: usize) -> Option<&mut Tag> {
+        self.tags.get_mut(index)
+    }
+
+    /// Returns the number of tags in the document.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Returns whether the document has no tags.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns the line ending used by the document.
+    ///
+    /// This is the line ending of the document's first line, or `\n` if it has only one line.
+    pub fn line_ending(&self) -> &'static [u8] {
+        if self.line_ending.is_empty() {
+            b"\n"
+        } else {
+            self.line_ending
+        }
+    }
+
+    /// Inserts a tag at `index`, shifting all tags after it.
+    ///
+    /// The inserted tag is given a line ending matching the rest of the document if it does not
+    /// already end with one. If the tag is inserted at the end of the document, the same is done
+    /// for the previously last tag.
+    ///
+    /// # Panics
+    /// Panics if `index > len()`.
+    pub fn insert_tag(&mut self, index: usize, mut tag: Tag) {
+        let line_ending = self.line_ending();
+        tag.end_line(line_ending);
+        if index == self.tags.len() {
+            if let Some(last) = self.tags.last_mut() {
+                last.end_line(line_ending);
+            }
+        }
+        self.tags.insert(index, tag);
+    }
+
+    /// Appends a tag to the end of the document.
+    ///
+    /// The tag is given a line ending in the same way as [`insert_tag()`].
+    ///
+    /// [`insert_tag()`]: Document::insert_tag()
+    pub fn push_tag(&mut self, tag: Tag) {
+        self.insert_tag(self.tags.len(), tag);
+    }
+
+    /// Removes and returns the tag at `index`, along with the trivia following it.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove_tag(&mut self, index: usize) -> Tag {
+        self.tags.remove(index)
+    }
+
+    /// Moves the tag at `from` so that it is located at `to`, shifting the tags in between.
+    ///
+    /// The tag is moved along with the trivia following it. If either the moved tag or the
+    /// previously last tag is no longer last, it is given a line ending matching the rest of the
+    /// document if it does not already end with one.
+    ///
+    /// # Panics
+    /// Panics if either `from` or `to` is out of bounds.
+    pub fn move_tag(&mut self, from: usize, to: usize) {
+        let tag = self.tags.remove(from);
+        self.tags.insert(to, tag);
+        if from == to {
+            return;
+        }
+
+        let line_ending = self.line_ending();
+        let last = self.tags.len() - 1;
+        if from == last {
+            self.tags[to].end_line(line_ending);
+        } else if to == last {
+            // The previously last tag is now directly before the moved tag.
+            self.tags[last - 1].end_line(line_ending);
+        }
+    }
+
+    /// Returns the document's bytes, including all edits made to it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.leading.clone();
+        for tag in &self.tags {
+            bytes.extend_from_slice(tag.as_bytes());
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, Tag};
+
+    #[test]
+    fn round_trip() {
+        let input = b"// header\r\n\r\n#TITLE:f\\:o\\;o // comment\r\n:bar;\r\n#EMPTY;#NOSEMI:baz\r\n\r\n#B#D:x; trailing\r\n\t";
+
+        assert_eq!(Document::parse(input).to_bytes(), input);
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        assert_eq!(Document::parse(b"").to_bytes(), b"");
+    }
+
+    #[test]
+    fn round_trip_no_tags() {
+        let document = Document::parse(b"// only a comment #\n");
+
+        assert!(document.is_empty());
+        assert_eq!(document.leading(), b"// only a comment #\n");
+        assert_eq!(document.to_bytes(), b"// only a comment #\n");
+    }
+
+    #[test]
+    fn tags() {
+        let document = Document::parse(b"foo\n#TITLE:bar;\n#NOSEMI:baz\n#B#D:x;\n");
+
+        assert_eq!(document.leading(), b"foo\n");
+        assert_eq!(document.len(), 3);
+        assert_eq!(
+            document
+                .tags()
+                .iter()
+                .map(|tag| tag.as_bytes())
+                .collect::<Vec<_>>(),
+            vec![
+                &b"#TITLE:bar;\n"[..],
+                &b"#NOSEMI:baz\n"[..],
+                &b"#B#D:x;\n"[..]
+            ]
+        );
+        assert_eq!(document.tag(2).unwrap().name(), b"B#D");
+    }
+
+    #[test]
+    fn line_ending_lf() {
+        assert_eq!(Document::parse(b"#FOO;\n").line_ending(), b"\n");
+    }
+
+    #[test]
+    fn line_ending_crlf() {
+        assert_eq!(Document::parse(b"#FOO;\r\n#BAR;\n").line_ending(), b"\r\n");
+    }
+
+    #[test]
+    fn line_ending_default() {
+        assert_eq!(Document::default().line_ending(), b"\n");
+    }
+
+    #[test]
+    fn set_parameter() {
+        let mut document = Document::parse(b"#FOO:bar; // comment\r\n#BAZ:qux\r\n");
+
+        document.tag_mut(1).unwrap().set_parameter(0, b"#quux");
+
+        assert_eq!(
+            document.to_bytes(),
+            b"#FOO:bar; // comment\r\n#BAZ:\\#quux\r\n"
+        );
+    }
+
+    #[test]
+    fn insert_tag() {
+        let mut document = Document::parse(b"#FOO:bar;\r\n#BAZ:qux;\r\n");
+
+        document.insert_tag(1, Tag::new(b"NEW", &[b"value"]));
+
+        assert_eq!(
+            document.to_bytes(),
+            b"#FOO:bar;\r\n#NEW:value;\r\n#BAZ:qux;\r\n"
+        );
+    }
+
+    #[test]
+    fn push_tag_after_missing_final_line_ending() {
+        let mut document = Document::parse(b"#FOO:bar\n#BAZ:qux");
+
+        document.push_tag(Tag::new(b"NEW", &[]));
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar\n#BAZ:qux\n#NEW;\n");
+        assert_eq!(Document::parse(&document.to_bytes()), document);
+    }
+
+    #[test]
+    fn push_tag_empty_document() {
+        let mut document = Document::default();
+
+        document.push_tag(Tag::new(b"NEW", &[]));
+
+        assert_eq!(document.to_bytes(), b"#NEW;\n");
+    }
+
+    #[test]
+    fn remove_tag() {
+        let mut document = Document::parse(b"#FOO:bar;\n// comment\n#BAZ:qux;\n#QUUX;\n");
+
+        let removed = document.remove_tag(0);
+
+        assert_eq!(removed.as_bytes(), b"#FOO:bar;\n// comment\n");
+        assert_eq!(document.to_bytes(), b"#BAZ:qux;\n#QUUX;\n");
+    }
+
+    #[test]
+    fn insert_tag_between_adjacent_tags() {
+        let mut document = Document::parse(b"#A;#B;");
+
+        document.insert_tag(1, Tag::new(b"NEW", &[]));
+
+        assert_eq!(document.to_bytes(), b"#A;#NEW;\n#B;");
+    }
+
+    #[test]
+    fn remove_last_tag() {
+        let mut document = Document::parse(b"#FOO:bar;\n#BAZ:qux;");
+
+        document.remove_tag(1);
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n");
+    }
+
+    #[test]
+    fn move_tag_forward() {
+        let mut document = Document::parse(b"#A;\n#B; // b\n#C;\n");
+
+        document.move_tag(0, 2);
+
+        assert_eq!(document.to_bytes(), b"#B; // b\n#C;\n#A;\n");
+    }
+
+    #[test]
+    fn move_tag_backward() {
+        let mut document = Document::parse(b"#A;\r\n#B;\r\n#C:no semicolon");
+
+        document.move_tag(2, 0);
+
+        assert_eq!(document.to_bytes(), b"#C:no semicolon\r\n#A;\r\n#B;\r\n");
+        assert_eq!(Document::parse(&document.to_bytes()), document);
+    }
+
+    #[test]
+    fn move_tag_between_adjacent_tags() {
+        let mut document = Document::parse(b"#A;#B;#C;");
+
+        document.move_tag(0, 1);
+
+        assert_eq!(document.to_bytes(), b"#B;#A;#C;");
+    }
+
+    #[test]
+    fn move_tag_same_index() {
+        let mut document = Document::parse(b"#A;#B;");
+
+        document.move_tag(1, 1);
+
+        assert_eq!(document.to_bytes(), b"#A;#B;");
+    }
+}
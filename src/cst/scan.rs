@@ -0,0 +1,183 @@
+use crate::lex::{Kind, Lexer};
+use alloc::vec::Vec;
+
+/// The structure of a single tag, as found by [`tag()`].
+///
+/// All offsets are relative to the start of the scanned bytes.
+#[derive(Debug, PartialEq)]
+pub(super) struct Structure {
+    /// The offset directly after the tag, which is either the `#` beginning the next tag or the
+    /// end of the input.
+    pub(super) end: usize,
+    /// The offsets of the `:` bytes separating the values of the tag's first parameter list.
+    pub(super) separators: Vec<usize>,
+    /// The offset directly after the final value of the tag's first parameter list.
+    ///
+    /// If the tag is terminated, this is the offset of the terminating `;`.
+    pub(super) values_end: usize,
+    pub(super) terminated: bool,
+}
+
+/// Returns the offset of the `#` beginning the first tag, if there is one.
+pub(super) fn leading(bytes: &[u8]) -> Option<usize> {
+    Lexer::new(bytes)
+        .find(|token| token.kind() == Kind::TagStart)
+        .map(|token| token.span().start)
+}
+
+/// Scans a single tag, beginning with its `#`.
+///
+/// A tag missing its `;` runs until the next tag, with any trailing whitespace and line endings
+/// belonging to the space between the tags.
+pub(super) fn tag(bytes: &[u8]) -> Structure {
+    let mut end = bytes.len();
+    let mut separators = Vec::new();
+    let mut semicolon = None;
+    // The end of the most recent token that was not whitespace or a line ending.
+    let mut content_end = 1;
+
+    // Skip the leading `#`.
+    for token in Lexer::new(bytes).skip(1) {
+        match token.kind() {
+            Kind::TagStart => {
+                end = token.span().start;
+                break;
+            }
+            Kind::Colon if semicolon.is_none() => separators.push(token.span().start),
+            Kind::Semicolon => {
+                semicolon.get_or_insert(token.span().start);
+            }
+            Kind::Whitespace | Kind::Newline => continue,
+            _ => {}
+        }
+        content_end = token.span().end;
+    }
+
+    match semicolon {
+        Some(values_end) => Structure {
+            end,
+            separators,
+            values_end,
+            terminated: true,
+        },
+        None => Structure {
+            end,
+            separators,
+            values_end: content_end,
+            terminated: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{leading, tag, Structure};
+
+    #[test]
+    fn leading_empty() {
+        assert_eq!(leading(b""), None);
+    }
+
+    #[test]
+    fn leading_immediate() {
+        assert_eq!(leading(b"#foo;"), Some(0));
+    }
+
+    #[test]
+    fn leading_skips_comment() {
+        assert_eq!(leading(b"// #not\n \r\n#foo;"), Some(11));
+    }
+
+    #[test]
+    fn leading_content() {
+        assert_eq!(leading(b"foo /#bar;"), Some(5));
+    }
+
+    #[test]
+    fn tag_terminated() {
+        assert_eq!(
+            tag(b"#foo:bar:baz;\n"),
+            Structure {
+                end: 14,
+                separators: vec![4, 8],
+                values_end: 12,
+                terminated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn tag_ends_at_next_tag() {
+        assert_eq!(tag(b"#foo:bar;#baz;").end, 9);
+    }
+
+    #[test]
+    fn tag_escaped() {
+        assert_eq!(
+            tag(b"#foo:b\\:a\\;r;\n"),
+            Structure {
+                end: 14,
+                separators: vec![4],
+                values_end: 12,
+                terminated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn tag_comment() {
+        assert_eq!(
+            tag(b"#foo:bar // baz: qux;\n:quux;\r\n"),
+            Structure {
+                end: 30,
+                separators: vec![4, 22],
+                values_end: 27,
+                terminated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn tag_missing_semicolon() {
+        assert_eq!(
+            tag(b"#foo:bar\r\n\n#baz;"),
+            Structure {
+                end: 11,
+                separators: vec![4],
+                values_end: 8,
+                terminated: false,
+            }
+        );
+    }
+
+    #[test]
+    fn tag_unescaped_number_sign() {
+        assert_eq!(tag(b"#foo:b#ar;\n").end, 11);
+    }
+
+    #[test]
+    fn tag_trailing_content() {
+        assert_eq!(
+            tag(b"#foo:bar; baz:qux;\n#quux;"),
+            Structure {
+                end: 19,
+                separators: vec![4],
+                values_end: 8,
+                terminated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn tag_empty() {
+        assert_eq!(
+            tag(b"#"),
+            Structure {
+                end: 1,
+                separators: vec![],
+                values_end: 1,
+                terminated: false,
+            }
+        );
+    }
+}
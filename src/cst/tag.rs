@@ -0,0 +1,291 @@
+use super::scan;
+use crate::ser::escaped::Escaped;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A single tag within a [`Document`], along with the trivia following it.
+///
+/// A tag owns its exact source bytes, from its `#` up to the `#` beginning the next tag. This
+/// includes any whitespace, line endings, and comments between them, as well as any content
+/// following the tag's `;`. A tag is made up of a name followed by any number of parameters, each
+/// of which is accessed by its raw bytes as they appear in the source, including any escape
+/// sequences and comments within it.
+///
+/// [`Document`]: crate::cst::Document
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tag {
+    bytes: Vec<u8>,
+
+    separators: Vec<usize>,
+    values_end: usize,
+    terminated: bool,
+}
+
+impl Tag {
+    /// Creates a new tag with the given name and parameters.
+    ///
+    /// The name and parameters are escaped as needed. The tag is terminated by a `;`, but is not
+    /// followed by a line ending; one is added when the tag is inserted into a [`Document`].
+    ///
+    /// [`Document`]: crate::cst::Document
+    pub fn new(name: &[u8], parameters: &[&[u8]]) -> Self {
+        let mut bytes = Vec::new();
+        bytes.push(b'#');
+        bytes.extend(Escaped::new(name));
+        for parameter in parameters {
+            bytes.push(b':');
+            bytes.extend(Escaped::new(parameter));
+        }
+        bytes.push(b';');
+        Self::from_source(bytes)
+    }
+
+    /// Creates a tag from source bytes beginning with a `#` and spanning exactly one tag.
+    pub(super) fn from_source(bytes: Vec<u8>) -> Self {
+        let structure = scan::tag(&bytes);
+        debug_assert_eq!(structure.end, bytes.len());
+        Self {
+            bytes,
+
+            separators: structure.separators,
+            values_end: structure.values_end,
+            terminated: structure.terminated,
+        }
+    }
+
+    /// Returns the range of the value at `index`, where the name is the value at index `0`.
+    fn value_range(&self, index: usize) -> Option<Range<usize>> {
+        let start = match index {
+            0 => 1,
+            _ => self.separators.get(index - 1)? + 1,
+        };
+        let end = self
+            .separators
+            .get(index)
+            .copied()
+            .unwrap_or(self.values_end);
+        Some(start..end)
+    }
+
+    /// Returns the tag's exact source bytes, including its leading `#` and any trivia following
+    /// it.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the raw bytes of the tag's name.
+    pub fn name(&self) -> &[u8] {
+        // The name always exists, even if it is empty.
+        &self.bytes[1..self.separators.first().copied().unwrap_or(self.values_end)]
+    }
+
+    /// Returns the raw bytes of the parameter at `index`, if it exists.
+    pub fn parameter(&self, index: usize) -> Option<&[u8]> {
+        self.value_range(index + 1).map(|range| &self.bytes[range])
+    }
+
+    /// Returns an iterator over the raw bytes of each of the tag's parameters, in order.
+    pub fn parameters(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        (0..self.parameter_count()).filter_map(move |index| self.parameter(index))
+    }
+
+    /// Returns the number of parameters in the tag.
+    pub fn parameter_count(&self) -> usize {
+        self.separators.len()
+    }
+
+    /// Returns whether the tag is terminated by a `;`.
+    ///
+    /// Tags missing their `;` are preserved as they are, including when they are edited.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Replaces the raw bytes of the value at `index` with `bytes`.
+    fn splice(&mut self, index: usize, bytes: &[u8]) {
+        let range = self.value_range(index).expect("value index out of bounds");
+        let removed = range.len();
+        let end = range.end;
+        self.bytes.splice(range, bytes.iter().copied());
+
+        let shift = |offset: &mut usize| {
+            if *offset >= end {
+                *offset = *offset + bytes.len() - removed;
+            }
+        };
+        self.separators.iter_mut().for_each(shift);
+        shift(&mut self.values_end);
+    }
+
+    /// Sets the tag's name, escaping it as needed.
+    pub fn set_name(&mut self, name: &[u8]) {
+        self.splice(0, &Escaped::new(name).collect::<Vec<_>>());
+    }
+
+    /// Sets the parameter at `index`, escaping it as needed.
+    ///
+    /// Only the parameter's own bytes are replaced. Everything else in the tag, including the
+    /// other parameters and any trivia, is left untouched.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than [`parameter_count()`].
+    ///
+    /// [`parameter_count()`]: Tag::parameter_count()
+    pub fn set_parameter(&mut self, index: usize, value: &[u8]) {
+        self.set_parameter_raw(index, &Escaped::new(value).collect::<Vec<_>>());
+    }
+
+    /// Sets the parameter at `index` to bytes that are already in MSD format.
+    ///
+    /// The bytes are written without escaping, so they must not contain any unescaped `#`, `:`,
+    /// or `;`.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than [`parameter_count()`].
+    ///
+    /// [`parameter_count()`]: Tag::parameter_count()
+    pub fn set_parameter_raw(&mut self, index: usize, value: &[u8]) {
+        assert!(
+            index < self.parameter_count(),
+            "parameter index (is {}) should be < parameter count (is {})",
+            index,
+            self.parameter_count()
+        );
+        self.splice(index + 1, value);
+    }
+
+    /// Ensures the tag ends with a line ending, so that another tag can follow it.
+    pub(super) fn end_line(&mut self, line_ending: &[u8]) {
+        if !self.bytes.ends_with(b"\n") {
+            self.bytes.extend_from_slice(line_ending);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tag;
+
+    #[test]
+    fn new() {
+        let tag = Tag::new(b"FOO", &[b"bar", b"b:a;z"]);
+
+        assert_eq!(tag.as_bytes(), b"#FOO:bar:b\\:a\\;z;");
+        assert_eq!(tag.name(), b"FOO");
+        assert_eq!(tag.parameter(0), Some(&b"bar"[..]));
+        assert_eq!(tag.parameter(1), Some(&b"b\\:a\\;z"[..]));
+        assert!(tag.is_terminated());
+    }
+
+    #[test]
+    fn new_no_parameters() {
+        let tag = Tag::new(b"FOO", &[]);
+
+        assert_eq!(tag.as_bytes(), b"#FOO;");
+        assert_eq!(tag.parameter_count(), 0);
+        assert_eq!(tag.parameter(0), None);
+    }
+
+    #[test]
+    fn parameters() {
+        let tag = Tag::from_source(b"#FOO:bar // comment\n:baz;\r\n".to_vec());
+
+        assert_eq!(
+            tag.parameters().collect::<Vec<_>>(),
+            vec![&b"bar // comment\n"[..], &b"baz"[..]]
+        );
+    }
+
+    #[test]
+    fn unterminated() {
+        let tag = Tag::from_source(b"#FOO:bar  \r\n\n".to_vec());
+
+        assert!(!tag.is_terminated());
+        assert_eq!(tag.parameters().collect::<Vec<_>>(), vec![&b"bar"[..]]);
+    }
+
+    #[test]
+    fn empty_name() {
+        let tag = Tag::from_source(b"#:bar;".to_vec());
+
+        assert_eq!(tag.name(), b"");
+        assert_eq!(tag.parameter(0), Some(&b"bar"[..]));
+    }
+
+    #[test]
+    fn set_parameter_preserves_trivia() {
+        let mut tag = Tag::from_source(b"#FOO : bar // comment\r\n: b\\;az ;  // end\r\n".to_vec());
+
+        tag.set_parameter(0, b"a:b");
+
+        assert_eq!(tag.as_bytes(), b"#FOO :a\\:b: b\\;az ;  // end\r\n");
+        assert_eq!(tag.parameter(1), Some(&b" b\\;az "[..]));
+    }
+
+    #[test]
+    fn set_parameter_unterminated() {
+        let mut tag = Tag::from_source(b"#FOO:bar:baz\n\n".to_vec());
+
+        tag.set_parameter(1, b"quux");
+
+        assert_eq!(tag.as_bytes(), b"#FOO:bar:quux\n\n");
+        assert!(!tag.is_terminated());
+    }
+
+    #[test]
+    fn set_parameter_empty() {
+        let mut tag = Tag::from_source(b"#FOO::baz;\n".to_vec());
+
+        tag.set_parameter(0, b"bar");
+
+        assert_eq!(tag.as_bytes(), b"#FOO:bar:baz;\n");
+        assert_eq!(tag.parameter(1), Some(&b"baz"[..]));
+    }
+
+    #[test]
+    fn set_parameter_raw() {
+        let mut tag = Tag::from_source(b"#FOO:bar;\n".to_vec());
+
+        tag.set_parameter_raw(0, b"b\\#az");
+
+        assert_eq!(tag.as_bytes(), b"#FOO:b\\#az;\n");
+        assert_eq!(tag.parameter(0), Some(&b"b\\#az"[..]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_parameter_out_of_bounds() {
+        let mut tag = Tag::from_source(b"#FOO:bar;\n".to_vec());
+
+        tag.set_parameter(1, b"baz");
+    }
+
+    #[test]
+    fn set_name() {
+        let mut tag = Tag::from_source(b"#FOO:bar;\n".to_vec());
+
+        tag.set_name(b"B#R");
+
+        assert_eq!(tag.as_bytes(), b"#B\\#R:bar;\n");
+        assert_eq!(tag.name(), b"B\\#R");
+        assert_eq!(tag.parameter(0), Some(&b"bar"[..]));
+    }
+
+    #[test]
+    fn end_line() {
+        let mut tag = Tag::new(b"FOO", &[]);
+
+        tag.end_line(b"\r\n");
+
+        assert_eq!(tag.as_bytes(), b"#FOO;\r\n");
+    }
+
+    #[test]
+    fn end_line_already_ended() {
+        let mut tag = Tag::from_source(b"#FOO;\n".to_vec());
+
+        tag.end_line(b"\r\n");
+
+        assert_eq!(tag.as_bytes(), b"#FOO;\n");
+    }
+}
@@ -0,0 +1,203 @@
+use crate::de::{error, render, warning, Error, PathSegment, Position};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, fmt::Display};
+
+/// How severe a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The input could not be read with the requested options or into the requested type.
+    Error,
+    /// The input was readable, but only by recovering from malformed input.
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => formatter.write_str("error"),
+            Severity::Warning => formatter.write_str("warning"),
+        }
+    }
+}
+
+/// The kind of a [`Diagnostic`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A deviation from the MSD format.
+    Syntax(warning::Kind),
+    /// A tag that could not be deserialized into the requested type.
+    Type(error::Kind),
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Syntax(kind) => kind.fmt(formatter),
+            Kind::Type(kind) => kind.fmt(formatter),
+        }
+    }
+}
+
+/// A single problem found while validating MSD input.
+///
+/// Diagnostics are returned by [`validate()`] and [`validate_as()`].
+///
+/// [`validate()`]: crate::de::validate()
+/// [`validate_as()`]: crate::de::validate_as()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    kind: Kind,
+    position: Position,
+    /// Whether `position` is where the problem was actually found. See [`Error`].
+    positioned: bool,
+    path: Vec<PathSegment>,
+}
+
+impl Diagnostic {
+    pub(in crate::de) fn new(severity: Severity, kind: Kind, position: Position) -> Self {
+        Self {
+            severity,
+            kind,
+            position,
+            positioned: true,
+            path: Vec::new(),
+        }
+    }
+
+    /// Creates an error diagnostic from a deserialization error, keeping its position and path.
+    pub(in crate::de) fn from_error(error: &Error) -> Self {
+        Self {
+            severity: Severity::Error,
+            kind: Kind::Type(error.kind().clone()),
+            position: error.position(),
+            positioned: error.known_position().is_some(),
+            path: error.path().to_vec(),
+        }
+    }
+
+    /// Prepends a segment to the diagnostic's path.
+    pub(in crate::de) fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+
+    /// Returns how severe the problem is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the kind of problem that was found.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the position in the input at which the problem was found.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the path from the top level of the input to the value at which the problem was
+    /// found.
+    ///
+    /// For syntax problems, this is the tag the problem was found in, if any. See
+    /// [`Error::path()`] for type problems.
+    ///
+    /// [`Error::path()`]: crate::de::Error::path()
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Renders the diagnostic as an excerpt of the line of `input` at which the problem was found.
+    ///
+    /// `input` must be the input that was validated. See [`Error::render()`] for the format.
+    ///
+    /// [`Error::render()`]: crate::de::Error::render()
+    pub fn render(&self, input: &[u8]) -> String {
+        let position = if self.positioned {
+            Some(self.position)
+        } else {
+            None
+        };
+        render::render(self.severity, &self.kind, position, &self.path, input)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}: {} at line {} column {}",
+            self.severity,
+            self.kind,
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, Kind, Severity};
+    use crate::de::{error, warning, PathSegment, Position};
+
+    #[test]
+    fn display_syntax_warning() {
+        assert_eq!(
+            format!(
+                "{}",
+                Diagnostic::new(
+                    Severity::Warning,
+                    Kind::Syntax(warning::Kind::MissingSemicolon),
+                    Position::new(1, 2)
+                )
+            ),
+            "warning: missing `;` at line 1 column 2"
+        );
+    }
+
+    #[test]
+    fn display_type_error() {
+        assert_eq!(
+            format!(
+                "{}",
+                Diagnostic::new(
+                    Severity::Error,
+                    Kind::Type(error::Kind::ExpectedU32),
+                    Position::new(3, 4)
+                )
+            ),
+            "error: expected u32 at line 3 column 4"
+        );
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            Diagnostic::new(
+                Severity::Warning,
+                Kind::Syntax(warning::Kind::MissingSemicolon),
+                Position::new(0, 8)
+            )
+            .within(PathSegment::Tag("FOO".to_owned()))
+            .render(b"#FOO:bar\n#BAZ;\n"),
+            "warning: missing `;`\n --> line 1, column 9\n  |\n1 | #FOO:bar\n  |         ^ missing `;` in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let diagnostic = Diagnostic::new(
+            Severity::Error,
+            Kind::Syntax(warning::Kind::UnescapedNumberSign),
+            Position::new(5, 6),
+        );
+
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(
+            diagnostic.kind(),
+            &Kind::Syntax(warning::Kind::UnescapedNumberSign)
+        );
+        assert_eq!(diagnostic.position(), Position::new(5, 6));
+    }
+}
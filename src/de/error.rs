@@ -1,10 +1,45 @@
-use crate::de::Position;
+use crate::de::{render, PathSegment, Position, Severity};
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, fmt::Display};
 use serde::{
     de,
     de::{Expected, Unexpected},
 };
-use std::{fmt, fmt::Display};
 
+/// A limit on the size of the input, as configured through [`Options`].
+///
+/// [`Options`]: crate::de::Options
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// The length of a single tag in bytes.
+    TagBytes,
+    /// The number of tags in the input.
+    Tags,
+    /// The number of parameter lists within a single tag.
+    ParameterLists,
+    /// The number of parameters within a single tag.
+    Parameters,
+    /// The length of the entire input in bytes.
+    InputBytes,
+}
+
+impl Display for Limit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::TagBytes => formatter.write_str("tag length"),
+            Limit::Tags => formatter.write_str("number of tags"),
+            Limit::ParameterLists => formatter.write_str("number of parameter lists in a tag"),
+            Limit::Parameters => formatter.write_str("number of parameters in a tag"),
+            Limit::InputBytes => formatter.write_str("input length"),
+        }
+    }
+}
+
+/// The kind of an [`Error`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Kind {
     // Formatting errors.
@@ -15,6 +50,8 @@ pub enum Kind {
     UnexpectedValues,
     UnexpectedValue,
     EndOfValues,
+    MissingSemicolon,
+    UnescapedNumberSign,
 
     // Value errors.
     ExpectedBool,
@@ -38,6 +75,9 @@ pub enum Kind {
     // IO-related errors.
     Io,
 
+    // Resource limit errors.
+    LimitExceeded(Limit),
+
     // User-provided errors (provided through `serde::de::Error` trait methods).
     Custom(String),
     InvalidType(String, String),
@@ -59,6 +99,7 @@ pub enum Kind {
     CannotDeserializeAsOptionInSeq,
     CannotDeserializeNestedSeq,
     MustDeserializeEnumVariantAsIdentifier,
+    MustDeserializeSectionHeaderAsStruct,
 }
 
 impl Display for Kind {
@@ -71,6 +112,8 @@ impl Display for Kind {
             Kind::UnexpectedValues => formatter.write_str("unexpected values"),
             Kind::UnexpectedValue => formatter.write_str("unexpected value"),
             Kind::EndOfValues => formatter.write_str("unexpected end of values"),
+            Kind::MissingSemicolon => formatter.write_str("missing `;`"),
+            Kind::UnescapedNumberSign => formatter.write_str("unescaped `#`"),
             Kind::ExpectedBool => formatter.write_str("expected bool"),
             Kind::ExpectedI8 => formatter.write_str("expected i8"),
             Kind::ExpectedI16 => formatter.write_str("expected i16"),
@@ -89,6 +132,7 @@ impl Display for Kind {
             Kind::ExpectedUnit => formatter.write_str("expected unit value"),
             Kind::ExpectedIdentifier => formatter.write_str("expected identifier"),
             Kind::Io => formatter.write_str("io error"),
+            Kind::LimitExceeded(limit) => write!(formatter, "exceeded maximum {}", limit),
             Kind::Custom(msg) => formatter.write_str(msg),
             Kind::InvalidType(unexpected, expected) => {
                 write!(
@@ -163,6 +207,9 @@ impl Display for Kind {
             Kind::MustDeserializeEnumVariantAsIdentifier => {
                 formatter.write_str("must deserialize enum variant as identifier")
             }
+            Kind::MustDeserializeSectionHeaderAsStruct => {
+                formatter.write_str("must deserialize section header as struct")
+            }
         }
     }
 }
@@ -171,16 +218,146 @@ impl Display for Kind {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Error {
     position: Position,
+    /// Whether `position` is where the error actually occurred, rather than a placeholder for an
+    /// error created without knowing its position.
+    positioned: bool,
     kind: Kind,
+    path: Vec<PathSegment>,
 }
 
 impl Error {
     pub(super) fn new(kind: Kind, position: Position) -> Self {
-        Self { position, kind }
+        Self {
+            position,
+            positioned: true,
+            kind,
+            path: Vec::new(),
+        }
+    }
+
+    /// Creates an error whose position is not known, such as one raised by a `Visitor`.
+    fn unpositioned(kind: Kind) -> Self {
+        Self {
+            position: Position::new(0, 0),
+            positioned: false,
+            kind,
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the position in the input at which the error occurred.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the path from the top level of the input to the value at which the error occurred.
+    ///
+    /// The path begins with the outermost segment. It is empty if the error occurred at the top
+    /// level.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::PathSegment;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// #[serde(rename_all = "UPPERCASE")]
+    /// struct Song {
+    ///     title: String,
+    ///     bpms: (f32, f32),
+    /// }
+    ///
+    /// let error = msd::from_bytes::<Song>(b"#TITLE:foo;\n#BPMS:0.000:abc;\n").unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.path(),
+    ///     [PathSegment::Tag("BPMS".to_owned()), PathSegment::Parameter(1)]
+    /// );
+    /// ```
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Prepends a segment to the error's path, as the error propagates outward.
+    pub(in crate::de) fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+
+    /// Offsets the index of the element directly within the outermost segment of the path by
+    /// `skipped`.
+    ///
+    /// This is used when deserialization began partway through a sequence, after `skipped`
+    /// elements.
+    pub(in crate::de) fn skip_elements(&mut self, skipped: usize) {
+        if let Some(PathSegment::Element(index)) = self.path.get_mut(1) {
+            *index += skipped;
+        }
+    }
+
+    /// Renders the error as an excerpt of the line of `input` at which it occurred.
+    ///
+    /// `input` must be the input that was being deserialized. The offending value is underlined
+    /// and labeled with the name of the innermost tag along the error's [`path()`]. Lines and
+    /// columns are displayed one-indexed, with columns counted in characters rather than bytes.
+    ///
+    /// Some errors, such as a missing field, are not raised at any particular position. For these,
+    /// only the message is rendered.
+    ///
+    /// [`path()`]: Error::path()
+    ///
+    /// # Example
+    /// ```
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// #[serde(rename_all = "UPPERCASE")]
+    /// struct Song {
+    ///     title: String,
+    ///     offset: u32,
+    /// }
+    ///
+    /// let input = b"#TITLE:foo;\n#OFFSET:abc;\n";
+    /// let error = msd::from_bytes::<Song>(input).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.render(input),
+    ///     "\
+    /// error: expected u32
+    ///  --> line 2, column 9
+    ///   |
+    /// 2 | #OFFSET:abc;
+    ///   |         ^^^ expected u32 in tag `OFFSET`
+    /// "
+    /// );
+    /// ```
+    pub fn render(&self, input: &[u8]) -> String {
+        render::render(
+            Severity::Error,
+            &self.kind,
+            self.known_position(),
+            &self.path,
+            input,
+        )
+    }
+
+    /// Returns the position at which the error occurred, if it is known.
+    pub(in crate::de) fn known_position(&self) -> Option<Position> {
+        if self.positioned {
+            Some(self.position)
+        } else {
+            None
+        }
     }
 
     pub(in crate::de) fn set_position(&mut self, position: Position) {
         self.position = position;
+        self.positioned = true;
     }
 }
 
@@ -189,50 +366,41 @@ impl de::Error for Error {
     where
         T: Display,
     {
-        Self::new(Kind::Custom(msg.to_string()), Position::new(0, 0))
+        Self::unpositioned(Kind::Custom(msg.to_string()))
     }
 
     fn invalid_type(unexpected: Unexpected, expected: &dyn Expected) -> Self {
-        Self::new(
-            Kind::InvalidType(unexpected.to_string(), expected.to_string()),
-            Position::new(0, 0),
-        )
+        Self::unpositioned(Kind::InvalidType(
+            unexpected.to_string(),
+            expected.to_string(),
+        ))
     }
 
     fn invalid_value(unexpected: Unexpected, expected: &dyn Expected) -> Self {
-        Self::new(
-            Kind::InvalidValue(unexpected.to_string(), expected.to_string()),
-            Position::new(0, 0),
-        )
+        Self::unpositioned(Kind::InvalidValue(
+            unexpected.to_string(),
+            expected.to_string(),
+        ))
     }
 
     fn invalid_length(len: usize, expected: &dyn Expected) -> Self {
-        Self::new(
-            Kind::InvalidLength(len, expected.to_string()),
-            Position::new(0, 0),
-        )
+        Self::unpositioned(Kind::InvalidLength(len, expected.to_string()))
     }
 
     fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
-        Self::new(
-            Kind::UnknownVariant(variant.to_owned(), expected),
-            Position::new(0, 0),
-        )
+        Self::unpositioned(Kind::UnknownVariant(variant.to_owned(), expected))
     }
 
     fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
-        Self::new(
-            Kind::UnknownField(field.to_owned(), expected),
-            Position::new(0, 0),
-        )
+        Self::unpositioned(Kind::UnknownField(field.to_owned(), expected))
     }
 
     fn missing_field(field: &'static str) -> Self {
-        Self::new(Kind::MissingField(field), Position::new(0, 0))
+        Self::unpositioned(Kind::MissingField(field))
     }
 
     fn duplicate_field(field: &'static str) -> Self {
-        Self::new(Kind::DuplicateField(field), Position::new(0, 0))
+        Self::unpositioned(Kind::DuplicateField(field))
     }
 }
 
@@ -244,11 +412,17 @@ impl Display for Error {
             self.kind,
             self.position.line(),
             self.position.column()
-        )
+        )?;
+        for (index, segment) in self.path.iter().enumerate() {
+            formatter.write_str(if index == 0 { " in " } else { ", " })?;
+            segment.fmt(formatter)?;
+        }
+        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+// This is `std::error::Error` when `std` is available.
+impl de::StdError for Error {}
 
 /// An alias for a [`Result`] with the error type [`Error`].
 ///
@@ -257,11 +431,66 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, Kind};
-    use crate::de::Position;
+    use super::{Error, Kind, Limit};
+    use crate::de::{PathSegment, Position};
     use serde::de::Error as SerdeError;
     use serde::de::Unexpected;
 
+    #[test]
+    fn path() {
+        assert_eq!(
+            Error::new(Kind::ExpectedU8, Position::new(1, 2))
+                .within(PathSegment::Parameter(1))
+                .within(PathSegment::Tag("foo".to_owned()))
+                .path(),
+            [
+                PathSegment::Tag("foo".to_owned()),
+                PathSegment::Parameter(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn render_labels_innermost_tag() {
+        assert_eq!(
+            Error::new(Kind::ExpectedU8, Position::new(1, 5))
+                .within(PathSegment::Tag("BAR".to_owned()))
+                .render(b"#FOO:1;\n#BAR:x;\n"),
+            "error: expected u8\n --> line 2, column 6\n  |\n2 | #BAR:x;\n  |      ^ expected u8 in tag `BAR`\n"
+        );
+    }
+
+    #[test]
+    fn render_unknown_position() {
+        assert_eq!(
+            Error::missing_field("TITLE").render(b"#FOO:1;\n"),
+            "error: missing field TITLE\n"
+        );
+    }
+
+    #[test]
+    fn set_position_makes_position_known() {
+        let mut error = Error::custom("foo");
+        assert_eq!(error.known_position(), None);
+
+        error.set_position(Position::new(1, 2));
+
+        assert_eq!(error.known_position(), Some(Position::new(1, 2)));
+    }
+
+    #[test]
+    fn display_with_path() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::ExpectedU8, Position::new(1, 2))
+                    .within(PathSegment::Key("bar".to_owned()))
+                    .within(PathSegment::Tag("foo".to_owned()))
+            ),
+            "expected u8 at line 1 column 2 in tag `foo`, key `bar`"
+        );
+    }
+
     #[test]
     fn end_of_file() {
         assert_eq!(
@@ -321,6 +550,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn missing_semicolon() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::MissingSemicolon, Position::new(7, 8))
+            ),
+            "missing `;` at line 7 column 8"
+        );
+    }
+
+    #[test]
+    fn unescaped_number_sign() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::UnescapedNumberSign, Position::new(7, 8))
+            ),
+            "unescaped `#` at line 7 column 8"
+        );
+    }
+
     #[test]
     fn expected_bool() {
         assert_eq!(
@@ -471,6 +722,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn limit_exceeded() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::LimitExceeded(Limit::TagBytes), Position::new(25, 26))
+            ),
+            "exceeded maximum tag length at line 25 column 26"
+        );
+    }
+
     #[test]
     fn custom() {
         let mut error = Error::custom("foo");
@@ -682,6 +944,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn must_deserialize_section_header_as_struct() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(
+                    Kind::MustDeserializeSectionHeaderAsStruct,
+                    Position::new(44, 45)
+                )
+            ),
+            "must deserialize section header as struct at line 44 column 45"
+        );
+    }
+
     #[test]
     fn set_position() {
         let mut error = Error::new(Kind::EndOfFile, Position::new(0, 0));
@@ -0,0 +1,92 @@
+use std::{io, io::Read};
+
+/// Input provided in chunks as it is received, such as from an asynchronous reader.
+///
+/// Reading returns an error of kind [`io::ErrorKind::WouldBlock`] once every chunk provided so far
+/// has been read, until the end of the input is signaled through [`Feed::finish()`]. Chunks remain
+/// available after being read, so that the whole input can be read again.
+#[derive(Debug, Default)]
+pub(super) struct Feed {
+    bytes: Vec<u8>,
+    offset: usize,
+    finished: bool,
+}
+
+impl Feed {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provides the next chunk of input.
+    pub(super) fn extend(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+    }
+
+    /// Signals that no more chunks will be provided.
+    pub(super) fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Returns every chunk provided so far, as a single slice.
+    pub(super) fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Read for Feed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = &self.bytes[self.offset..];
+        if available.is_empty() && !self.finished {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let len = buf.len().min(available.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.offset += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Feed;
+    use claims::{assert_err, assert_ok_eq};
+    use std::io::{ErrorKind, Read};
+
+    #[test]
+    fn read_chunks() {
+        let mut feed = Feed::new();
+        let mut buffer = [0; 4];
+
+        feed.extend(b"foo");
+        feed.extend(b"ba");
+        assert_ok_eq!(feed.read(&mut buffer), 4);
+        assert_eq!(&buffer, b"foob");
+        assert_ok_eq!(feed.read(&mut buffer), 1);
+        assert_eq!(&buffer[..1], b"a");
+    }
+
+    #[test]
+    fn would_block() {
+        let mut feed = Feed::new();
+        feed.extend(b"foo");
+        let mut buffer = [0; 4];
+
+        assert_ok_eq!(feed.read(&mut buffer), 3);
+        assert_eq!(
+            assert_err!(feed.read(&mut buffer)).kind(),
+            ErrorKind::WouldBlock
+        );
+    }
+
+    #[test]
+    fn finish() {
+        let mut feed = Feed::new();
+        feed.extend(b"foo");
+        feed.finish();
+        let mut buffer = [0; 4];
+
+        assert_ok_eq!(feed.read(&mut buffer), 3);
+        assert_ok_eq!(feed.read(&mut buffer), 0);
+        assert_eq!(feed.as_slice(), b"foo");
+    }
+}
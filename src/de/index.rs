@@ -0,0 +1,395 @@
+use crate::de::{
+    error, parse::Tags, r#struct::value, Error, Options, PathSegment, Position, Result,
+};
+use serde::{
+    de,
+    de::{DeserializeOwned, MapAccess, Visitor},
+    ser,
+    ser::SerializeMap,
+    Deserialize, Serialize,
+};
+use std::{
+    fmt,
+    io::{Read, Seek, SeekFrom},
+};
+
+/// The location of a single tag within an MSD document, as recorded by an [`Index`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    name: String,
+    offset: u64,
+    length: u64,
+    position: Position,
+}
+
+impl Entry {
+    /// Returns the name of the tag.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the byte offset of the tag's `#` from the beginning of the document.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the length of the tag in bytes, including its `#` and any whitespace or comments
+    /// following its `;`.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns the position of the tag's `#` within the document.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Seeks to the tag within `reader` and deserializes its parameters as a value of type `T`.
+    ///
+    /// The parameters are deserialized in the same way as a struct field named after the tag.
+    /// Positions within any returned error are relative to the whole document.
+    ///
+    /// # Errors
+    /// Returns an error if `reader` cannot be read at the tag's location, or if the tag cannot be
+    /// deserialized as `T` with the given `options`.
+    pub fn read<T, R>(&self, reader: &mut R, options: Options) -> Result<T>
+    where
+        T: DeserializeOwned,
+        R: Read + Seek,
+    {
+        let mut bytes = vec![0; self.length as usize];
+        reader
+            .seek(SeekFrom::Start(self.offset))
+            .and_then(|_| reader.read_exact(&mut bytes))
+            .map_err(|_| Error::new(error::Kind::Io, self.position))?;
+        self.deserialize(&bytes, options).map_err(|mut error| {
+            // The bytes were read from the tag's `#` onward, so their positions begin there.
+            if let Some(position) = error.known_position() {
+                error.set_position(position.relative_to(self.position));
+            }
+            error
+        })
+    }
+
+    fn deserialize<T>(&self, bytes: &[u8], options: Options) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut tags = Tags::with_options(bytes, options);
+        let mut tag = tags.next()?;
+        let mut values = tag.next()?;
+        let field = values.next()?.parse_identifier()?;
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let value = T::deserialize(value::Deserializer::new(
+            &field,
+            &mut tags,
+            stored_tag,
+            stored_values,
+        ))
+        .map_err(|error| error.within(PathSegment::Tag(field.clone())))?;
+        tags.assert_exhausted()?;
+        Ok(value)
+    }
+}
+
+/// An index from tag names to their locations within an MSD document.
+///
+/// An index is built in a single pass over a document, after which any tag can be read on demand
+/// by seeking directly to it, without reading the rest of the document again. This is useful when
+/// only a few tags are needed from many large documents, such as the metadata of simfiles whose
+/// note data makes up the bulk of their size.
+///
+/// An `Index` can itself be serialized, so that it can be persisted alongside the document it
+/// describes. It is written as an MSD document with a tag for each entry, in the form
+/// `#NAME:offset:length:line:column;`. A persisted index is only valid for as long as the document
+/// it describes is unchanged.
+///
+/// # Example
+/// ```
+/// use msd::de::{Index, Options};
+/// use std::io::Cursor;
+///
+/// let mut reader = Cursor::new(b"#TITLE:Foo;\n#NOTES:0000:1000;\n#BPM:120;\n".to_vec());
+/// let index = Index::build(&mut reader, Options::new()).unwrap();
+///
+/// let bpm: Option<f64> = index.get(&mut reader, "BPM", Options::new()).unwrap();
+/// assert_eq!(bpm, Some(120.0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    /// Builds an index of every tag in the document read from `reader`.
+    ///
+    /// The document is read one byte at a time, so `reader` should be buffered if reading from it
+    /// directly is expensive. The offsets recorded are relative to the position of `reader` when
+    /// this is called.
+    ///
+    /// # Errors
+    /// Returns an error if the document cannot be read with the given `options`.
+    pub fn build<R>(reader: R, options: Options) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut entries = Vec::new();
+        let mut tags = Tags::with_options(reader, options);
+        loop {
+            let mut tag = match tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => break,
+                Err(error) => return Err(error),
+            };
+            let position = tag.origin_position();
+            // The first parameter list always contains at least the name.
+            let name = tag.next()?.next()?.parse_identifier()?;
+            let (offset, length) = tags.tag_span();
+            entries.push(Entry {
+                name,
+                offset: offset as u64,
+                length: length as u64,
+                position,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// Returns every entry in the index, in the order the tags appear in the document.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the entry for the first tag named `name`, if there is one.
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Seeks to the first tag named `name` within `reader` and deserializes its parameters as a
+    /// value of type `T`, returning `None` if there is no such tag.
+    ///
+    /// See [`Entry::read()`] for details.
+    ///
+    /// # Errors
+    /// Returns an error if `reader` cannot be read at the tag's location, or if the tag cannot be
+    /// deserialized as `T` with the given `options`.
+    pub fn get<T, R>(&self, reader: &mut R, name: &str, options: Options) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+        R: Read + Seek,
+    {
+        self.entry(name)
+            .map(|entry| entry.read(reader, options))
+            .transpose()
+    }
+}
+
+impl Serialize for Index {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for entry in &self.entries {
+            map.serialize_entry(
+                &entry.name,
+                &(
+                    entry.offset,
+                    entry.length,
+                    entry.position.line(),
+                    entry.position.column(),
+                ),
+            )?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Index {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct IndexVisitor;
+
+        impl<'de> Visitor<'de> for IndexVisitor {
+            type Value = Index;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("tag index")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                // Entries are read in order, since tag names may be repeated.
+                while let Some((name, (offset, length, line, column))) = map.next_entry()? {
+                    entries.push(Entry {
+                        name,
+                        offset,
+                        length,
+                        position: Position::new(line, column),
+                    });
+                }
+                Ok(Index { entries })
+            }
+        }
+
+        deserializer.deserialize_map(IndexVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Index};
+    use crate::de::{error, Error, Options, PathSegment, Position};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use std::io::Cursor;
+
+    const INPUT: &[u8] =
+        b"// Song\n#TITLE:Foo;\n#NOTES:\n  dance-single:\n  0000\n;\n#BPMS:0=120;\n";
+
+    fn entry(name: &str, offset: u64, length: u64, position: Position) -> Entry {
+        Entry {
+            name: name.to_owned(),
+            offset,
+            length,
+            position,
+        }
+    }
+
+    #[test]
+    fn build() {
+        let index = assert_ok!(Index::build(INPUT, Options::new()));
+
+        assert_eq!(
+            index.entries(),
+            [
+                entry("TITLE", 8, 12, Position::new(1, 0)),
+                entry("NOTES", 20, 33, Position::new(2, 0)),
+                entry("BPMS", 53, 13, Position::new(6, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_empty() {
+        assert_ok_eq!(
+            Index::build(b"".as_slice(), Options::new()),
+            Index::default()
+        );
+    }
+
+    #[test]
+    fn build_error() {
+        assert_err_eq!(
+            Index::build(b"#TITLE:Foo\n#BPMS:0=120;\n".as_slice(), Options::strict()),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn build_with_options() {
+        let index = assert_ok!(Index::build(
+            b"#TITLE:Foo\n#BPMS:0=120;\n".as_slice(),
+            Options::lenient()
+        ));
+
+        assert_eq!(
+            index.entries(),
+            [
+                entry("TITLE", 0, 11, Position::new(0, 0)),
+                entry("BPMS", 11, 13, Position::new(1, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn entry_repeated() {
+        let index = assert_ok!(Index::build(
+            b"#NOTES:1;\n#NOTES:2;\n".as_slice(),
+            Options::new()
+        ));
+
+        assert_some_eq!(
+            index.entry("NOTES"),
+            &entry("NOTES", 0, 10, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn get() {
+        let mut reader = Cursor::new(INPUT);
+        let index = assert_ok!(Index::build(&mut reader, Options::new()));
+
+        assert_ok_eq!(
+            index.get(&mut reader, "BPMS", Options::new()),
+            Some("0=120".to_owned())
+        );
+        assert_ok_eq!(
+            index.get(&mut reader, "TITLE", Options::new()),
+            Some("Foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn get_multiline() {
+        let mut reader = Cursor::new(INPUT);
+        let index = assert_ok!(Index::build(&mut reader, Options::new()));
+
+        assert_ok_eq!(
+            index.get(&mut reader, "NOTES", Options::new()),
+            Some(("\n  dance-single".to_owned(), "\n  0000\n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn get_missing() {
+        let mut reader = Cursor::new(INPUT);
+        let index = assert_ok!(Index::build(&mut reader, Options::new()));
+
+        assert_none!(assert_ok!(index.get::<String, _>(
+            &mut reader,
+            "ARTIST",
+            Options::new()
+        )));
+    }
+
+    #[test]
+    fn get_error_position() {
+        let mut reader = Cursor::new(INPUT);
+        let index = assert_ok!(Index::build(&mut reader, Options::new()));
+
+        assert_err_eq!(
+            index.get::<u64, _>(&mut reader, "TITLE", Options::new()),
+            Error::new(error::Kind::ExpectedU64, Position::new(1, 7))
+                .within(PathSegment::Tag("TITLE".to_owned()))
+        );
+    }
+
+    #[test]
+    fn get_io_error() {
+        let index = assert_ok!(Index::build(INPUT, Options::new()));
+        let mut reader = Cursor::new(b"#TITLE:Foo;\n".as_slice());
+
+        assert_err_eq!(
+            index.get::<String, _>(&mut reader, "BPMS", Options::new()),
+            Error::new(error::Kind::Io, Position::new(6, 0))
+        );
+    }
+
+    #[test]
+    fn persist() {
+        let index = assert_ok!(Index::build(INPUT, Options::new()));
+        let persisted = assert_ok!(crate::to_bytes(&index));
+
+        assert_eq!(
+            persisted,
+            b"#TITLE:8:12:1:0;\n#NOTES:20:33:2:0;\n#BPMS:53:13:6:0;\n"
+        );
+        assert_ok_eq!(crate::from_bytes::<Index>(&persisted), index);
+    }
+}
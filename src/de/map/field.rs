@@ -1,18 +1,34 @@
 use crate::de::{
     parse::{Tag, Values},
-    tuple, Error, Result,
+    path, tuple, Error, PathSegment, Result,
 };
+use alloc::string::String;
 use serde::de::{DeserializeSeed, MapAccess};
 
 pub(in crate::de) struct Access<'a, 'b> {
     tag: &'a mut Tag<'b>,
+    index: usize,
 
     values: Option<Values<'a>>,
+    key: Option<String>,
 }
 
 impl<'a, 'b> Access<'a, 'b> {
     pub(in crate::de) fn new(tag: &'a mut Tag<'b>) -> Self {
-        Self { tag, values: None }
+        Self {
+            tag,
+            index: 0,
+
+            values: None,
+            key: None,
+        }
+    }
+
+    /// Returns the index of the parameter list of the entry most recently begun.
+    fn begin_entry(&mut self) -> usize {
+        let index = self.index;
+        self.index += 1;
+        index
     }
 }
 
@@ -27,8 +43,13 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             Ok(values) => values,
             Err(_) => return Ok(None),
         };
-        let key = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        let index = self.begin_entry();
+        let name = path::key(&values);
+        let key = seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|error| error.within(PathSegment::ParameterList(index)))?;
         self.values = Some(values);
+        self.key = Some(name);
 
         Ok(Some(key))
     }
@@ -41,11 +62,18 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             .values
             .take()
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+        let name = self
+            .key
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
 
-        let value = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
-
-        Ok(value)
+        seed.deserialize(tuple::element::Deserializer::new(&mut values))
+            .and_then(|value| values.assert_exhausted().map(|()| value))
+            .map_err(|error| {
+                error
+                    .within(PathSegment::Key(name))
+                    .within(PathSegment::ParameterList(self.index - 1))
+            })
     }
 
     fn next_entry_seed<K, V>(
@@ -61,10 +89,20 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             Ok(values) => values,
             Err(_) => return Ok(None),
         };
-        let key = key_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-
-        let value = value_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
+        let index = self.begin_entry();
+        let name = path::key(&values);
+        let key = key_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|error| error.within(PathSegment::ParameterList(index)))?;
+
+        let value = value_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .and_then(|value| values.assert_exhausted().map(|()| value))
+            .map_err(|error| {
+                error
+                    .within(PathSegment::Key(name))
+                    .within(PathSegment::ParameterList(index))
+            })?;
 
         Ok(Some((key, value)))
     }
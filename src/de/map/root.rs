@@ -1,19 +1,26 @@
 use crate::de::{
+    error,
     parse::{StoredValues, Tags},
-    tuple, Error, Result,
+    path, tuple, Error, PathSegment, Result,
 };
+use crate::io::Read;
+use alloc::string::String;
 use serde::de::{DeserializeSeed, MapAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
 
     values: Option<StoredValues>,
+    key: Option<String>,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags, values: None }
+        Self {
+            tags,
+            values: None,
+            key: None,
+        }
     }
 }
 
@@ -29,12 +36,17 @@ where
     {
         let mut tag = match self.tags.next() {
             Ok(tag) => tag,
-            Err(_) => return Ok(None),
+            Err(error) if *error.kind() == error::Kind::EndOfFile => return Ok(None),
+            Err(error) => return Err(error),
         };
         let mut values = tag.next()?;
         tag.assert_exhausted()?;
-        let key = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        let name = path::key(&values);
+        let key = seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|error| error.within(PathSegment::Tag(name.clone())))?;
         self.values = Some(values.into_stored());
+        self.key = Some(name);
 
         Ok(Some(key))
     }
@@ -43,19 +55,19 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Values` will live longer than the
-        // referenced buffer.
-        let mut values = unsafe {
-            self.values
-                .take()
-                .expect("call to `next_value()` not preceeded by successful call to `next_key()`")
-                .into_values()
-        };
-
-        let value = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
-
-        Ok(value)
+        let mut values = self
+            .values
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`")
+            .into_values(self.tags.buffer());
+        let name = self
+            .key
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+
+        seed.deserialize(tuple::element::Deserializer::new(&mut values))
+            .and_then(|value| values.assert_exhausted().map(|()| value))
+            .map_err(|error| error.within(PathSegment::Tag(name)))
     }
 
     fn next_entry_seed<K, V>(
@@ -69,16 +81,23 @@ where
     {
         let mut tag = match self.tags.next() {
             Ok(tag) => tag,
-            Err(_) => return Ok(None),
+            Err(error) if *error.kind() == error::Kind::EndOfFile => return Ok(None),
+            Err(error) => return Err(error),
         };
         let mut values = tag.next()?;
         tag.assert_exhausted()?;
-        let key = key_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-
-        let value = value_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
-
-        Ok(Some((key, value)))
+        let name = path::key(&values);
+        let entry = key_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .and_then(|key| {
+                let value =
+                    value_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+                values.assert_exhausted()?;
+                Ok((key, value))
+            })
+            .map_err(|error| error.within(PathSegment::Tag(name)))?;
+
+        Ok(Some(entry))
     }
 }
 
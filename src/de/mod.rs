@@ -19,24 +19,61 @@
 //!
 //! [`Deserialize`]: serde::Deserialize
 
+mod diagnostic;
 mod r#enum;
 mod error;
+#[cfg(feature = "async")]
+mod feed;
+#[cfg(feature = "std")]
+mod index;
 mod map;
+mod options;
 mod parse;
+mod parsed;
+mod path;
 mod position;
+mod render;
+mod schema;
+mod sections;
 mod seq;
 mod r#struct;
 mod tuple;
-
-pub use error::{Error, Result};
-
-use position::Position;
+mod validate;
+mod warning;
+
+pub use crate::path::Segment as PathSegment;
+pub use diagnostic::{Diagnostic, Kind as DiagnosticKind, Severity};
+pub use error::{Error, Kind as ErrorKind, Limit, Result};
+#[cfg(feature = "std")]
+pub use index::{Entry as IndexEntry, Index};
+pub use options::Options;
+pub use position::Position;
+pub use schema::{Kind as ViolationKind, ParameterType, Schema, TagSchema, Violation};
+pub use validate::{validate, validate_as};
+pub use warning::{Kind as WarningKind, Warning};
+
+#[cfg(any(
+    feature = "diff",
+    feature = "format",
+    feature = "merge",
+    feature = "query"
+))]
+pub(crate) use parsed::ParsedTag;
+pub(crate) use parsed::ParsedTags;
+
+use crate::{io::Read, raw};
+use alloc::{borrow::ToOwned, vec::Vec};
+#[cfg(feature = "async")]
+use core::task::Poll;
+#[cfg(feature = "async")]
+use feed::Feed;
+#[cfg(feature = "async")]
+use futures_util::io::{AsyncRead, AsyncReadExt};
 use serde::{
     de,
-    de::{DeserializeOwned, Visitor},
+    de::{DeserializeOwned, SeqAccess, Visitor},
     Deserialize,
 };
-use std::io::Read;
 
 /// Deserializes data from MSD format.
 ///
@@ -71,6 +108,96 @@ impl<R> Deserializer<R> {
             tags: parse::Tags::new(reader),
         }
     }
+
+    /// Creates a `Deserializer` that parses its input according to the given [`Options`].
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"\xEF\xBB\xBF#foo;\n".as_slice(),
+    ///     msd::de::Options::lenient().report_warnings(true),
+    /// );
+    ///
+    /// assert_eq!(String::deserialize(&mut deserializer).unwrap(), "foo");
+    /// assert_eq!(deserializer.warnings().len(), 1);
+    /// ```
+    pub fn with_options(reader: R, options: Options) -> Self
+    where
+        R: Read,
+    {
+        Self {
+            tags: parse::Tags::with_options(reader, options),
+        }
+    }
+
+    /// Returns the warnings recorded so far while parsing the input.
+    ///
+    /// Warnings are only recorded if enabled through [`Options::report_warnings()`].
+    pub fn warnings(&self) -> &[Warning] {
+        self.tags.warnings()
+    }
+}
+
+impl<R> Deserializer<R>
+where
+    R: Read,
+{
+    /// Deserializes a document split into sections, each beginning with a tag named `delimiter`.
+    ///
+    /// The tags before the first delimiter are deserialized as the header `H`, which must be a
+    /// struct, or `()` if the document has no header. Each section is then deserialized as an `S`,
+    /// in the same way as an element of a sequence of structs stored in a field named `delimiter`:
+    /// the delimiter tag itself, usually empty as in `#NOTEDATA:;`, is followed by the section's
+    /// fields. A tag that is not a field of `S` ends its section, and is an error unless it is
+    /// another delimiter.
+    ///
+    /// This is how formats such as `.ssc` repeat a group of tags for each chart.
+    ///
+    /// # Example
+    /// ```
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Song {
+    ///     #[serde(rename = "TITLE")]
+    ///     title: String,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Chart {
+    ///     #[serde(rename = "METER")]
+    ///     meter: u8,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::new(
+    ///     b"#TITLE:Foo;\n#NOTEDATA:;\n#METER:3;\n#NOTEDATA:;\n#METER:9;\n".as_slice(),
+    /// );
+    /// let (song, charts) = deserializer
+    ///     .deserialize_sections::<Song, Chart>("NOTEDATA")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(song, Song { title: "Foo".to_owned() });
+    /// assert_eq!(charts, vec![Chart { meter: 3 }, Chart { meter: 9 }]);
+    /// ```
+    pub fn deserialize_sections<'de, H, S>(&mut self, delimiter: &str) -> Result<(H, Vec<S>)>
+    where
+        H: Deserialize<'de>,
+        S: Deserialize<'de>,
+    {
+        let header = H::deserialize(sections::Header::new(&mut self.tags, delimiter))?;
+        let mut sections = Vec::new();
+        let mut access = seq::field::Access::new(delimiter, &mut self.tags);
+        while let Some(section) = access
+            .next_element()
+            .map_err(|error| error.within(PathSegment::Tag(delimiter.to_owned())))?
+        {
+            sections.push(section);
+        }
+        self.tags.assert_exhausted()?;
+        Ok((header, sections))
+    }
 }
 
 impl<'de, 'a, R> de::Deserializer<'de> for &'a mut Deserializer<R>
@@ -463,10 +590,24 @@ where
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == raw::TOKEN {
+            // Capture the entire tag verbatim.
+            let mut tag = self.tags.next()?;
+            let mut values = tag.next()?;
+            let value = values.rest()?;
+            let value_position = value.position();
+            let parsed = value.parse_raw();
+            tag.assert_exhausted()?;
+            self.tags.assert_exhausted()?;
+            return visitor.visit_byte_buf(parsed).map_err(|mut error: Error| {
+                error.set_position(value_position);
+                error
+            });
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -522,13 +663,13 @@ where
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let result = visitor.visit_map(r#struct::root::Access::new(&mut self.tags))?;
+        let result = visitor.visit_map(r#struct::root::Access::new(&mut self.tags, fields))?;
         self.tags.assert_exhausted()?;
         Ok(result)
     }
@@ -580,10 +721,6 @@ where
     }
 }
 
-unsafe impl<R> Send for Deserializer<R> {}
-
-unsafe impl<R> Sync for Deserializer<R> {}
-
 /// Deserialize a value of type `T` from the given `reader`.
 pub fn from_reader<R, T>(reader: R) -> Result<T>
 where
@@ -594,6 +731,79 @@ where
     T::deserialize(&mut deserializer)
 }
 
+/// Deserialize a value of type `T` from the given asynchronous `reader`.
+///
+/// See [`from_async_reader_with_options()`] for details.
+///
+/// # Example
+/// ```
+/// # async fn example() {
+/// let deserialized: u64 = msd::de::from_async_reader(b"#42;\n".as_slice()).await.unwrap();
+///
+/// assert_eq!(deserialized, 42);
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn from_async_reader<R, T>(reader: R) -> Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    from_async_reader_with_options(reader, Options::new()).await
+}
+
+/// Deserialize a value of type `T` from the given asynchronous `reader`, using the given
+/// `options`.
+///
+/// Deserialization is not incremental: since it is synchronous, the whole stream is read into
+/// memory first, and the value is only deserialized once the end of the stream is reached. While
+/// the stream is being read, the limits configured through `options` are enforced and
+/// `options`' requirements on the format are checked, so that input violating them is rejected
+/// without reading the rest of the stream.
+///
+/// # Example
+/// ```
+/// use msd::de::{ErrorKind, Limit, Options};
+///
+/// # async fn example() {
+/// let error = msd::de::from_async_reader_with_options::<_, Vec<u64>>(
+///     b"#1;\n#2;\n#3;\n".as_slice(),
+///     Options::new().max_tags(2),
+/// )
+/// .await
+/// .unwrap_err();
+///
+/// assert_eq!(*error.kind(), ErrorKind::LimitExceeded(Limit::Tags));
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn from_async_reader_with_options<R, T>(mut reader: R, options: Options) -> Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut feed = Feed::new();
+    let mut tags = parse::Tags::with_options(&mut feed, options.clone());
+    let mut chunk = [0; 1024];
+    loop {
+        match tags.poll_exhausted() {
+            Poll::Ready(Ok(())) => break,
+            Poll::Ready(Err(error)) => return Err(error),
+            // The tags scanned so far are waiting on the next chunk.
+            Poll::Pending => {}
+        }
+        match reader.read(&mut chunk).await {
+            Ok(0) => tags.reader_mut().finish(),
+            Ok(len) => tags.reader_mut().extend(&chunk[..len]),
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            // Every byte read so far has been scanned, so the error is at the end of them.
+            Err(_) => return Err(Error::new(error::Kind::Io, tags.current_position())),
+        }
+    }
+    let mut deserializer = Deserializer::with_options(feed.as_slice(), options);
+    T::deserialize(&mut deserializer)
+}
+
 /// Deserialize a value of type `T` from a slice of bytes.
 pub fn from_bytes<'a, T>(bytes: &'a [u8]) -> Result<T>
 where
@@ -603,14 +813,31 @@ where
     T::deserialize(&mut deserializer)
 }
 
+/// Deserialize a header of type `H` followed by sections of type `S` from a slice of bytes, each
+/// section beginning with a tag named `delimiter`.
+///
+/// See [`Deserializer::deserialize_sections()`] for details.
+pub fn from_bytes_sections<'a, H, S>(bytes: &'a [u8], delimiter: &str) -> Result<(H, Vec<S>)>
+where
+    H: Deserialize<'a>,
+    S: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(bytes);
+    deserializer.deserialize_sections(delimiter)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{error, Deserializer, Error, Position};
+    use super::{error, Deserializer, Error, Limit, Options, PathSegment, Position};
+    use crate::{RawValue, UnrecognizedTags};
     use claims::{assert_err_eq, assert_ok_eq};
     use serde::{de, de::Visitor, Deserialize};
     use serde_bytes::ByteBuf;
     use serde_derive::Deserialize;
-    use std::{collections::HashMap, fmt};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+    };
 
     #[test]
     fn bool_true() {
@@ -3005,4 +3232,886 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn extended_lexicon() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            selectable: bool,
+            offset: i32,
+            bpms: Vec<(u32, bool)>,
+        }
+
+        let mut deserializer = Deserializer::with_options(
+            b"#SELECTABLE:YES;\n#OFFSET:+12.000;\n#BPMS:0:on;\n#BPMS:+4.0:0;\n".as_slice(),
+            Options::new()
+                .allow_extended_bools(true)
+                .allow_plus_signs(true)
+                .allow_zero_fraction_integers(true),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                selectable: true,
+                offset: 12,
+                bpms: vec![(0, true), (4, false)],
+            }
+        );
+    }
+
+    #[test]
+    fn extended_lexicon_not_allowed_by_default() {
+        let mut deserializer = Deserializer::new(b"#YES;".as_slice());
+
+        assert_err_eq!(
+            bool::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedBool, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn extended_lexicon_not_allowed_by_lenient() {
+        let mut deserializer = Deserializer::with_options(b"#YES;".as_slice(), Options::lenient());
+
+        assert_err_eq!(
+            bool::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedBool, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn raw_value_root() {
+        let mut deserializer = Deserializer::new(b"#foo\\#: bar // comment\n:baz;\n".as_slice());
+
+        assert_ok_eq!(
+            RawValue::deserialize(&mut deserializer),
+            RawValue::new(&b"foo\\#: bar // comment\n:baz"[..])
+        );
+    }
+
+    #[test]
+    fn raw_value_struct_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            title: String,
+            notes: RawValue,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#TITLE: foo ;\n#NOTES:\n  dance-single:\n  0000\\;\n  1000 // comment\n;\n"
+                .as_slice(),
+        );
+
+        assert_ok_eq!(
+            Chart::deserialize(&mut deserializer),
+            Chart {
+                title: " foo ".to_owned(),
+                notes: RawValue::new(&b"\n  dance-single:\n  0000\\;\n  1000 // comment\n"[..]),
+            }
+        );
+    }
+
+    #[test]
+    fn raw_value_seq_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            notes: Vec<RawValue>,
+        }
+        let mut deserializer = Deserializer::new(b"#NOTES:foo:bar;\n#NOTES: baz ;\n".as_slice());
+
+        assert_ok_eq!(
+            Chart::deserialize(&mut deserializer),
+            Chart {
+                notes: vec![RawValue::new(&b"foo:bar"[..]), RawValue::new(&b" baz "[..])],
+            }
+        );
+    }
+
+    #[test]
+    fn raw_value_tuple_element() {
+        let mut deserializer = Deserializer::new(b"#42: foo\\:bar ;\n".as_slice());
+
+        assert_ok_eq!(
+            <(u64, RawValue)>::deserialize(&mut deserializer),
+            (42, RawValue::new(&b" foo\\:bar "[..]))
+        );
+    }
+
+    #[test]
+    fn raw_value_missing_field_value() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            notes: RawValue,
+        }
+        let mut deserializer = Deserializer::new(b"#NOTES;\n".as_slice());
+
+        assert_err_eq!(
+            Chart::deserialize(&mut deserializer),
+            Error::new(error::Kind::EndOfValues, Position::new(0, 6))
+                .within(PathSegment::Tag("NOTES".to_owned()))
+        );
+    }
+
+    #[test]
+    fn unrecognized_tags() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#ARTIST:foo\\:bar;\n#TITLE:baz;\n#BPMS:0.000=120.000 // comment\n;\n#EMPTY;\n"
+                .as_slice(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "baz".to_owned(),
+                unrecognized: vec![
+                    RawValue::new(&b"ARTIST:foo\\:bar"[..]),
+                    RawValue::new(&b"BPMS:0.000=120.000 // comment\n"[..]),
+                    RawValue::new(&b"EMPTY"[..]),
+                ]
+                .into_iter()
+                .collect(),
+            }
+        );
+    }
+
+    #[test]
+    fn unrecognized_tags_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let mut deserializer = Deserializer::new(b"#TITLE:foo;\n".as_slice());
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                unrecognized: UnrecognizedTags::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn unrecognized_tags_in_seq_of_structs() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            notes: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#;\n#NOTES:foo;\n#METER:1;\n#;\n#NOTES:bar;\n#;\n#METER:2;\n#NOTES:baz;\n".as_slice(),
+        );
+
+        assert_ok_eq!(
+            Vec::<Chart>::deserialize(&mut deserializer),
+            vec![
+                Chart {
+                    notes: "foo".to_owned(),
+                    unrecognized: vec![RawValue::new(&b"METER:1"[..])].into_iter().collect(),
+                },
+                Chart {
+                    notes: "bar".to_owned(),
+                    unrecognized: UnrecognizedTags::new(),
+                },
+                Chart {
+                    notes: "baz".to_owned(),
+                    unrecognized: vec![RawValue::new(&b"METER:2"[..])].into_iter().collect(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_and_sync<T: Send + Sync>() {}
+
+        assert_send_and_sync::<Deserializer<&[u8]>>();
+    }
+
+    #[test]
+    fn revisited_tags_survive_moving_deserializer() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Chart {
+            notes: Vec<u64>,
+            bpm: u64,
+        }
+
+        let mut deserializer = Deserializer::new(b"#notes:1;\n#notes:2;\n#bpm:3;\n".as_slice());
+        let handle = std::thread::spawn(move || Chart::deserialize(&mut deserializer));
+
+        assert_ok_eq!(
+            handle.join().unwrap(),
+            Chart {
+                notes: vec![1, 2],
+                bpm: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            offset: u32,
+        }
+        let mut deserializer = Deserializer::new(b"#TITLE:foo;\n#OFFSET:abc;\n".as_slice());
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedU32, Position::new(1, 8))
+                .within(PathSegment::Tag("OFFSET".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_parameter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            bpms: (f32, f32),
+        }
+        let mut deserializer = Deserializer::new(b"#BPMS:0.000:abc;\n".as_slice());
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedF32, Position::new(0, 12))
+                .within(PathSegment::Parameter(1))
+                .within(PathSegment::Tag("BPMS".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_seq() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            notes: Vec<(String, u8)>,
+        }
+        let mut deserializer = Deserializer::new(b"#NOTES:foo:1;\n#NOTES:bar:x;\n".as_slice());
+
+        assert_err_eq!(
+            Chart::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedU8, Position::new(1, 11))
+                .within(PathSegment::Parameter(1))
+                .within(PathSegment::Element(1))
+                .within(PathSegment::Tag("NOTES".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_map() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            bpms: HashMap<String, f64>,
+        }
+        let mut deserializer = Deserializer::new(b"#BPMS:0.000:120;1.000:x;\n".as_slice());
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedF64, Position::new(0, 22))
+                .within(PathSegment::Key("1.000".to_owned()))
+                .within(PathSegment::ParameterList(1))
+                .within(PathSegment::Tag("BPMS".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_root_seq() {
+        let mut deserializer = Deserializer::new(b"#1;\n#2;\n#x;\n".as_slice());
+
+        assert_err_eq!(
+            Vec::<u8>::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedU8, Position::new(2, 1))
+                .within(PathSegment::Element(2))
+        );
+    }
+
+    #[test]
+    fn error_path_root_map() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#bar:x;\n".as_slice());
+
+        assert_err_eq!(
+            HashMap::<String, u8>::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedU8, Position::new(1, 5))
+                .within(PathSegment::Tag("bar".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_display() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            bpms: (f32, f32),
+        }
+
+        assert_eq!(
+            format!(
+                "{}",
+                Song::deserialize(&mut Deserializer::new(b"#BPMS:0.000:abc;\n".as_slice()))
+                    .unwrap_err()
+            ),
+            "expected f32 at line 0 column 12 in tag `BPMS`, parameter 1"
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "UPPERCASE")]
+    struct Chart {
+        stepstype: String,
+        meter: u8,
+    }
+
+    #[test]
+    fn sections() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            offset: f64,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#TITLE:foo;\n#OFFSET:-0.5;\n#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n#NOTEDATA:;\n#METER:9;\n#STEPSTYPE:dance-double;\n"
+                .as_slice(),
+        );
+
+        assert_ok_eq!(
+            deserializer.deserialize_sections::<Song, Chart>("NOTEDATA"),
+            (
+                Song {
+                    title: "foo".to_owned(),
+                    offset: -0.5,
+                },
+                vec![
+                    Chart {
+                        stepstype: "dance-single".to_owned(),
+                        meter: 3,
+                    },
+                    Chart {
+                        stepstype: "dance-double".to_owned(),
+                        meter: 9,
+                    },
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn sections_without_header() {
+        let mut deserializer =
+            Deserializer::new(b"#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n".as_slice());
+
+        assert_ok_eq!(
+            deserializer.deserialize_sections::<(), Chart>("NOTEDATA"),
+            (
+                (),
+                vec![Chart {
+                    stepstype: "dance-single".to_owned(),
+                    meter: 3,
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn sections_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+        }
+        let mut deserializer = Deserializer::new(b"#TITLE:foo;\n".as_slice());
+
+        assert_ok_eq!(
+            deserializer.deserialize_sections::<Song, Chart>("NOTEDATA"),
+            (
+                Song {
+                    title: "foo".to_owned()
+                },
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn sections_unrecognized_tags_in_header() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#TITLE:foo;\n#ARTIST:bar;\n#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n"
+                .as_slice(),
+        );
+
+        assert_ok_eq!(
+            deserializer.deserialize_sections::<Song, Chart>("NOTEDATA"),
+            (
+                Song {
+                    title: "foo".to_owned(),
+                    unrecognized: vec![RawValue::new(&b"ARTIST:bar"[..])]
+                        .into_iter()
+                        .collect(),
+                },
+                vec![Chart {
+                    stepstype: "dance-single".to_owned(),
+                    meter: 3,
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn sections_unexpected_tag() {
+        let mut deserializer = Deserializer::new(
+            b"#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n#CREDIT:foo;\n".as_slice(),
+        );
+
+        assert_err_eq!(
+            deserializer.deserialize_sections::<(), Chart>("NOTEDATA"),
+            Error::new(error::Kind::UnexpectedTag, Position::new(3, 0))
+        );
+    }
+
+    #[test]
+    fn sections_error_path() {
+        let mut deserializer = Deserializer::new(
+            b"#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:abc;\n"
+                .as_slice(),
+        );
+
+        assert_err_eq!(
+            deserializer.deserialize_sections::<(), Chart>("NOTEDATA"),
+            Error::new(error::Kind::ExpectedU8, Position::new(5, 7))
+                .within(PathSegment::Tag("METER".to_owned()))
+                .within(PathSegment::Element(1))
+                .within(PathSegment::Tag("NOTEDATA".to_owned()))
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Strict {
+        a: Option<String>,
+        b: Option<String>,
+    }
+
+    #[test]
+    fn strict_missing_semicolon_root_seq() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x\n#b:y;\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_semicolon_root_seq_last_tag() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#b:y\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingSemicolon, Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_semicolon_root_map() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x\n#b:y;\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            BTreeMap::<String, String>::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_semicolon_root_struct() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#b:y\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Strict::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingSemicolon, Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_semicolon_struct_field_seq() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            a: Vec<String>,
+        }
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#a:y\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingSemicolon, Position::new(2, 0))
+                .within(PathSegment::Tag("a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn strict_unescaped_number_sign_root_struct() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#b:y#z;\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Strict::deserialize(&mut deserializer),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(1, 4))
+        );
+    }
+
+    #[test]
+    fn strict_unescaped_number_sign_root_seq() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x#y;\n#b:z;\n".as_slice(), Options::strict());
+
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 4))
+        );
+    }
+
+    #[test]
+    fn strict_conforming_input() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#b:y;\n".as_slice(), Options::strict());
+
+        assert_ok_eq!(
+            Strict::deserialize(&mut deserializer),
+            Strict {
+                a: Some("x".to_owned()),
+                b: Some("y".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn max_tags_root_seq() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a;\n#b;\n".as_slice(), Options::new().max_tags(1));
+
+        assert_err_eq!(
+            Vec::<String>::deserialize(&mut deserializer),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn max_tags_root_map() {
+        let mut deserializer = Deserializer::with_options(
+            b"#a:1;\n#b:2;\n#c:3;\n".as_slice(),
+            Options::new().max_tags(2),
+        );
+
+        assert_err_eq!(
+            BTreeMap::<String, u64>::deserialize(&mut deserializer),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn max_tags_root_struct() {
+        let mut deserializer =
+            Deserializer::with_options(b"#a:x;\n#b:y;\n".as_slice(), Options::new().max_tags(1));
+
+        assert_err_eq!(
+            Strict::deserialize(&mut deserializer),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn max_tag_bytes_root_struct() {
+        let mut deserializer = Deserializer::with_options(
+            b"#a:x;\n#b:yyyyyyyy".as_slice(),
+            Options::new().max_tag_bytes(6),
+        );
+
+        assert_err_eq!(
+            Strict::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagBytes),
+                Position::new(1, 6)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameters_struct_field_seq() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            a: Vec<String>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#a:x;\n#a:y:z;\n".as_slice(),
+            Options::new().max_parameters(1),
+        );
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(1, 4)
+            )
+            .within(PathSegment::Tag("a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn max_input_bytes_root_seq() {
+        let mut deserializer = Deserializer::with_options(
+            b"#a:x;\n#b:y;\n".as_slice(),
+            Options::new().max_input_bytes(8),
+        );
+
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::InputBytes),
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use crate::{
+            block_on::block_on,
+            de::{
+                error, from_async_reader, from_async_reader_with_options, Error, Limit, Options,
+                PathSegment, Position,
+            },
+        };
+        use claims::{assert_err_eq, assert_ok_eq};
+        use futures_util::io::AsyncRead;
+        use serde_derive::Deserialize;
+        use std::{
+            io,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        /// Reads at most `chunk_size` bytes at a time, returning `Poll::Pending` before each chunk.
+        struct ChunkedReader<'a> {
+            bytes: &'a [u8],
+            chunk_size: usize,
+            pending: bool,
+        }
+
+        impl<'a> ChunkedReader<'a> {
+            fn new(bytes: &'a [u8], chunk_size: usize) -> Self {
+                Self {
+                    bytes,
+                    chunk_size,
+                    pending: true,
+                }
+            }
+        }
+
+        impl AsyncRead for ChunkedReader<'_> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                _context: &mut Context,
+                buffer: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                if self.pending {
+                    self.pending = false;
+                    return Poll::Pending;
+                }
+                self.pending = true;
+                let len = self.chunk_size.min(buffer.len()).min(self.bytes.len());
+                buffer[..len].copy_from_slice(&self.bytes[..len]);
+                self.bytes = &self.bytes[len..];
+                Poll::Ready(Ok(len))
+            }
+        }
+
+        /// Reads `bytes`, then fails.
+        struct FailingReader<'a> {
+            bytes: &'a [u8],
+        }
+
+        impl AsyncRead for FailingReader<'_> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                _context: &mut Context,
+                buffer: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                if self.bytes.is_empty() {
+                    Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "failed")))
+                } else {
+                    let len = buffer.len().min(self.bytes.len());
+                    buffer[..len].copy_from_slice(&self.bytes[..len]);
+                    self.bytes = &self.bytes[len..];
+                    Poll::Ready(Ok(len))
+                }
+            }
+        }
+
+        /// Repeats `bytes` endlessly, reading at most one repetition at a time.
+        struct EndlessReader<'a> {
+            bytes: &'a [u8],
+            read: usize,
+        }
+
+        impl AsyncRead for EndlessReader<'_> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                _context: &mut Context,
+                buffer: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                let offset = self.read % self.bytes.len();
+                let len = buffer.len().min(self.bytes.len() - offset);
+                buffer[..len].copy_from_slice(&self.bytes[offset..offset + len]);
+                self.read += len;
+                Poll::Ready(Ok(len))
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            bpm: f64,
+        }
+
+        #[test]
+        fn slice() {
+            assert_ok_eq!(
+                block_on(from_async_reader::<_, u64>(b"#42;\n".as_slice())),
+                42
+            );
+        }
+
+        #[test]
+        fn chunked() {
+            assert_ok_eq!(
+                block_on(from_async_reader::<_, Song>(ChunkedReader::new(
+                    b"#title:foo;\n#bpm:120;\n",
+                    3
+                ))),
+                Song {
+                    title: "foo".to_owned(),
+                    bpm: 120.0,
+                }
+            );
+        }
+
+        #[test]
+        fn empty() {
+            assert_err_eq!(
+                block_on(from_async_reader::<_, u64>(ChunkedReader::new(b"", 3))),
+                Error::new(error::Kind::EndOfFile, Position::new(0, 0))
+            );
+        }
+
+        #[test]
+        fn deserialization_error() {
+            assert_err_eq!(
+                block_on(from_async_reader::<_, Song>(ChunkedReader::new(
+                    b"#title:foo;\n#bpm:bar;\n",
+                    3
+                ))),
+                Error::new(error::Kind::ExpectedF64, Position::new(1, 5))
+                    .within(PathSegment::Tag("bpm".to_owned()))
+            );
+        }
+
+        #[test]
+        fn max_input_bytes_endless() {
+            let mut reader = EndlessReader {
+                bytes: b"#title:foo;\n",
+                read: 0,
+            };
+
+            assert_err_eq!(
+                block_on(from_async_reader_with_options::<_, Vec<(String, String)>>(
+                    &mut reader,
+                    Options::new().max_input_bytes(100)
+                )),
+                Error::new(
+                    error::Kind::LimitExceeded(Limit::InputBytes),
+                    Position::new(8, 4)
+                )
+            );
+            // Reading stopped with the chunk exceeding the limit.
+            assert_eq!(reader.read, 108);
+        }
+
+        #[test]
+        fn max_tag_bytes_chunked() {
+            assert_err_eq!(
+                block_on(from_async_reader_with_options::<_, Song>(
+                    ChunkedReader::new(b"#title:foo;\n#bpm:120;\n", 3),
+                    Options::new().max_tag_bytes(10)
+                )),
+                Error::new(
+                    error::Kind::LimitExceeded(Limit::TagBytes),
+                    Position::new(0, 10)
+                )
+            );
+        }
+
+        #[test]
+        fn strict_missing_semicolon() {
+            assert_err_eq!(
+                block_on(from_async_reader_with_options::<_, Song>(
+                    ChunkedReader::new(b"#title:foo\n#bpm:120;\n", 3),
+                    Options::strict()
+                )),
+                Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+            );
+        }
+
+        #[test]
+        fn strict_well_formed() {
+            assert_ok_eq!(
+                block_on(from_async_reader_with_options::<_, Song>(
+                    ChunkedReader::new(b"#title:foo;\n#bpm:120;\n", 3),
+                    Options::strict()
+                )),
+                Song {
+                    title: "foo".to_owned(),
+                    bpm: 120.0,
+                }
+            );
+        }
+
+        #[test]
+        fn io_error() {
+            assert_err_eq!(
+                block_on(from_async_reader::<_, Song>(FailingReader {
+                    bytes: b"#title:foo;\n#bpm"
+                })),
+                Error::new(error::Kind::Io, Position::new(1, 4))
+            );
+        }
+    }
 }
@@ -0,0 +1,353 @@
+use crate::de::Limit;
+
+/// Options controlling how strictly MSD input is parsed.
+///
+/// MSD has never been formally specified, and many files in the wild deviate from the format
+/// informally described in the [`crate`] documentation. These options determine which deviations
+/// are recovered from and which are reported as errors.
+///
+/// The default options match the behavior of [`Deserializer::new()`]: a tag missing its closing
+/// `;` is recovered from when a `#` begins a new line, a `#` in the middle of a line is treated as
+/// though it was escaped, and any content before the first tag is an error.
+///
+/// Limits on the size of the input can also be set, which should be done when reading untrusted
+/// input. Input exceeding a limit results in an [`ErrorKind::LimitExceeded`] error. None of the
+/// preset options have any limits.
+///
+/// # Example
+/// ```
+/// use serde::Deserialize;
+///
+/// let mut deserializer =
+///     msd::Deserializer::with_options(b"#foo:bar\n".as_slice(), msd::de::Options::strict());
+///
+/// assert!(String::deserialize(&mut deserializer).is_err());
+/// ```
+///
+/// [`Deserializer::new()`]: crate::Deserializer::new()
+/// [`ErrorKind::LimitExceeded`]: crate::de::ErrorKind::LimitExceeded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub(in crate::de) lexicon: Lexicon,
+    pub(in crate::de) allow_missing_semicolons: bool,
+    pub(in crate::de) allow_unescaped_number_signs: bool,
+    pub(in crate::de) skip_content_before_first_tag: bool,
+    pub(in crate::de) skip_content_between_tags: bool,
+    pub(in crate::de) report_warnings: bool,
+    pub(in crate::de) limits: Limits,
+}
+
+impl Options {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        Self {
+            lexicon: Lexicon::new(),
+            allow_missing_semicolons: true,
+            allow_unescaped_number_signs: true,
+            skip_content_before_first_tag: false,
+            skip_content_between_tags: false,
+            report_warnings: false,
+            limits: Limits::new(),
+        }
+    }
+
+    /// Returns options that only accept input conforming to the MSD format.
+    ///
+    /// A tag missing its closing `;` and an unescaped `#` within a tag are both errors.
+    pub fn strict() -> Self {
+        Self {
+            lexicon: Lexicon::new(),
+            allow_missing_semicolons: false,
+            allow_unescaped_number_signs: false,
+            skip_content_before_first_tag: false,
+            skip_content_between_tags: false,
+            report_warnings: false,
+            limits: Limits::new(),
+        }
+    }
+
+    /// Returns options that recover from as much malformed input as possible.
+    ///
+    /// In addition to the default recoveries, any content before the first tag (such as a byte
+    /// order mark) is skipped, as is any unterminated content following a tag's final `;`.
+    ///
+    /// Only the syntax of the input is recovered from; additional spellings of scalar values must
+    /// be enabled separately, through [`allow_extended_bools()`], [`allow_plus_signs()`], and
+    /// [`allow_zero_fraction_integers()`].
+    ///
+    /// [`allow_extended_bools()`]: Options::allow_extended_bools()
+    /// [`allow_plus_signs()`]: Options::allow_plus_signs()
+    /// [`allow_zero_fraction_integers()`]: Options::allow_zero_fraction_integers()
+    pub fn lenient() -> Self {
+        Self {
+            lexicon: Lexicon::new(),
+            allow_missing_semicolons: true,
+            allow_unescaped_number_signs: true,
+            skip_content_before_first_tag: true,
+            skip_content_between_tags: true,
+            report_warnings: false,
+            limits: Limits::new(),
+        }
+    }
+
+    /// Sets whether a tag may be missing its closing `;`.
+    ///
+    /// When allowed, a `#` at the beginning of a line or the end of the input will end the
+    /// current tag.
+    pub fn allow_missing_semicolons(mut self, allow: bool) -> Self {
+        self.allow_missing_semicolons = allow;
+        self
+    }
+
+    /// Sets whether a `#` in the middle of a line may be left unescaped.
+    ///
+    /// When allowed, the `#` is interpreted as though it was escaped.
+    pub fn allow_unescaped_number_signs(mut self, allow: bool) -> Self {
+        self.allow_unescaped_number_signs = allow;
+        self
+    }
+
+    /// Sets whether content before the first tag is skipped rather than treated as an error.
+    pub fn skip_content_before_first_tag(mut self, skip: bool) -> Self {
+        self.skip_content_before_first_tag = skip;
+        self
+    }
+
+    /// Sets whether content between tags is skipped.
+    ///
+    /// Content between tags is any text following a tag's final `;` that is not itself
+    /// terminated by a `;` before the next tag begins. When not skipped, such content is
+    /// interpreted as an additional parameter list missing its closing `;`.
+    pub fn skip_content_between_tags(mut self, skip: bool) -> Self {
+        self.skip_content_between_tags = skip;
+        self
+    }
+
+    /// Sets whether additional spellings of `bool` values are accepted.
+    ///
+    /// When allowed, `yes`/`no`, `on`/`off`, and `1`/`0` are accepted in addition to
+    /// `true`/`false`, all without regard to case.
+    pub fn allow_extended_bools(mut self, allow: bool) -> Self {
+        self.lexicon.allow_extended_bools = allow;
+        self
+    }
+
+    /// Sets whether integers may be written with a leading `+`.
+    pub fn allow_plus_signs(mut self, allow: bool) -> Self {
+        self.lexicon.allow_plus_signs = allow;
+        self
+    }
+
+    /// Sets whether integers may be written with a fractional part made up entirely of zeros,
+    /// such as `1.000`.
+    pub fn allow_zero_fraction_integers(mut self, allow: bool) -> Self {
+        self.lexicon.allow_zero_fraction_integers = allow;
+        self
+    }
+
+    /// Sets whether each recovery from malformed input is recorded as a [`Warning`].
+    ///
+    /// Recorded warnings can be retrieved using [`Deserializer::warnings()`].
+    ///
+    /// [`Deserializer::warnings()`]: crate::Deserializer::warnings()
+    /// [`Warning`]: crate::de::Warning
+    pub fn report_warnings(mut self, report: bool) -> Self {
+        self.report_warnings = report;
+        self
+    }
+
+    /// Sets the maximum length of a single tag in bytes, including its `#`.
+    ///
+    /// Each tag is buffered in its entirety while it is read, so this bounds the memory used when
+    /// reading input that is missing its delimiters.
+    pub fn max_tag_bytes(mut self, max: usize) -> Self {
+        self.limits.tag_bytes = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of tags in the input.
+    pub fn max_tags(mut self, max: usize) -> Self {
+        self.limits.tags = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of parameter lists within a single tag.
+    pub fn max_parameter_lists(mut self, max: usize) -> Self {
+        self.limits.parameter_lists = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of parameters within a single tag, counted across all of its
+    /// parameter lists and not including the tag's name.
+    pub fn max_parameters(mut self, max: usize) -> Self {
+        self.limits.parameters = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of the entire input in bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::{ErrorKind, Limit, Options};
+    /// use serde::Deserialize;
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#foo:bar;\n".as_slice(),
+    ///     Options::new().max_input_bytes(4),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     String::deserialize(&mut deserializer).unwrap_err().kind(),
+    ///     &ErrorKind::LimitExceeded(Limit::InputBytes)
+    /// );
+    /// ```
+    pub fn max_input_bytes(mut self, max: usize) -> Self {
+        self.limits.input_bytes = Some(max);
+        self
+    }
+}
+
+/// The spellings accepted when parsing scalar values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::de) struct Lexicon {
+    pub(in crate::de) allow_extended_bools: bool,
+    pub(in crate::de) allow_plus_signs: bool,
+    pub(in crate::de) allow_zero_fraction_integers: bool,
+}
+
+impl Lexicon {
+    pub(in crate::de) fn new() -> Self {
+        Self {
+            allow_extended_bools: false,
+            allow_plus_signs: false,
+            allow_zero_fraction_integers: false,
+        }
+    }
+}
+
+/// The limits on the size of the input, each of which is unlimited when `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::de) struct Limits {
+    pub(in crate::de) tag_bytes: Option<usize>,
+    pub(in crate::de) tags: Option<usize>,
+    pub(in crate::de) parameter_lists: Option<usize>,
+    pub(in crate::de) parameters: Option<usize>,
+    pub(in crate::de) input_bytes: Option<usize>,
+}
+
+impl Limits {
+    pub(in crate::de) fn new() -> Self {
+        Self {
+            tag_bytes: None,
+            tags: None,
+            parameter_lists: None,
+            parameters: None,
+            input_bytes: None,
+        }
+    }
+
+    /// Returns the maximum allowed for `limit`, if there is one.
+    pub(in crate::de) fn get(&self, limit: Limit) -> Option<usize> {
+        match limit {
+            Limit::TagBytes => self.tag_bytes,
+            Limit::Tags => self.tags,
+            Limit::ParameterLists => self.parameter_lists,
+            Limit::Parameters => self.parameters,
+            Limit::InputBytes => self.input_bytes,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lexicon, Options};
+    use crate::de::Limit;
+
+    #[test]
+    fn default() {
+        assert_eq!(Options::default(), Options::new());
+    }
+
+    #[test]
+    fn strict() {
+        let options = Options::strict();
+
+        assert!(!options.allow_missing_semicolons);
+        assert!(!options.allow_unescaped_number_signs);
+        assert!(!options.skip_content_before_first_tag);
+        assert!(!options.skip_content_between_tags);
+        assert!(!options.lexicon.allow_extended_bools);
+        assert!(!options.lexicon.allow_plus_signs);
+        assert!(!options.lexicon.allow_zero_fraction_integers);
+    }
+
+    #[test]
+    fn lenient() {
+        let options = Options::lenient();
+
+        assert!(options.allow_missing_semicolons);
+        assert!(options.allow_unescaped_number_signs);
+        assert!(options.skip_content_before_first_tag);
+        assert!(options.skip_content_between_tags);
+        assert!(!options.lexicon.allow_extended_bools);
+        assert!(!options.lexicon.allow_plus_signs);
+        assert!(!options.lexicon.allow_zero_fraction_integers);
+    }
+
+    #[test]
+    fn setters() {
+        let options = Options::strict()
+            .allow_missing_semicolons(true)
+            .allow_unescaped_number_signs(true)
+            .skip_content_before_first_tag(true)
+            .skip_content_between_tags(true)
+            .report_warnings(true);
+
+        assert_eq!(options, Options::lenient().report_warnings(true));
+        assert_eq!(
+            Options::new()
+                .allow_extended_bools(true)
+                .allow_plus_signs(true)
+                .allow_zero_fraction_integers(true)
+                .lexicon,
+            Lexicon {
+                allow_extended_bools: true,
+                allow_plus_signs: true,
+                allow_zero_fraction_integers: true,
+            }
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let options = Options::new()
+            .max_tag_bytes(1)
+            .max_tags(2)
+            .max_parameter_lists(3)
+            .max_parameters(4)
+            .max_input_bytes(5);
+
+        assert_eq!(options.limits.get(Limit::TagBytes), Some(1));
+        assert_eq!(options.limits.get(Limit::Tags), Some(2));
+        assert_eq!(options.limits.get(Limit::ParameterLists), Some(3));
+        assert_eq!(options.limits.get(Limit::Parameters), Some(4));
+        assert_eq!(options.limits.get(Limit::InputBytes), Some(5));
+    }
+
+    #[test]
+    fn no_limits() {
+        let options = Options::new();
+
+        assert_eq!(options.limits.get(Limit::TagBytes), None);
+        assert_eq!(options.limits.get(Limit::Tags), None);
+        assert_eq!(options.limits.get(Limit::ParameterLists), None);
+        assert_eq!(options.limits.get(Limit::Parameters), None);
+        assert_eq!(options.limits.get(Limit::InputBytes), None);
+    }
+}
@@ -1,20 +1,15 @@
 use super::{StoredValues, Values};
-use crate::de::{error, Error, Position, Result};
-use std::slice;
-
-enum State {
-    None,
-    MaybeEnteringComment,
-    InComment,
-    Escaping,
-}
+use crate::{
+    de::{error, options::Lexicon, Error, Position, Result},
+    scan::{Class, Scanner},
+};
 
 // Tag without the lifetime. Used when storing within an Access.
+//
+// A tag always spans the entire buffer of the `Tags` it was read from, so only the iteration state
+// is stored, as offsets into that buffer.
 #[derive(Debug)]
 pub(in crate::de) struct StoredTag {
-    byte_ptr: *const u8,
-    byte_len: usize,
-
     first_values: bool,
 
     current_byte_index: usize,
@@ -22,18 +17,25 @@ pub(in crate::de) struct StoredTag {
 
     origin_position: Position,
 
+    lexicon: Lexicon,
+
     revisit: Option<StoredValues>,
 }
 
 impl StoredTag {
-    // # Safety
-    // The caller must guarantee that the buffer referenced by the returned `Tag` does not outlive
-    // the returned `Tag`. In other words, this returned `Tag` must outlive the `Tags` from which
-    // it was originally created.
-    pub(in crate::de) unsafe fn into_tag<'a>(self) -> Tag<'a> {
+    /// Restores the `Tag` from the buffer it was read from.
+    ///
+    /// # Panics
+    /// Panics if `buffer` is shorter than the buffer the tag was read from.
+    pub(in crate::de) fn into_tag(self, buffer: &[u8]) -> Tag<'_> {
+        // Every offset into the buffer is at most `current_byte_index`, so checking it here keeps
+        // all of the restored tag's offsets within `buffer`.
+        assert!(
+            self.current_byte_index <= buffer.len(),
+            "buffer is shorter than the buffer the tag was read from"
+        );
         Tag {
-            // SAFETY: The lifetime of this slice is guaranteed by the caller.
-            bytes: unsafe { slice::from_raw_parts(self.byte_ptr, self.byte_len) },
+            bytes: buffer,
 
             first_values: self.first_values,
 
@@ -42,8 +44,9 @@ impl StoredTag {
 
             origin_position: self.origin_position,
 
-            // SAFETY: The revisit is guaranteed to have the same lifetime as the containing `Tag`.
-            revisit: unsafe { self.revisit.map(|stored| stored.into_values()) },
+            lexicon: self.lexicon,
+
+            revisit: self.revisit.map(|stored| stored.into_values(buffer)),
         }
     }
 
@@ -64,11 +67,22 @@ pub(in crate::de) struct Tag<'a> {
 
     origin_position: Position,
 
+    lexicon: Lexicon,
+
     revisit: Option<Values<'a>>,
 }
 
 impl<'a> Tag<'a> {
+    #[cfg(test)]
     pub(in crate::de) fn new(bytes: &'a [u8], position: Position) -> Self {
+        Self::with_lexicon(bytes, position, Lexicon::new())
+    }
+
+    pub(in crate::de) fn with_lexicon(
+        bytes: &'a [u8],
+        position: Position,
+        lexicon: Lexicon,
+    ) -> Self {
         Self {
             bytes,
 
@@ -79,6 +93,8 @@ impl<'a> Tag<'a> {
 
             origin_position: position,
 
+            lexicon,
+
             revisit: None,
         }
     }
@@ -91,77 +107,40 @@ impl<'a> Tag<'a> {
         let mut values = None;
         let started_byte_index = self.current_byte_index;
         let started_position = self.current_position;
-        let mut state = State::None;
+        let mut scanner = Scanner::within_tag();
+        // Comments are not values, so a comment following the final `;` does not begin another
+        // parameter list.
         let mut encountered_non_whitespace = false;
+        let mut pending_slash = false;
         let mut last_byte_newline = false;
         loop {
             if let Some(byte) = self.bytes.get(self.current_byte_index) {
                 // Process byte.
-                match state {
-                    State::None => {
-                        match byte {
-                            b';' => {
-                                // This is the end of a `Values`.
-                                values = Some(Values::new(
-                                    // SAFETY: Both ends of the range used here have already been
-                                    // determined to be within the bounds of self.bytes.
-                                    unsafe {
-                                        self.bytes.get_unchecked(
-                                            started_byte_index..self.current_byte_index,
-                                        )
-                                    },
-                                    started_position,
-                                ));
-                            }
-                            b'\\' => {
-                                // Enter an escaping state.
-                                state = State::Escaping;
-                            }
-                            b'/' => {
-                                state = State::MaybeEnteringComment;
-                            }
-                            _ => {}
-                        }
-                    }
-                    State::MaybeEnteringComment => {
-                        match byte {
-                            b';' => {
-                                // This is the end of a `Values`.
-                                values = Some(Values::new(
-                                    // SAFETY: Both ends of the range used here have already been
-                                    // determined to be within the bounds of self.bytes.
-                                    unsafe {
-                                        self.bytes.get_unchecked(
-                                            started_byte_index..self.current_byte_index,
-                                        )
-                                    },
-                                    started_position,
-                                ));
-                            }
-                            b'\\' => {
-                                // Enter an escaping state.
-                                state = State::Escaping;
-                            }
-                            b'/' => {
-                                state = State::InComment;
-                            }
-                            _ => {
-                                state = State::None;
-                            }
+                let class = scanner.next(*byte);
+                match class {
+                    Class::Slash => pending_slash = true,
+                    Class::CommentStart => pending_slash = false,
+                    Class::Comment => {}
+                    _ => {
+                        if pending_slash || !byte.is_ascii_whitespace() {
+                            encountered_non_whitespace = true;
                         }
-                    }
-                    State::InComment => {
-                        // Consume bytes until we are on a new line.
-                        if matches!(byte, b'\n') {
-                            state = State::None;
-                        }
-                    }
-                    State::Escaping => {
-                        state = State::None;
+                        pending_slash = false;
                     }
                 }
-                if !byte.is_ascii_whitespace() {
-                    encountered_non_whitespace = true;
+                if class == Class::Semicolon {
+                    // This is the end of a `Values`.
+                    values = Some(Values::with_offset(
+                        // SAFETY: Both ends of the range used here have already been determined to
+                        // be within the bounds of self.bytes.
+                        unsafe {
+                            self.bytes
+                                .get_unchecked(started_byte_index..self.current_byte_index)
+                        },
+                        started_byte_index,
+                        started_position,
+                        self.lexicon,
+                    ));
                 }
                 last_byte_newline = matches!(byte, b'\n');
                 if last_byte_newline {
@@ -176,21 +155,23 @@ impl<'a> Tag<'a> {
                     return Ok(values);
                 }
             } else {
-                if self.first_values || encountered_non_whitespace {
+                if self.first_values || encountered_non_whitespace || pending_slash {
                     self.first_values = false;
                     let ending_byte_index = if last_byte_newline {
                         self.current_byte_index - 1
                     } else {
                         self.current_byte_index
                     };
-                    return Ok(Values::new(
+                    return Ok(Values::with_offset(
                         // SAFETY: self.current_byte_index is guaranteed to only be one past the
                         // last value in the slice.
                         unsafe {
                             self.bytes
                                 .get_unchecked(started_byte_index..ending_byte_index)
                         },
+                        started_byte_index,
                         started_position,
+                        self.lexicon,
                     ));
                 }
                 return Err(Error::new(error::Kind::EndOfTag, self.current_position));
@@ -198,22 +179,60 @@ impl<'a> Tag<'a> {
         }
     }
 
+    pub(in crate::de) fn origin_position(&self) -> Position {
+        self.origin_position
+    }
+
     pub(in crate::de) fn reset(&mut self) {
         self.first_values = true;
         self.current_byte_index = 0;
         self.current_position = self.origin_position.increment_column();
     }
 
-    // SAFETY: `values` must reference the same buffer referenced by this tag. In other words,
-    // `values` should have been created by a call to this tag's `next()` method.
-    pub(in crate::de) unsafe fn revisit(&mut self, values: Values<'a>) {
+    /// Returns the verbatim bytes of all of the tag's parameter lists, from the start of the tag up
+    /// to but excluding the `;` ending its final parameter list.
+    ///
+    /// If the tag is missing its final `;` and ends in a comment, the comment is excluded, since a
+    /// `;` written after it would be commented out.
+    pub(in crate::de) fn contents(mut self) -> &'a [u8] {
+        self.reset();
+        self.revisit = None;
+        let mut end = 0;
+        while let Ok(values) = self.next() {
+            end = values.end_offset();
+        }
+        // SAFETY: `end` is the end of values found within `self.bytes`.
+        let contents = unsafe { self.bytes.get_unchecked(..end) };
+
+        let mut scanner = Scanner::within_tag();
+        let mut slash = None;
+        let mut comment_start = None;
+        for (index, byte) in contents.iter().enumerate() {
+            match scanner.next(*byte) {
+                Class::Slash => slash = Some(index),
+                Class::CommentStart => comment_start = slash,
+                Class::Comment => {}
+                _ => comment_start = None,
+            }
+        }
+        match comment_start {
+            Some(start) => &contents[..start],
+            None => contents,
+        }
+    }
+
+    // `values` must have been created by a call to this tag's `next()` method.
+    pub(in crate::de) fn revisit(&mut self, values: Values<'a>) {
         self.revisit = Some(values);
     }
 
     pub(in crate::de) fn assert_exhausted(&self) -> Result<()> {
         let mut current_position = self.current_position;
-        // SAFETY: self.current_byte_index is guaranteed to be within the bounds of self.bytes.
-        for byte in unsafe { self.bytes.get_unchecked(self.current_byte_index..) } {
+        let remaining = self
+            .bytes
+            .get(self.current_byte_index..)
+            .expect("current byte index is past the end of the tag");
+        for byte in remaining {
             if !byte.is_ascii_whitespace() {
                 return Err(Error::new(error::Kind::UnexpectedValues, current_position));
             } else if matches!(byte, b'\n') {
@@ -227,9 +246,6 @@ impl<'a> Tag<'a> {
 
     pub(in crate::de) fn into_stored(self) -> StoredTag {
         StoredTag {
-            byte_ptr: self.bytes.as_ptr(),
-            byte_len: self.bytes.len(),
-
             first_values: self.first_values,
 
             current_byte_index: self.current_byte_index,
@@ -237,6 +253,8 @@ impl<'a> Tag<'a> {
 
             origin_position: self.origin_position,
 
+            lexicon: self.lexicon,
+
             revisit: self.revisit.map(|values| values.into_stored()),
         }
     }
@@ -318,6 +336,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comment_after_final_values() {
+        let mut tag = Tag::new(b"foo; // comment\n// another\n", Position::new(0, 0));
+
+        assert_ok_eq!(tag.next(), Values::new(b"foo", Position::new(0, 1)));
+        assert_err_eq!(
+            tag.next(),
+            Error::new(error::Kind::EndOfTag, Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn slash_after_final_values() {
+        let mut tag = Tag::new(b"foo; /", Position::new(0, 0));
+
+        assert_ok_eq!(tag.next(), Values::new(b"foo", Position::new(0, 1)));
+        assert_ok_eq!(tag.next(), Values::new(b" /", Position::new(0, 5)));
+    }
+
     #[test]
     fn end_of_values_after_maybe_entering_comment() {
         let mut tag = Tag::new(b"foo: /;\n", Position::new(0, 0));
@@ -389,12 +426,63 @@ mod tests {
         assert_ok_eq!(tag.next(), Values::new(b"foo", Position::new(0, 1)));
     }
 
+    #[test]
+    fn contents() {
+        let tag = Tag::new(b"foo:bar\\;baz;\n", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"foo:bar\\;baz");
+    }
+
+    #[test]
+    fn contents_multiple_values() {
+        let tag = Tag::new(b"foo:bar;\nbaz // comment\n;\n\n", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"foo:bar;\nbaz // comment\n");
+    }
+
+    #[test]
+    fn contents_trailing_comment() {
+        let tag = Tag::new(b"bar; // note\n", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"bar");
+    }
+
+    #[test]
+    fn contents_unterminated_comment() {
+        let tag = Tag::new(b"foo:bar // comment\n", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"foo:bar ");
+    }
+
+    #[test]
+    fn contents_comment_within_values() {
+        let tag = Tag::new(b"foo:bar // comment\nbaz", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"foo:bar // comment\nbaz");
+    }
+
+    #[test]
+    fn contents_after_next() {
+        let mut tag = Tag::new(b"foo:bar;baz;\n", Position::new(0, 0));
+
+        assert_ok!(tag.next());
+
+        assert_eq!(tag.contents(), b"foo:bar;baz");
+    }
+
+    #[test]
+    fn contents_unterminated() {
+        let tag = Tag::new(b"foo:bar\n", Position::new(0, 0));
+
+        assert_eq!(tag.contents(), b"foo:bar");
+    }
+
     #[test]
     fn revisit() {
         let mut tag = Tag::new(b"foo;", Position::new(0, 0));
 
         let values = assert_ok!(tag.next());
-        unsafe { tag.revisit(values) };
+        tag.revisit(values);
         assert_ok_eq!(tag.next(), Values::new(b"foo", Position::new(0, 1)));
     }
 
@@ -424,7 +512,7 @@ mod tests {
         let buffer = b"foo;";
         let tag = Tag::new(buffer, Position::new(0, 0));
         let stored = tag.into_stored();
-        let mut unstored_tag = unsafe { stored.into_tag() };
+        let mut unstored_tag = stored.into_tag(buffer);
 
         assert_ok_eq!(
             unstored_tag.next(),
@@ -442,7 +530,7 @@ mod tests {
         let mut tag = Tag::new(buffer, Position::new(0, 0));
         assert_ok!(tag.next());
         let stored = tag.into_stored();
-        let mut unstored_tag = unsafe { stored.into_tag() };
+        let mut unstored_tag = stored.into_tag(buffer);
 
         assert_ok_eq!(
             unstored_tag.next(),
@@ -454,6 +542,17 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic]
+    fn into_stored_shorter_buffer() {
+        let buffer = b"foo;bar;";
+        let mut tag = Tag::new(buffer, Position::new(0, 0));
+        assert_ok!(tag.next());
+        let stored = tag.into_stored();
+
+        stored.into_tag(b"fo");
+    }
+
     #[test]
     fn stored_origin_position() {
         let buffer = b"foo;bar;";
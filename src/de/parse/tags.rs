@@ -1,28 +1,83 @@
 use super::Tag;
-use crate::de::{error, parse::StoredTag, Error, Position, Result};
-use std::io::{Bytes, Read};
-
-enum State {
-    None,
-    MaybeEnteringComment,
-    InComment,
-    Escaping,
+use crate::de::{
+    error, parse::StoredTag, warning, Error, Limit, Options, Position, Result, Warning,
+};
+use crate::{
+    io,
+    io::{Bytes, Read},
+    scan::{Class, Scanner},
+};
+use alloc::vec::Vec;
+use core::task::Poll;
+
+/// The progress made scanning the tag currently being read, kept between calls that return
+/// `Poll::Pending`.
+#[derive(Debug)]
+struct Progress {
+    // Whether the tag's `#` was encountered, rather than the end of the input.
+    started_tag: bool,
+    // The length of the buffer directly after the most recent unescaped `;`, along with the
+    // position of any content that has followed it.
+    end_of_last_values: Option<usize>,
+    trailing_content_position: Option<Position>,
+    // The number of parameter lists ended by a `;` and the number of `:` separating parameters,
+    // used to enforce the configured limits.
+    parameter_lists: usize,
+    separators: usize,
 }
 
 #[derive(Debug)]
 pub(in crate::de) struct Tags<R> {
     reader: Bytes<R>,
+    scanner: Scanner,
 
     buffer: Vec<u8>,
 
     first_tag: bool,
+    skipped_content: bool,
+    progress: Option<Progress>,
 
     current_position: Position,
+    // The position of a `/` that may begin a comment.
+    slash_position: Option<Position>,
+    // The position of the `#` beginning the most recently returned tag.
+    tag_position: Position,
+    // The number of bytes consumed from the reader, and the offset of the `#` beginning the most
+    // recently returned tag.
+    offset: usize,
+    tag_offset: usize,
+    tag_count: usize,
 
     encountered_error: Option<Error>,
     exhausted: bool,
 
     revisit: Option<StoredTag>,
+
+    options: Options,
+    warnings: Vec<Warning>,
+}
+
+impl<R> Tags<R> {
+    pub(in crate::de) fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub(in crate::de) fn current_position(&self) -> Position {
+        self.current_position
+    }
+
+    /// Returns the byte offset and length of the most recently returned tag, including its `#`.
+    #[cfg(any(feature = "std", test))]
+    pub(in crate::de) fn tag_span(&self) -> (usize, usize) {
+        (self.tag_offset, self.buffer.len() + 1)
+    }
+
+    /// Returns the buffer containing the most recently returned tag.
+    ///
+    /// Stored tags and values are restored from this buffer.
+    pub(in crate::de) fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
 }
 
 impl<R> Tags<R>
@@ -30,103 +85,239 @@ where
     R: Read,
 {
     pub(in crate::de) fn new(reader: R) -> Self {
+        Self::with_options(reader, Options::new())
+    }
+
+    pub(in crate::de) fn with_options(reader: R, options: Options) -> Self {
         Self {
-            reader: reader.bytes(),
+            reader: Bytes::new(reader),
+            scanner: Scanner::new(),
 
             buffer: Vec::with_capacity(1024),
 
             first_tag: true,
+            skipped_content: false,
+            progress: None,
 
             current_position: Position::new(0, 0),
+            slash_position: None,
+            tag_position: Position::new(0, 0),
+            offset: 0,
+            tag_offset: 0,
+            tag_count: 0,
 
             encountered_error: None,
             exhausted: false,
 
             revisit: None,
+
+            options,
+            warnings: Vec::new(),
         }
     }
 
-    fn iterate_to_first_tag(&mut self) -> Result<()> {
-        enum State {
-            None,
-            MaybeEnteringComment,
-            InComment,
+    /// Reads the next byte of input, returning `None` at the end of the input.
+    ///
+    /// `Poll::Pending` is returned if the reader has no more input available yet.
+    fn poll_byte(&mut self) -> Poll<Result<Option<u8>>> {
+        match self.reader.next() {
+            Some(Ok(byte)) => {
+                self.offset += 1;
+                Poll::Ready(
+                    self.enforce(Limit::InputBytes, self.offset, self.current_position)
+                        .map(|()| Some(byte)),
+                )
+            }
+            Some(Err(error)) if io::would_block(&error) => Poll::Pending,
+            Some(Err(_error)) => {
+                let error = Error::new(error::Kind::Io, self.current_position);
+                self.encountered_error = Some(error.clone());
+                self.exhausted = true;
+                Poll::Ready(Err(error))
+            }
+            None => Poll::Ready(Ok(None)),
         }
+    }
 
-        let mut state = State::None;
+    /// Returns the error for a reader that has no more input available yet when reading
+    /// synchronously, which is treated as a failure to read.
+    fn blocked(&mut self) -> Error {
+        let error = Error::new(error::Kind::Io, self.current_position);
+        self.encountered_error = Some(error.clone());
+        self.exhausted = true;
+        error
+    }
 
-        if self.first_tag {
-            self.first_tag = false;
-            loop {
-                let byte = match self.reader.next() {
-                    Some(byte) => match byte {
-                        Ok(byte) => byte,
-                        Err(_error) => {
-                            let error = Error::new(error::Kind::Io, self.current_position);
-                            self.encountered_error = Some(error.clone());
-                            self.exhausted = true;
-                            break Err(error);
-                        }
-                    },
-                    None => {
-                        self.exhausted = true;
-                        break Ok(());
-                    }
-                };
+    fn iterate_to_first_tag(&mut self) -> Result<()> {
+        match self.poll_first_tag() {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(self.blocked()),
+        }
+    }
 
-                match state {
-                    State::None => {
-                        match byte {
-                            b'#' => {
-                                break Ok(());
-                            }
-                            b'/' => {
-                                state = State::MaybeEnteringComment;
-                            }
-                            _ => {
-                                // Non-whitespace bytes are not allowed before the first tag.
-                                if !byte.is_ascii_whitespace() {
-                                    let error =
-                                        Error::new(error::Kind::ExpectedTag, self.current_position);
-                                    self.encountered_error = Some(error.clone());
-                                    break Err(error);
-                                }
-                            }
-                        }
+    fn poll_first_tag(&mut self) -> Poll<Result<()>> {
+        while self.first_tag {
+            let byte = match self.poll_byte() {
+                Poll::Ready(Ok(Some(byte))) => byte,
+                Poll::Ready(Ok(None)) => {
+                    self.first_tag = false;
+                    self.exhausted = true;
+                    break;
+                }
+                Poll::Ready(Err(error)) => {
+                    self.first_tag = false;
+                    return Poll::Ready(Err(error));
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let class = self.scanner.next(byte);
+            if let Some(position) = self.slash_position.take() {
+                if class != Class::CommentStart {
+                    if let Err(error) = self.skip_content_before_first_tag(position) {
+                        self.first_tag = false;
+                        return Poll::Ready(Err(error));
                     }
-                    State::MaybeEnteringComment => match byte {
-                        b'/' => {
-                            state = State::InComment;
-                        }
-                        _ => {
-                            let error = Error::new(
-                                error::Kind::ExpectedTag,
-                                self.current_position.decrement_column(),
-                            );
-                            self.encountered_error = Some(error.clone());
-                            break Err(error);
-                        }
-                    },
-                    State::InComment => {
-                        // Consume bytes until we are on a new line.
-                        if matches!(byte, b'\n') {
-                            state = State::None;
-                        }
+                }
+            }
+            match class {
+                Class::TagStart => {
+                    self.first_tag = false;
+                    break;
+                }
+                Class::Slash => self.slash_position = Some(self.current_position),
+                // Non-whitespace bytes are not allowed before the first tag.
+                Class::Content if !byte.is_ascii_whitespace() => {
+                    if let Err(error) = self.skip_content_before_first_tag(self.current_position) {
+                        self.first_tag = false;
+                        return Poll::Ready(Err(error));
                     }
                 }
+                _ => {}
+            }
 
-                if matches!(byte, b'\n') {
-                    self.current_position = self.current_position.increment_line();
-                } else {
-                    self.current_position = self.current_position.increment_column();
-                }
+            if matches!(byte, b'\n') {
+                self.current_position = self.current_position.increment_line();
+            } else {
+                self.current_position = self.current_position.increment_column();
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Handles content encountered before the first tag, either skipping it or returning an
+    /// error depending on the configured options.
+    ///
+    /// Only the first skipped content results in a warning.
+    fn skip_content_before_first_tag(&mut self, position: Position) -> Result<()> {
+        if self.options.skip_content_before_first_tag {
+            if !self.skipped_content {
+                self.skipped_content = true;
+                self.warn(warning::Kind::ContentBeforeFirstTag, position);
+            }
+            Ok(())
+        } else {
+            let error = Error::new(error::Kind::ExpectedTag, position);
+            self.encountered_error = Some(error.clone());
+            Err(error)
+        }
+    }
+
+    /// Handles a tag that was not terminated by a `;` before either the next tag or the end of
+    /// the input, given the `progress` made scanning it.
+    fn recover_unterminated_tag(&mut self, progress: &Progress) -> Result<()> {
+        if let (Some(end_of_last_values), Some(trailing_content_position)) = (
+            progress.end_of_last_values,
+            progress.trailing_content_position,
+        ) {
+            if self.options.skip_content_between_tags {
+                self.buffer.truncate(end_of_last_values);
+                self.warn(warning::Kind::ContentBetweenTags, trailing_content_position);
+                return Ok(());
             }
+        }
+        self.recover(
+            self.options.allow_missing_semicolons,
+            warning::Kind::MissingSemicolon,
+            error::Kind::MissingSemicolon,
+            self.current_position,
+        )?;
+        // The unterminated content makes up one more parameter list.
+        self.enforce(
+            Limit::ParameterLists,
+            progress.parameter_lists + 1,
+            self.current_position,
+        )?;
+        self.enforce(
+            Limit::Parameters,
+            progress.parameter_lists + progress.separators,
+            self.current_position,
+        )
+    }
+
+    /// Enforces the limits on parameter lists and parameters after a tag's `;` has ended
+    /// `parameter_lists` lists containing `separators` unescaped `:` bytes in total.
+    fn enforce_parameter_counts(
+        &mut self,
+        parameter_lists: usize,
+        separators: usize,
+    ) -> Result<()> {
+        self.enforce(
+            Limit::ParameterLists,
+            parameter_lists,
+            self.current_position,
+        )?;
+        // The first value of the first list is the tag's name, rather than a parameter.
+        self.enforce(
+            Limit::Parameters,
+            parameter_lists + separators - 1,
+            self.current_position,
+        )
+    }
+
+    /// Returns an error if `count` exceeds the maximum configured for `limit`.
+    fn enforce(&mut self, limit: Limit, count: usize, position: Position) -> Result<()> {
+        if self
+            .options
+            .limits
+            .get(limit)
+            .map_or(false, |max| count > max)
+        {
+            let error = Error::new(error::Kind::LimitExceeded(limit), position);
+            self.encountered_error = Some(error.clone());
+            self.exhausted = true;
+            Err(error)
         } else {
-            // Already found the first tag.
             Ok(())
         }
     }
 
+    /// Either records a warning or returns an error for a deviation from the MSD format,
+    /// depending on whether recovering from the deviation is `allowed`.
+    fn recover(
+        &mut self,
+        allowed: bool,
+        warning_kind: warning::Kind,
+        error_kind: error::Kind,
+        position: Position,
+    ) -> Result<()> {
+        if allowed {
+            self.warn(warning_kind, position);
+            Ok(())
+        } else {
+            let error = Error::new(error_kind, position);
+            self.encountered_error = Some(error.clone());
+            self.exhausted = true;
+            Err(error)
+        }
+    }
+
+    fn warn(&mut self, kind: warning::Kind, position: Position) {
+        if self.options.report_warnings {
+            self.warnings.push(Warning::new(kind, position));
+        }
+    }
+
     /// Returns the next tag in the input, if there is one.
     ///
     /// Note that this is not an `Iterator::next()`, because it is impossible for an iterator to
@@ -139,129 +330,228 @@ where
         }
 
         if let Some(revisit) = self.revisit.take() {
-            return Ok(
-                // SAFETY: `revisit` is guaranteed to point to valid contents on the current
-                // buffer.
-                unsafe { revisit.into_tag() },
-            );
+            // The buffer is only cleared when moving past the revisited tag, so it is still
+            // intact.
+            return Ok(revisit.into_tag(&self.buffer));
         }
 
-        let mut state = State::None;
-        let mut end_of_values = false;
-        let mut starting_new_line = false;
+        match self.poll_tag() {
+            Poll::Ready(Ok(())) => Ok(Tag::with_lexicon(
+                &self.buffer,
+                self.tag_position,
+                self.options.lexicon,
+            )),
+            Poll::Ready(Err(error)) => Err(error),
+            Poll::Pending => Err(self.blocked()),
+        }
+    }
 
-        // Find the first tag, if necessary.
-        if self.first_tag {
-            self.iterate_to_first_tag()?;
-            if self.exhausted {
-                let error = Error::new(error::Kind::EndOfFile, self.current_position);
-                self.encountered_error = Some(error.clone());
-                return Err(error);
+    /// Scans every tag that can be read from the input available so far, returning
+    /// `Poll::Ready` once the end of the input has been reached.
+    ///
+    /// This allows input to be provided to the reader in chunks as it arrives, with each chunk
+    /// checked against the configured options before the next one is read.
+    #[cfg(feature = "async")]
+    pub(in crate::de) fn poll_exhausted(&mut self) -> Poll<Result<()>> {
+        loop {
+            match self.poll_tag() {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(error)) if *error.kind() == error::Kind::EndOfFile => {
+                    return Poll::Ready(Ok(()));
+                }
+                poll => return poll,
             }
         }
+    }
 
-        // Reuse the same buffer.
-        self.buffer.clear();
+    /// Returns the reader that input is being read from.
+    #[cfg(feature = "async")]
+    pub(in crate::de) fn reader_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
 
-        let started_position = self.current_position;
-        // Consume the `#` that has already been encountered.
-        self.current_position = self.current_position.increment_column();
+    /// Scans the next tag into the buffer.
+    ///
+    /// If the reader has no more input available yet, `Poll::Pending` is returned and scanning
+    /// resumes from the same point on the next call.
+    fn poll_tag(&mut self) -> Poll<Result<()>> {
+        if let Some(error) = &self.encountered_error {
+            return Poll::Ready(Err(error.clone()));
+        }
+
+        let mut progress = match self.progress.take() {
+            Some(progress) => progress,
+            None => match self.poll_begin_tag() {
+                Poll::Ready(Ok(progress)) => progress,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            },
+        };
 
         loop {
-            let byte = match self.reader.next() {
-                Some(byte) => match byte {
-                    Ok(byte) => byte,
-                    Err(_error) => {
-                        let error = Error::new(error::Kind::Io, self.current_position);
-                        self.encountered_error = Some(error.clone());
-                        self.exhausted = true;
-                        return Err(error);
-                    }
-                },
-                None => {
+            let byte = match self.poll_byte() {
+                Poll::Ready(Ok(Some(byte))) => byte,
+                Poll::Ready(Ok(None)) => {
                     self.exhausted = true;
-                    if self.buffer.is_empty() {
+                    if progress.started_tag {
+                        if !self.scanner.end_of_values() {
+                            if let Err(error) = self.recover_unterminated_tag(&progress) {
+                                return Poll::Ready(Err(error));
+                            }
+                        }
+                        return Poll::Ready(Ok(()));
+                    } else {
                         let error = Error::new(error::Kind::EndOfFile, self.current_position);
                         self.encountered_error = Some(error.clone());
-                        return Err(error);
-                    } else {
-                        return Ok(Tag::new(&self.buffer, started_position));
+                        return Poll::Ready(Err(error));
                     }
                 }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => {
+                    self.progress = Some(progress);
+                    return Poll::Pending;
+                }
             };
+            match self.scan_byte(byte, &mut progress) {
+                Ok(true) => return Poll::Ready(Ok(())),
+                Ok(false) => {}
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+        }
+    }
 
-            // Process byte.
-            match state {
-                State::None => {
-                    match byte {
-                        b'#' => {
-                            // We are lenient on the formatting here. If a `#` is at the
-                            // start of a newline we begin a new tag and assume the
-                            // previous tag was missing the closing `;` (some old
-                            // implementations of MSD didn't explicitly require the `;`).
-                            // If we are in the middle of a line, we assume it was meant to
-                            // be escaped.
-                            if starting_new_line || end_of_values {
-                                // Entering a new tag. Return the previous one.
-                                return Ok(Tag::new(&self.buffer, started_position));
-                            }
-                            end_of_values = false;
-                        }
-                        b';' => {
-                            end_of_values = true;
-                        }
-                        b'\\' => {
-                            state = State::Escaping;
-                            end_of_values = false;
-                        }
-                        b'/' => {
-                            state = State::MaybeEnteringComment;
-                        }
-                        _ => {
-                            if !byte.is_ascii_whitespace() {
-                                end_of_values = false;
-                            }
-                        }
-                    }
-                }
-                State::MaybeEnteringComment => match byte {
-                    b';' => {
-                        end_of_values = true;
-                        state = State::None;
-                    }
-                    b'\\' => {
-                        state = State::Escaping;
-                        end_of_values = false;
-                    }
-                    b'/' => {
-                        state = State::InComment;
-                    }
-                    _ => {
-                        state = State::None;
-                        end_of_values = false;
-                    }
-                },
-                State::InComment => {
-                    // Consume bytes until we are on a new line.
-                    if matches!(byte, b'\n') {
-                        state = State::None;
-                    }
-                }
-                State::Escaping => {
-                    state = State::None;
-                    end_of_values = false;
+    /// Processes a single byte of the tag being scanned, returning whether it is the `#` beginning
+    /// the next tag.
+    fn scan_byte(&mut self, byte: u8, progress: &mut Progress) -> Result<bool> {
+        let end_of_values = self.scanner.end_of_values();
+        let class = self.scanner.next(byte);
+
+        // The position at which this byte begins any content following the tag's most recent
+        // `;`, if it does.
+        let mut content_position = None;
+        if let Some(position) = self.slash_position.take() {
+            if class != Class::CommentStart {
+                // The previous `/` did not begin a comment.
+                content_position = Some(position);
+            }
+        }
+
+        match class {
+            Class::TagStart => {
+                // We are lenient on the formatting here by default. If a `#` is at the start of a
+                // newline we begin a new tag and assume the previous tag was missing the closing
+                // `;` (some old implementations of MSD didn't explicitly require the `;`). If we
+                // are in the middle of a line, we assume it was meant to be escaped.
+                if !end_of_values {
+                    self.recover_unterminated_tag(progress)?;
                 }
+                // Entering a new tag. The previous one is complete.
+                return Ok(true);
+            }
+            Class::NumberSign => {
+                self.recover(
+                    self.options.allow_unescaped_number_signs,
+                    warning::Kind::UnescapedNumberSign,
+                    error::Kind::UnescapedNumberSign,
+                    self.current_position,
+                )?;
+                content_position.get_or_insert(self.current_position);
             }
-            self.buffer.push(byte);
+            Class::Semicolon => {
+                progress.end_of_last_values = Some(self.buffer.len() + 1);
+                progress.trailing_content_position = None;
+                content_position = None;
+                progress.parameter_lists += 1;
+                self.enforce_parameter_counts(progress.parameter_lists, progress.separators)?;
+            }
+            Class::Colon => {
+                progress.separators += 1;
+                // The parameter list in progress is counted as well.
+                self.enforce(
+                    Limit::Parameters,
+                    progress.parameter_lists + progress.separators,
+                    self.current_position,
+                )?;
+                content_position.get_or_insert(self.current_position);
+            }
+            Class::Slash => {
+                self.slash_position = Some(self.current_position);
+            }
+            Class::Escape => {
+                content_position.get_or_insert(self.current_position);
+            }
+            Class::Text if !byte.is_ascii_whitespace() => {
+                content_position.get_or_insert(self.current_position);
+            }
+            Class::Escaped
+            | Class::CommentStart
+            | Class::Comment
+            | Class::Content
+            | Class::Text => {}
+        }
+        // The buffered bytes, this byte, and the tag's `#`.
+        self.enforce(
+            Limit::TagBytes,
+            self.buffer.len() + 2,
+            self.current_position,
+        )?;
+        self.buffer.push(byte);
+
+        if progress.end_of_last_values.is_some() && progress.trailing_content_position.is_none() {
+            progress.trailing_content_position = content_position;
+        }
 
-            if matches!(byte, b'\n') {
-                self.current_position = self.current_position.increment_line();
-                starting_new_line = true;
-            } else {
-                self.current_position = self.current_position.increment_column();
-                starting_new_line = false;
+        if matches!(byte, b'\n') {
+            self.current_position = self.current_position.increment_line();
+        } else {
+            self.current_position = self.current_position.increment_column();
+        }
+        Ok(false)
+    }
+
+    /// Begins scanning a new tag, directly after its `#`.
+    fn poll_begin_tag(&mut self) -> Poll<Result<Progress>> {
+        // Find the first tag, if necessary.
+        if self.first_tag {
+            match self.poll_first_tag() {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+            if self.exhausted {
+                let error = Error::new(error::Kind::EndOfFile, self.current_position);
+                self.encountered_error = Some(error.clone());
+                return Poll::Ready(Err(error));
             }
         }
+
+        // Reuse the same buffer.
+        self.buffer.clear();
+
+        // A new tag only begins if its `#` has been encountered.
+        let started_tag = !self.exhausted;
+        if started_tag {
+            self.tag_count += 1;
+            if let Err(error) = self.enforce(Limit::Tags, self.tag_count, self.current_position) {
+                return Poll::Ready(Err(error));
+            }
+        }
+
+        self.tag_position = self.current_position;
+        // The `#` has already been consumed.
+        self.tag_offset = self.offset - 1;
+        // Consume the `#` that has already been encountered.
+        if started_tag {
+            self.current_position = self.current_position.increment_column();
+        }
+
+        Poll::Ready(Ok(Progress {
+            started_tag,
+            end_of_last_values: None,
+            trailing_content_position: None,
+            parameter_lists: 0,
+            separators: 0,
+        }))
     }
 
     /// Returns whether there will be another tag.
@@ -279,8 +569,8 @@ where
         }
     }
 
-    // SAFETY: `tag` must reference this struct's buffer.
-    pub(in crate::de) unsafe fn revisit(&mut self, tag: StoredTag) {
+    // `tag` must have been returned by the most recent call to `next()`.
+    pub(in crate::de) fn revisit(&mut self, tag: StoredTag) {
         self.revisit = Some(tag)
     }
 
@@ -318,8 +608,12 @@ where
 #[cfg(test)]
 mod tests {
     use super::Tags;
-    use crate::de::{error, parse::Tag, Error, Position};
+    use crate::{
+        de::{error, parse::Tag, warning, Error, Limit, Options, Position, Warning},
+        lex::{Kind, Lexer},
+    };
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use std::mem;
 
     #[test]
     fn empty_reader() {
@@ -381,6 +675,190 @@ mod tests {
         assert_ok_eq!(tags.next(), Tag::new(b"baz;", Position::new(0, 10)));
     }
 
+    #[test]
+    fn tag_span() {
+        let input = b"\n  #foo;\n#bar:baz\n#qux;";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok!(tags.next());
+        assert_eq!(tags.tag_span(), (3, 6));
+        assert_ok!(tags.next());
+        assert_eq!(tags.tag_span(), (9, 9));
+        assert_ok!(tags.next());
+        assert_eq!(tags.tag_span(), (18, 5));
+    }
+
+    #[test]
+    fn max_tag_bytes() {
+        let input = b"#foo:bar;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_tag_bytes(5));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagBytes),
+                Position::new(0, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tag_bytes_not_exceeded() {
+        let input = b"#foo:bar;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_tag_bytes(10));
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo:bar;\n", Position::new(0, 0)));
+    }
+
+    #[test]
+    fn max_tag_bytes_without_delimiters() {
+        let input = [b'a'; 100];
+        let mut input = b"#".iter().chain(input.iter()).copied().collect::<Vec<_>>();
+        input.extend_from_slice(b"#a;");
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_tag_bytes(50));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagBytes),
+                Position::new(0, 50)
+            )
+        );
+        assert!(tags.buffer().len() < 50);
+    }
+
+    #[test]
+    fn max_tags() {
+        let input = b"#a;\n#b;\n#c;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_tags(2));
+
+        assert_ok!(tags.next());
+        assert_ok!(tags.next());
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn max_tags_not_exceeded() {
+        let input = b"#a;\n#b;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_tags(2));
+
+        assert_ok!(tags.next());
+        assert_ok!(tags.next());
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn max_parameter_lists() {
+        let input = b"#a:b;c;d;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_parameter_lists(2));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::ParameterLists),
+                Position::new(0, 8)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameter_lists_unterminated() {
+        let input = b"#a:b;c\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_parameter_lists(1));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::ParameterLists),
+                Position::new(1, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameter_lists_skipped_content() {
+        let input = b"#a:b;c\n";
+        let mut tags = Tags::with_options(
+            input.as_slice(),
+            Options::new()
+                .skip_content_between_tags(true)
+                .max_parameter_lists(1),
+        );
+
+        assert_ok_eq!(tags.next(), Tag::new(b"a:b;", Position::new(0, 0)));
+    }
+
+    #[test]
+    fn max_parameters() {
+        let input = b"#a:b:c;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_parameters(1));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameters_across_parameter_lists() {
+        let input = b"#a:b;c;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_parameters(1));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 6)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameters_not_exceeded() {
+        let input = b"#a:b\\:c // d:e\n;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_parameters(1));
+
+        assert_ok!(tags.next());
+    }
+
+    #[test]
+    fn max_input_bytes() {
+        let input = b"#a;\n#b;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_input_bytes(5));
+
+        assert_ok!(tags.next());
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::InputBytes),
+                Position::new(1, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn max_input_bytes_before_first_tag() {
+        let input = b"  #a;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().max_input_bytes(1));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::InputBytes),
+                Position::new(0, 1)
+            )
+        );
+    }
+
     #[test]
     fn finds_new_tag_without_previous_tag_ending() {
         let input = b"#foo:bar\n#baz;\n";
@@ -390,6 +868,31 @@ mod tests {
         assert_ok_eq!(tags.next(), Tag::new(b"baz;\n", Position::new(1, 0)));
     }
 
+    #[test]
+    fn number_sign_at_end_of_file() {
+        let input = b"#foo;\n#";
+        let mut tags = Tags::new(input.as_slice());
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo;\n", Position::new(0, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"", Position::new(1, 0)));
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(1, 1))
+        );
+    }
+
+    #[test]
+    fn strict_number_sign_at_end_of_file() {
+        let input = b"#foo;\n#";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo;\n", Position::new(0, 0)));
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 1))
+        );
+    }
+
     #[test]
     fn ignores_unescaped_number_sign() {
         let input = b"#foo:bar#baz;\n";
@@ -585,7 +1088,7 @@ mod tests {
         let mut tags = Tags::new(input.as_slice());
 
         let tag = assert_ok!(tags.next()).into_stored();
-        unsafe { tags.revisit(tag) };
+        tags.revisit(tag);
         assert_ok_eq!(tags.next(), Tag::new(b"foo;\n", Position::new(0, 0)));
     }
 
@@ -619,10 +1122,357 @@ mod tests {
 
         let tag = assert_ok!(tags.next()).into_stored();
         assert_ok!(tags.assert_exhausted());
-        unsafe { tags.revisit(tag) };
+        tags.revisit(tag);
         assert_err_eq!(
             tags.assert_exhausted(),
             Error::new(error::Kind::UnexpectedTag, Position::new(0, 0))
         );
     }
+
+    #[test]
+    fn strict_missing_semicolon() {
+        let input = b"#foo:bar\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_semicolon_at_end_of_file() {
+        let input = b"#foo:bar\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_unescaped_number_sign() {
+        let input = b"#foo:bar#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn strict_unescaped_number_sign_after_slash() {
+        let input = b"#foo:bar/#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 9))
+        );
+    }
+
+    #[test]
+    fn strict_repeats_error() {
+        let input = b"#foo:bar\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingSemicolon, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_well_formed() {
+        let input = b"#foo:bar;\n#baz:qux\\#;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::strict());
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo:bar;\n", Position::new(0, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"baz:qux\\#;\n", Position::new(1, 0)));
+    }
+
+    #[test]
+    fn lenient_skips_byte_order_mark() {
+        let input = b"\xEF\xBB\xBF#foo:bar;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(
+                b"foo:bar;\n",
+                Position::new(0, 3),
+                Options::lenient().lexicon
+            )
+        );
+    }
+
+    #[test]
+    fn lenient_skips_content_before_first_tag() {
+        let input = b"foo\n/bar\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"baz;\n", Position::new(2, 0), Options::lenient().lexicon)
+        );
+    }
+
+    #[test]
+    fn lenient_skips_slash_before_first_tag() {
+        let input = b"/#foo;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"foo;\n", Position::new(0, 1), Options::lenient().lexicon)
+        );
+    }
+
+    #[test]
+    fn lenient_skips_content_between_tags() {
+        let input = b"#foo:bar;\ngarbage\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"foo:bar;", Position::new(0, 0), Options::lenient().lexicon)
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"baz;\n", Position::new(2, 0), Options::lenient().lexicon)
+        );
+    }
+
+    #[test]
+    fn lenient_skips_content_at_end_of_file() {
+        let input = b"#foo:bar;\ngarbage";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"foo:bar;", Position::new(0, 0), Options::lenient().lexicon)
+        );
+    }
+
+    #[test]
+    fn lenient_keeps_terminated_parameter_lists() {
+        let input = b"#foo:bar;\nbaz;\n#qux;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(
+                b"foo:bar;\nbaz;\n",
+                Position::new(0, 0),
+                Options::lenient().lexicon
+            )
+        );
+    }
+
+    #[test]
+    fn lenient_keeps_comment_between_tags() {
+        let input = b"#foo:bar; // comment\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::lenient());
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(
+                b"foo:bar; // comment\n",
+                Position::new(0, 0),
+                Options::lenient().lexicon
+            )
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::with_lexicon(b"baz;\n", Position::new(1, 0), Options::lenient().lexicon)
+        );
+    }
+
+    #[test]
+    fn no_warnings_by_default() {
+        let input = b"#foo:bar\n#baz#;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        assert_ok!(tags.next());
+        assert_ok!(tags.next());
+
+        assert_eq!(tags.warnings(), &[]);
+    }
+
+    #[test]
+    fn warns_missing_semicolon() {
+        let input = b"#foo:bar\n#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().report_warnings(true));
+
+        assert_ok!(tags.next());
+
+        assert_eq!(
+            tags.warnings(),
+            &[Warning::new(
+                warning::Kind::MissingSemicolon,
+                Position::new(1, 0)
+            )]
+        );
+    }
+
+    #[test]
+    fn warns_unescaped_number_sign() {
+        let input = b"#foo:bar#baz;\n";
+        let mut tags = Tags::with_options(input.as_slice(), Options::new().report_warnings(true));
+
+        assert_ok!(tags.next());
+
+        assert_eq!(
+            tags.warnings(),
+            &[Warning::new(
+                warning::Kind::UnescapedNumberSign,
+                Position::new(0, 8)
+            )]
+        );
+    }
+
+    #[test]
+    fn warns_content_before_first_tag_once() {
+        let input = b"foo\nbar\n#baz;\n";
+        let mut tags =
+            Tags::with_options(input.as_slice(), Options::lenient().report_warnings(true));
+
+        assert_ok!(tags.next());
+
+        assert_eq!(
+            tags.warnings(),
+            &[Warning::new(
+                warning::Kind::ContentBeforeFirstTag,
+                Position::new(0, 0)
+            )]
+        );
+    }
+
+    #[test]
+    fn warns_content_between_tags() {
+        let input = b"#foo:bar;\n  garbage\n#baz;\n";
+        let mut tags =
+            Tags::with_options(input.as_slice(), Options::lenient().report_warnings(true));
+
+        assert_ok!(tags.next());
+
+        assert_eq!(
+            tags.warnings(),
+            &[Warning::new(
+                warning::Kind::ContentBetweenTags,
+                Position::new(1, 2)
+            )]
+        );
+    }
+
+    // The tags found in the input, each given by its span and the positions of the values within
+    // each of its parameter lists.
+    type Structure = Vec<((usize, usize), Vec<Vec<Position>>)>;
+
+    fn parsed_structure(input: &[u8]) -> Structure {
+        let mut tags =
+            Tags::with_options(input, Options::new().skip_content_before_first_tag(true));
+        let mut structure = Vec::new();
+        while let Ok(mut tag) = tags.next() {
+            let mut lists = Vec::new();
+            while let Ok(mut values) = tag.next() {
+                let mut positions = Vec::new();
+                while let Ok(value) = values.next() {
+                    positions.push(value.position());
+                }
+                lists.push(positions);
+            }
+            structure.push((tags.tag_span(), lists));
+        }
+        structure
+    }
+
+    // A tag in progress while lexing.
+    struct LexedTag {
+        start: usize,
+        lists: Vec<Vec<Position>>,
+        values: Vec<Position>,
+        // Whether anything other than whitespace and comments has been found within the list in
+        // progress.
+        encountered_content: bool,
+    }
+
+    impl LexedTag {
+        fn finish(mut self, end: usize, structure: &mut Structure) {
+            if self.lists.is_empty() || self.encountered_content {
+                self.lists.push(self.values);
+            }
+            structure.push(((self.start, end - self.start), self.lists));
+        }
+    }
+
+    fn lexed_structure(input: &[u8]) -> Structure {
+        let mut structure = Vec::new();
+        let mut tag: Option<LexedTag> = None;
+        for token in Lexer::new(input) {
+            let after = token.position().increment_column();
+            match (token.kind(), &mut tag) {
+                (Kind::TagStart, _) => {
+                    if let Some(tag) = tag.take() {
+                        tag.finish(token.span().start, &mut structure);
+                    }
+                    tag = Some(LexedTag {
+                        start: token.span().start,
+                        lists: Vec::new(),
+                        values: vec![after],
+                        encountered_content: false,
+                    });
+                }
+                (Kind::Colon, Some(tag)) => {
+                    tag.values.push(after);
+                    tag.encountered_content = true;
+                }
+                (Kind::Semicolon, Some(tag)) => {
+                    tag.lists.push(mem::replace(&mut tag.values, vec![after]));
+                    tag.encountered_content = false;
+                }
+                (Kind::Whitespace | Kind::Newline | Kind::Comment, _) => {}
+                (_, Some(tag)) => tag.encountered_content = true,
+                (_, None) => {}
+            }
+        }
+        if let Some(tag) = tag {
+            tag.finish(input.len(), &mut structure);
+        }
+        structure
+    }
+
+    #[test]
+    fn matches_lexer() {
+        // Every input of up to five bytes drawn from the bytes meaningful to the format.
+        const ALPHABET: &[u8] = b"#:;\\/\n\r a";
+        let mut inputs = vec![Vec::new()];
+        let mut start = 0;
+        for _ in 0..5 {
+            let end = inputs.len();
+            for index in start..end {
+                for byte in ALPHABET {
+                    let mut input = inputs[index].clone();
+                    input.push(*byte);
+                    inputs.push(input);
+                }
+            }
+            start = end;
+        }
+
+        for input in inputs {
+            assert_eq!(
+                parsed_structure(&input),
+                lexed_structure(&input),
+                "input: {:?}",
+                String::from_utf8_lossy(&input)
+            );
+        }
+    }
 }
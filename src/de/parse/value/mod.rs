@@ -2,12 +2,13 @@ mod clean;
 mod trim;
 
 use super::utf8_char_width::utf8_char_width;
-use crate::de::{error, Error, Position, Result};
+use crate::de::{error, options::Lexicon, Error, Position, Result};
+use alloc::{string::String, vec::Vec};
 use arrayvec::ArrayVec;
 use clean::Clean;
+use core::str;
 use either::Either;
 use num_traits::{Float, PrimInt, Signed};
-use std::str;
 use trim::Trim;
 
 fn parse_ident<I>(mut value_bytes: I, ident: &[u8]) -> bool
@@ -53,12 +54,33 @@ where
     }
 }
 
-fn parse_positive_integer_inner<I, N>(value_bytes: I, mut result: Option<N>) -> Option<N>
+/// Returns whether the remaining bytes are a fractional part made up entirely of zeros.
+///
+/// Note that the `.` is already matched.
+fn parse_zero_fraction<I>(mut value_bytes: I) -> bool
+where
+    I: Iterator<Item = u8>,
+{
+    matches!(value_bytes.next(), Some(b'0')) && value_bytes.all(|byte| byte == b'0')
+}
+
+fn parse_positive_integer_inner<I, N>(
+    mut value_bytes: I,
+    mut result: Option<N>,
+    allow_zero_fraction: bool,
+) -> Option<N>
 where
     I: Iterator<Item = u8>,
     N: PrimInt,
 {
-    for byte in value_bytes {
+    while let Some(byte) = value_bytes.next() {
+        if allow_zero_fraction && result.is_some() && byte == b'.' {
+            return if parse_zero_fraction(value_bytes) {
+                result
+            } else {
+                None
+            };
+        }
         if result.is_none() {
             result = Some(N::zero());
         }
@@ -86,15 +108,29 @@ where
     result
 }
 
-fn parse_unsigned_integer<I, N>(value_bytes: I) -> Option<N>
+fn parse_unsigned_integer<I, N>(mut value_bytes: I, lexicon: Lexicon) -> Option<N>
 where
     I: Iterator<Item = u8>,
     N: PrimInt,
 {
-    parse_positive_integer_inner(value_bytes, None)
+    match value_bytes.next()? {
+        b'+' if lexicon.allow_plus_signs => {
+            parse_positive_integer_inner(value_bytes, None, lexicon.allow_zero_fraction_integers)
+        }
+        byte @ b'0'..=b'9' => parse_positive_integer_inner(
+            value_bytes,
+            Some(
+                // SAFETY: Casting this value to `N` will always succeed, because the maximum value
+                // it can be is 9.
+                unsafe { N::from::<u8>(byte - b'0').unwrap_unchecked() },
+            ),
+            lexicon.allow_zero_fraction_integers,
+        ),
+        _ => None,
+    }
 }
 
-fn parse_signed_integer<I, N>(mut value_bytes: I) -> Option<N>
+fn parse_signed_integer<I, N>(mut value_bytes: I, lexicon: Lexicon) -> Option<N>
 where
     I: Iterator<Item = u8>,
     N: PrimInt + Signed,
@@ -104,7 +140,14 @@ where
             // Negative.
             let mut result = None;
 
-            for byte in value_bytes {
+            while let Some(byte) = value_bytes.next() {
+                if lexicon.allow_zero_fraction_integers && result.is_some() && byte == b'.' {
+                    return if parse_zero_fraction(value_bytes) {
+                        result
+                    } else {
+                        None
+                    };
+                }
                 if result.is_none() {
                     result = Some(N::zero());
                 }
@@ -140,8 +183,28 @@ where
                     // it can be is 9.
                     unsafe { N::from::<u8>(byte - b'0').unwrap_unchecked() },
                 ),
+                lexicon.allow_zero_fraction_integers,
             )
         }
+        b'+' if lexicon.allow_plus_signs => {
+            parse_positive_integer_inner(value_bytes, None, lexicon.allow_zero_fraction_integers)
+        }
+        _ => None,
+    }
+}
+
+fn parse_extended_bool<I>(value_bytes: I) -> Option<bool>
+where
+    I: Iterator<Item = u8>,
+{
+    // The longest accepted spelling is `false`.
+    let mut buffer = ArrayVec::<u8, 5>::new();
+    for byte in value_bytes {
+        buffer.try_push(byte.to_ascii_lowercase()).ok()?;
+    }
+    match buffer.as_slice() {
+        b"true" | b"yes" | b"on" | b"1" => Some(true),
+        b"false" | b"no" | b"off" | b"0" => Some(false),
         _ => None,
     }
 }
@@ -275,7 +338,7 @@ where
         }
 
         let exponent_number: i32 =
-            parse_positive_integer_inner(value_bytes, Some((first_byte - b'0').into()))
+            parse_positive_integer_inner(value_bytes, Some((first_byte - b'0').into()), false)
                 .unwrap_or(i32::MAX);
         if exponent_negative {
             exponent = exponent.saturating_sub(exponent_number);
@@ -304,11 +367,27 @@ where
 pub(in crate::de) struct Value<'a> {
     bytes: &'a [u8],
     position: Position,
+
+    lexicon: Lexicon,
 }
 
 impl<'a> Value<'a> {
+    #[cfg(test)]
     pub(in crate::de) fn new(bytes: &'a [u8], position: Position) -> Self {
-        Self { bytes, position }
+        Self::with_lexicon(bytes, position, Lexicon::new())
+    }
+
+    pub(in crate::de) fn with_lexicon(
+        bytes: &'a [u8],
+        position: Position,
+        lexicon: Lexicon,
+    ) -> Self {
+        Self {
+            bytes,
+            position,
+
+            lexicon,
+        }
     }
 
     pub(in crate::de) fn position(&self) -> Position {
@@ -316,6 +395,11 @@ impl<'a> Value<'a> {
     }
 
     pub(in crate::de) fn parse_bool(&self) -> Result<bool> {
+        if self.lexicon.allow_extended_bools {
+            return parse_extended_bool(Trim::new(Clean::new(self.bytes)))
+                .ok_or_else(|| Error::new(error::Kind::ExpectedBool, self.position));
+        }
+
         let mut value = Trim::new(Clean::new(self.bytes));
         match value
             .next()
@@ -340,54 +424,54 @@ impl<'a> Value<'a> {
     }
 
     pub(in crate::de) fn parse_i8(&self) -> Result<i8> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedI8, self.position))
     }
 
     pub(in crate::de) fn parse_i16(&self) -> Result<i16> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedI16, self.position))
     }
 
     pub(in crate::de) fn parse_i32(&self) -> Result<i32> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedI32, self.position))
     }
 
     pub(in crate::de) fn parse_i64(&self) -> Result<i64> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedI64, self.position))
     }
 
     #[cfg(has_i128)]
     pub(in crate::de) fn parse_i128(&self) -> Result<i128> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedI128, self.position))
     }
 
     pub(in crate::de) fn parse_u8(&self) -> Result<u8> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedU8, self.position))
     }
 
     pub(in crate::de) fn parse_u16(&self) -> Result<u16> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedU16, self.position))
     }
 
     pub(in crate::de) fn parse_u32(&self) -> Result<u32> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedU32, self.position))
     }
 
     pub(in crate::de) fn parse_u64(&self) -> Result<u64> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedU64, self.position))
     }
 
     #[cfg(has_i128)]
     pub(in crate::de) fn parse_u128(&self) -> Result<u128> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)), self.lexicon)
             .ok_or_else(|| Error::new(error::Kind::ExpectedU128, self.position))
     }
 
@@ -462,6 +546,11 @@ impl<'a> Value<'a> {
         Clean::new(self.bytes).collect()
     }
 
+    /// Returns the value's bytes exactly as they appear in the source.
+    pub(in crate::de) fn parse_raw(&self) -> Vec<u8> {
+        self.bytes.to_vec()
+    }
+
     pub(in crate::de) fn parse_unit(&self) -> Result<()> {
         // A unit must contain only whitespace and comments.
         if Clean::new(self.bytes).all(|b| b.is_ascii_whitespace()) {
@@ -480,9 +569,15 @@ impl<'a> Value<'a> {
 #[cfg(test)]
 mod tests {
     use super::Value;
-    use crate::de::{error, Error, Position};
+    use crate::de::{error, options::Lexicon, Error, Position};
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
 
+    const EXTENDED: Lexicon = Lexicon {
+        allow_extended_bools: true,
+        allow_plus_signs: true,
+        allow_zero_fraction_integers: true,
+    };
+
     #[test]
     fn get_position() {
         let value = Value::new(b"", Position::new(1, 2));
@@ -514,6 +609,179 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_bool_yes_not_allowed() {
+        let value = Value::new(b"YES", Position::new(0, 0));
+
+        assert_err_eq!(
+            value.parse_bool(),
+            Error::new(error::Kind::ExpectedBool, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_bool_extended_true() {
+        for spelling in [
+            b"true".as_slice(),
+            b"TRUE",
+            b"yes",
+            b"YES",
+            b"Yes",
+            b"on",
+            b"ON",
+            b"1",
+            b" 1 ",
+        ] {
+            let value = Value::with_lexicon(spelling, Position::new(0, 0), EXTENDED);
+
+            assert_ok_eq!(value.parse_bool(), true);
+        }
+    }
+
+    #[test]
+    fn parse_bool_extended_false() {
+        for spelling in [
+            b"false".as_slice(),
+            b"False",
+            b"no",
+            b"NO",
+            b"off",
+            b"OFF",
+            b"0",
+        ] {
+            let value = Value::with_lexicon(spelling, Position::new(0, 0), EXTENDED);
+
+            assert_ok_eq!(value.parse_bool(), false);
+        }
+    }
+
+    #[test]
+    fn parse_bool_extended_invalid() {
+        for spelling in [b"".as_slice(), b"2", b"yess", b"falsey", b"o"] {
+            let value = Value::with_lexicon(spelling, Position::new(0, 0), EXTENDED);
+
+            assert_err_eq!(
+                value.parse_bool(),
+                Error::new(error::Kind::ExpectedBool, Position::new(0, 0))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_i32_plus_sign_not_allowed() {
+        let value = Value::new(b"+5", Position::new(0, 0));
+
+        assert_err_eq!(
+            value.parse_i32(),
+            Error::new(error::Kind::ExpectedI32, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_i32_plus_sign() {
+        let value = Value::with_lexicon(b"+5", Position::new(0, 0), EXTENDED);
+
+        assert_ok_eq!(value.parse_i32(), 5);
+    }
+
+    #[test]
+    fn parse_u32_plus_sign() {
+        let value = Value::with_lexicon(b"+5", Position::new(0, 0), EXTENDED);
+
+        assert_ok_eq!(value.parse_u32(), 5);
+    }
+
+    #[test]
+    fn parse_u32_plus_sign_only() {
+        let value = Value::with_lexicon(b"+", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_u32(),
+            Error::new(error::Kind::ExpectedU32, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_i32_plus_minus() {
+        let value = Value::with_lexicon(b"+-5", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_i32(),
+            Error::new(error::Kind::ExpectedI32, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_u64_zero_fraction_not_allowed() {
+        let value = Value::new(b"1.000", Position::new(0, 0));
+
+        assert_err_eq!(
+            value.parse_u64(),
+            Error::new(error::Kind::ExpectedU64, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_u64_zero_fraction() {
+        let value = Value::with_lexicon(b"1.000", Position::new(0, 0), EXTENDED);
+
+        assert_ok_eq!(value.parse_u64(), 1);
+    }
+
+    #[test]
+    fn parse_i64_negative_zero_fraction() {
+        let value = Value::with_lexicon(b"-12.0", Position::new(0, 0), EXTENDED);
+
+        assert_ok_eq!(value.parse_i64(), -12);
+    }
+
+    #[test]
+    fn parse_i16_plus_sign_zero_fraction() {
+        let value = Value::with_lexicon(b"+7.00", Position::new(0, 0), EXTENDED);
+
+        assert_ok_eq!(value.parse_i16(), 7);
+    }
+
+    #[test]
+    fn parse_u8_nonzero_fraction() {
+        let value = Value::with_lexicon(b"1.5", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_u8(),
+            Error::new(error::Kind::ExpectedU8, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_u8_empty_fraction() {
+        let value = Value::with_lexicon(b"1.", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_u8(),
+            Error::new(error::Kind::ExpectedU8, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_i8_fraction_only() {
+        let value = Value::with_lexicon(b"-.0", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_i8(),
+            Error::new(error::Kind::ExpectedI8, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_u8_zero_fraction_overflow() {
+        let value = Value::with_lexicon(b"256.0", Position::new(0, 0), EXTENDED);
+
+        assert_err_eq!(
+            value.parse_u8(),
+            Error::new(error::Kind::ExpectedU8, Position::new(0, 0))
+        );
+    }
+
     #[test]
     fn parse_i8_positive() {
         let value = Value::new(b"42", Position::new(0, 0));
@@ -1160,6 +1428,13 @@ mod tests {
         assert!(result.is_nan());
     }
 
+    #[test]
+    fn parse_f64_infinity_mixed_case() {
+        let value = Value::new(b"Infinity", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_f64(), f64::INFINITY);
+    }
+
     #[test]
     fn parse_f64_negative_nan() {
         let value = Value::new(b"-NaN", Position::new(0, 0));
@@ -1310,6 +1585,13 @@ mod tests {
         assert_eq!(value.parse_byte_buf(), b"\xF0\x9Ffoo",);
     }
 
+    #[test]
+    fn parse_raw_preserves_escapes_and_comments() {
+        let value = Value::new(b" \\#foo\n// comment\nbar ", Position::new(0, 0));
+
+        assert_eq!(value.parse_raw(), b" \\#foo\n// comment\nbar ");
+    }
+
     #[test]
     fn parse_unit() {
         let value = Value::new(b"", Position::new(0, 0));
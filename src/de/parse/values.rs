@@ -1,62 +1,81 @@
 use super::Value;
-use crate::de::{error, Error, Position, Result};
-use std::slice;
-
-enum State {
-    None,
-    MaybeEnteringComment,
-    InComment,
-    Escaping,
-}
-
+use crate::{
+    de::{error, options::Lexicon, Error, Position, Result},
+    scan::{Class, Scanner},
+};
+
+// Values without the lifetime. Used when storing within an Access.
+//
+// The values are located by their byte offsets within the buffer of the tag they were read from.
 #[derive(Debug)]
 pub(in crate::de) struct StoredValues {
-    byte_ptr: *const u8,
-    byte_len: usize,
+    start: usize,
+    end: usize,
 
     exhausted: bool,
 
     current_byte_index: usize,
     current_position: Position,
+
+    lexicon: Lexicon,
 }
 
 impl StoredValues {
-    // # Safety
-    // The caller must guarantee that the buffer referenced by the returned `Values` does not
-    // outlive the returned `Values`. In other words, this returned `Values` must outlive the
-    // `Values` from which it was originally created.
-    pub(in crate::de) unsafe fn into_values<'a>(self) -> Values<'a> {
+    /// Restores the `Values` from the buffer of the tag they were read from.
+    ///
+    /// # Panics
+    /// Panics if `buffer` is shorter than the buffer the values were read from.
+    pub(in crate::de) fn into_values(self, buffer: &[u8]) -> Values<'_> {
         Values {
-            // SAFETY: The lifetime of this slice is guaranteed by the caller.
-            bytes: unsafe { slice::from_raw_parts(self.byte_ptr, self.byte_len) },
+            bytes: &buffer[self.start..self.end],
+            offset: self.start,
 
             exhausted: self.exhausted,
 
             current_byte_index: self.current_byte_index,
             current_position: self.current_position,
+
+            lexicon: self.lexicon,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub(in crate::de) struct Values<'a> {
     bytes: &'a [u8],
+    // The offset of `bytes` within the buffer of the containing tag.
+    offset: usize,
 
     exhausted: bool,
 
     current_byte_index: usize,
     current_position: Position,
+
+    lexicon: Lexicon,
 }
 
 impl<'a> Values<'a> {
+    #[cfg(test)]
     pub(in crate::de) fn new(bytes: &'a [u8], position: Position) -> Self {
+        Self::with_offset(bytes, 0, position, Lexicon::new())
+    }
+
+    pub(in crate::de) fn with_offset(
+        bytes: &'a [u8],
+        offset: usize,
+        position: Position,
+        lexicon: Lexicon,
+    ) -> Self {
         Self {
             bytes,
+            offset,
 
             exhausted: false,
 
             current_byte_index: 0,
             current_position: position,
+
+            lexicon,
         }
     }
 
@@ -68,73 +87,22 @@ impl<'a> Values<'a> {
         let mut value = None;
         let started_byte_index = self.current_byte_index;
         let started_position = self.current_position;
-        let mut state = State::None;
+        let mut scanner = Scanner::within_tag();
         loop {
             if let Some(byte) = self.bytes.get(self.current_byte_index) {
                 // TODO: Put the parsing logic in here instead.
-                match state {
-                    State::None => {
-                        match byte {
-                            b':' => {
-                                // This is the end of a `Value`.
-                                value = Some(Value::new(
-                                    // SAFETY: Both ends of the range used here have already been
-                                    // determined to be within the bounds of self.bytes.
-                                    unsafe {
-                                        self.bytes.get_unchecked(
-                                            started_byte_index..self.current_byte_index,
-                                        )
-                                    },
-                                    started_position,
-                                ));
-                            }
-                            b'\\' => {
-                                // Enter an escaping state.
-                                state = State::Escaping;
-                            }
-                            b'/' => {
-                                state = State::MaybeEnteringComment;
-                            }
-                            _ => {}
-                        }
-                    }
-                    State::MaybeEnteringComment => {
-                        match byte {
-                            b':' => {
-                                // This is the end of a `Value`.
-                                value = Some(Value::new(
-                                    // SAFETY: Both ends of the range used here have already been
-                                    // determined to be within the bounds of self.bytes.
-                                    unsafe {
-                                        self.bytes.get_unchecked(
-                                            started_byte_index..self.current_byte_index,
-                                        )
-                                    },
-                                    started_position,
-                                ));
-                            }
-                            b'\\' => {
-                                // Enter an escaping state.
-                                state = State::Escaping;
-                            }
-                            b'/' => {
-                                // Handle comment state.
-                                state = State::InComment;
-                            }
-                            _ => {
-                                state = State::None;
-                            }
-                        }
-                    }
-                    State::InComment => {
-                        // Consume bytes until we are on a new line.
-                        if matches!(byte, b'\n') {
-                            state = State::None;
-                        }
-                    }
-                    State::Escaping => {
-                        state = State::None;
-                    }
+                if scanner.next(*byte) == Class::Colon {
+                    // This is the end of a `Value`.
+                    value = Some(Value::with_lexicon(
+                        // SAFETY: Both ends of the range used here have already been determined to
+                        // be within the bounds of self.bytes.
+                        unsafe {
+                            self.bytes
+                                .get_unchecked(started_byte_index..self.current_byte_index)
+                        },
+                        started_position,
+                        self.lexicon,
+                    ));
                 }
 
                 if matches!(byte, b'\n') {
@@ -149,7 +117,7 @@ impl<'a> Values<'a> {
                 }
             } else if !self.exhausted {
                 self.exhausted = true;
-                return Ok(Value::new(
+                return Ok(Value::with_lexicon(
                     // SAFETY: self.current_byte_index is guaranteed to only be one past the
                     // last value in the slice.
                     unsafe {
@@ -157,6 +125,7 @@ impl<'a> Values<'a> {
                             .get_unchecked(started_byte_index..self.current_byte_index)
                     },
                     started_position,
+                    self.lexicon,
                 ));
             } else {
                 return Err(Error::new(error::Kind::EndOfValues, self.current_position));
@@ -164,6 +133,27 @@ impl<'a> Values<'a> {
         }
     }
 
+    /// Returns a single `Value` spanning all of the remaining values, including the `:` separators
+    /// between them.
+    pub(in crate::de) fn rest(&mut self) -> Result<Value<'a>> {
+        let started_byte_index = self.current_byte_index;
+        let started_position = self.current_position;
+        self.next()?;
+        while !self.exhausted {
+            self.next()?;
+        }
+        Ok(Value::with_lexicon(
+            // SAFETY: Once exhausted, self.current_byte_index is one past the last value in the
+            // slice, and started_byte_index was a previous value of it.
+            unsafe {
+                self.bytes
+                    .get_unchecked(started_byte_index..self.current_byte_index)
+            },
+            started_position,
+            self.lexicon,
+        ))
+    }
+
     pub(in crate::de) fn assert_exhausted(&self) -> Result<()> {
         if self.exhausted {
             Ok(())
@@ -175,23 +165,41 @@ impl<'a> Values<'a> {
         }
     }
 
+    /// The offset just past the end of these values within the buffer of their tag.
+    pub(in crate::de) fn end_offset(&self) -> usize {
+        self.offset + self.bytes.len()
+    }
+
     pub(in crate::de) fn into_stored(self) -> StoredValues {
         StoredValues {
-            byte_ptr: self.bytes.as_ptr(),
-            byte_len: self.bytes.len(),
+            start: self.offset,
+            end: self.offset + self.bytes.len(),
 
             exhausted: self.exhausted,
 
             current_byte_index: self.current_byte_index,
             current_position: self.current_position,
+
+            lexicon: self.lexicon,
         }
     }
 }
 
+// The offset only locates the values within their tag's buffer, so it is not compared.
+impl PartialEq for Values<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+            && self.exhausted == other.exhausted
+            && self.current_byte_index == other.current_byte_index
+            && self.current_position == other.current_position
+            && self.lexicon == other.lexicon
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Values;
-    use crate::de::{error, parse::Value, Error, Position};
+    use crate::de::{error, options::Lexicon, parse::Value, Error, Position};
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
 
     #[test]
@@ -216,6 +224,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rest() {
+        let mut values = Values::new(b"foo:bar\\:baz:// qux\n", Position::new(0, 0));
+
+        assert_ok_eq!(
+            values.rest(),
+            Value::new(b"foo:bar\\:baz:// qux\n", Position::new(0, 0))
+        );
+        assert_ok!(values.assert_exhausted());
+    }
+
+    #[test]
+    fn rest_after_next() {
+        let mut values = Values::new(b"foo:bar:baz", Position::new(0, 0));
+
+        assert_ok_eq!(values.next(), Value::new(b"foo", Position::new(0, 0)));
+        assert_ok_eq!(values.rest(), Value::new(b"bar:baz", Position::new(0, 4)));
+        assert_ok!(values.assert_exhausted());
+    }
+
+    #[test]
+    fn rest_empty() {
+        let mut values = Values::new(b"", Position::new(0, 0));
+
+        assert_ok_eq!(values.rest(), Value::new(b"", Position::new(0, 0)));
+    }
+
+    #[test]
+    fn rest_exhausted() {
+        let mut values = Values::new(b"foo", Position::new(0, 0));
+
+        assert_ok!(values.next());
+        assert_err_eq!(
+            values.rest(),
+            Error::new(error::Kind::EndOfValues, Position::new(0, 3))
+        );
+    }
+
     #[test]
     fn finds_multiple_values() {
         let mut values = Values::new(b"foo:bar:baz", Position::new(0, 0));
@@ -345,7 +391,7 @@ mod tests {
         let buffer = b"foo";
         let values = Values::new(buffer, Position::new(0, 0));
         let stored = values.into_stored();
-        let mut unstored_values = unsafe { stored.into_values() };
+        let mut unstored_values = stored.into_values(buffer);
 
         assert_ok_eq!(
             unstored_values.next(),
@@ -363,7 +409,7 @@ mod tests {
         let mut values = Values::new(buffer, Position::new(0, 0));
         assert_ok!(values.next());
         let stored = values.into_stored();
-        let mut unstored_values = unsafe { stored.into_values() };
+        let mut unstored_values = stored.into_values(buffer);
 
         assert_ok_eq!(
             unstored_values.next(),
@@ -381,4 +427,23 @@ mod tests {
 
         assert_eq!(values.current_position(), Position::new(1, 2));
     }
+
+    #[test]
+    fn into_stored_with_offset() {
+        let buffer = b"foo;bar:baz;";
+        let mut values =
+            Values::with_offset(&buffer[4..11], 4, Position::new(0, 5), Lexicon::new());
+        assert_ok!(values.next());
+        let stored = values.into_stored();
+        let mut unstored_values = stored.into_values(buffer);
+
+        assert_ok_eq!(
+            unstored_values.next(),
+            Value::new(b"baz", Position::new(0, 9))
+        );
+        assert_err_eq!(
+            unstored_values.next(),
+            Error::new(error::Kind::EndOfValues, Position::new(0, 12))
+        );
+    }
 }
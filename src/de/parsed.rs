@@ -0,0 +1,231 @@
+use crate::de::{error, parse, Options, Position, Result};
+use crate::io::Read;
+use alloc::{vec, vec::Vec};
+
+/// A tag as read by the parser, with each of its values unescaped and stripped of comments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ParsedTag {
+    /// The position of the tag's `#`.
+    pub(crate) position: Position,
+    pub(crate) name: Vec<u8>,
+    /// The tag's parameter lists, each ended by a `;`. The name is not included in the first
+    /// list.
+    pub(crate) parameter_lists: Vec<Vec<Vec<u8>>>,
+}
+
+impl ParsedTag {
+    /// Reads the single tag spanning `bytes`, located at `position` within its document.
+    ///
+    /// The bytes must begin with the tag's `#` and end where the [`Lexer`] ends the tag. Since the
+    /// lexer splits tags the same way the parser does when recovering from every deviation from the
+    /// format, they are read as exactly one tag.
+    ///
+    /// [`Lexer`]: crate::lex::Lexer
+    #[cfg(any(feature = "lint", feature = "merge", test))]
+    pub(crate) fn read(bytes: &[u8], position: Position) -> Result<Self> {
+        let mut tag = match ParsedTags::with_options(bytes, Options::lenient()).next() {
+            Some(tag) => tag?,
+            None => ParsedTag {
+                position,
+                name: Vec::new(),
+                parameter_lists: vec![Vec::new()],
+            },
+        };
+        tag.position = position;
+        Ok(tag)
+    }
+}
+
+/// An iterator over the tags of MSD input, as read by the parser.
+///
+/// Unlike the [`Deserializer`], no type directs the parsing, so every parameter of every tag is
+/// read as bytes.
+///
+/// [`Deserializer`]: crate::Deserializer
+pub(crate) struct ParsedTags<R> {
+    tags: parse::Tags<R>,
+    finished: bool,
+}
+
+impl<R> ParsedTags<R>
+where
+    R: Read,
+{
+    pub(crate) fn with_options(reader: R, options: Options) -> Self {
+        Self {
+            tags: parse::Tags::with_options(reader, options),
+            finished: false,
+        }
+    }
+
+    /// Returns the next tag whose name satisfies `predicate`.
+    ///
+    /// The values of tags whose names do not satisfy `predicate` are skipped without being
+    /// unescaped.
+    pub(crate) fn next_matching<F>(&mut self, mut predicate: F) -> Option<Result<ParsedTag>>
+    where
+        F: FnMut(&[u8]) -> bool,
+    {
+        loop {
+            if self.finished {
+                return None;
+            }
+            let mut tag = match self.tags.next() {
+                Ok(tag) => tag,
+                Err(error) => {
+                    self.finished = true;
+                    return match error.kind() {
+                        error::Kind::EndOfFile => None,
+                        _ => Some(Err(error)),
+                    };
+                }
+            };
+
+            let position = tag.origin_position();
+            // The first parameter list always contains at least the name.
+            let mut first_values = match tag.next() {
+                Ok(values) => values,
+                Err(error) => return Some(Err(error)),
+            };
+            let name = match first_values.next() {
+                Ok(value) => value.parse_byte_buf(),
+                Err(error) => return Some(Err(error)),
+            };
+            if !predicate(&name) {
+                continue;
+            }
+
+            let mut parameter_lists = vec![parameters(first_values)];
+            while let Ok(values) = tag.next() {
+                parameter_lists.push(parameters(values));
+            }
+
+            return Some(Ok(ParsedTag {
+                position,
+                name,
+                parameter_lists,
+            }));
+        }
+    }
+}
+
+fn parameters(mut values: parse::Values) -> Vec<Vec<u8>> {
+    let mut parameters = Vec::new();
+    while let Ok(value) = values.next() {
+        parameters.push(value.parse_byte_buf());
+    }
+    parameters
+}
+
+impl<R> Iterator for ParsedTags<R>
+where
+    R: Read,
+{
+    type Item = Result<ParsedTag>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_matching(|_| true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParsedTag, ParsedTags};
+    use crate::de::{Options, Position};
+    use claims::{assert_err, assert_none, assert_ok_eq, assert_some_eq};
+
+    #[test]
+    fn empty() {
+        let mut tags = ParsedTags::with_options(b"".as_slice(), Options::new());
+
+        assert_none!(tags.next());
+    }
+
+    #[test]
+    fn tags() {
+        let mut tags = ParsedTags::with_options(
+            b"#TITLE:f\\:o // comment\n:bar;\n#EMPTY;\n#BPMS:\n   0:120;\n   4:60;\n".as_slice(),
+            Options::new(),
+        );
+
+        assert_some_eq!(
+            tags.next().map(Result::unwrap),
+            ParsedTag {
+                position: Position::new(0, 0),
+                name: b"TITLE".to_vec(),
+                parameter_lists: vec![vec![b"f:o \n".to_vec(), b"bar".to_vec()]],
+            }
+        );
+        assert_some_eq!(
+            tags.next().map(Result::unwrap),
+            ParsedTag {
+                position: Position::new(2, 0),
+                name: b"EMPTY".to_vec(),
+                parameter_lists: vec![vec![]],
+            }
+        );
+        assert_some_eq!(
+            tags.next().map(Result::unwrap),
+            ParsedTag {
+                position: Position::new(3, 0),
+                name: b"BPMS".to_vec(),
+                parameter_lists: vec![
+                    vec![b"\n   0".to_vec(), b"120".to_vec()],
+                    vec![b"\n   4".to_vec(), b"60".to_vec()]
+                ],
+            }
+        );
+        assert_none!(tags.next());
+    }
+
+    #[test]
+    fn next_matching() {
+        let mut tags = ParsedTags::with_options(
+            b"#TITLE:foo;\n#BPMS:0=120;\n#title:bar;\n".as_slice(),
+            Options::new(),
+        );
+
+        assert_some_eq!(
+            tags.next_matching(|name| name.eq_ignore_ascii_case(b"TITLE"))
+                .map(Result::unwrap),
+            ParsedTag {
+                position: Position::new(0, 0),
+                name: b"TITLE".to_vec(),
+                parameter_lists: vec![vec![b"foo".to_vec()]],
+            }
+        );
+        assert_some_eq!(
+            tags.next_matching(|name| name.eq_ignore_ascii_case(b"TITLE"))
+                .map(Result::unwrap),
+            ParsedTag {
+                position: Position::new(2, 0),
+                name: b"title".to_vec(),
+                parameter_lists: vec![vec![b"bar".to_vec()]],
+            }
+        );
+        assert_none!(tags.next_matching(|_| true));
+    }
+
+    #[test]
+    fn error() {
+        let mut tags = ParsedTags::with_options(b"foo\n#BAR;".as_slice(), Options::new());
+
+        assert_err!(tags.next().unwrap());
+        assert_none!(tags.next());
+    }
+
+    #[test]
+    fn read() {
+        assert_ok_eq!(
+            ParsedTag::read(b"#BPMS:0:120;\n4:60;\n", Position::new(3, 0)),
+            ParsedTag {
+                position: Position::new(3, 0),
+                name: b"BPMS".to_vec(),
+                parameter_lists: vec![
+                    vec![b"0".to_vec(), b"120".to_vec()],
+                    vec![b"\n4".to_vec(), b"60".to_vec()]
+                ],
+            }
+        );
+    }
+}
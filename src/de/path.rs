@@ -0,0 +1,32 @@
+use crate::de::parse::Values;
+use alloc::{borrow::ToOwned, string::String};
+
+/// Returns the text of the next value in `values`, for use as a [`PathSegment::Key`], without
+/// consuming it.
+///
+/// [`PathSegment::Key`]: crate::de::PathSegment::Key
+pub(in crate::de) fn key(values: &Values) -> String {
+    values.clone().next().map_or_else(
+        |_| String::new(),
+        |value| {
+            String::from_utf8_lossy(&value.parse_byte_buf())
+                .trim()
+                .to_owned()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::key;
+    use crate::de::{parse::Values, Position};
+
+    #[test]
+    fn key_does_not_consume() {
+        let mut values = Values::new(b" f\\:oo :bar", Position::new(0, 0));
+
+        assert_eq!(key(&values), "f:oo");
+        assert!(values.next().is_ok());
+        assert_eq!(key(&values), "bar");
+    }
+}
@@ -1,42 +1,94 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(in crate::de) struct Position {
+/// A location within MSD input.
+///
+/// Both the line and the column are zero-indexed. The column is measured in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
     line: usize,
     column: usize,
 }
 
 impl Position {
-    pub(in crate::de) fn new(line: usize, column: usize) -> Self {
+    pub(crate) fn new(line: usize, column: usize) -> Self {
         Self { line, column }
     }
 
-    pub(in crate::de) fn line(&self) -> usize {
+    /// Returns the zero-indexed line.
+    pub fn line(&self) -> usize {
         self.line
     }
 
-    pub(in crate::de) fn column(&self) -> usize {
+    /// Returns the zero-indexed column, in bytes.
+    pub fn column(&self) -> usize {
         self.column
     }
 
-    pub(in crate::de) fn increment_line(self) -> Self {
+    /// Returns the zero-indexed column, in characters, within the input the position refers to.
+    ///
+    /// The input is interpreted as UTF-8, with each byte of an invalid sequence counted as its
+    /// own character.
+    pub fn char_column(&self, input: &[u8]) -> usize {
+        let start = self.line_start(input);
+        input[start..self.offset(input)]
+            .iter()
+            .filter(|&&byte| !is_continuation_byte(byte))
+            .count()
+    }
+
+    /// Returns the offset of the start of the position's line within `input`.
+    ///
+    /// If the input has fewer lines, this is the end of the input.
+    pub(in crate::de) fn line_start(&self, input: &[u8]) -> usize {
+        if self.line == 0 {
+            return 0;
+        }
+        input
+            .iter()
+            .enumerate()
+            .filter(|(_, &byte)| byte == b'\n')
+            .nth(self.line - 1)
+            .map_or(input.len(), |(index, _)| index + 1)
+    }
+
+    /// Returns the byte offset of the position within `input`.
+    ///
+    /// The offset is clamped to the end of the position's line.
+    pub(in crate::de) fn offset(&self, input: &[u8]) -> usize {
+        let start = self.line_start(input);
+        let end = input[start..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(input.len(), |index| start + index);
+        (start + self.column).min(end)
+    }
+
+    /// Returns the position this position refers to when it was measured from `origin` rather
+    /// than from the start of the input.
+    pub(in crate::de) fn relative_to(self, origin: Position) -> Self {
+        if self.line == 0 {
+            Self::new(origin.line, origin.column + self.column)
+        } else {
+            Self::new(origin.line + self.line, self.column)
+        }
+    }
+
+    pub(crate) fn increment_line(self) -> Self {
         Self {
             line: self.line + 1,
             column: 0,
         }
     }
 
-    pub(in crate::de) fn increment_column(self) -> Self {
+    pub(crate) fn increment_column(self) -> Self {
         Self {
             line: self.line,
             column: self.column + 1,
         }
     }
+}
 
-    pub(in crate::de) fn decrement_column(self) -> Self {
-        Self {
-            line: self.line,
-            column: self.column - 1,
-        }
-    }
+/// Returns whether `byte` continues a multi-byte UTF-8 sequence.
+pub(in crate::de) fn is_continuation_byte(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
 }
 
 #[cfg(test)]
@@ -57,6 +109,28 @@ mod tests {
         assert_eq!(position.column(), 7);
     }
 
+    #[test]
+    fn ordering() {
+        assert!(Position::new(1, 0) > Position::new(0, 7));
+        assert!(Position::new(1, 2) < Position::new(1, 3));
+    }
+
+    #[test]
+    fn relative_to_same_line() {
+        assert_eq!(
+            Position::new(0, 3).relative_to(Position::new(2, 5)),
+            Position::new(2, 8)
+        );
+    }
+
+    #[test]
+    fn relative_to_later_line() {
+        assert_eq!(
+            Position::new(1, 3).relative_to(Position::new(2, 5)),
+            Position::new(3, 3)
+        );
+    }
+
     #[test]
     fn increment_line() {
         let position = Position::new(5, 7);
@@ -72,9 +146,35 @@ mod tests {
     }
 
     #[test]
-    fn decrement_column() {
-        let position = Position::new(5, 7);
+    fn char_column_ascii() {
+        assert_eq!(Position::new(1, 3).char_column(b"#foo;\n#bar;\n"), 3);
+    }
 
-        assert_eq!(position.decrement_column(), Position::new(5, 6));
+    #[test]
+    fn char_column_multi_byte() {
+        assert_eq!(
+            Position::new(1, 9).char_column("#a;\n#T:\u{e9}\u{1f600}x;".as_bytes()),
+            5
+        );
+    }
+
+    #[test]
+    fn char_column_invalid_utf8() {
+        assert_eq!(Position::new(0, 4).char_column(b"#\xff\xfex;"), 4);
+    }
+
+    #[test]
+    fn char_column_past_end_of_line() {
+        assert_eq!(Position::new(0, 10).char_column(b"#foo;\n#bar;"), 5);
+    }
+
+    #[test]
+    fn char_column_past_end_of_input() {
+        assert_eq!(Position::new(3, 2).char_column(b"#foo;\n"), 0);
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(Position::new(1, 2).offset(b"#foo;\r\n#bar;"), 9);
     }
 }
@@ -0,0 +1,356 @@
+use crate::de::{position::is_continuation_byte, PathSegment, Position, Severity};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::{Display, Write},
+    ops::Range,
+};
+
+/// Returns the number of characters in `bytes`, counting each byte of an invalid UTF-8 sequence
+/// as its own character.
+fn char_count(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .filter(|&&byte| !is_continuation_byte(byte))
+        .count()
+}
+
+/// Returns the name of the innermost tag along `path`, if there is one.
+fn tag_name(path: &[PathSegment]) -> Option<&str> {
+    path.iter().rev().find_map(|segment| match segment {
+        PathSegment::Tag(name) => Some(name.as_str()),
+        _ => None,
+    })
+}
+
+/// Returns the range of the value beginning at `offset`, limited to the line ending at
+/// `line_end`.
+///
+/// The value ends at the first unescaped `:`, `;`, or `#`, or at a comment. The range excludes
+/// whitespace surrounding the value. If there is no value at `offset`, the range is empty.
+fn value_range(input: &[u8], offset: usize, line_end: usize) -> Range<usize> {
+    let mut value: Option<Range<usize>> = None;
+    let mut index = offset;
+    while index < line_end {
+        let end = match input[index] {
+            b':' | b';' | b'#' => break,
+            b'/' if input.get(index + 1) == Some(&b'/') => break,
+            byte if byte.is_ascii_whitespace() => {
+                index += 1;
+                continue;
+            }
+            // An escaped byte is part of the value, whatever it is.
+            b'\\' => (index + 2).min(line_end),
+            _ => index + 1,
+        };
+        value = Some(value.map_or(index, |value| value.start)..end);
+        index = end;
+    }
+    value.unwrap_or(offset..offset)
+}
+
+/// Renders a problem within `input` as an excerpt of the offending line, with the problem's value
+/// underlined and labeled with the name of the innermost tag along `path`.
+///
+/// If `position` is not known, only the message is rendered, since there is no line to excerpt.
+/// Lines and columns are displayed one-indexed, in characters.
+pub(in crate::de) fn render(
+    severity: Severity,
+    message: &dyn Display,
+    position: Option<Position>,
+    path: &[PathSegment],
+    input: &[u8],
+) -> String {
+    let mut rendered = String::new();
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(rendered, "{}: {}", severity, message);
+    let position = match position {
+        Some(position) => position,
+        None => return rendered,
+    };
+
+    let line_start = position.line_start(input);
+    let line_end = input[line_start..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(input.len(), |index| line_start + index);
+    let offset = position.offset(input);
+    let value = value_range(input, offset, line_end);
+
+    let mut line = &input[line_start..line_end];
+    if let Some(stripped) = line.strip_suffix(b"\r") {
+        line = stripped;
+    }
+    let line_number = (position.line() + 1).to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    // Tabs are kept in the padding so that the underline lines up with the line above it.
+    let padding = input[line_start..value.start]
+        .iter()
+        .filter(|&&byte| !is_continuation_byte(byte))
+        .map(|&byte| if byte == b'\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let underline = "^".repeat(char_count(&input[value]).max(1));
+    let label = match tag_name(path) {
+        Some(tag) => format!("{} in tag `{}`", message, tag),
+        None => message.to_string(),
+    };
+
+    let _ = writeln!(
+        rendered,
+        "{}--> line {}, column {}",
+        gutter,
+        line_number,
+        position.char_column(input) + 1
+    );
+    let _ = writeln!(rendered, "{} |", gutter);
+    let _ = writeln!(
+        rendered,
+        "{} | {}",
+        line_number,
+        String::from_utf8_lossy(line)
+    );
+    let _ = writeln!(rendered, "{} | {}{} {}", gutter, padding, underline, label);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::de::{PathSegment, Position, Severity};
+
+    #[test]
+    fn value() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u32",
+                Some(Position::new(1, 6)),
+                &[PathSegment::Tag("BPMS".to_owned())],
+                b"#TITLE:foo;\n#BPMS:abc;\n"
+            ),
+            "error: expected u32\n --> line 2, column 7\n  |\n2 | #BPMS:abc;\n  |       ^^^ expected u32 in tag `BPMS`\n"
+        );
+    }
+
+    #[test]
+    fn value_with_whitespace_and_escapes() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected f32",
+                Some(Position::new(0, 8)),
+                &[PathSegment::Tag("OFFSET".to_owned())],
+                b"#OFFSET:  1\\:2 ;\r\n"
+            ),
+            "error: expected f32\n --> line 1, column 9\n  |\n1 | #OFFSET:  1\\:2 ;\n  |           ^^^^ expected f32 in tag `OFFSET`\n"
+        );
+    }
+
+    #[test]
+    fn second_parameter() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected bool",
+                Some(Position::new(0, 7)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:1:maybe;\n"
+            ),
+            "error: expected bool\n --> line 1, column 8\n  |\n1 | #FOO:1:maybe;\n  |        ^^^^^ expected bool in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn multi_byte_characters() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(0, 10)),
+                &[PathSegment::Tag("T\u{e9}".to_owned())],
+                "#T\u{e9}:\u{1f600}:\u{e9}\u{e9};".as_bytes()
+            ),
+            "error: expected u8\n --> line 1, column 7\n  |\n1 | #T\u{e9}:\u{1f600}:\u{e9}\u{e9};\n  |       ^^ expected u8 in tag `T\u{e9}`\n"
+        );
+    }
+
+    #[test]
+    fn tabs() {
+        assert_eq!(
+            render(
+                Severity::Warning,
+                &"expected u8",
+                Some(Position::new(0, 6)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:\tx;"
+            ),
+            "warning: expected u8\n --> line 1, column 7\n  |\n1 | #FOO:\tx;\n  |      \t^ expected u8 in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn empty_value() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(0, 5)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:;"
+            ),
+            "error: expected u8\n --> line 1, column 6\n  |\n1 | #FOO:;\n  |      ^ expected u8 in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn multi_line_value() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(0, 5)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:12\n34;"
+            ),
+            "error: expected u8\n --> line 1, column 6\n  |\n1 | #FOO:12\n  |      ^^ expected u8 in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn before_first_tag() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected tag",
+                Some(Position::new(1, 0)),
+                &[],
+                b"\nfoo #BAR;"
+            ),
+            "error: expected tag\n --> line 2, column 1\n  |\n2 | foo #BAR;\n  | ^^^ expected tag\n"
+        );
+    }
+
+    #[test]
+    fn escaped_name() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"unexpected tag",
+                Some(Position::new(1, 1)),
+                &[PathSegment::Tag("B:AR".to_owned())],
+                b"#FOO:bar;\n#B\\:AR :baz;\n"
+            ),
+            "error: unexpected tag\n --> line 2, column 2\n  |\n2 | #B\\:AR :baz;\n  |  ^^^^^ unexpected tag in tag `B:AR`\n"
+        );
+    }
+
+    #[test]
+    fn innermost_tag() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(1, 7)),
+                &[
+                    PathSegment::Tag("NOTES".to_owned()),
+                    PathSegment::Element(0),
+                    PathSegment::Tag("METER".to_owned()),
+                    PathSegment::Parameter(0),
+                ],
+                b"#NOTES:;\n#METER:x;\n"
+            ),
+            "error: expected u8\n --> line 2, column 8\n  |\n2 | #METER:x;\n  |        ^ expected u8 in tag `METER`\n"
+        );
+    }
+
+    #[test]
+    fn comment_after_value() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(0, 5)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:12 // 34\n;"
+            ),
+            "error: expected u8\n --> line 1, column 6\n  |\n1 | #FOO:12 // 34\n  |      ^^ expected u8 in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn unknown_position() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"missing field `TITLE`",
+                None,
+                &[],
+                b"#FOO:bar;\n"
+            ),
+            "error: missing field `TITLE`\n"
+        );
+    }
+
+    #[test]
+    fn unknown_position_within_tag() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"invalid value",
+                None,
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO:bar;\n"
+            ),
+            "error: invalid value\n"
+        );
+    }
+
+    #[test]
+    fn tag_without_parameters() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(0, 1)),
+                &[PathSegment::Tag("FOO".to_owned())],
+                b"#FOO\n#BAR;"
+            ),
+            "error: expected u8\n --> line 1, column 2\n  |\n1 | #FOO\n  |  ^^^ expected u8 in tag `FOO`\n"
+        );
+    }
+
+    #[test]
+    fn end_of_file() {
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"unexpected end of file",
+                Some(Position::new(1, 0)),
+                &[],
+                b"#FOO:bar;\n"
+            ),
+            "error: unexpected end of file\n --> line 2, column 1\n  |\n2 | \n  | ^ unexpected end of file\n"
+        );
+    }
+
+    #[test]
+    fn wide_gutter() {
+        let input = b"#A;\n".repeat(9);
+        let mut input = input;
+        input.extend_from_slice(b"#B:x;\n");
+
+        assert_eq!(
+            render(
+                Severity::Error,
+                &"expected u8",
+                Some(Position::new(9, 3)),
+                &[PathSegment::Tag("B".to_owned())],
+                &input
+            ),
+            "error: expected u8\n  --> line 10, column 4\n   |\n10 | #B:x;\n   |    ^ expected u8 in tag `B`\n"
+        );
+    }
+}
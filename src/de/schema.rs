@@ -0,0 +1,721 @@
+use crate::{
+    de::{
+        error, parse::Tags, parse::Value, validate, warning, Error, Options, ParsedTags, Position,
+        Result,
+    },
+    io::Read,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, fmt::Display};
+
+/// The type a parameter must be readable as, as checked by a [`Schema`].
+///
+/// Each type is checked exactly as the [`Deserializer`] reads it, including the lexicon allowed by
+/// the [`Options`] used for validation.
+///
+/// [`Deserializer`]: crate::Deserializer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterType {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Char,
+    /// Valid UTF-8.
+    String,
+    /// Any bytes at all.
+    Bytes,
+}
+
+impl ParameterType {
+    const ALL: [ParameterType; 14] = [
+        ParameterType::Bool,
+        ParameterType::I8,
+        ParameterType::I16,
+        ParameterType::I32,
+        ParameterType::I64,
+        ParameterType::U8,
+        ParameterType::U16,
+        ParameterType::U32,
+        ParameterType::U64,
+        ParameterType::F32,
+        ParameterType::F64,
+        ParameterType::Char,
+        ParameterType::String,
+        ParameterType::Bytes,
+    ];
+
+    const NAMES: &'static [&'static str] = &[
+        "bool", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "char",
+        "string", "bytes",
+    ];
+
+    /// Returns the name of the type, as used in schema files.
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    /// Returns the type with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| ty.name() == name)
+    }
+
+    /// Returns whether `value` can be read as this type.
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            ParameterType::Bool => value.parse_bool().is_ok(),
+            ParameterType::I8 => value.parse_i8().is_ok(),
+            ParameterType::I16 => value.parse_i16().is_ok(),
+            ParameterType::I32 => value.parse_i32().is_ok(),
+            ParameterType::I64 => value.parse_i64().is_ok(),
+            ParameterType::U8 => value.parse_u8().is_ok(),
+            ParameterType::U16 => value.parse_u16().is_ok(),
+            ParameterType::U32 => value.parse_u32().is_ok(),
+            ParameterType::U64 => value.parse_u64().is_ok(),
+            ParameterType::F32 => value.parse_f32().is_ok(),
+            ParameterType::F64 => value.parse_f64().is_ok(),
+            ParameterType::Char => value.parse_char().is_ok(),
+            ParameterType::String => value.parse_string().is_ok(),
+            ParameterType::Bytes => true,
+        }
+    }
+}
+
+impl Display for ParameterType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.name())
+    }
+}
+
+/// The expected shape of a single tag within a [`Schema`].
+///
+/// A tag's parameters are counted across all of its parameter lists.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagSchema {
+    name: String,
+    required: bool,
+    parameters: Vec<ParameterType>,
+    /// The number of parameters that must be present.
+    required_parameters: usize,
+}
+
+impl TagSchema {
+    /// Returns a schema for an optional tag named `name` that takes no parameters.
+    ///
+    /// Names are matched ignoring ASCII case.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            required: false,
+            parameters: Vec::new(),
+            required_parameters: 0,
+        }
+    }
+
+    /// Sets whether the tag must be present in the document.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Adds a parameter of type `ty` that must be present.
+    ///
+    /// Parameters following an optional parameter are also optional.
+    pub fn parameter(mut self, ty: ParameterType) -> Self {
+        if self.required_parameters == self.parameters.len() {
+            self.required_parameters += 1;
+        }
+        self.parameters.push(ty);
+        self
+    }
+
+    /// Adds a parameter of type `ty` that may be omitted.
+    pub fn optional_parameter(mut self, ty: ParameterType) -> Self {
+        self.parameters.push(ty);
+        self
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+/// A declarative description of the tags a document may contain.
+///
+/// A schema lists each expected tag, whether it is required, and the number and types of its
+/// parameters. It can be constructed in code or loaded from a schema file with
+/// [`Schema::from_reader()`], and [`Schema::validate()`] reports every way in which a document
+/// violates it.
+///
+/// # Example
+/// ```
+/// use msd::de::{Options, ParameterType, Schema, TagSchema, ViolationKind};
+///
+/// let schema = Schema::new()
+///     .tag(TagSchema::new("TITLE").required(true).parameter(ParameterType::String))
+///     .tag(TagSchema::new("OFFSET").parameter(ParameterType::F64));
+///
+/// let violations = schema
+///     .validate(b"#OFFSET:0.5:1;\n".as_slice(), Options::new())
+///     .unwrap();
+///
+/// assert_eq!(violations.len(), 2);
+/// assert_eq!(
+///     violations[0].kind(),
+///     &ViolationKind::TooManyParameters("OFFSET".to_owned(), 2, 1)
+/// );
+/// assert_eq!(
+///     violations[1].kind(),
+///     &ViolationKind::MissingTag("TITLE".to_owned())
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    tags: Vec<TagSchema>,
+    deny_unknown_tags: bool,
+}
+
+impl Schema {
+    /// Returns a schema expecting no tags, and allowing any others.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an expected tag.
+    pub fn tag(mut self, tag: TagSchema) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Sets whether tags not described by the schema are violations.
+    pub fn deny_unknown_tags(mut self, deny: bool) -> Self {
+        self.deny_unknown_tags = deny;
+        self
+    }
+
+    /// Loads a schema from a schema file.
+    ///
+    /// A schema file is itself an MSD document, in which each tag describes the tag of the same
+    /// name. Its first parameter is either `required` or `optional`, and each following parameter
+    /// is the name of a [`ParameterType`], followed by a `?` if the parameter may be omitted. A tag
+    /// named `*` with the parameter `deny` or `allow` configures whether other tags are allowed.
+    ///
+    /// ```text
+    /// #TITLE:required:string;
+    /// #OFFSET:optional:f64;
+    /// #DISPLAYBPM:optional:f64:f64?;
+    /// #*:deny;
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the schema file is not valid MSD, if a tag has no parameters, or if a
+    /// parameter is not one of the values described above.
+    pub fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: Read,
+    {
+        const PRESENCES: &[&str] = &["required", "optional"];
+        const UNKNOWN_TAGS: &[&str] = &["deny", "allow"];
+
+        let mut schema = Self::new();
+        for tag in ParsedTags::with_options(reader, Options::new()) {
+            let tag = tag?;
+            let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).trim().to_string();
+            let mut parameters = tag
+                .parameter_lists
+                .iter()
+                .flatten()
+                .map(|bytes| text(bytes));
+            let first = parameters
+                .next()
+                .ok_or_else(|| Error::new(error::Kind::EndOfValues, tag.position))?;
+            let unknown = |value: String, expected| {
+                Error::new(error::Kind::UnknownVariant(value, expected), tag.position)
+            };
+
+            let name = text(&tag.name);
+            if name == "*" {
+                schema.deny_unknown_tags = match first.as_str() {
+                    "deny" => true,
+                    "allow" => false,
+                    _ => return Err(unknown(first, UNKNOWN_TAGS)),
+                };
+                if parameters.next().is_some() {
+                    return Err(Error::new(error::Kind::UnexpectedValue, tag.position));
+                }
+                continue;
+            }
+
+            let mut tag_schema = TagSchema::new(&name).required(match first.as_str() {
+                "required" => true,
+                "optional" => false,
+                _ => return Err(unknown(first, PRESENCES)),
+            });
+            for parameter in parameters {
+                let (ty, optional) = match parameter.strip_suffix('?') {
+                    Some(ty) => (ty, true),
+                    None => (parameter.as_str(), false),
+                };
+                let ty = match ParameterType::from_name(ty) {
+                    Some(ty) => ty,
+                    None => return Err(unknown(ty.to_string(), ParameterType::NAMES)),
+                };
+                tag_schema = if optional {
+                    tag_schema.optional_parameter(ty)
+                } else {
+                    tag_schema.parameter(ty)
+                };
+            }
+            schema.tags.push(tag_schema);
+        }
+        Ok(schema)
+    }
+
+    /// Validates an MSD document against the schema, returning every violation found.
+    ///
+    /// Violations are ordered by their positions. Required tags that are missing entirely are
+    /// reported at the end of the document.
+    ///
+    /// The document is read recovering from every deviation from the MSD format, so that one
+    /// malformed tag does not hide problems in the rest of the document. Deviations that `options`
+    /// does not allow are reported as [`Kind::Syntax`] violations.
+    ///
+    /// # Errors
+    /// Returns an error if the document cannot be read at all, such as when reading fails or a
+    /// limit set by `options` is exceeded.
+    pub fn validate<R>(&self, reader: R, options: Options) -> Result<Vec<Violation>>
+    where
+        R: Read,
+    {
+        let mut violations = Vec::new();
+        let mut found = alloc::vec![false; self.tags.len()];
+
+        let mut tags = Tags::with_options(
+            reader,
+            Options {
+                // Unlike the input to `validate()`, the document is read from a stream, so the
+                // limits still guard against unbounded input.
+                limits: options.limits,
+                ..validate::recovering_options(&options)
+            },
+        );
+        loop {
+            let mut tag = match tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => break,
+                Err(error) => return Err(error),
+            };
+            let position = tag.origin_position();
+            // The first parameter list always contains at least the name.
+            let mut values = tag.next()?;
+            let name_bytes = values.next()?.parse_byte_buf();
+            let name = String::from_utf8_lossy(&name_bytes).trim().to_string();
+
+            let index = match self.tags.iter().position(|schema| schema.matches(&name)) {
+                Some(index) => index,
+                None => {
+                    if self.deny_unknown_tags {
+                        violations.push(Violation {
+                            kind: Kind::UnknownTag(name),
+                            position,
+                        });
+                    }
+                    continue;
+                }
+            };
+            found[index] = true;
+            let schema = &self.tags[index];
+
+            let mut count = 0;
+            loop {
+                while let Ok(value) = values.next() {
+                    if let Some(&ty) = schema.parameters.get(count) {
+                        if !ty.accepts(&value) {
+                            violations.push(Violation {
+                                kind: Kind::InvalidParameter(name.clone(), count, ty),
+                                position: value.position(),
+                            });
+                        }
+                    }
+                    count += 1;
+                }
+                values = match tag.next() {
+                    Ok(values) => values,
+                    Err(_) => break,
+                };
+            }
+            if count < schema.required_parameters {
+                violations.push(Violation {
+                    kind: Kind::TooFewParameters(name, count, schema.required_parameters),
+                    position,
+                });
+            } else if count > schema.parameters.len() {
+                violations.push(Violation {
+                    kind: Kind::TooManyParameters(name, count, schema.parameters.len()),
+                    position,
+                });
+            }
+        }
+
+        violations.extend(
+            tags.warnings()
+                .iter()
+                .filter(|warning| !validate::is_allowed(warning.kind(), &options))
+                .map(|warning| Violation {
+                    kind: Kind::Syntax(warning.kind().clone()),
+                    position: warning.position(),
+                }),
+        );
+        violations.sort_by_key(|violation| violation.position);
+
+        let end_of_document = tags.current_position();
+        violations.extend(
+            self.tags
+                .iter()
+                .zip(found)
+                .filter(|(schema, found)| schema.required && !found)
+                .map(|(schema, _)| Violation {
+                    kind: Kind::MissingTag(schema.name.clone()),
+                    position: end_of_document,
+                }),
+        );
+        Ok(violations)
+    }
+}
+
+/// The kind of a [`Violation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A required tag, with the given name, is not present.
+    MissingTag(String),
+    /// A tag, with the given name, is not described by a schema denying unknown tags.
+    UnknownTag(String),
+    /// A tag has fewer parameters than required, given as the tag's name, the number of
+    /// parameters found, and the number required.
+    TooFewParameters(String, usize, usize),
+    /// A tag has more parameters than described, given as the tag's name, the number of
+    /// parameters found, and the number described.
+    TooManyParameters(String, usize, usize),
+    /// A parameter cannot be read as its type, given as the tag's name, the zero-indexed position
+    /// of the parameter, and the expected type.
+    InvalidParameter(String, usize, ParameterType),
+    /// A deviation from the MSD format that the options used for validation do not allow.
+    Syntax(warning::Kind),
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::MissingTag(name) => write!(formatter, "missing required tag `#{}`", name),
+            Kind::UnknownTag(name) => write!(formatter, "unknown tag `#{}`", name),
+            Kind::TooFewParameters(name, found, required) => write!(
+                formatter,
+                "`#{}` has {} parameters, expected at least {}",
+                name, found, required
+            ),
+            Kind::TooManyParameters(name, found, described) => write!(
+                formatter,
+                "`#{}` has {} parameters, expected at most {}",
+                name, found, described
+            ),
+            Kind::InvalidParameter(name, index, ty) => write!(
+                formatter,
+                "parameter {} of `#{}` is not a valid {}",
+                index, name, ty
+            ),
+            Kind::Syntax(kind) => kind.fmt(formatter),
+        }
+    }
+}
+
+/// A single way in which a document does not match a [`Schema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    kind: Kind,
+    position: Position,
+}
+
+impl Violation {
+    /// Returns the kind of violation that was found.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the position in the input at which the violation was found.
+    ///
+    /// Missing tags are reported at the end of the input.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} at line {} column {}",
+            self.kind,
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kind, ParameterType, Schema, TagSchema, Violation};
+    use crate::de::{error, warning, Error, Limit, Options, Position};
+    use alloc::{borrow::ToOwned, format, vec, vec::Vec};
+    use claims::{assert_err_eq, assert_ok_eq};
+
+    fn song() -> Schema {
+        Schema::new()
+            .tag(
+                TagSchema::new("TITLE")
+                    .required(true)
+                    .parameter(ParameterType::String),
+            )
+            .tag(TagSchema::new("OFFSET").parameter(ParameterType::F64))
+            .tag(
+                TagSchema::new("DISPLAYBPM")
+                    .parameter(ParameterType::F64)
+                    .optional_parameter(ParameterType::F64),
+            )
+    }
+
+    fn validate(schema: &Schema, input: &[u8]) -> Vec<Violation> {
+        schema.validate(input, Options::new()).unwrap()
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            validate(
+                &song(),
+                b"#TITLE:Foo;\n#offset: -0.5 ;\n#DISPLAYBPM:120:180;\n#BPMS:0=120;\n"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn missing_tag() {
+        assert_eq!(
+            validate(&song(), b"#OFFSET:0;\n"),
+            vec![Violation {
+                kind: Kind::MissingTag("TITLE".to_owned()),
+                position: Position::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_tag() {
+        assert_eq!(
+            validate(
+                &song().deny_unknown_tags(true),
+                b"#TITLE:Foo;\n#BPMS:0=120;\n"
+            ),
+            vec![Violation {
+                kind: Kind::UnknownTag("BPMS".to_owned()),
+                position: Position::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn too_few_parameters() {
+        assert_eq!(
+            validate(&song(), b"#TITLE:Foo;\n#DISPLAYBPM;\n"),
+            vec![Violation {
+                kind: Kind::TooFewParameters("DISPLAYBPM".to_owned(), 0, 1),
+                position: Position::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn too_many_parameters() {
+        assert_eq!(
+            validate(&song(), b"#TITLE:Foo;\n#DISPLAYBPM:1:2;3;\n"),
+            vec![Violation {
+                kind: Kind::TooManyParameters("DISPLAYBPM".to_owned(), 3, 2),
+                position: Position::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        assert_eq!(
+            validate(&song(), b"#TITLE:Foo;\n#OFFSET:foo;\n#DISPLAYBPM:120:*;\n"),
+            vec![
+                Violation {
+                    kind: Kind::InvalidParameter("OFFSET".to_owned(), 0, ParameterType::F64),
+                    position: Position::new(1, 8),
+                },
+                Violation {
+                    kind: Kind::InvalidParameter("DISPLAYBPM".to_owned(), 1, ParameterType::F64),
+                    position: Position::new(2, 16),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lexicon_from_options() {
+        let schema = Schema::new().tag(TagSchema::new("OFFSET").parameter(ParameterType::I32));
+
+        assert_ok_eq!(
+            schema.validate(
+                b"#OFFSET:+1;\n".as_slice(),
+                Options::new().allow_plus_signs(true)
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn missing_tag_after_other_violations() {
+        assert_eq!(
+            validate(&song(), b"#OFFSET:foo;\n#DISPLAYBPM;"),
+            vec![
+                Violation {
+                    kind: Kind::InvalidParameter("OFFSET".to_owned(), 0, ParameterType::F64),
+                    position: Position::new(0, 8),
+                },
+                Violation {
+                    kind: Kind::TooFewParameters("DISPLAYBPM".to_owned(), 0, 1),
+                    position: Position::new(1, 0),
+                },
+                Violation {
+                    kind: Kind::MissingTag("TITLE".to_owned()),
+                    position: Position::new(1, 12),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert_ok_eq!(
+            song().validate(b"#TITLE:Foo\n#OFFSET:0;\n".as_slice(), Options::strict()),
+            vec![Violation {
+                kind: Kind::Syntax(warning::Kind::MissingSemicolon),
+                position: Position::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn violations_after_syntax_error() {
+        assert_ok_eq!(
+            song().validate(
+                b"#TITLE:Foo\n#OFFSET:bar;\n#DISPLAYBPM:1:2:3;\n".as_slice(),
+                Options::strict()
+            ),
+            vec![
+                Violation {
+                    kind: Kind::Syntax(warning::Kind::MissingSemicolon),
+                    position: Position::new(1, 0),
+                },
+                Violation {
+                    kind: Kind::InvalidParameter("OFFSET".to_owned(), 0, ParameterType::F64),
+                    position: Position::new(1, 8),
+                },
+                Violation {
+                    kind: Kind::TooManyParameters("DISPLAYBPM".to_owned(), 3, 2),
+                    position: Position::new(2, 0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_syntax_deviation() {
+        assert_ok_eq!(
+            song().validate(b"#TITLE:Foo\n#OFFSET:0;\n".as_slice(), Options::new()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn limit_exceeded() {
+        assert_err_eq!(
+            song().validate(
+                b"#TITLE:Foo;\n#OFFSET:0;\n".as_slice(),
+                Options::new().max_tags(1)
+            ),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn from_reader() {
+        assert_ok_eq!(
+            Schema::from_reader(
+                b"#TITLE:required:string;\n#OFFSET:optional:f64;\n#DISPLAYBPM:optional:f64:f64?;\n"
+                    .as_slice()
+            ),
+            song()
+        );
+    }
+
+    #[test]
+    fn from_reader_deny_unknown_tags() {
+        assert_ok_eq!(
+            Schema::from_reader(b"#*:deny;\n".as_slice()),
+            Schema::new().deny_unknown_tags(true)
+        );
+    }
+
+    #[test]
+    fn from_reader_unknown_type() {
+        assert_err_eq!(
+            Schema::from_reader(b"#TITLE:required:str;\n".as_slice()),
+            Error::new(
+                error::Kind::UnknownVariant("str".to_owned(), ParameterType::NAMES),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn from_reader_missing_presence() {
+        assert_err_eq!(
+            Schema::from_reader(b"#TITLE;\n".as_slice()),
+            Error::new(error::Kind::EndOfValues, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parameter_type_names() {
+        for ty in ParameterType::ALL {
+            assert_eq!(ParameterType::from_name(ty.name()), Some(ty));
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!(
+                "{}",
+                Violation {
+                    kind: Kind::InvalidParameter("OFFSET".to_owned(), 0, ParameterType::F64),
+                    position: Position::new(1, 8),
+                }
+            ),
+            "parameter 0 of `#OFFSET` is not a valid f64 at line 1 column 8"
+        );
+    }
+}
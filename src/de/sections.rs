@@ -0,0 +1,128 @@
+use crate::de::{error, parse::Tags, r#struct, Error, Result};
+use crate::io::Read;
+use serde::{de, de::Visitor, forward_to_deserialize_any};
+
+/// Deserializes the header preceding the first section of a document.
+///
+/// The header is read as a struct in the same way as an entire document is, except that it ends at
+/// the first tag named `delimiter`.
+pub(in crate::de) struct Header<'a, R> {
+    tags: &'a mut Tags<R>,
+    delimiter: &'a str,
+}
+
+impl<'a, R> Header<'a, R> {
+    pub(in crate::de) fn new(tags: &'a mut Tags<R>, delimiter: &'a str) -> Self {
+        Self { tags, delimiter }
+    }
+}
+
+impl<'a, 'de, R> de::Deserializer<'de> for Header<'a, R>
+where
+    R: Read,
+{
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self
+            .tags
+            .error_at_current_tag(error::Kind::MustDeserializeSectionHeaderAsStruct))
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // An empty header, for documents made up only of sections.
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(r#struct::root::Access::with_delimiter(
+            self.tags,
+            fields,
+            self.delimiter,
+        ))
+    }
+
+    forward_to_deserialize_any! {
+        <W: Visitor<'de>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Header;
+    use crate::de::{error, parse::Tags, Error, Position};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::Deserialize;
+    use serde_derive::Deserialize;
+
+    #[test]
+    fn r#struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: String,
+        }
+        let mut tags = Tags::new(b"#TITLE:Foo;\n#NOTEDATA:;\n".as_slice());
+
+        assert_ok_eq!(
+            Song::deserialize(Header::new(&mut tags, "NOTEDATA")),
+            Song {
+                title: "Foo".to_owned()
+            }
+        );
+        // The delimiter is left to begin the first section.
+        assert_ok!(tags.next());
+        assert_ok!(tags.assert_exhausted());
+    }
+
+    #[test]
+    fn unit() {
+        let mut tags = Tags::new(b"#NOTEDATA:;\n".as_slice());
+
+        assert_ok!(<()>::deserialize(Header::new(&mut tags, "NOTEDATA")));
+        assert_ok!(tags.next());
+    }
+
+    #[test]
+    fn not_struct() {
+        let mut tags = Tags::new(b"#TITLE:Foo;\n".as_slice());
+
+        assert_err_eq!(
+            String::deserialize(Header::new(&mut tags, "NOTEDATA")),
+            Error::new(
+                error::Kind::MustDeserializeSectionHeaderAsStruct,
+                Position::new(0, 1)
+            )
+        );
+    }
+}
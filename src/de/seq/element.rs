@@ -1,14 +1,20 @@
 use crate::de::{error, map, parse::Tags, r#enum, r#struct, tuple, Error, Result};
+use crate::{io::Read, raw};
 use serde::{de, de::Visitor};
-use std::io::Read;
 
 pub(in crate::de) struct Deserializer<'a, R> {
     tags: &'a mut Tags<R>,
+    // The name of the tags delimiting the elements of the sequence.
+    delimiter: &'a str,
 }
 
 impl<'a, R> Deserializer<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags }
+        Self::with_delimiter(tags, "")
+    }
+
+    pub(in crate::de) fn with_delimiter(tags: &'a mut Tags<R>, delimiter: &'a str) -> Self {
+        Self { tags, delimiter }
     }
 }
 
@@ -380,10 +386,23 @@ where
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == raw::TOKEN {
+            // Capture all of the tag's parameters verbatim.
+            let mut tag = self.tags.next()?;
+            let mut values = tag.next()?;
+            let value = values.rest()?;
+            let value_position = value.position();
+            let parsed = value.parse_raw();
+            tag.assert_exhausted()?;
+            return visitor.visit_byte_buf(parsed).map_err(|mut error: Error| {
+                error.set_position(value_position);
+                error
+            });
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -450,7 +469,11 @@ where
         value.parse_unit()?;
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
-        visitor.visit_map(r#struct::Access::new(self.tags, fields))
+        visitor.visit_map(r#struct::Access::with_delimiter(
+            self.tags,
+            fields,
+            self.delimiter,
+        ))
     }
 
     fn deserialize_enum<V>(
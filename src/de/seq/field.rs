@@ -1,16 +1,21 @@
 use super::element;
-use crate::de::{parse::Tags, Error, Result};
+use crate::de::{error, parse::Tags, Error, PathSegment, Result};
+use crate::io::Read;
 use serde::de::{DeserializeSeed, SeqAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     field: &'a str,
     tags: &'a mut Tags<R>,
+    index: usize,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(field: &'a str, tags: &'a mut Tags<R>) -> Self {
-        Self { field, tags }
+        Self {
+            field,
+            tags,
+            index: 0,
+        }
     }
 }
 
@@ -26,7 +31,8 @@ where
     {
         let mut tag = match self.tags.next() {
             Ok(tag) => tag,
-            Err(_) => return Ok(None),
+            Err(error) if *error.kind() == error::Kind::EndOfFile => return Ok(None),
+            Err(error) => return Err(error),
         };
 
         // Check that the field name matches.
@@ -34,18 +40,19 @@ where
         let value = values.next()?;
         if value.parse_identifier()? == self.field {
             // Deserialize the rest of the tag.
-            // SAFETY: `values` was created by a call to `tag.next()`.
-            unsafe { tag.revisit(values) };
+            tag.revisit(values);
             let stored = tag.into_stored();
-            unsafe { self.tags.revisit(stored) };
+            self.tags.revisit(stored);
+            let index = self.index;
+            self.index += 1;
             Ok(Some(
-                seed.deserialize(element::Deserializer::new(self.tags))?,
+                seed.deserialize(element::Deserializer::with_delimiter(self.tags, self.field))
+                    .map_err(|error| error.within(PathSegment::Element(index)))?,
             ))
         } else {
             tag.reset();
             let stored = tag.into_stored();
-            // SAFETY: `tag` points to the current buffer of `self.tags`.
-            unsafe { self.tags.revisit(stored) };
+            self.tags.revisit(stored);
             Ok(None)
         }
     }
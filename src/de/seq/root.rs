@@ -1,15 +1,16 @@
 use super::element;
-use crate::de::{parse::Tags, Error, Result};
+use crate::de::{error, parse::Tags, Error, PathSegment, Result};
+use crate::io::Read;
 use serde::de::{DeserializeSeed, SeqAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
+    index: usize,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags }
+        Self { tags, index: 0 }
     }
 }
 
@@ -25,12 +26,16 @@ where
     {
         let tag = match self.tags.next() {
             Ok(tag) => tag,
-            Err(_) => return Ok(None),
+            Err(error) if *error.kind() == error::Kind::EndOfFile => return Ok(None),
+            Err(error) => return Err(error),
         };
         let stored = tag.into_stored();
-        unsafe { self.tags.revisit(stored) };
+        self.tags.revisit(stored);
+        let index = self.index;
+        self.index += 1;
         Ok(Some(
-            seed.deserialize(element::Deserializer::new(self.tags))?,
+            seed.deserialize(element::Deserializer::new(self.tags))
+                .map_err(|error| error.within(PathSegment::Element(index)))?,
         ))
     }
 }
@@ -1,32 +1,51 @@
 pub(in crate::de) mod root;
 
 mod field;
-mod value;
+mod unrecognized;
+pub(in crate::de) mod value;
 
 use crate::de::{
+    error,
     parse::{StoredTag, StoredValues, Tags},
-    Error, Result,
+    Error, PathSegment, Result,
 };
+use crate::io::Read;
+use alloc::{borrow::ToOwned, collections::BTreeSet};
 use serde::de::{DeserializeSeed, MapAccess};
-use std::{collections::HashSet, io::Read};
+use unrecognized::Collector;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
-    fields: HashSet<&'static str>,
-
-    // These stored fields contain raw pointers to the internal buffers of the tag and values
-    // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
-    // call to `self.tags.next()`.
+    fields: BTreeSet<&'static str>,
+    all_fields: &'static [&'static str],
+    unrecognized: Collector,
+    // The name of the tags delimiting this struct from the next one in a sequence.
+    delimiter: &'a str,
+
+    // These stored fields contain offsets into the internal buffer of `self.tags`. Note that they
+    // are only valid until another call to `self.tags.next()`.
     tag: Option<StoredTag>,
     values: Option<StoredValues>,
     field: Option<&'static str>,
 }
 
 impl<'a, R> Access<'a, R> {
+    #[cfg(test)]
     pub(in crate::de) fn new(tags: &'a mut Tags<R>, fields: &'static [&'static str]) -> Self {
+        Self::with_delimiter(tags, fields, "")
+    }
+
+    pub(in crate::de) fn with_delimiter(
+        tags: &'a mut Tags<R>,
+        fields: &'static [&'static str],
+        delimiter: &'a str,
+    ) -> Self {
         Self {
             tags,
             fields: fields.iter().copied().collect(),
+            all_fields: fields,
+            unrecognized: Collector::new(fields),
+            delimiter,
 
             tag: None,
             values: None,
@@ -45,29 +64,43 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (mut tag, values, value, field) = loop {
+            let mut tag = match self.tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => {
+                    return self
+                        .unrecognized
+                        .next_key_seed(seed, self.tags.current_position())
+                }
+                Err(error) => return Err(error),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            let field = value.parse_identifier()?;
+            // The delimiting tag begins the next struct in the sequence, so it is never collected.
+            if field != self.delimiter && self.unrecognized.should_collect(self.all_fields, &field)
+            {
+                self.unrecognized.collect(tag.contents());
+            } else {
+                break (tag, values, value, field);
+            }
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
 
         // Only return the result if the field is in the list of possible fields for the struct.
         if let Some(static_field) = self.fields.take(field.as_str()) {
             let result = seed.deserialize(field::Deserializer::new(&field, value.position()))?;
-            // Note that these raw values will only live until the next call to `next_key_seed()`, at
-            // which point they will be overwritten.
+            // Note that these stored values will only be valid until the next call to
+            // `next_key_seed()`, at which point they will be overwritten.
             self.values = Some(values.into_stored());
             self.tag = Some(tag.into_stored());
             self.field = Some(static_field);
             Ok(Some(result))
         } else {
+            let position = tag.origin_position();
             tag.reset();
             let stored_tag = tag.into_stored();
-            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
-            unsafe { self.tags.revisit(stored_tag) };
-            Ok(None)
+            self.tags.revisit(stored_tag);
+            self.unrecognized.next_key_seed(seed, position)
         }
     }
 
@@ -75,8 +108,10 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
-        // referenced buffer.
+        if self.unrecognized.is_pending() {
+            return self.unrecognized.next_value_seed(seed);
+        }
+
         let tag = self
             .tag
             .take()
@@ -91,6 +126,7 @@ where
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
 
         seed.deserialize(value::Deserializer::new(field, self.tags, tag, values))
+            .map_err(|error| error.within(PathSegment::Tag(field.to_owned())))
     }
 
     fn next_entry_seed<K, V>(
@@ -102,32 +138,51 @@ where
         K: DeserializeSeed<'de>,
         V: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (mut tag, values, value, field) = loop {
+            let mut tag = match self.tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => {
+                    return self.unrecognized.next_entry_seed(
+                        key_seed,
+                        value_seed,
+                        self.tags.current_position(),
+                    )
+                }
+                Err(error) => return Err(error),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            let field = value.parse_identifier()?;
+            // The delimiting tag begins the next struct in the sequence, so it is never collected.
+            if field != self.delimiter && self.unrecognized.should_collect(self.all_fields, &field)
+            {
+                self.unrecognized.collect(tag.contents());
+            } else {
+                break (tag, values, value, field);
+            }
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
 
         // Only return the result if the field is in the list of possible fields for the struct.
         if let Some(static_field) = self.fields.take(field.as_str()) {
             let key = key_seed.deserialize(field::Deserializer::new(&field, value.position()))?;
             let stored_tag = tag.into_stored();
             let stored_values = values.into_stored();
-            let value = value_seed.deserialize(value::Deserializer::new(
-                static_field,
-                self.tags,
-                stored_tag,
-                stored_values,
-            ))?;
+            let value = value_seed
+                .deserialize(value::Deserializer::new(
+                    static_field,
+                    self.tags,
+                    stored_tag,
+                    stored_values,
+                ))
+                .map_err(|error| error.within(PathSegment::Tag(static_field.to_owned())))?;
             Ok(Some((key, value)))
         } else {
+            let position = tag.origin_position();
             tag.reset();
             let stored_tag = tag.into_stored();
-            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
-            unsafe { self.tags.revisit(stored_tag) };
-            Ok(None)
+            self.tags.revisit(stored_tag);
+            self.unrecognized
+                .next_entry_seed(key_seed, value_seed, position)
         }
     }
 }
@@ -139,6 +194,7 @@ mod tests {
         parse::{Tag, Tags},
         Position,
     };
+    use crate::RawValue;
     use claims::{assert_none, assert_ok, assert_ok_eq, assert_some_eq};
     use serde::{
         de,
@@ -257,4 +313,28 @@ mod tests {
         // Should also revisit the tag.
         assert_ok_eq!(tags.next(), Tag::new(b"bar:42;\n", Position::new(0, 0)));
     }
+
+    #[test]
+    fn next_key_unrecognized_before_repeated_field() {
+        let mut tags = Tags::new(b"#foo:42;\n#bar:1;\n#foo:43;\n".as_slice());
+        let mut access = Access::new(&mut tags, &["foo", "$msd::unrecognized"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("foo".to_owned())
+        );
+        assert_ok_eq!(access.next_value::<u64>(), 42);
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("$msd::unrecognized".to_owned())
+        );
+        assert_ok_eq!(
+            access.next_value::<Vec<RawValue>>(),
+            vec![RawValue::new(&b"bar:1"[..])]
+        );
+        assert_none!(assert_ok!(access.next_key::<Identifier>()));
+
+        // Should also revisit the repeated tag.
+        assert_ok_eq!(tags.next(), Tag::new(b"foo:43;\n", Position::new(2, 0)));
+    }
 }
@@ -1,25 +1,52 @@
+use super::unrecognized::Collector;
 use crate::de::{
+    error,
     parse::{StoredTag, StoredValues, Tags},
-    Error, Result,
+    Error, PathSegment, Result,
 };
+use crate::io::Read;
+use alloc::string::String;
 use serde::de::{DeserializeSeed, MapAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
+    fields: &'static [&'static str],
+    unrecognized: Collector,
+    // The name of the tag ending the struct, if any, which begins the first section of a document
+    // split into sections.
+    delimiter: Option<&'a str>,
 
-    // These stored fields contain raw pointers to the internal buffers of the tag and values
-    // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
-    // call to `self.tags.next()`.
+    // These stored fields contain offsets into the internal buffer of `self.tags`. Note that they
+    // are only valid until another call to `self.tags.next()`.
     tag: Option<StoredTag>,
     values: Option<StoredValues>,
     field: Option<String>,
 }
 
 impl<'a, R> Access<'a, R> {
-    pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
+    pub(in crate::de) fn new(tags: &'a mut Tags<R>, fields: &'static [&'static str]) -> Self {
         Self {
             tags,
+            fields,
+            unrecognized: Collector::new(fields),
+            delimiter: None,
+
+            tag: None,
+            values: None,
+            field: None,
+        }
+    }
+
+    pub(in crate::de) fn with_delimiter(
+        tags: &'a mut Tags<R>,
+        fields: &'static [&'static str],
+        delimiter: &'a str,
+    ) -> Self {
+        Self {
+            tags,
+            fields,
+            unrecognized: Collector::new(fields),
+            delimiter: Some(delimiter),
 
             tag: None,
             values: None,
@@ -38,17 +65,36 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (tag, values, value, field) = loop {
+            let mut tag = match self.tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => {
+                    return self
+                        .unrecognized
+                        .next_key_seed(seed, self.tags.current_position())
+                }
+                Err(error) => return Err(error),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            let field = value.parse_identifier()?;
+            if self.delimiter == Some(field.as_str()) {
+                let position = tag.origin_position();
+                tag.reset();
+                let stored_tag = tag.into_stored();
+                self.tags.revisit(stored_tag);
+                return self.unrecognized.next_key_seed(seed, position);
+            }
+            if self.unrecognized.should_collect(self.fields, &field) {
+                self.unrecognized.collect(tag.contents());
+            } else {
+                break (tag, values, value, field);
+            }
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
 
         let result = seed.deserialize(super::field::Deserializer::new(&field, value.position()))?;
-        // Note that these raw values will only live until the next call to `next_key_seed()`, at
-        // which point they will be overwritten.
+        // Note that these stored values will only be valid until the next call to
+        // `next_key_seed()`, at which point they will be overwritten.
         self.values = Some(values.into_stored());
         self.tag = Some(tag.into_stored());
         self.field = Some(field);
@@ -59,8 +105,10 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
-        // referenced buffer.
+        if self.unrecognized.is_pending() {
+            return self.unrecognized.next_value_seed(seed);
+        }
+
         let tag = self
             .tag
             .take()
@@ -77,6 +125,7 @@ where
         seed.deserialize(super::value::Deserializer::new(
             &field, self.tags, tag, values,
         ))
+        .map_err(|error| error.within(PathSegment::Tag(field)))
     }
 
     fn next_entry_seed<K, V>(
@@ -88,24 +137,49 @@ where
         K: DeserializeSeed<'de>,
         V: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (tag, values, value, field) = loop {
+            let mut tag = match self.tags.next() {
+                Ok(tag) => tag,
+                Err(error) if *error.kind() == error::Kind::EndOfFile => {
+                    return self.unrecognized.next_entry_seed(
+                        key_seed,
+                        value_seed,
+                        self.tags.current_position(),
+                    )
+                }
+                Err(error) => return Err(error),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            let field = value.parse_identifier()?;
+            if self.delimiter == Some(field.as_str()) {
+                let position = tag.origin_position();
+                tag.reset();
+                let stored_tag = tag.into_stored();
+                self.tags.revisit(stored_tag);
+                return self
+                    .unrecognized
+                    .next_entry_seed(key_seed, value_seed, position);
+            }
+            if self.unrecognized.should_collect(self.fields, &field) {
+                self.unrecognized.collect(tag.contents());
+            } else {
+                break (tag, values, value, field);
+            }
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
 
         let key =
             key_seed.deserialize(super::field::Deserializer::new(&field, value.position()))?;
         let stored_tag = tag.into_stored();
         let stored_values = values.into_stored();
-        let value = value_seed.deserialize(super::value::Deserializer::new(
-            &field,
-            self.tags,
-            stored_tag,
-            stored_values,
-        ))?;
+        let value = value_seed
+            .deserialize(super::value::Deserializer::new(
+                &field,
+                self.tags,
+                stored_tag,
+                stored_values,
+            ))
+            .map_err(|error| error.within(PathSegment::Tag(field)))?;
         Ok(Some((key, value)))
     }
 }
@@ -114,6 +188,7 @@ where
 mod tests {
     use super::Access;
     use crate::de::parse::Tags;
+    use crate::RawValue;
     use claims::{assert_none, assert_ok, assert_ok_eq, assert_some_eq};
     use serde::{
         de,
@@ -154,7 +229,7 @@ mod tests {
     #[test]
     fn next_key_and_value() {
         let mut tags = Tags::new(b"#foo:42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_some_eq!(
             assert_ok!(access.next_key::<Identifier>()),
@@ -168,7 +243,7 @@ mod tests {
     fn next_value_without_next_key() {
         // Should panic if `next_value()` is called before `next_key()`.
         let mut tags = Tags::new(b"#42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         let _ = access.next_value::<u64>();
     }
@@ -176,7 +251,7 @@ mod tests {
     #[test]
     fn next_key_none() {
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_key::<Identifier>()));
     }
@@ -186,7 +261,7 @@ mod tests {
     fn next_value_after_next_key_none() {
         // Should panic if `next_value()` is called before `next_key()`.
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_key::<Identifier>()));
         let _ = access.next_value::<u64>();
@@ -195,7 +270,7 @@ mod tests {
     #[test]
     fn next_entry() {
         let mut tags = Tags::new(b"#foo:42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_some_eq!(
             assert_ok!(access.next_entry::<Identifier, u64>()),
@@ -206,8 +281,44 @@ mod tests {
     #[test]
     fn next_entry_none() {
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_entry::<Identifier, u64>()));
     }
+
+    #[test]
+    fn next_key_unrecognized() {
+        let mut tags = Tags::new(b"#bar:1;\n#foo:42;\n#baz;\n".as_slice());
+        let mut access = Access::new(&mut tags, &["foo", "$msd::unrecognized"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("foo".to_owned())
+        );
+        assert_ok_eq!(access.next_value::<u64>(), 42);
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("$msd::unrecognized".to_owned())
+        );
+        assert_ok_eq!(
+            access.next_value::<Vec<RawValue>>(),
+            vec![RawValue::new(&b"bar:1"[..]), RawValue::new(&b"baz"[..])]
+        );
+        assert_none!(assert_ok!(access.next_key::<Identifier>()));
+    }
+
+    #[test]
+    fn next_entry_unrecognized() {
+        let mut tags = Tags::new(b"#bar:1;\n".as_slice());
+        let mut access = Access::new(&mut tags, &["foo", "$msd::unrecognized"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<Identifier, Vec<RawValue>>()),
+            (
+                Identifier("$msd::unrecognized".to_owned()),
+                vec![RawValue::new(&b"bar:1"[..])]
+            )
+        );
+        assert_none!(assert_ok!(access.next_entry::<Identifier, Vec<RawValue>>()));
+    }
 }
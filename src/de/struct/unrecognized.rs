@@ -0,0 +1,166 @@
+use super::field;
+use crate::de::{Error, Position, Result};
+use crate::{unrecognized::FIELD, RawValue};
+use alloc::{vec, vec::Vec};
+use core::mem;
+use serde::de::{
+    value::{BytesDeserializer, SeqAccessDeserializer},
+    DeserializeSeed, SeqAccess,
+};
+
+/// Collects the tags that do not match any of a struct's fields, if the struct has a field for
+/// them.
+pub(super) enum Collector {
+    /// The struct has no field for unrecognized tags.
+    Disabled,
+    Collecting(Vec<RawValue>),
+    /// The field's key has been returned, but its value has not yet been deserialized.
+    Pending(Vec<RawValue>),
+    Finished,
+}
+
+impl Collector {
+    pub(super) fn new(fields: &[&str]) -> Self {
+        if fields.contains(&FIELD) {
+            Self::Collecting(Vec::new())
+        } else {
+            Self::Disabled
+        }
+    }
+
+    /// Returns whether a tag with the given name should be collected.
+    pub(super) fn should_collect(&self, fields: &[&str], field: &str) -> bool {
+        matches!(self, Self::Collecting(_)) && !fields.contains(&field)
+    }
+
+    /// Collects the contents of a tag.
+    pub(super) fn collect(&mut self, tag: &[u8]) {
+        if let Self::Collecting(tags) = self {
+            tags.push(RawValue::new(tag));
+        }
+    }
+
+    /// Returns the key of the field for unrecognized tags, if it has not yet been returned.
+    ///
+    /// This should be called once there are no more tags for the struct.
+    pub(super) fn next_key_seed<'de, K>(
+        &mut self,
+        seed: K,
+        position: Position,
+    ) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if let Self::Collecting(tags) = self {
+            *self = Self::Pending(mem::take(tags));
+            seed.deserialize(field::Deserializer::new(FIELD, position))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the entry of the field for unrecognized tags, if it has not yet been returned.
+    ///
+    /// This should be called once there are no more tags for the struct.
+    pub(super) fn next_entry_seed<'de, K, V>(
+        &mut self,
+        key_seed: K,
+        value_seed: V,
+        position: Position,
+    ) -> Result<Option<(K::Value, V::Value)>>
+    where
+        K: DeserializeSeed<'de>,
+        V: DeserializeSeed<'de>,
+    {
+        if let Self::Collecting(tags) = self {
+            let tags = mem::take(tags);
+            *self = Self::Finished;
+            let key = key_seed.deserialize(field::Deserializer::new(FIELD, position))?;
+            let value = value_seed
+                .deserialize(SeqAccessDeserializer::new(Access::new(tags.into_iter())))?;
+            Ok(Some((key, value)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns whether the key of the field for unrecognized tags was just returned.
+    pub(super) fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending(_))
+    }
+
+    /// Deserializes the value of the field for unrecognized tags.
+    ///
+    /// # Panics
+    /// Panics if the field's key was not just returned.
+    pub(super) fn next_value_seed<'de, V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match mem::replace(self, Self::Finished) {
+            Self::Pending(tags) => {
+                seed.deserialize(SeqAccessDeserializer::new(Access::new(tags.into_iter())))
+            }
+            _ => panic!("unrecognized tags field value requested before its key"),
+        }
+    }
+}
+
+/// Provides the tags collected for a struct's unrecognized tags field as a sequence of raw values.
+pub(super) struct Access {
+    tags: vec::IntoIter<RawValue>,
+}
+
+impl Access {
+    pub(super) fn new(tags: vec::IntoIter<RawValue>) -> Self {
+        Self { tags }
+    }
+}
+
+impl<'de> SeqAccess<'de> for Access {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.tags.next() {
+            Some(tag) => seed
+                .deserialize(BytesDeserializer::new(tag.as_bytes()))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.tags.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+    use crate::RawValue;
+    use claims::{assert_none, assert_ok, assert_ok_eq};
+    use serde::de::SeqAccess;
+
+    #[test]
+    fn next_element() {
+        let mut access = Access::new(vec![RawValue::new(&b"foo:bar"[..])].into_iter());
+
+        assert_ok_eq!(
+            access.next_element::<RawValue>(),
+            Some(RawValue::new(&b"foo:bar"[..]))
+        );
+        assert_none!(assert_ok!(access.next_element::<RawValue>()));
+    }
+
+    #[test]
+    fn size_hint() {
+        let access =
+            Access::new(vec![RawValue::new(&b"foo"[..]), RawValue::new(&b"bar"[..])].into_iter());
+
+        assert_eq!(access.size_hint(), Some(2));
+    }
+}
@@ -3,8 +3,8 @@ use crate::de::{
     parse::{StoredTag, StoredValues, Tags},
     r#enum, seq, tuple, Error, Result,
 };
+use crate::{io::Read, raw};
 use serde::{de, de::Visitor};
-use std::io::Read;
 
 pub(in super::super) struct Deserializer<'a, R> {
     field: &'a str,
@@ -40,7 +40,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let values = unsafe { self.values.into_values() };
+        let values = self.values.into_values(self.tags.buffer());
         Err(Error::new(
             error::Kind::CannotDeserializeAsSelfDescribing,
             values.current_position(),
@@ -51,12 +51,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_bool()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_bool(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -67,12 +67,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_i8()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_i8(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -83,12 +83,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_i16()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_i16(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -99,12 +99,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_i32()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_i32(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -115,12 +115,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_i64()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_i64(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -132,12 +132,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_i128()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_i128(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -148,12 +148,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_u8()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_u8(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -164,12 +164,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_u16()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_u16(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -180,12 +180,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_u32()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_u32(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -196,12 +196,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_u64()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_u64(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -213,12 +213,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_u128()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_u128(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -229,12 +229,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_f32()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_f32(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -245,12 +245,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_f64()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_f64(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -261,12 +261,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_char()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_char(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -277,12 +277,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_string()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_str(&parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -293,12 +293,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_string()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_string(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -309,12 +309,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_byte_buf();
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_bytes(&parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -325,12 +325,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_byte_buf();
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_byte_buf(parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -349,9 +349,9 @@ where
     where
         V: Visitor<'de>,
     {
-        let values = unsafe { self.values.into_values() };
+        let values = self.values.into_values(self.tags.buffer());
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_unit().map_err(|mut error: Error| {
             error.set_position(values.current_position());
             error
@@ -362,19 +362,31 @@ where
     where
         V: Visitor<'de>,
     {
-        let values = unsafe { self.values.into_values() };
+        let values = self.values.into_values(self.tags.buffer());
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_unit().map_err(|mut error: Error| {
             error.set_position(values.current_position());
             error
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == raw::TOKEN {
+            // Capture all of the tag's parameters verbatim.
+            let mut values = self.values.into_values(self.tags.buffer());
+            let value = values.rest()?;
+            let value_position = value.position();
+            let parsed = value.parse_raw();
+            self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
+            return visitor.visit_byte_buf(parsed).map_err(|mut error: Error| {
+                error.set_position(value_position);
+                error
+            });
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -382,9 +394,9 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut tag = unsafe { self.tag.into_tag() };
+        let mut tag = self.tag.into_tag(self.tags.buffer());
         tag.reset();
-        unsafe { self.tags.revisit(tag.into_stored()) };
+        self.tags.revisit(tag.into_stored());
 
         visitor.visit_seq(seq::field::Access::new(self.field, self.tags))
     }
@@ -393,10 +405,10 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let result = visitor.visit_seq(tuple::Access::new(&mut values, len))?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         Ok(result)
     }
 
@@ -409,10 +421,10 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let result = visitor.visit_seq(tuple::Access::new(&mut values, len))?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         Ok(result)
     }
 
@@ -420,9 +432,8 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut tag = unsafe { self.tag.into_tag() };
-        // SAFETY: `self.values` references the same buffer that `self.tag` references.
-        unsafe { tag.revisit(self.values.into_values()) };
+        let mut tag = self.tag.into_tag(self.tags.buffer());
+        tag.revisit(self.values.into_values(self.tags.buffer()));
         let result = visitor.visit_map(map::field::Access::new(&mut tag))?;
         tag.assert_exhausted()?;
         Ok(result)
@@ -437,7 +448,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let values = unsafe { self.values.into_values() };
+        let values = self.values.into_values(self.tags.buffer());
         Err(Error::new(
             error::Kind::CannotDeserializeNestedStruct,
             values.current_position(),
@@ -453,10 +464,10 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let result = visitor.visit_enum(r#enum::Access::new(&mut values))?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         Ok(result)
     }
 
@@ -464,12 +475,12 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut values = unsafe { self.values.into_values() };
+        let mut values = self.values.into_values(self.tags.buffer());
         let value = values.next()?;
         let value_position = value.position();
         let parsed = value.parse_identifier()?;
         values.assert_exhausted()?;
-        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        self.tag.into_tag(self.tags.buffer()).assert_exhausted()?;
         visitor.visit_str(&parsed).map_err(|mut error: Error| {
             error.set_position(value_position);
             error
@@ -480,7 +491,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let values = unsafe { self.values.into_values() };
+        let values = self.values.into_values(self.tags.buffer());
         Err(Error::new(
             error::Kind::CannotDeserializeAsSelfDescribing,
             values.current_position(),
@@ -1,4 +1,5 @@
 use crate::de::{error, parse::Values, r#enum, Error, Result};
+use crate::raw;
 use serde::de::Visitor;
 
 pub(in crate::de) struct Deserializer<'a, 'b> {
@@ -290,10 +291,20 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == raw::TOKEN {
+            // Capture the single parameter verbatim.
+            let value = self.values.next()?;
+            return visitor
+                .visit_byte_buf(value.parse_raw())
+                .map_err(|mut error: Error| {
+                    error.set_position(value.position());
+                    error
+                });
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -1,16 +1,21 @@
 pub(in crate::de) mod element;
 
-use super::{parse::Values, Error, Result};
+use super::{parse::Values, Error, PathSegment, Result};
 use serde::de::{DeserializeSeed, SeqAccess};
 
 pub(in crate::de) struct Access<'a, 'b> {
     values: &'a mut Values<'b>,
     len: usize,
+    index: usize,
 }
 
 impl<'a, 'b> Access<'a, 'b> {
     pub(in crate::de) fn new(values: &'a mut Values<'b>, len: usize) -> Self {
-        Self { values, len }
+        Self {
+            values,
+            len,
+            index: 0,
+        }
     }
 }
 
@@ -22,8 +27,11 @@ impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
         T: DeserializeSeed<'de>,
     {
         self.len = self.len.saturating_sub(1);
+        let index = self.index;
+        self.index += 1;
         Ok(Some(
-            seed.deserialize(element::Deserializer::new(self.values))?,
+            seed.deserialize(element::Deserializer::new(self.values))
+                .map_err(|error| error.within(PathSegment::Parameter(index)))?,
         ))
     }
 
@@ -35,7 +43,7 @@ impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
 #[cfg(test)]
 mod tests {
     use super::Access;
-    use crate::de::{error, parse::Values, Error, Position};
+    use crate::de::{error, parse::Values, Error, PathSegment, Position};
     use claims::{assert_err_eq, assert_ok, assert_some_eq};
     use serde::de::SeqAccess;
 
@@ -51,6 +59,7 @@ mod tests {
         assert_err_eq!(
             access.next_element::<bool>(),
             Error::new(error::Kind::EndOfValues, Position::new(0, 0))
+                .within(PathSegment::Parameter(0))
         );
         assert_some_eq!(access.size_hint(), 0);
     }
@@ -66,6 +75,7 @@ mod tests {
         assert_err_eq!(
             access.next_element::<bool>(),
             Error::new(error::Kind::EndOfValues, Position::new(0, 2))
+                .within(PathSegment::Parameter(1))
         );
         assert_some_eq!(access.size_hint(), 0);
     }
@@ -86,6 +96,7 @@ mod tests {
         assert_err_eq!(
             access.next_element::<bool>(),
             Error::new(error::Kind::EndOfValues, Position::new(0, 6))
+                .within(PathSegment::Parameter(2))
         );
         assert_some_eq!(access.size_hint(), 0);
     }
@@ -108,6 +119,7 @@ mod tests {
         assert_err_eq!(
             access.next_element::<bool>(),
             Error::new(error::Kind::EndOfValues, Position::new(0, 10))
+                .within(PathSegment::Parameter(3))
         );
         assert_some_eq!(access.size_hint(), 0);
     }
@@ -0,0 +1,522 @@
+use crate::de::{
+    diagnostic, error, options::Limits, parse::Tags, warning, Deserializer, Diagnostic, Options,
+    PathSegment, Position, Severity,
+};
+use alloc::{string::String, vec, vec::Vec};
+use serde::de::DeserializeOwned;
+
+/// The location of a single tag within the input, along with its name if it has one.
+struct Span {
+    start: Position,
+    end: Position,
+    name: Option<String>,
+}
+
+impl Span {
+    fn contains(&self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+}
+
+/// Returns options that recover from every deviation from the format that could otherwise be
+/// reported as an error by `options`.
+pub(in crate::de) fn recovering_options(options: &Options) -> Options {
+    Options {
+        lexicon: options.lexicon,
+        allow_missing_semicolons: true,
+        allow_unescaped_number_signs: true,
+        skip_content_before_first_tag: true,
+        skip_content_between_tags: options.skip_content_between_tags,
+        report_warnings: true,
+        // The input is already in memory, so there is nothing for limits to guard against.
+        limits: Limits::new(),
+    }
+}
+
+/// Returns whether `options` allows recovering from the given deviation from the format.
+pub(in crate::de) fn is_allowed(kind: &warning::Kind, options: &Options) -> bool {
+    match kind {
+        warning::Kind::MissingSemicolon => options.allow_missing_semicolons,
+        warning::Kind::UnescapedNumberSign => options.allow_unescaped_number_signs,
+        warning::Kind::ContentBeforeFirstTag => options.skip_content_before_first_tag,
+        warning::Kind::ContentBetweenTags => options.skip_content_between_tags,
+    }
+}
+
+/// Scans every tag in the input, recording syntax diagnostics along the way.
+fn scan(input: &[u8], options: &Options, diagnostics: &mut Vec<Diagnostic>) -> Vec<Span> {
+    let mut tags = Tags::with_options(input, recovering_options(options));
+    let mut starts = Vec::new();
+    // Since every deviation is recovered from, the only error possible here is the end of the
+    // input.
+    while let Ok(mut tag) = tags.next() {
+        let start = tag.origin_position();
+        let name = tag
+            .next()
+            .and_then(|mut values| values.next())
+            .and_then(|value| value.parse_identifier())
+            .ok();
+        starts.push((start, name));
+    }
+
+    let end_of_input = tags.current_position();
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(Some(end_of_input))
+        .collect::<Vec<_>>();
+    let spans = starts
+        .into_iter()
+        .zip(ends)
+        .map(|((start, name), end)| Span { start, end, name })
+        .collect::<Vec<_>>();
+
+    diagnostics.extend(tags.warnings().iter().map(|warning| {
+        let diagnostic = Diagnostic::new(
+            if is_allowed(warning.kind(), options) {
+                Severity::Warning
+            } else {
+                Severity::Error
+            },
+            diagnostic::Kind::Syntax(warning.kind().clone()),
+            warning.position(),
+        );
+        let position = warning.position();
+        let span = match warning.kind() {
+            // A missing `;` is reported where the next tag begins, which is where the tag missing
+            // it ends.
+            warning::Kind::MissingSemicolon => spans
+                .iter()
+                .find(|span| span.start < position && position <= span.end),
+            warning::Kind::UnescapedNumberSign => spans.iter().find(|span| span.contains(position)),
+            // Content before or between tags is, by definition, not within any tag.
+            warning::Kind::ContentBeforeFirstTag | warning::Kind::ContentBetweenTags => None,
+        };
+        match span.and_then(|span| span.name.clone()) {
+            Some(name) => diagnostic.within(PathSegment::Tag(name)),
+            None => diagnostic,
+        }
+    }));
+
+    spans
+}
+
+/// Returns the index of the span at which deserialization should resume after the tag at `index`
+/// failed with an error at `path`.
+///
+/// A tag within a sequence of structs can only be deserialized along with the rest of its
+/// element, so deserialization resumes from the tag beginning the element: the nearest preceding
+/// tag named by the outermost segment of the path. Any other tag stands alone at the top level.
+/// If the element's beginning can't be found, `None` is returned to resume from the start of the
+/// input.
+fn resume_point(spans: &[Span], index: usize, path: &[PathSegment]) -> Option<usize> {
+    match path.first() {
+        Some(PathSegment::Tag(name)) => spans[..=index]
+            .iter()
+            .rposition(|span| span.name.as_deref() == Some(name.as_str())),
+        _ => Some(index),
+    }
+}
+
+/// Converts a position into a byte offset within the input.
+fn offset(line_starts: &[usize], input_len: usize, position: Position) -> usize {
+    line_starts
+        .get(position.line())
+        .map_or(input_len, |line_start| line_start + position.column())
+        .min(input_len)
+}
+
+/// Validates MSD input, returning every problem found.
+///
+/// The entire input is scanned, recovering from each deviation from the MSD format. Deviations
+/// that `options` would recover from are reported with [`Severity::Warning`], while those that it
+/// would not are reported with [`Severity::Error`].
+///
+/// # Example
+/// ```
+/// use msd::de::{Options, Severity};
+///
+/// let diagnostics = msd::de::validate(b"#foo:bar\n#baz:qux;\n", Options::strict());
+///
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].severity(), Severity::Error);
+/// ```
+pub fn validate(input: &[u8], options: Options) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    scan(input, &options, &mut diagnostics);
+    diagnostics
+}
+
+/// Validates MSD input against the type `T`, returning every problem found.
+///
+/// In addition to the syntax diagnostics reported by [`validate()`], each tag that cannot be
+/// deserialized as part of `T` is reported with [`Severity::Error`]. After a tag fails, it is
+/// ignored and deserialization resumes from that tag, allowing problems in later tags to be found
+/// as well. Once the end of the input is reached, the whole input is deserialized once more
+/// without the ignored tags, to find problems that depend on tags read before resuming, such as
+/// missing fields.
+///
+/// # Example
+/// ```
+/// use msd::de::{Options, Severity};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Song {
+///     #[serde(rename = "TITLE")]
+///     title: Option<String>,
+///     #[serde(rename = "OFFSET")]
+///     offset: Option<f64>,
+///     #[serde(rename = "BPM")]
+///     bpm: Option<f64>,
+/// }
+///
+/// let diagnostics = msd::de::validate_as::<Song>(
+///     b"#TITLE:foo;\n#OFFSET:bar;\n#BPM:baz;\n",
+///     Options::new(),
+/// );
+///
+/// assert_eq!(diagnostics.len(), 2);
+/// assert_eq!(diagnostics[0].position().line(), 1);
+/// assert_eq!(diagnostics[1].position().line(), 2);
+/// ```
+pub fn validate_as<T>(input: &[u8], options: Options) -> Vec<Diagnostic>
+where
+    T: DeserializeOwned,
+{
+    let mut diagnostics = Vec::new();
+    let spans = scan(input, &options, &mut diagnostics);
+
+    let line_starts = Some(0)
+        .into_iter()
+        .chain(
+            input
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| matches!(byte, b'\n'))
+                .map(|(index, _)| index + 1),
+        )
+        .collect::<Vec<_>>();
+    let mut buffer = input.to_vec();
+    let mut ignored = vec![false; spans.len()];
+    // The span from which the current attempt began, or `None` if it began at the start of the
+    // input.
+    let mut resumed_from: Option<usize> = None;
+
+    loop {
+        let (start, origin) = match resumed_from {
+            Some(index) => (
+                offset(&line_starts, buffer.len(), spans[index].start),
+                spans[index].start,
+            ),
+            None => (0, Position::new(0, 0)),
+        };
+        let mut deserializer = Deserializer::with_options(
+            &buffer[start..],
+            recovering_options(&options).report_warnings(false),
+        );
+        let mut error = match T::deserialize(&mut deserializer) {
+            Ok(_) if resumed_from.is_some() => {
+                resumed_from = None;
+                continue;
+            }
+            Ok(_) => break,
+            Err(error) => error,
+        };
+        if let Some(position) = error.known_position() {
+            error.set_position(position.relative_to(origin));
+        }
+        if let (Some(index), Some(PathSegment::Tag(name))) = (resumed_from, error.path().first()) {
+            // Elements of the sequence before the resumed span were skipped, other than those
+            // that were ignored.
+            let skipped = spans[..index]
+                .iter()
+                .zip(&ignored)
+                .filter(|(span, &ignored)| !ignored && span.name.as_deref() == Some(name.as_str()))
+                .count();
+            error.skip_elements(skipped);
+        }
+
+        let failed = error
+            .known_position()
+            .and_then(|position| spans.iter().position(|span| span.contains(position)))
+            .filter(|&index| !ignored[index]);
+        let index = match failed {
+            Some(index) => index,
+            // Having skipped the tags before the resumed span, the error may only be due to their
+            // absence, so it is checked against the whole input instead.
+            None if resumed_from.is_some() => {
+                resumed_from = None;
+                continue;
+            }
+            None => {
+                // A missing field is expected if the tag providing it was ignored.
+                if let error::Kind::MissingField(field) = error.kind() {
+                    if spans
+                        .iter()
+                        .zip(&ignored)
+                        .any(|(span, &ignored)| ignored && span.name.as_deref() == Some(*field))
+                    {
+                        break;
+                    }
+                }
+                // The error can't be attributed to a single tag, so no further recovery is
+                // possible.
+                diagnostics.push(Diagnostic::from_error(&error));
+                break;
+            }
+        };
+
+        diagnostics.push(Diagnostic::from_error(&error));
+        ignored[index] = true;
+        // Blank out the tag, preserving newlines so that all other positions are unchanged.
+        let start = offset(&line_starts, buffer.len(), spans[index].start);
+        let end = offset(&line_starts, buffer.len(), spans[index].end);
+        for byte in &mut buffer[start..end] {
+            if !matches!(byte, b'\n') {
+                *byte = b' ';
+            }
+        }
+        resumed_from = resume_point(&spans, index, error.path());
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.position());
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, validate_as};
+    use crate::de::{
+        diagnostic, error, warning, Diagnostic, Error, Options, PathSegment, Position, Severity,
+    };
+    use serde_derive::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Song {
+        title: String,
+        offset: f64,
+        bpm: f64,
+    }
+
+    #[test]
+    fn validate_no_diagnostics() {
+        assert_eq!(validate(b"#foo:bar;\n#baz:qux;\n", Options::strict()), &[]);
+    }
+
+    #[test]
+    fn validate_empty() {
+        assert_eq!(validate(b"", Options::strict()), &[]);
+    }
+
+    #[test]
+    fn validate_reports_all_syntax_errors() {
+        assert_eq!(
+            validate(b"#foo:bar\n#baz:q#ux\n#qux;\n", Options::strict()),
+            &[
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Syntax(warning::Kind::MissingSemicolon),
+                    Position::new(1, 0)
+                )
+                .within(PathSegment::Tag("foo".to_owned())),
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Syntax(warning::Kind::UnescapedNumberSign),
+                    Position::new(1, 6)
+                )
+                .within(PathSegment::Tag("baz".to_owned())),
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Syntax(warning::Kind::MissingSemicolon),
+                    Position::new(2, 0)
+                )
+                .within(PathSegment::Tag("baz".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_allowed_recoveries_are_warnings() {
+        assert_eq!(
+            validate(b"foo\n#bar:baz\n#qux;\n", Options::new()),
+            &[
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Syntax(warning::Kind::ContentBeforeFirstTag),
+                    Position::new(0, 0)
+                ),
+                Diagnostic::new(
+                    Severity::Warning,
+                    diagnostic::Kind::Syntax(warning::Kind::MissingSemicolon),
+                    Position::new(2, 0)
+                )
+                .within(PathSegment::Tag("bar".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_skipped_content_between_tags() {
+        assert_eq!(
+            validate(b"#foo:bar;\ngarbage\n#qux;\n", Options::lenient()),
+            &[Diagnostic::new(
+                Severity::Warning,
+                diagnostic::Kind::Syntax(warning::Kind::ContentBetweenTags),
+                Position::new(1, 0)
+            )]
+        );
+    }
+
+    #[test]
+    fn validate_as_no_diagnostics() {
+        assert_eq!(
+            validate_as::<Song>(b"#title:foo;\n#offset:0.5;\n#bpm:120;\n", Options::new()),
+            &[]
+        );
+    }
+
+    #[test]
+    fn validate_as_reports_all_type_errors() {
+        assert_eq!(
+            validate_as::<Song>(b"#title:foo;\n#offset:bar;\n#bpm:baz;\n", Options::new()),
+            &[
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Type(error::Kind::ExpectedF64),
+                    Position::new(1, 8)
+                )
+                .within(PathSegment::Tag("offset".to_owned())),
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Type(error::Kind::ExpectedF64),
+                    Position::new(2, 5)
+                )
+                .within(PathSegment::Tag("bpm".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_as_reports_missing_field() {
+        let input = b"#title:foo;\n#offset:0.5;\n";
+        let diagnostics = validate_as::<Song>(input, Options::new());
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind(),
+            &diagnostic::Kind::Type(error::Kind::MissingField("bpm"))
+        );
+        assert_eq!(diagnostics[0].path(), &[]);
+        // The field is missing from the input as a whole, so there is no line to point to.
+        assert_eq!(diagnostics[0].render(input), "error: missing field bpm\n");
+    }
+
+    #[test]
+    fn validate_as_reports_missing_field_after_type_errors() {
+        let diagnostics = validate_as::<Song>(b"#offset:foo;\n#bpm:bar;\n", Options::new());
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.kind().clone(), diagnostic.position()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    diagnostic::Kind::Type(error::Kind::MissingField("title")),
+                    Position::new(0, 0)
+                ),
+                (
+                    diagnostic::Kind::Type(error::Kind::ExpectedF64),
+                    Position::new(0, 8)
+                ),
+                (
+                    diagnostic::Kind::Type(error::Kind::ExpectedF64),
+                    Position::new(1, 5)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_as_resumes_within_sequence() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            #[serde(rename = "METER")]
+            meter: Option<u8>,
+            #[serde(rename = "DIFFICULTY")]
+            difficulty: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Simfile {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(rename = "NOTES")]
+            notes: Vec<Chart>,
+        }
+
+        assert_eq!(
+            validate_as::<Simfile>(
+                b"#TITLE:foo;\n#NOTES:;\n#METER:1;\n#NOTES:;\n#METER:x;\n#DIFFICULTY:Hard;\n#NOTES:;\n#METER:y;\n",
+                Options::new()
+            ),
+            &[
+                Diagnostic::from_error(
+                    &Error::new(error::Kind::ExpectedU8, Position::new(4, 7))
+                        .within(PathSegment::Tag("METER".to_owned()))
+                        .within(PathSegment::Element(1))
+                        .within(PathSegment::Tag("NOTES".to_owned()))
+                ),
+                Diagnostic::from_error(
+                    &Error::new(error::Kind::ExpectedU8, Position::new(7, 7))
+                        .within(PathSegment::Tag("METER".to_owned()))
+                        .within(PathSegment::Element(2))
+                        .within(PathSegment::Tag("NOTES".to_owned()))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_as_reports_unknown_tag() {
+        assert_eq!(
+            validate_as::<Song>(
+                b"#title:foo;\n#offset:0.5;\n#bpm:120;\n#foo:bar;\n",
+                Options::new()
+            ),
+            &[Diagnostic::new(
+                Severity::Error,
+                diagnostic::Kind::Type(error::Kind::CannotDeserializeAsSelfDescribing),
+                Position::new(3, 5)
+            )
+            .within(PathSegment::Tag("foo".to_owned()))]
+        );
+    }
+
+    #[test]
+    fn validate_as_reports_syntax_and_type_errors() {
+        assert_eq!(
+            validate_as::<HashMap<String, u64>>(b"#foo:1\n#bar:baz;\n#qux:2;\n", Options::strict()),
+            &[
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Syntax(warning::Kind::MissingSemicolon),
+                    Position::new(1, 0)
+                )
+                .within(PathSegment::Tag("foo".to_owned())),
+                Diagnostic::new(
+                    Severity::Error,
+                    diagnostic::Kind::Type(error::Kind::ExpectedU64),
+                    Position::new(1, 5)
+                )
+                .within(PathSegment::Tag("bar".to_owned())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::de::Position;
+use core::{fmt, fmt::Display};
+
+/// The kind of a [`Warning`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    MissingSemicolon,
+    UnescapedNumberSign,
+    ContentBeforeFirstTag,
+    ContentBetweenTags,
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::MissingSemicolon => formatter.write_str("missing `;`"),
+            Kind::UnescapedNumberSign => formatter.write_str("unescaped `#`"),
+            Kind::ContentBeforeFirstTag => formatter.write_str("skipped content before first tag"),
+            Kind::ContentBetweenTags => formatter.write_str("skipped content between tags"),
+        }
+    }
+}
+
+/// A recovery from malformed input that occurred during deserialization.
+///
+/// Warnings are only recorded when enabled with [`Options::report_warnings()`].
+///
+/// [`Options::report_warnings()`]: crate::de::Options::report_warnings()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    position: Position,
+    kind: Kind,
+}
+
+impl Warning {
+    pub(in crate::de) fn new(kind: Kind, position: Position) -> Self {
+        Self { position, kind }
+    }
+
+    /// Returns the kind of recovery that occurred.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the position in the input at which the recovery occurred.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} at line {} column {}",
+            self.kind,
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kind, Warning};
+    use crate::de::Position;
+
+    #[test]
+    fn missing_semicolon() {
+        assert_eq!(
+            format!(
+                "{}",
+                Warning::new(Kind::MissingSemicolon, Position::new(1, 2))
+            ),
+            "missing `;` at line 1 column 2"
+        );
+    }
+
+    #[test]
+    fn unescaped_number_sign() {
+        assert_eq!(
+            format!(
+                "{}",
+                Warning::new(Kind::UnescapedNumberSign, Position::new(2, 3))
+            ),
+            "unescaped `#` at line 2 column 3"
+        );
+    }
+
+    #[test]
+    fn content_before_first_tag() {
+        assert_eq!(
+            format!(
+                "{}",
+                Warning::new(Kind::ContentBeforeFirstTag, Position::new(3, 4))
+            ),
+            "skipped content before first tag at line 3 column 4"
+        );
+    }
+
+    #[test]
+    fn content_between_tags() {
+        assert_eq!(
+            format!(
+                "{}",
+                Warning::new(Kind::ContentBetweenTags, Position::new(4, 5))
+            ),
+            "skipped content between tags at line 4 column 5"
+        );
+    }
+}
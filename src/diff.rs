@@ -0,0 +1,574 @@
+//! Comparing MSD documents tag by tag.
+//!
+//! [`diff()`] compares two [`Document`]s by their tags and parameters rather than their text, so
+//! that differences in formatting are not reported. Parameters are compared as they are read by
+//! the parser, with escape sequences resolved and comments removed, and with each line trimmed of
+//! surrounding whitespace. Comments following a tag's final `;` are not part of the tag at all.
+//!
+//! # Example
+//! ```
+//! use msd::{
+//!     de::Options,
+//!     diff::{Difference, Document},
+//! };
+//!
+//! let old = Document::parse(b"#TITLE:Foo;\n#ARTIST:Bar;\n", Options::new()).unwrap();
+//! let new = Document::parse(b"#TITLE:  Foo  ;\n#ARTIST:Baz;\n", Options::new()).unwrap();
+//!
+//! let differences = msd::diff::diff(&old, &new);
+//!
+//! assert_eq!(differences.len(), 1);
+//! assert!(matches!(differences[0], Difference::Changed { .. }));
+//! ```
+
+use crate::{
+    de::{Options, ParsedTag, ParsedTags, Position, Result},
+    format::normalize,
+};
+use alloc::{vec, vec::Vec};
+
+/// A step in the alignment of two sequences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    /// The elements at the given indices are equal.
+    Both(usize, usize),
+    /// The element at the given index is only in the old sequence.
+    Old(usize),
+    /// The element at the given index is only in the new sequence.
+    New(usize),
+}
+
+/// Aligns two sequences along their longest common subsequence.
+///
+/// Within each run of unequal elements, the elements of the old sequence come first.
+fn align<T>(old: &[T], new: &[T]) -> Vec<Step>
+where
+    T: PartialEq,
+{
+    // Common prefixes and suffixes are aligned directly, which keeps the table below small when
+    // the sequences are mostly the same.
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // `lengths[i][j]` is the length of the longest common subsequence of `old_middle[i..]` and
+    // `new_middle[j..]`.
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0_usize; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut steps = (0..prefix).map(|i| Step::Both(i, i)).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            steps.push(Step::Both(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len()
+            || (i < old_middle.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            steps.push(Step::Old(prefix + i));
+            i += 1;
+        } else {
+            steps.push(Step::New(prefix + j));
+            j += 1;
+        }
+    }
+    steps.extend((0..suffix).map(|k| Step::Both(old.len() - suffix + k, new.len() - suffix + k)));
+    steps
+}
+
+/// A tag of a [`Document`], with its parameters normalized for comparison.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tag {
+    position: Position,
+    name: Vec<u8>,
+    parameter_lists: Vec<Vec<Vec<u8>>>,
+}
+
+impl Tag {
+    /// Normalizes a tag read by the parser.
+    pub(crate) fn from_parsed(tag: ParsedTag) -> Self {
+        Self {
+            position: tag.position,
+            name: tag.name,
+            parameter_lists: tag
+                .parameter_lists
+                .iter()
+                .map(|parameters| {
+                    parameters
+                        .iter()
+                        .map(|parameter| normalize(parameter))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the position of the tag's `#` within its document.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the tag's name.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// Returns the tag's parameter lists, each ended by one of the tag's `;` characters.
+    ///
+    /// The name is not included in the first parameter list. Each parameter is unescaped,
+    /// stripped of comments, and has each of its lines trimmed of surrounding whitespace.
+    pub fn parameter_lists(&self) -> &[Vec<Vec<u8>>] {
+        &self.parameter_lists
+    }
+
+    /// Returns whether the tag has the same name and parameters as `other`, regardless of
+    /// position.
+    pub(crate) fn same_as(&self, other: &Tag) -> bool {
+        self.name == other.name && self.parameter_lists == other.parameter_lists
+    }
+}
+
+/// The tags of an MSD document, read for comparison.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    tags: Vec<Tag>,
+}
+
+impl Document {
+    /// Parses a document according to `options`.
+    pub fn parse(input: &[u8], options: Options) -> Result<Self> {
+        Ok(Self {
+            tags: ParsedTags::with_options(input, options)
+                .map(|tag| tag.map(Tag::from_parsed))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Returns the document's tags, in order.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+}
+
+/// A difference between two values of a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterDifference<'a> {
+    list: usize,
+    index: usize,
+    old: Option<&'a [u8]>,
+    new: Option<&'a [u8]>,
+}
+
+impl<'a> ParameterDifference<'a> {
+    /// Returns the index of the parameter list containing the parameter.
+    pub fn list(&self) -> usize {
+        self.list
+    }
+
+    /// Returns the index of the parameter within its parameter list.
+    ///
+    /// Within the first parameter list, the parameter following the name has index `0`.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the old value of the parameter, or `None` if it was added.
+    pub fn old_value(&self) -> Option<&'a [u8]> {
+        self.old
+    }
+
+    /// Returns the new value of the parameter, or `None` if it was removed.
+    pub fn new_value(&self) -> Option<&'a [u8]> {
+        self.new
+    }
+
+    /// Compares the old and new values of the parameter line by line.
+    ///
+    /// This is most useful for parameters spanning many lines, such as the note data of a chart.
+    pub fn lines(&self) -> Vec<Line<'a>> {
+        let old = self
+            .old
+            .map_or_else(Vec::new, |old| old.split(|&byte| byte == b'\n').collect());
+        let new = self
+            .new
+            .map_or_else(Vec::new, |new| new.split(|&byte| byte == b'\n').collect());
+        align(&old, &new)
+            .into_iter()
+            .map(|step| match step {
+                Step::Both(i, _) => Line::Unchanged(old[i]),
+                Step::Old(i) => Line::Removed(old[i]),
+                Step::New(j) => Line::Added(new[j]),
+            })
+            .collect()
+    }
+}
+
+/// A line of a parameter, as compared by [`ParameterDifference::lines()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Unchanged(&'a [u8]),
+    Removed(&'a [u8]),
+    Added(&'a [u8]),
+}
+
+/// A difference between two documents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Difference<'a> {
+    /// A tag only in the new document.
+    Added(&'a Tag),
+    /// A tag only in the old document.
+    Removed(&'a Tag),
+    /// A tag whose parameters are unchanged, but whose position relative to the other tags is
+    /// different.
+    Moved { old: &'a Tag, new: &'a Tag },
+    /// A tag whose parameters are different.
+    Changed {
+        old: &'a Tag,
+        new: &'a Tag,
+        parameters: Vec<ParameterDifference<'a>>,
+    },
+}
+
+fn parameter_differences<'a>(old: &'a Tag, new: &'a Tag) -> Vec<ParameterDifference<'a>> {
+    let mut differences = Vec::new();
+    let lists = old.parameter_lists.len().max(new.parameter_lists.len());
+    for list in 0..lists {
+        let old_parameters = old.parameter_lists.get(list).map_or(&[][..], Vec::as_slice);
+        let new_parameters = new.parameter_lists.get(list).map_or(&[][..], Vec::as_slice);
+        for index in 0..old_parameters.len().max(new_parameters.len()) {
+            let old = old_parameters.get(index).map(Vec::as_slice);
+            let new = new_parameters.get(index).map(Vec::as_slice);
+            if old != new {
+                differences.push(ParameterDifference {
+                    list,
+                    index,
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+    differences
+}
+
+/// How the tags of two documents correspond to each other, as found by [`pair()`].
+pub(crate) struct Pairing {
+    steps: Vec<Step>,
+    /// The index of the new tag corresponding to each old tag, if there is one.
+    pub(crate) old: Vec<Option<usize>>,
+    /// The index of the old tag corresponding to each new tag, if there is one.
+    pub(crate) new: Vec<Option<usize>>,
+}
+
+/// Finds the tags of `old` and `new` that correspond to each other.
+///
+/// Tags are first aligned by their contents, ignoring their positions. The tags that only appear
+/// in one of the documents are then paired, first with tags that are the same and then with tags
+/// that share their name, in order.
+pub(crate) fn pair(old: &[Tag], new: &[Tag]) -> Pairing {
+    let old_contents = old
+        .iter()
+        .map(|tag| (&tag.name, &tag.parameter_lists))
+        .collect::<Vec<_>>();
+    let new_contents = new
+        .iter()
+        .map(|tag| (&tag.name, &tag.parameter_lists))
+        .collect::<Vec<_>>();
+    let steps = align(&old_contents, &new_contents);
+
+    let mut old_pairs = vec![None; old.len()];
+    let mut new_pairs = vec![None; new.len()];
+    let mut unmatched_old = Vec::new();
+    let mut unmatched_new = Vec::new();
+    for step in &steps {
+        match *step {
+            Step::Both(i, j) => {
+                old_pairs[i] = Some(j);
+                new_pairs[j] = Some(i);
+            }
+            Step::Old(i) => unmatched_old.push(i),
+            Step::New(j) => unmatched_new.push(j),
+        }
+    }
+    for same in [Tag::same_as as fn(&Tag, &Tag) -> bool, |old, new| {
+        old.name == new.name
+    }] {
+        for &j in &unmatched_new {
+            if new_pairs[j].is_some() {
+                continue;
+            }
+            if let Some(&i) = unmatched_old
+                .iter()
+                .find(|&&i| old_pairs[i].is_none() && same(&old[i], &new[j]))
+            {
+                old_pairs[i] = Some(j);
+                new_pairs[j] = Some(i);
+            }
+        }
+    }
+
+    Pairing {
+        steps,
+        old: old_pairs,
+        new: new_pairs,
+    }
+}
+
+/// Compares two documents tag by tag.
+///
+/// Tags that are the same in both documents and in the same order are not reported. Of the
+/// remaining tags, those with the same name and parameters are reported as
+/// [`Moved`](Difference::Moved), and those left with the same name are paired in order and
+/// reported as [`Changed`](Difference::Changed). Any others are reported as
+/// [`Added`](Difference::Added) or [`Removed`](Difference::Removed).
+///
+/// Differences are ordered by the position of their tags in the new document, with removed tags
+/// following the tags that preceded them in the old document.
+pub fn diff<'a>(old: &'a Document, new: &'a Document) -> Vec<Difference<'a>> {
+    let pairing = pair(&old.tags, &new.tags);
+    pairing
+        .steps
+        .into_iter()
+        .filter_map(|step| match step {
+            Step::Both(..) => None,
+            Step::Old(i) => match pairing.old[i] {
+                Some(_) => None,
+                None => Some(Difference::Removed(&old.tags[i])),
+            },
+            Step::New(j) => Some(match pairing.new[j] {
+                Some(i) => {
+                    let (old, new) = (&old.tags[i], &new.tags[j]);
+                    if old.same_as(new) {
+                        Difference::Moved { old, new }
+                    } else {
+                        Difference::Changed {
+                            old,
+                            new,
+                            parameters: parameter_differences(old, new),
+                        }
+                    }
+                }
+                None => Difference::Added(&new.tags[j]),
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{align, diff, Difference, Document, Line, ParameterDifference, Step};
+    use crate::de::{Options, Position};
+    use alloc::vec;
+    use claims::{assert_matches, assert_ok};
+
+    fn document(input: &[u8]) -> Document {
+        assert_ok!(Document::parse(input, Options::new()))
+    }
+
+    #[test]
+    fn align_equal() {
+        assert_eq!(
+            align(&[1, 2], &[1, 2]),
+            vec![Step::Both(0, 0), Step::Both(1, 1)]
+        );
+    }
+
+    #[test]
+    fn align_replaced() {
+        assert_eq!(
+            align(&[1, 2, 3], &[1, 4, 3]),
+            vec![
+                Step::Both(0, 0),
+                Step::Old(1),
+                Step::New(1),
+                Step::Both(2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn align_inserted_and_removed() {
+        assert_eq!(
+            align(&[1, 2, 3, 4], &[0, 1, 3, 4, 5]),
+            vec![
+                Step::New(0),
+                Step::Both(0, 1),
+                Step::Old(1),
+                Step::Both(2, 2),
+                Step::Both(3, 3),
+                Step::New(4)
+            ]
+        );
+    }
+
+    #[test]
+    fn align_empty() {
+        assert_eq!(align::<u8>(&[], &[]), vec![]);
+        assert_eq!(align(&[1], &[]), vec![Step::Old(0)]);
+    }
+
+    #[test]
+    fn no_differences() {
+        let old = document(b"#TITLE:Foo;\n#NOTES:\n     dance-single:\n     0000\n     0000\n;\n");
+        let new = document(
+            b"// comment\n#TITLE:  Foo  ;\r\n#NOTES:\r\n  dance-single:\r\n  0000 // comment\r\n  0000\r\n;\r\n",
+        );
+
+        assert_eq!(diff(&old, &new), vec![]);
+    }
+
+    #[test]
+    fn trailing_comments_ignored() {
+        let old = document(b"#A:b; // c\n#D:e;\t// f\n// g\n");
+        let new = document(b"#A:b;\n#D:e;\n");
+
+        assert_eq!(diff(&old, &new), vec![]);
+    }
+
+    #[test]
+    fn escape_spelling_ignored() {
+        let old = document(b"#TITLE:a\\b;\n");
+        let new = document(b"#TITLE:a\\\\b;\n");
+
+        assert_eq!(diff(&old, &new), vec![]);
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let old = document(b"#TITLE:Foo;\n#SUBTITLE:Bar;\n");
+        let new = document(b"#TITLE:Foo;\n#ARTIST:Baz;\n");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Difference::Removed(&old.tags[1]),
+                Difference::Added(&new.tags[1])
+            ]
+        );
+    }
+
+    #[test]
+    fn changed() {
+        let old = document(b"#TITLE:Foo;\n#BPMS:0:120;\n4:60;\n");
+        let new = document(b"#TITLE:Foo;\n#BPMS:0:120;\n4:90;\n8:60;\n");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Difference::Changed {
+                old: &old.tags[1],
+                new: &new.tags[1],
+                parameters: vec![
+                    ParameterDifference {
+                        list: 1,
+                        index: 1,
+                        old: Some(b"60"),
+                        new: Some(b"90"),
+                    },
+                    ParameterDifference {
+                        list: 2,
+                        index: 0,
+                        old: None,
+                        new: Some(b"8"),
+                    },
+                    ParameterDifference {
+                        list: 2,
+                        index: 1,
+                        old: None,
+                        new: Some(b"60"),
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn moved() {
+        let old = document(b"#TITLE:Foo;\n#ARTIST:Bar;\n#BPMS:0=120;\n");
+        let new = document(b"#BPMS:0=120;\n#TITLE:Foo;\n#ARTIST:Bar;\n");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Difference::Moved {
+                old: &old.tags[2],
+                new: &new.tags[0],
+            }]
+        );
+    }
+
+    #[test]
+    fn moved_with_trailing_comment() {
+        let old = document(b"#TITLE:Foo; // title\n#ARTIST:Bar;\n");
+        let new = document(b"#ARTIST:Bar;\n#TITLE:Foo;\n");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Difference::Moved {
+                old: &old.tags[0],
+                new: &new.tags[1],
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicate_names_paired_in_order() {
+        let old = document(b"#NOTES:Easy:1;\n#NOTES:Hard:9;\n");
+        let new = document(b"#NOTES:Easy:2;\n#NOTES:Hard:10;\n");
+
+        let differences = diff(&old, &new);
+
+        assert_eq!(differences.len(), 2);
+        assert_matches!(
+            &differences[0],
+            Difference::Changed { old, new, .. } if old.position() == Position::new(0, 0)
+                && new.position() == Position::new(0, 0)
+        );
+        assert_matches!(
+            &differences[1],
+            Difference::Changed { old, new, .. } if old.position() == Position::new(1, 0)
+                && new.position() == Position::new(1, 0)
+        );
+    }
+
+    #[test]
+    fn parameter_lines() {
+        let old = document(b"#NOTES:\n0000\n1000\n0000\n;\n");
+        let new = document(b"#NOTES:\n0000\n0100\n0000\n;\n");
+
+        let differences = diff(&old, &new);
+
+        assert_matches!(&differences[..], [Difference::Changed { parameters, .. }] if parameters[0].lines() == vec![
+            Line::Unchanged(b"0000"),
+            Line::Removed(b"1000"),
+            Line::Added(b"0100"),
+            Line::Unchanged(b"0000"),
+        ]);
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_matches!(
+            Document::parse(b"foo\n#TITLE:Foo;\n", Options::new()),
+            Err(_)
+        );
+    }
+}
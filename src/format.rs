@@ -0,0 +1,476 @@
+//! Formatting MSD documents.
+//!
+//! [`format()`] rewrites a document into a consistent layout without changing what it means to
+//! the parser:
+//!
+//! - Every tag begins on a line of its own and, unless it ends in a comment, is terminated by a
+//!   `;`.
+//! - Lines between tags end with `\n` and carry no trailing whitespace.
+//! - Comments following a tag are separated from it by a single space, and at most one blank line
+//!   separates any two tags.
+//! - Escape sequences are written the way the serializer writes them: unescaped `#`s within a tag
+//!   are escaped, as are `\`s that do not escape anything.
+//!
+//! The bytes of a tag's values, including their whitespace, indentation, and line endings, are
+//! never changed, since they are all part of what the values mean. A tag missing its `;` is
+//! terminated after the last of its values' bytes, unless those end in a comment.
+//!
+//! The formatted document is parsed again and compared against the original before it is
+//! returned. Tags are compared by their exact names and parameters, and a document that cannot be
+//! parsed is not formatted at all.
+//!
+//! # Example
+//! ```
+//! let input = b"#TITLE:foo;  #ARTIST:b#r;  \r\n\r\n\r\n#BPMS:\n   0:120;\n   4:60;  // tempo";
+//!
+//! assert_eq!(
+//!     msd::format::format(input).unwrap(),
+//!     b"#TITLE:foo;\n#ARTIST:b\\#r;\n\n#BPMS:\n   0:120;\n   4:60; // tempo\n"
+//! );
+//! ```
+
+use crate::{
+    de::{Options, ParsedTag, ParsedTags, Position},
+    lex::{Kind, Lexer, Token},
+    ser::escaped::Escaped,
+};
+use alloc::vec::Vec;
+use core::{fmt, fmt::Display};
+use serde::de::StdError;
+
+/// An error indicating that formatting would change the meaning of a document.
+///
+/// This occurs for documents whose tags the parser splits differently than the formatter expects,
+/// and for documents the parser cannot read, in which case the document is left as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    position: Position,
+}
+
+impl Error {
+    /// Returns the position in the original document of the first tag whose meaning would change,
+    /// or of the error preventing the document from being parsed.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "formatting would change the meaning of the tag at line {} column {}",
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+impl StdError for Error {}
+
+/// Formats an MSD document.
+///
+/// Returns an error, rather than a formatted document, if formatting would change the meaning of
+/// any tag.
+pub fn format(input: &[u8]) -> Result<Vec<u8>, Error> {
+    let tokens = Lexer::new(input).collect::<Vec<_>>();
+    let mut formatter = Formatter {
+        output: Vec::with_capacity(input.len()),
+        newlines: 0,
+        space: &[],
+    };
+
+    let mut start = tokens
+        .iter()
+        .position(|token| token.kind() == Kind::TagStart)
+        .unwrap_or(tokens.len());
+    formatter.leading(&tokens[..start]);
+    while start < tokens.len() {
+        let end = tokens[start + 1..]
+            .iter()
+            .position(|token| token.kind() == Kind::TagStart)
+            .map_or(tokens.len(), |offset| start + 1 + offset);
+        formatter.tag(&tokens[start..end], end == tokens.len());
+        start = end;
+    }
+    if !formatter.output.is_empty() && !formatter.output.ends_with(b"\n") {
+        formatter.output.push(b'\n');
+    }
+
+    verify(input, &formatter.output)?;
+    Ok(formatter.output)
+}
+
+struct Formatter<'a> {
+    output: Vec<u8>,
+    // Line endings and whitespace that are only written if followed by more content on the line.
+    newlines: usize,
+    space: &'a [u8],
+}
+
+impl<'a> Formatter<'a> {
+    /// Writes `bytes`, preceded by any pending line endings and whitespace.
+    ///
+    /// At most `max_newlines` of the pending line endings are written.
+    fn write(&mut self, bytes: &[u8], max_newlines: usize) {
+        for _ in 0..self.newlines.min(max_newlines) {
+            self.output.push(b'\n');
+        }
+        self.newlines = 0;
+        self.output.extend_from_slice(self.space);
+        self.space = &[];
+        self.output.extend_from_slice(bytes);
+    }
+
+    fn newline(&mut self) {
+        self.newlines += 1;
+        self.space = &[];
+    }
+
+    /// Writes the content preceding the first tag, such as a header comment.
+    fn leading(&mut self, tokens: &[Token<'a>]) {
+        for token in tokens {
+            match token.kind() {
+                Kind::Whitespace => self.space = token.bytes(),
+                Kind::Newline => self.newline(),
+                _ => {
+                    // Blank lines at the start of the document are dropped.
+                    let max_newlines = if self.output.is_empty() { 0 } else { 2 };
+                    self.write(trim(token.bytes()), max_newlines);
+                }
+            }
+        }
+        if !self.output.is_empty() && self.newlines > 0 {
+            self.write(b"", 2);
+        }
+        self.newlines = 0;
+        self.space = &[];
+    }
+
+    /// Writes a single tag, along with the trivia following it.
+    fn tag(&mut self, tokens: &[Token<'a>], last: bool) {
+        let end = tokens
+            .iter()
+            .rposition(|token| {
+                !matches!(
+                    token.kind(),
+                    Kind::Whitespace | Kind::Newline | Kind::Comment
+                )
+            })
+            .map_or(0, |index| index + 1);
+        let terminated = tokens[..end].last().map(Token::kind) == Some(Kind::Semicolon);
+        // Everything following the final value of a tag that is missing its `;` still belongs to
+        // that value, except for the `\n` ending the tag's last line.
+        let (values, trivia) = if terminated {
+            tokens.split_at(end)
+        } else if tokens.last().map(Token::kind) == Some(Kind::Newline) {
+            tokens.split_at(tokens.len() - 1)
+        } else {
+            (tokens, &[][..])
+        };
+
+        for token in values {
+            match token.kind() {
+                // Whitespace and line endings within a tag are part of its values.
+                Kind::Whitespace | Kind::Newline => self.write(token.bytes(), 0),
+                Kind::Name | Kind::Parameter => {
+                    self.write(&Escaped::new(token.bytes()).collect::<Vec<_>>(), 0)
+                }
+                Kind::Escape => match token.bytes() {
+                    // An escaped `/` may be preventing a comment, so it is left as it is.
+                    bytes @ [b'\\', b':' | b';' | b'\\' | b'#' | b'/'] => self.write(bytes, 0),
+                    // A `\` escaping any other byte is read as itself, so it is escaped.
+                    bytes => self.write(&Escaped::new(bytes).collect::<Vec<_>>(), 0),
+                },
+                Kind::Comment => self.write(trim(token.bytes()), 0),
+                _ => self.write(token.bytes(), 0),
+            }
+        }
+        if !terminated {
+            // A `\r` preceding the final `\n` is still part of the value.
+            if let Some(newline) = trivia.first() {
+                self.write(&newline.bytes()[..newline.bytes().len() - 1], 0);
+            }
+            // A `;` following a comment would be part of the comment, and one on a line of its own
+            // would add that line to the value, so a tag ending in a comment is left unterminated.
+            if values.last().map(Token::kind) != Some(Kind::Comment) {
+                self.write(b";", 0);
+            }
+        }
+
+        for token in trivia {
+            match token.kind() {
+                Kind::Newline => self.newline(),
+                Kind::Comment => {
+                    if self.newlines == 0 {
+                        self.space = b" ";
+                    }
+                    self.write(trim(token.bytes()), 2);
+                }
+                _ => {}
+            }
+        }
+        self.space = &[];
+        let max_newlines = if last { 1 } else { 2 };
+        self.newlines = self.newlines.max(1);
+        self.write(b"", max_newlines);
+    }
+}
+
+/// Returns the tags of `input` exactly as the parser reads them.
+fn meaning(input: &[u8]) -> Result<Vec<ParsedTag>, Error> {
+    ParsedTags::with_options(input, Options::new().skip_content_before_first_tag(true))
+        .map(|tag| {
+            tag.map_err(|error| Error {
+                position: error.position(),
+            })
+        })
+        .collect()
+}
+
+/// Trims the whitespace surrounding each line of `value`, along with any blank lines at its start
+/// or end.
+pub(crate) fn normalize(value: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(value.len());
+    for (index, line) in value.split(|&byte| byte == b'\n').enumerate() {
+        if index > 0 {
+            normalized.push(b'\n');
+        }
+        normalized.extend_from_slice(trim(line));
+    }
+    trim(&normalized).to_vec()
+}
+
+fn trim(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+/// Verifies that `formatted` has the same meaning as `input`.
+///
+/// Both documents must be read by the parser without error, and must contain exactly the same
+/// tags with exactly the same values.
+fn verify(input: &[u8], formatted: &[u8]) -> Result<(), Error> {
+    let original = meaning(input)?;
+    let changed = |index: usize| Error {
+        position: original
+            .get(index)
+            .or_else(|| original.last())
+            .map_or(Position::new(0, 0), |tag| tag.position),
+    };
+    let formatted_tags = ParsedTags::with_options(
+        formatted,
+        Options::new().skip_content_before_first_tag(true),
+    );
+    let mut count = 0;
+    for (index, formatted_tag) in formatted_tags.enumerate() {
+        match (formatted_tag, original.get(index)) {
+            (Ok(formatted_tag), Some(tag))
+                if formatted_tag.name == tag.name
+                    && formatted_tag.parameter_lists == tag.parameter_lists => {}
+            _ => return Err(changed(index)),
+        }
+        count += 1;
+    }
+    if count < original.len() {
+        return Err(changed(count));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, normalize, verify, Error};
+    use crate::{de::Position, from_bytes};
+    use alloc::{collections::BTreeMap, string::String};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+
+    #[test]
+    fn empty() {
+        assert_ok_eq!(format(b""), b"");
+    }
+
+    #[test]
+    fn already_formatted() {
+        let input = b"#TITLE:foo;\n#BPMS:\n   0.000:120.000;\n   4.000:60.000;\n";
+
+        assert_ok_eq!(format(input), input);
+    }
+
+    #[test]
+    fn tags_on_own_lines() {
+        assert_ok_eq!(format(b"#A:foo;#B:bar;  #C;"), b"#A:foo;\n#B:bar;\n#C;\n");
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_ok_eq!(
+            format(b"#A:foo;\r\n#B:\r\nbar\r\n;\r\n"),
+            b"#A:foo;\n#B:\r\nbar\r\n;\n"
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        assert_ok_eq!(format(b"#A:foo;  \n#B:bar;\t \n"), b"#A:foo;\n#B:bar;\n");
+    }
+
+    #[test]
+    fn whitespace_within_values_preserved() {
+        let input = b"#A:foo  \n  bar\t\n;\n";
+
+        assert_ok_eq!(format(input), input);
+    }
+
+    #[test]
+    fn indentation_preserved() {
+        let input = b"#NOTES:\n     dance-single:\n\t:\n  Beginner:\n0000\n0000\n;\n";
+
+        assert_ok_eq!(format(input), input);
+    }
+
+    #[test]
+    fn multi_line_parameter() {
+        let input = b"#DESC:line1\n      line2;";
+
+        assert_ok_eq!(format(input), b"#DESC:line1\n      line2;\n");
+        assert_eq!(
+            from_bytes::<BTreeMap<String, String>>(&format(input).unwrap()).unwrap(),
+            from_bytes::<BTreeMap<String, String>>(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn blank_lines_within_tag_preserved() {
+        let input = b"#A:foo\n   \n\nbar;\n";
+
+        assert_ok_eq!(format(input), input);
+    }
+
+    #[test]
+    fn blank_lines_between_tags_collapsed() {
+        assert_ok_eq!(
+            format(b"#A:foo;\n\n\n\n#B:bar;\n\n\n"),
+            b"#A:foo;\n\n#B:bar;\n"
+        );
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        assert_ok_eq!(format(b"#A:foo  \n#B:bar"), b"#A:foo  ;\n#B:bar;\n");
+    }
+
+    #[test]
+    fn missing_semicolon_before_comment() {
+        assert_ok_eq!(
+            format(b"#A:foo\n// comment\n#B:bar;"),
+            b"#A:foo\n// comment\n#B:bar;\n"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_ok_eq!(
+            format(b"#A:foo;\t// first\n\n\n// second\n#B:b//ar\n;"),
+            b"#A:foo; // first\n\n// second\n#B:b//ar\n;\n"
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert_ok_eq!(
+            format(b"#A\\B:f\\o\\:o\\/\\/:b#ar;"),
+            b"#A\\\\B:f\\\\o\\:o\\/\\/:b\\#ar;\n"
+        );
+    }
+
+    #[test]
+    fn escaped_whitespace() {
+        assert_ok_eq!(format(b"#A:foo\\ ;"), b"#A:foo\\\\ ;\n");
+    }
+
+    #[test]
+    fn leading_content() {
+        assert_ok_eq!(
+            format(b"\n\n// header  \n\n\n#A:foo;"),
+            b"// header\n\n#A:foo;\n"
+        );
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        assert_ok_eq!(format(b"\xEF\xBB\xBF#A:foo;"), b"\xEF\xBB\xBF#A:foo;\n");
+    }
+
+    #[test]
+    fn idempotent() {
+        let input = b"// header\r\n#TITLE:f\\:o\\;o // comment\r\n:bar;\r\n#EMPTY;#NOSEMI:baz\r\n\r\n#B#D:x; trailing\r\n\t";
+        let formatted = format(input).unwrap();
+
+        assert_ok_eq!(format(&formatted), formatted);
+    }
+
+    #[test]
+    fn normalize_lines() {
+        assert_eq!(normalize(b"\n  foo \r\n\tbar\n\n"), b"foo\nbar");
+    }
+
+    #[test]
+    fn verify_same_meaning() {
+        assert_ok!(verify(
+            b"#A:  foo\r\n  bar;  // comment",
+            b"#A:  foo\r\n  bar;\n"
+        ));
+    }
+
+    #[test]
+    fn verify_changed_indentation() {
+        assert_err_eq!(
+            verify(b"#DESC:line1\n      line2;", b"#DESC:line1\n   line2;\n"),
+            Error {
+                position: Position::new(0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn verify_changed_value() {
+        assert_err_eq!(
+            verify(b"#A:foo;\n#B:bar;\n", b"#A:foo;\n#B:baz;\n"),
+            Error {
+                position: Position::new(1, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn verify_changed_parameter_lists() {
+        assert_err_eq!(
+            verify(b"#A:foo:bar;\n", b"#A:foo;bar;\n"),
+            Error {
+                position: Position::new(0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn verify_added_tag() {
+        assert_err_eq!(
+            verify(b"#A:foo;\n", b"#A:foo;\n#B;\n"),
+            Error {
+                position: Position::new(0, 0)
+            }
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! The I/O traits used for reading and writing MSD.
+//!
+//! When the `std` feature is enabled, these are simply the traits provided by `std::io`.
+//! Otherwise, minimal replacements are provided, which are implemented for byte slices and
+//! `Vec<u8>` and can be implemented for any other source or destination of bytes.
+//!
+//! # Example
+//! ```
+//! use msd::io::{Error, Read};
+//!
+//! /// Reads the same byte forever.
+//! struct Repeat(u8);
+//!
+//! impl Read for Repeat {
+//!     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+//!         buf.fill(self.0);
+//!         Ok(buf.len())
+//!     }
+//! }
+//!
+//! let mut byte = [0];
+//! Repeat(b'#').read(&mut byte).unwrap();
+//!
+//! assert_eq!(byte, [b'#']);
+//! ```
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{Error, Read, Write};
+#[cfg(feature = "std")]
+pub use std::io::{Error, Read, Write};
+
+use core::slice;
+
+/// An iterator over the bytes of a reader.
+///
+/// Unlike [`std::io::Bytes`], the reader remains accessible while iterating, so that more input can
+/// be provided to it between reads.
+#[derive(Debug)]
+pub(crate) struct Bytes<R> {
+    inner: R,
+}
+
+impl<R> Bytes<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+}
+
+impl<R> Iterator for Bytes<R>
+where
+    R: Read,
+{
+    type Item = Result<u8, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = 0;
+        loop {
+            match self.inner.read(slice::from_mut(&mut byte)) {
+                Ok(0) => return None,
+                Ok(_) => return Some(Ok(byte)),
+                // Interrupted reads are retried, as they are by `std::io::Bytes`.
+                Err(error) if interrupted(&error) => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Returns whether `error` indicates that no more input is available from the reader yet, rather
+/// than that reading failed.
+#[cfg(feature = "std")]
+pub(crate) fn would_block(error: &Error) -> bool {
+    error.kind() == std::io::ErrorKind::WouldBlock
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn would_block(_error: &Error) -> bool {
+    false
+}
+
+/// Returns whether `error` indicates that the read was interrupted and should be retried.
+#[cfg(feature = "std")]
+fn interrupted(error: &Error) -> bool {
+    error.kind() == std::io::ErrorKind::Interrupted
+}
+
+#[cfg(not(feature = "std"))]
+fn interrupted(_error: &Error) -> bool {
+    false
+}
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use alloc::vec::Vec;
+
+    /// An error that occurred while reading or writing.
+    #[derive(Debug)]
+    pub struct Error;
+
+    /// A source of bytes.
+    pub trait Read {
+        /// Reads bytes into `buf`, returning how many bytes were read.
+        ///
+        /// A return value of `0` indicates the end of the input.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            let len = buf.len().min(self.len());
+            let (read, remaining) = self.split_at(len);
+            buf[..len].copy_from_slice(read);
+            *self = remaining;
+            Ok(len)
+        }
+    }
+
+    impl<R> Read for &mut R
+    where
+        R: Read + ?Sized,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            (**self).read(buf)
+        }
+    }
+
+    /// A destination for bytes.
+    pub trait Write {
+        /// Writes all of `buf`.
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error>;
+    }
+
+    impl Write for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    impl<W> Write for &mut W
+    where
+        W: Write + ?Sized,
+    {
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+            (**self).write_all(buf)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{Read, Write};
+        use crate::io::Bytes;
+        use claims::{assert_ok, assert_ok_eq};
+
+        #[test]
+        fn read_slice() {
+            let mut reader = b"foo".as_slice();
+            let mut buffer = [0; 2];
+
+            assert_eq!(reader.read(&mut buffer).unwrap(), 2);
+            assert_eq!(&buffer, b"fo");
+            assert_eq!(reader.read(&mut buffer).unwrap(), 1);
+            assert_eq!(&buffer[..1], b"o");
+            assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+        }
+
+        #[test]
+        fn bytes() {
+            assert_eq!(
+                Bytes::new(b"foo".as_slice())
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap(),
+                b"foo"
+            );
+        }
+
+        #[test]
+        fn custom_reader() {
+            /// Reads one byte at a time.
+            struct Trickle<'a>(&'a [u8]);
+
+            impl crate::io::Read for Trickle<'_> {
+                fn read(&mut self, buf: &mut [u8]) -> Result<usize, crate::io::Error> {
+                    match (self.0.split_first(), buf.first_mut()) {
+                        (Some((byte, rest)), Some(slot)) => {
+                            *slot = *byte;
+                            self.0 = rest;
+                            Ok(1)
+                        }
+                        _ => Ok(0),
+                    }
+                }
+            }
+
+            assert_ok_eq!(crate::from_reader::<_, u64>(Trickle(b"#42;\n")), 42);
+        }
+
+        #[test]
+        fn custom_writer() {
+            /// Counts the bytes written to it.
+            struct Counter(usize);
+
+            impl crate::io::Write for Counter {
+                fn write_all(&mut self, buf: &[u8]) -> Result<(), crate::io::Error> {
+                    self.0 += buf.len();
+                    Ok(())
+                }
+            }
+
+            let mut counter = Counter(0);
+            assert_ok!(crate::to_writer(&mut counter, &42u64));
+
+            assert_eq!(counter.0, b"#42;\n".len());
+        }
+
+        #[test]
+        fn write_vec() {
+            let mut output = Vec::new();
+
+            output.write_all(b"foo").unwrap();
+            (&mut output).write_all(b"bar").unwrap();
+
+            assert_eq!(output, b"foobar");
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! A lexer splitting MSD input into spanned tokens.
+//!
+//! The [`Lexer`] splits input into [`Token`]s covering every byte of the input, each located by
+//! both its byte range and its [`Position`]. Concatenating the bytes of every token reproduces
+//! the input exactly, which makes the lexer suitable for syntax highlighting and other tooling
+//! that needs to inspect the input's formatting.
+//!
+//! Tags are found by the same scanner the deserializer uses, so the two always agree on where tags
+//! begin: a tag begins at a `#` either at the start of a line or following the `;` of the previous
+//! tag. Any other `#` within a tag is treated as part of a name or parameter.
+//!
+//! # Example
+//! ```
+//! use msd::lex::{Kind, Lexer};
+//!
+//! let kinds = Lexer::new(b"#TITLE:foo\\;bar; // comment\n")
+//!     .map(|token| token.kind())
+//!     .collect::<Vec<_>>();
+//!
+//! assert_eq!(
+//!     kinds,
+//!     vec![
+//!         Kind::TagStart,
+//!         Kind::Name,
+//!         Kind::Colon,
+//!         Kind::Parameter,
+//!         Kind::Escape,
+//!         Kind::Parameter,
+//!         Kind::Semicolon,
+//!         Kind::Whitespace,
+//!         Kind::Comment,
+//!         Kind::Newline,
+//!     ]
+//! );
+//! ```
+
+use crate::{
+    de::Position,
+    scan::{Class, Scanner},
+};
+use core::ops::Range;
+
+/// The kind of a [`Token`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// The `#` beginning a tag.
+    TagStart,
+    /// Text within a tag's name.
+    Name,
+    /// A `:` separating a tag's name and parameters.
+    Colon,
+    /// Text within one of a tag's parameters.
+    Parameter,
+    /// A `;` terminating a tag's parameters.
+    Semicolon,
+    /// A comment, from its `//` up to the end of the line. The line ending is not included.
+    Comment,
+    /// A `\` along with the byte it escapes.
+    Escape,
+    /// A run of whitespace other than line endings.
+    Whitespace,
+    /// A line ending, either `\n` or `\r\n`.
+    Newline,
+    /// Text preceding the first tag.
+    Content,
+}
+
+/// A single token within MSD input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Token<'a> {
+    kind: Kind,
+    bytes: &'a [u8],
+    start: usize,
+    position: Position,
+}
+
+impl<'a> Token<'a> {
+    /// Returns the kind of the token.
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Returns the token's bytes, exactly as they appear in the input.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the range of bytes within the input covered by the token.
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.start + self.bytes.len()
+    }
+
+    /// Returns the position in the input at which the token begins.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+/// Where the lexer is located within the current tag.
+#[derive(Clone, Copy, Debug)]
+enum Context {
+    InName,
+    InParameters,
+}
+
+/// An iterator over the [`Token`]s of MSD input.
+///
+/// Lexing never fails; every byte of the input is part of exactly one token.
+#[derive(Clone, Debug)]
+pub struct Lexer<'a> {
+    bytes: &'a [u8],
+
+    current_byte_index: usize,
+    current_position: Position,
+
+    context: Context,
+    scanner: Scanner,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer over the given input.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+
+            current_byte_index: 0,
+            current_position: Position::new(0, 0),
+
+            context: Context::InName,
+            scanner: Scanner::new(),
+        }
+    }
+
+    fn get(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.current_byte_index + offset).copied()
+    }
+
+    fn starts_newline(&self, offset: usize) -> bool {
+        match self.get(offset) {
+            Some(b'\n') => true,
+            Some(b'\r') => matches!(self.get(offset + 1), Some(b'\n')),
+            _ => false,
+        }
+    }
+
+    /// Returns the length of the comment beginning at the current byte, up to the end of its
+    /// line.
+    fn comment_len(&self) -> usize {
+        let mut scanner = self.scanner;
+        let mut len = 0;
+        while let Some(byte) = self.get(len) {
+            if self.starts_newline(len)
+                || !matches!(
+                    scanner.next(byte),
+                    Class::Slash | Class::CommentStart | Class::Comment
+                )
+            {
+                break;
+            }
+            len += 1;
+        }
+        len
+    }
+
+    /// Returns the length of the text beginning at the current byte.
+    fn text_len(&self) -> usize {
+        let mut scanner = self.scanner;
+        let mut len = 0;
+        while let Some(byte) = self.get(len) {
+            let continues_text = !byte.is_ascii_whitespace()
+                && match scanner.next(byte) {
+                    Class::Text | Class::Content | Class::NumberSign => true,
+                    // A `/` only continues the text if it does not begin a comment.
+                    Class::Slash => !matches!(self.get(len + 1), Some(b'/')),
+                    _ => false,
+                };
+            // The first byte always belongs to the text.
+            if !continues_text && len > 0 {
+                break;
+            }
+            len += 1;
+        }
+        len
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.get(0)?;
+
+        let (kind, len) = if self.starts_newline(0) {
+            (Kind::Newline, if byte == b'\r' { 2 } else { 1 })
+        } else {
+            // Classify the byte without consuming it; the whole token is consumed below.
+            let mut scanner = self.scanner;
+            match scanner.next(byte) {
+                Class::TagStart => {
+                    self.context = Context::InName;
+                    (Kind::TagStart, 1)
+                }
+                Class::Slash if matches!(self.get(1), Some(b'/')) => {
+                    (Kind::Comment, self.comment_len())
+                }
+                _ if byte.is_ascii_whitespace() => {
+                    let mut len = 1;
+                    while matches!(self.get(len), Some(byte) if byte.is_ascii_whitespace())
+                        && !self.starts_newline(len)
+                    {
+                        len += 1;
+                    }
+                    (Kind::Whitespace, len)
+                }
+                Class::Escape => (Kind::Escape, if self.get(1).is_some() { 2 } else { 1 }),
+                Class::Colon => {
+                    self.context = Context::InParameters;
+                    (Kind::Colon, 1)
+                }
+                Class::Semicolon => {
+                    self.context = Context::InParameters;
+                    (Kind::Semicolon, 1)
+                }
+                Class::Content => (Kind::Content, self.text_len()),
+                _ => {
+                    let kind = match self.context {
+                        Context::InName => Kind::Name,
+                        Context::InParameters => Kind::Parameter,
+                    };
+                    (kind, self.text_len())
+                }
+            }
+        };
+
+        let start = self.current_byte_index;
+        let bytes = &self.bytes[start..start + len];
+        let position = self.current_position;
+        for byte in bytes {
+            self.scanner.next(*byte);
+            if matches!(byte, b'\n') {
+                self.current_position = self.current_position.increment_line();
+            } else {
+                self.current_position = self.current_position.increment_column();
+            }
+        }
+        self.current_byte_index += len;
+
+        Some(Token {
+            kind,
+            bytes,
+            start,
+            position,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kind, Lexer, Token};
+    use crate::de::Position;
+    use alloc::vec::Vec;
+
+    fn kinds(bytes: &[u8]) -> Vec<(Kind, &[u8])> {
+        Lexer::new(bytes)
+            .map(|token| (token.kind(), token.bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(kinds(b""), vec![]);
+    }
+
+    #[test]
+    fn tag() {
+        assert_eq!(
+            kinds(b"#FOO:bar:baz;\n"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"bar"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"baz"),
+                (Kind::Semicolon, b";"),
+                (Kind::Newline, b"\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn whitespace_within_parameter() {
+        assert_eq!(
+            kinds(b"#FOO: bar\t baz ;"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Whitespace, b" "),
+                (Kind::Parameter, b"bar"),
+                (Kind::Whitespace, b"\t "),
+                (Kind::Parameter, b"baz"),
+                (Kind::Whitespace, b" "),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf() {
+        assert_eq!(
+            kinds(b"#FOO;  \r\n\r#BAR;"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Semicolon, b";"),
+                (Kind::Whitespace, b"  "),
+                (Kind::Newline, b"\r\n"),
+                (Kind::Whitespace, b"\r"),
+                (Kind::TagStart, b"#"),
+                (Kind::Name, b"BAR"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            kinds(b"#F\\#O:b\\:a\\r\\"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"F"),
+                (Kind::Escape, b"\\#"),
+                (Kind::Name, b"O"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"b"),
+                (Kind::Escape, b"\\:"),
+                (Kind::Parameter, b"a"),
+                (Kind::Escape, b"\\r"),
+                (Kind::Escape, b"\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            kinds(b"// #header\r\n#FOO:b/a//r;\n:baz;"),
+            vec![
+                (Kind::Comment, &b"// #header"[..]),
+                (Kind::Newline, b"\r\n"),
+                (Kind::TagStart, b"#"),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"b/a"),
+                (Kind::Comment, b"//r;"),
+                (Kind::Newline, b"\n"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"baz"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_before_first_tag() {
+        assert_eq!(
+            kinds(b"foo\\:bar #BAZ;"),
+            vec![
+                (Kind::Content, &b"foo\\:bar"[..]),
+                (Kind::Whitespace, b" "),
+                (Kind::TagStart, b"#"),
+                (Kind::Name, b"BAZ"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn number_sign_within_tag() {
+        assert_eq!(
+            kinds(b"#FOO:b#ar;#BAZ;"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"b#ar"),
+                (Kind::Semicolon, b";"),
+                (Kind::TagStart, b"#"),
+                (Kind::Name, b"BAZ"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn number_sign_after_whitespace() {
+        assert_eq!(
+            kinds(b"#FOO:bar\n #BAZ"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"bar"),
+                (Kind::Newline, b"\n"),
+                (Kind::Whitespace, b" "),
+                (Kind::Parameter, b"#BAZ"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        assert_eq!(
+            kinds(b"#FOO:bar\n#BAZ;"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Colon, b":"),
+                (Kind::Parameter, b"bar"),
+                (Kind::Newline, b"\n"),
+                (Kind::TagStart, b"#"),
+                (Kind::Name, b"BAZ"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_after_comment_following_semicolon() {
+        assert_eq!(
+            kinds(b"#FOO; // bar\n#BAZ;")
+                .into_iter()
+                .filter(|(kind, _)| *kind == Kind::TagStart)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn content_after_semicolon() {
+        assert_eq!(
+            kinds(b"#FOO; bar #BAZ;"),
+            vec![
+                (Kind::TagStart, &b"#"[..]),
+                (Kind::Name, b"FOO"),
+                (Kind::Semicolon, b";"),
+                (Kind::Whitespace, b" "),
+                (Kind::Parameter, b"bar"),
+                (Kind::Whitespace, b" "),
+                (Kind::Parameter, b"#BAZ"),
+                (Kind::Semicolon, b";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_and_positions() {
+        let tokens = Lexer::new(b"#FOO:\nbar;").collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens[3],
+            Token {
+                kind: Kind::Newline,
+                bytes: b"\n",
+                start: 5,
+                position: Position::new(0, 5),
+            }
+        );
+        assert_eq!(tokens[4].span(), 6..9);
+        assert_eq!(tokens[4].position(), Position::new(1, 0));
+        assert_eq!(tokens[5].position(), Position::new(1, 3));
+    }
+
+    #[test]
+    fn lossless() {
+        let input = b"junk // c\n#A:b\\;c // d\r\n:e;\t\r\n#F:g\n#H#I;:j";
+
+        assert_eq!(
+            Lexer::new(input)
+                .flat_map(|token| token.bytes().iter().copied())
+                .collect::<Vec<_>>(),
+            input
+        );
+    }
+}
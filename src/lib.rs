@@ -6,6 +6,16 @@
 //! [`Serialize`](https://docs.rs/serde/*/serde/trait.Serialize.html) and
 //! [`Deserialize`](https://docs.rs/serde/*/serde/trait.Deserialize.html) traits.
 //!
+//! # `no_std` Support
+//! This crate can be used without the standard library by disabling the default `std` feature. An
+//! allocator is still required. Without `std`, input can be read from byte slices and output can be
+//! written to `Vec<u8>`s, most easily through [`from_bytes()`] and [`to_bytes()`]. Other sources and
+//! destinations can be used by implementing the [`io::Read`] and [`io::Write`] traits for them.
+//!
+//! The tooling modules, such as `format`, `diff`, and `lint`, are each enabled by a default feature
+//! of the same name, so disabling default features also leaves them out. They can be enabled again
+//! individually, and do not require `std`.
+//!
 //! # Example
 //! ```
 //! use std::collections::HashMap;
@@ -23,12 +33,44 @@
 //! assert_eq!(map, deserialized);
 //! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(unsafe_op_in_unsafe_fn)]
 
+extern crate alloc;
+
+#[cfg(feature = "check")]
+pub mod check;
+#[cfg(feature = "convert")]
+pub mod convert;
+#[cfg(feature = "cst")]
+pub mod cst;
 pub mod de;
+#[cfg(feature = "diff")]
+pub mod diff;
+#[cfg(feature = "format")]
+pub mod format;
+pub mod io;
+pub mod lex;
+#[cfg(feature = "lint")]
+pub mod lint;
+#[cfg(feature = "merge")]
+pub mod merge;
+#[cfg(feature = "query")]
+pub mod query;
 pub mod ser;
 
+mod path;
+mod raw;
+mod scan;
+mod unrecognized;
+
+#[cfg(all(test, feature = "async"))]
+mod block_on;
+
 #[doc(inline)]
 pub use de::{from_bytes, from_reader, Deserializer};
 #[doc(inline)]
 pub use ser::{to_bytes, to_writer, Serializer};
+
+pub use raw::RawValue;
+pub use unrecognized::UnrecognizedTags;
@@ -0,0 +1,697 @@
+//! Finding likely mistakes in MSD documents.
+//!
+//! Where [`validate()`] reports deviations from the MSD format, [`lint()`] reports documents that
+//! are well-formed but probably do not mean what they were intended to mean, such as a URL whose
+//! unescaped `//` begins a comment, or a tag that is repeated by mistake. Each problem is reported
+//! as a [`Lint`] by one of a set of [`Rule`]s, which can be enabled, disabled, and configured
+//! through a [`Config`].
+//!
+//! Documents are read the same way the deserializer reads them when recovering from every
+//! deviation from the format, so linting never fails.
+//!
+//! # Example
+//! ```
+//! use msd::lint::{Config, Rule, Severity};
+//!
+//! let lints = msd::lint::lint(
+//!     b"#TITLE:Foo;\n#BANNER:http://example.com/banner.png;\n#TITLE:Bar;\n",
+//!     &Config::new(),
+//! );
+//!
+//! assert_eq!(lints.len(), 2);
+//! assert_eq!(lints[0].rule(), Rule::UnescapedComment);
+//! assert_eq!(lints[0].severity(), Severity::Error);
+//! assert_eq!(lints[1].rule(), Rule::DuplicateTag);
+//! assert_eq!(lints[1].position().line(), 2);
+//! ```
+//!
+//! [`validate()`]: crate::de::validate()
+
+mod rule;
+
+pub use rule::{Rule, Severity};
+
+use crate::{
+    de::{ParsedTag, Position},
+    format::normalize,
+    from_bytes,
+    lex::{Kind, Lexer, Token},
+    to_bytes, unrecognized, UnrecognizedTags,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, fmt::Display};
+use serde::{
+    de::{Deserialize, Deserializer, IgnoredAny, MapAccess, Visitor},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
+
+/// Configures which [`Rule`]s [`lint()`] checks, and how.
+///
+/// Every rule is enabled by default.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    disabled: Vec<Rule>,
+    required: Vec<String>,
+    repeatable: Vec<String>,
+    sections: Vec<String>,
+}
+
+impl Config {
+    /// Returns the default configuration.
+    ///
+    /// No tags are required, `#NOTES` tags may be repeated, and `#NOTEDATA` tags begin new
+    /// sections, matching the layout of StepMania's `.sm` and `.ssc` files.
+    pub fn new() -> Self {
+        Self {
+            disabled: Vec::new(),
+            required: Vec::new(),
+            repeatable: vec!["NOTES".to_string()],
+            sections: vec!["NOTEDATA".to_string()],
+        }
+    }
+
+    /// Enables `rule`.
+    pub fn enable(mut self, rule: Rule) -> Self {
+        self.disabled.retain(|&disabled| disabled != rule);
+        self
+    }
+
+    /// Disables `rule`, so that it reports no lints.
+    pub fn disable(mut self, rule: Rule) -> Self {
+        if self.is_enabled(rule) {
+            self.disabled.push(rule);
+        }
+        self
+    }
+
+    /// Returns whether `rule` is enabled.
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+
+    /// Requires a tag named `name` to be present and not empty, as checked by
+    /// [`Rule::EmptyRequiredTag`].
+    pub fn require(mut self, name: &str) -> Self {
+        self.required.push(name.to_string());
+        self
+    }
+
+    /// Allows tags named `name` to be repeated without being reported by [`Rule::DuplicateTag`].
+    pub fn allow_duplicates(mut self, name: &str) -> Self {
+        self.repeatable.push(name.to_string());
+        self
+    }
+
+    /// Makes each tag named `name` begin a new section, within which tags are checked for
+    /// duplicates separately by [`Rule::DuplicateTag`].
+    pub fn section(mut self, name: &str) -> Self {
+        self.sections.push(name.to_string());
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single problem found by [`lint()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    rule: Rule,
+    position: Position,
+    message: String,
+}
+
+impl Lint {
+    /// Returns the rule that reported the problem.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Returns the severity of the problem, as determined by its rule.
+    pub fn severity(&self) -> Severity {
+        self.rule.severity()
+    }
+
+    /// Returns the position in the input at which the problem was found.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns a description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} at line {} column {}",
+            self.message,
+            self.position.line(),
+            self.position.column()
+        )
+    }
+}
+
+/// A tag of the linted document.
+struct Tag<'a> {
+    /// The tag's tokens, from its `#` up to the next tag.
+    tokens: &'a [Token<'a>],
+    /// The bytes of the tag's tokens.
+    bytes: &'a [u8],
+    parsed: ParsedTag,
+}
+
+impl Tag<'_> {
+    fn name(&self) -> String {
+        format!("#{}", String::from_utf8_lossy(&self.parsed.name))
+    }
+
+    fn is_named(&self, names: &[String]) -> bool {
+        names
+            .iter()
+            .any(|name| name.as_bytes().eq_ignore_ascii_case(&self.parsed.name))
+    }
+}
+
+/// Collects the lints reported by enabled rules.
+struct Lints<'a> {
+    config: &'a Config,
+    lints: Vec<Lint>,
+}
+
+impl Lints<'_> {
+    fn push(&mut self, rule: Rule, position: Position, message: String) {
+        if self.config.is_enabled(rule) {
+            self.lints.push(Lint {
+                rule,
+                position,
+                message,
+            });
+        }
+    }
+}
+
+/// Checks an MSD document against the rules enabled by `config`, returning every problem found.
+///
+/// Lints are ordered by their positions.
+pub fn lint(input: &[u8], config: &Config) -> Vec<Lint> {
+    let tokens = Lexer::new(input).collect::<Vec<_>>();
+    let mut starts = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| token.kind() == Kind::TagStart)
+        .map(|(index, _)| index)
+        .peekable();
+    let mut tags = Vec::new();
+    while let Some(start) = starts.next() {
+        let end = starts.peek().copied().unwrap_or(tokens.len());
+        let tokens = &tokens[start..end];
+        let bytes = &input[tokens[0].span().start..tokens[tokens.len() - 1].span().end];
+        // Reading a single tag split by the lexer only fails on input the parser cannot recover
+        // from, which cannot be linted any further.
+        if let Ok(parsed) = ParsedTag::read(bytes, tokens[0].position()) {
+            tags.push(Tag {
+                tokens,
+                bytes,
+                parsed,
+            });
+        }
+    }
+
+    let mut lints = Lints {
+        config,
+        lints: Vec::new(),
+    };
+    empty_required_tags(&tags, &mut lints);
+    duplicate_tags(&tags, &mut lints);
+    for tag in &tags {
+        unescaped_comments(tag, &mut lints);
+        stray_whitespace(tag, &mut lints);
+        round_trip(tag, &mut lints);
+    }
+    mixed_line_endings(&tokens, &mut lints);
+
+    let mut lints = lints.lints;
+    lints.sort_by_key(|lint| (lint.position.line(), lint.position.column()));
+    lints
+}
+
+fn empty_required_tags(tags: &[Tag], lints: &mut Lints) {
+    for name in &lints.config.required {
+        let mut found = false;
+        for tag in tags
+            .iter()
+            .filter(|tag| tag.is_named(core::slice::from_ref(name)))
+        {
+            found = true;
+            if tag
+                .parsed
+                .parameter_lists
+                .iter()
+                .flatten()
+                .all(|parameter| normalize(parameter).is_empty())
+            {
+                lints.push(
+                    Rule::EmptyRequiredTag,
+                    tag.parsed.position,
+                    format!("required tag `{}` is empty", tag.name()),
+                );
+            }
+        }
+        if !found {
+            lints.push(
+                Rule::EmptyRequiredTag,
+                Position::new(0, 0),
+                format!("missing required tag `#{}`", name),
+            );
+        }
+    }
+}
+
+fn duplicate_tags(tags: &[Tag], lints: &mut Lints) {
+    let config = lints.config;
+    // The names of the tags in the current section, in uppercase.
+    let mut seen = Vec::<Vec<u8>>::new();
+    for tag in tags {
+        if tag.is_named(&config.sections) {
+            seen.clear();
+            continue;
+        }
+        if tag.is_named(&config.repeatable) {
+            continue;
+        }
+        let name = tag.parsed.name.to_ascii_uppercase();
+        if seen.contains(&name) {
+            lints.push(
+                Rule::DuplicateTag,
+                tag.parsed.position,
+                format!("duplicate tag `{}`", tag.name()),
+            );
+        } else {
+            seen.push(name);
+        }
+    }
+}
+
+fn unescaped_comments(tag: &Tag, lints: &mut Lints) {
+    for window in tag.tokens.windows(2) {
+        if window[1].kind() == Kind::Comment
+            && !matches!(
+                window[0].kind(),
+                Kind::Whitespace | Kind::Newline | Kind::Semicolon
+            )
+        {
+            lints.push(
+                Rule::UnescapedComment,
+                window[1].position(),
+                format!(
+                    "unescaped `//` in `{}` begins a comment, discarding the rest of the line",
+                    tag.name()
+                ),
+            );
+        }
+    }
+}
+
+fn stray_whitespace(tag: &Tag, lints: &mut Lints) {
+    // Each parameter is followed by the `:` or `;` ending it. The name, preceding the first `:`,
+    // is not a parameter.
+    let mut parameter_start = None;
+    for (index, token) in tag.tokens.iter().enumerate() {
+        if !matches!(token.kind(), Kind::Colon | Kind::Semicolon) {
+            continue;
+        }
+        if let Some(start) = parameter_start {
+            let parameter: &[Token] = &tag.tokens[start..index];
+            // A parameter made up only of whitespace is empty, rather than surrounded by
+            // whitespace.
+            let blank = parameter
+                .iter()
+                .all(|token| token.kind() == Kind::Whitespace);
+            let stray = match (parameter.first(), parameter.last()) {
+                _ if blank => None,
+                (Some(first), _) if first.kind() == Kind::Whitespace => Some(("leading", first)),
+                (_, Some(last)) if last.kind() == Kind::Whitespace => Some(("trailing", last)),
+                _ => None,
+            };
+            if let Some((location, token)) = stray {
+                lints.push(
+                    Rule::StrayWhitespace,
+                    token.position(),
+                    format!("{} whitespace in parameter of `{}`", location, tag.name()),
+                );
+            }
+        }
+        parameter_start = Some(index + 1);
+    }
+}
+
+fn mixed_line_endings(tokens: &[Token], lints: &mut Lints) {
+    let mut newlines = tokens.iter().filter(|token| token.kind() == Kind::Newline);
+    let first = match newlines.next() {
+        Some(first) => first.bytes(),
+        None => return,
+    };
+    if let Some(token) = newlines.find(|token| token.bytes() != first) {
+        let name = |line_ending: &[u8]| if line_ending == b"\n" { "LF" } else { "CRLF" };
+        lints.push(
+            Rule::MixedLineEndings,
+            token.position(),
+            format!(
+                "{} line ending in a document using {} line endings",
+                name(token.bytes()),
+                name(first)
+            ),
+        );
+    }
+}
+
+/// A `struct` made up only of the [`UnrecognizedTags`] field, capturing every tag of its input.
+struct Captured(UnrecognizedTags);
+
+/// A field of [`Captured`], which is either the [`UnrecognizedTags`] field or ignored.
+struct Field(bool);
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a field identifier")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(Field(value == unrecognized::FIELD))
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Captured {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CapturedVisitor;
+
+        impl<'de> Visitor<'de> for CapturedVisitor {
+            type Value = Captured;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("captured tags")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut tags = UnrecognizedTags::new();
+                while let Some(Field(unrecognized)) = map.next_key()? {
+                    if unrecognized {
+                        tags = map.next_value()?;
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(Captured(tags))
+            }
+        }
+
+        deserializer.deserialize_struct("Captured", &[unrecognized::FIELD], CapturedVisitor)
+    }
+}
+
+impl Serialize for Captured {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Captured", 1)?;
+        state.serialize_field(unrecognized::FIELD, &self.0)?;
+        state.end()
+    }
+}
+
+/// Captures the tags of `input` as [`UnrecognizedTags`] and writes them back, as the serializer
+/// would after deserializing them into a `struct`.
+fn capture_and_write(input: &[u8]) -> Option<Vec<u8>> {
+    to_bytes(&from_bytes::<Captured>(input).ok()?).ok()
+}
+
+fn round_trip(tag: &Tag, lints: &mut Lints) {
+    // The tag must mean the same thing after being written back, and writing it back again must
+    // not change it any further.
+    let survives = capture_and_write(tag.bytes).map_or(false, |written| {
+        let same = match ParsedTag::read(&written, tag.parsed.position) {
+            Ok(read) => {
+                read.name == tag.parsed.name && read.parameter_lists == tag.parsed.parameter_lists
+            }
+            Err(_) => false,
+        };
+        same && capture_and_write(&written).as_ref() == Some(&written)
+    });
+    if !survives {
+        lints.push(
+            Rule::RoundTrip,
+            tag.parsed.position,
+            format!(
+                "`{}` would be read differently after being serialized",
+                tag.name()
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, Config, Lint, Rule};
+    use crate::de::Position;
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    fn rules(input: &[u8], config: &Config) -> Vec<(Rule, Position)> {
+        lint(input, config)
+            .into_iter()
+            .map(|lint| (lint.rule(), lint.position()))
+            .collect()
+    }
+
+    #[test]
+    fn no_lints() {
+        assert_eq!(
+            lint(
+                b"// header\n#TITLE:Foo;\n#NOTES:\n     dance-single:\n     0000 // measure 1\n;\n#NOTES:\n     dance-double:\n;\n",
+                &Config::new()
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn missing_required_tag() {
+        assert_eq!(
+            lint(b"#TITLE:Foo;\n", &Config::new().require("ARTIST")),
+            vec![Lint {
+                rule: Rule::EmptyRequiredTag,
+                position: Position::new(0, 0),
+                message: "missing required tag `#ARTIST`".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_required_tag() {
+        assert_eq!(
+            lint(
+                b"#TITLE:Foo;\n#artist:  ;\n",
+                &Config::new().require("TITLE").require("ARTIST")
+            ),
+            vec![Lint {
+                rule: Rule::EmptyRequiredTag,
+                position: Position::new(1, 0),
+                message: "required tag `#artist` is empty".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicate_tag() {
+        assert_eq!(
+            rules(b"#TITLE:Foo;\n#ARTIST:Bar;\n#title:Baz;\n", &Config::new()),
+            vec![(Rule::DuplicateTag, Position::new(2, 0))]
+        );
+    }
+
+    #[test]
+    fn duplicate_tag_allowed() {
+        assert_eq!(
+            rules(
+                b"#BGCHANGES:a;\n#BGCHANGES:b;\n",
+                &Config::new().allow_duplicates("BGCHANGES")
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn duplicate_tag_in_sections() {
+        assert_eq!(
+            rules(
+                b"#TITLE:Foo;\n#NOTEDATA:;\n#METER:1;\n#NOTEDATA:;\n#METER:2;\n#METER:3;\n",
+                &Config::new()
+            ),
+            vec![(Rule::DuplicateTag, Position::new(5, 0))]
+        );
+    }
+
+    #[test]
+    fn unescaped_comment() {
+        assert_eq!(
+            lint(b"#BANNER:http://example.com;\n", &Config::new()),
+            vec![Lint {
+                rule: Rule::UnescapedComment,
+                position: Position::new(0, 13),
+                message:
+                    "unescaped `//` in `#BANNER` begins a comment, discarding the rest of the line"
+                        .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn separated_comment() {
+        assert_eq!(
+            rules(b"#TITLE:Foo // comment\n;// comment\n", &Config::new()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn stray_whitespace() {
+        assert_eq!(
+            lint(b"#OFFSET: -0.5;\n#BPMS:0=120\t;\n", &Config::new()),
+            vec![
+                Lint {
+                    rule: Rule::StrayWhitespace,
+                    position: Position::new(0, 8),
+                    message: "leading whitespace in parameter of `#OFFSET`".to_string(),
+                },
+                Lint {
+                    rule: Rule::StrayWhitespace,
+                    position: Position::new(1, 11),
+                    message: "trailing whitespace in parameter of `#BPMS`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn whitespace_around_lines_allowed() {
+        assert_eq!(
+            rules(
+                b"#BPMS:\n   0=120,\n   4=60\n;\n# TITLE :Foo\\ ;\n",
+                &Config::new()
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        assert_eq!(
+            lint(
+                b"#TITLE:Foo;\r\n#ARTIST:Bar;\n#BPMS:0=120;\n",
+                &Config::new()
+            ),
+            vec![Lint {
+                rule: Rule::MixedLineEndings,
+                position: Position::new(1, 12),
+                message: "LF line ending in a document using CRLF line endings".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(
+            lint(b"#TITLE:Foo;\n#ARTIST:Bar\\", &Config::new()),
+            vec![Lint {
+                rule: Rule::RoundTrip,
+                position: Position::new(1, 0),
+                message: "`#ARTIST` would be read differently after being serialized".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn round_trip_unterminated() {
+        assert_eq!(
+            rules(b"#TITLE:Foo\n#ARTIST:Bar // comment\n", &Config::new()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn round_trip_trailing_comment() {
+        assert_eq!(
+            rules(
+                b"#TITLE:Foo; // note\n#ARTIST:Bar;\t// other\n// last\n",
+                &Config::new()
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn disabled_rule() {
+        assert_eq!(
+            rules(
+                b"#TITLE:Foo;\n#TITLE: Bar;\n",
+                &Config::new().disable(Rule::DuplicateTag)
+            ),
+            vec![(Rule::StrayWhitespace, Position::new(1, 7))]
+        );
+    }
+
+    #[test]
+    fn enabled_rule() {
+        let config = Config::new()
+            .disable(Rule::DuplicateTag)
+            .enable(Rule::DuplicateTag);
+
+        assert!(config.is_enabled(Rule::DuplicateTag));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!(
+                "{}",
+                Lint {
+                    rule: Rule::DuplicateTag,
+                    position: Position::new(1, 0),
+                    message: "duplicate tag `#TITLE`".to_string(),
+                }
+            ),
+            "duplicate tag `#TITLE` at line 1 column 0"
+        );
+    }
+}
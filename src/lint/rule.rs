@@ -0,0 +1,138 @@
+use core::{fmt, fmt::Display};
+
+/// How severe a [`Lint`] is.
+///
+/// [`Lint`]: crate::lint::Lint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The document is likely not read the way it was intended to be.
+    Error,
+    /// The document is read as intended, but is fragile or inconsistent.
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => formatter.write_str("error"),
+            Severity::Warning => formatter.write_str("warning"),
+        }
+    }
+}
+
+/// A check performed by [`lint()`].
+///
+/// [`lint()`]: crate::lint::lint()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A required tag is missing, or has no parameters that are not empty.
+    ///
+    /// Which tags are required is configured with [`Config::require()`].
+    ///
+    /// [`Config::require()`]: crate::lint::Config::require()
+    EmptyRequiredTag,
+    /// A tag has the same name as a previous tag.
+    ///
+    /// Tags are compared ignoring ASCII case. Tags that are expected to repeat can be allowed with
+    /// [`Config::allow_duplicates()`], and documents can be split into sections within which tags
+    /// are compared with [`Config::section()`].
+    ///
+    /// [`Config::allow_duplicates()`]: crate::lint::Config::allow_duplicates()
+    /// [`Config::section()`]: crate::lint::Config::section()
+    DuplicateTag,
+    /// A `//` directly following the content of a tag begins a comment, discarding the rest of the
+    /// line.
+    ///
+    /// This is usually an unescaped `//` within a value, such as in a URL. Comments separated
+    /// from the content before them by whitespace are assumed to be intentional.
+    UnescapedComment,
+    /// A parameter begins or ends with spaces or tabs.
+    ///
+    /// The whitespace is kept when the parameter is read as a string, but silently dropped when
+    /// it is read as a number, `bool`, or `char`. Parameters beginning or ending with a line
+    /// ending are assumed to be laid out across multiple lines intentionally.
+    StrayWhitespace,
+    /// A line ending differs from the document's first line ending.
+    MixedLineEndings,
+    /// A tag would be read differently after being captured and written back by the serializer,
+    /// as it is by [`RawValue`] or [`UnrecognizedTags`].
+    ///
+    /// This happens to unterminated tags ending in a `\`, which escapes the `;` written after it.
+    ///
+    /// [`RawValue`]: crate::RawValue
+    /// [`UnrecognizedTags`]: crate::UnrecognizedTags
+    RoundTrip,
+}
+
+impl Rule {
+    /// Every rule, in the order they are documented.
+    pub const ALL: [Rule; 6] = [
+        Rule::EmptyRequiredTag,
+        Rule::DuplicateTag,
+        Rule::UnescapedComment,
+        Rule::StrayWhitespace,
+        Rule::MixedLineEndings,
+        Rule::RoundTrip,
+    ];
+
+    /// Returns the rule's identifier, as used to configure it.
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::EmptyRequiredTag => "empty-required-tag",
+            Rule::DuplicateTag => "duplicate-tag",
+            Rule::UnescapedComment => "unescaped-comment",
+            Rule::StrayWhitespace => "stray-whitespace",
+            Rule::MixedLineEndings => "mixed-line-endings",
+            Rule::RoundTrip => "round-trip",
+        }
+    }
+
+    /// Returns the rule with the given identifier, if there is one.
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|rule| rule.id() == id)
+    }
+
+    /// Returns the severity of the lints reported by the rule.
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::EmptyRequiredTag | Rule::UnescapedComment | Rule::RoundTrip => Severity::Error,
+            Rule::DuplicateTag | Rule::StrayWhitespace | Rule::MixedLineEndings => {
+                Severity::Warning
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rule, Severity};
+    use claims::{assert_none, assert_some_eq};
+
+    #[test]
+    fn from_id() {
+        for rule in Rule::ALL {
+            assert_some_eq!(Rule::from_id(rule.id()), rule);
+        }
+    }
+
+    #[test]
+    fn from_unknown_id() {
+        assert_none!(Rule::from_id("unknown"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", Rule::StrayWhitespace), "stray-whitespace");
+    }
+
+    #[test]
+    fn severity_display() {
+        assert_eq!(format!("{}", Severity::Warning), "warning");
+    }
+}
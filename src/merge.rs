@@ -0,0 +1,567 @@
+//! Merging concurrent edits to an MSD document tag by tag.
+//!
+//! [`merge()`] performs a three-way merge of two [`Document`]s that were both edited from a common
+//! base. Rather than merging lines of text, which can interleave the lines of unrelated edits to
+//! a multi-line tag such as a chart's note data, tags are paired across the documents the same way
+//! [`diff()`] pairs them, and each tag is merged as a whole:
+//!
+//! - A tag changed or removed on only one side takes that side's version.
+//! - A tag changed the same way on both sides takes that version once.
+//! - A tag added on either side is kept. Tags added on both sides are only kept once if they are
+//!   the same.
+//! - A tag changed differently on both sides, changed on one side and removed on the other, or
+//!   added with different parameters on both sides under a name the base does not use, is a
+//!   conflict.
+//!
+//! Tags are compared the same way [`diff()`] compares them, so differences in formatting alone are
+//! never a conflict. Each merged tag is written exactly as it appears in the document it was taken
+//! from, and each conflicting tag is written with both versions surrounded by conflict markers,
+//! leaving every other tag merged.
+//!
+//! Tags are written in the order they appear in `ours`, with tags added in `theirs` following the
+//! tag that precedes them there. Where both sides add tags at the same place, those added in
+//! `ours` are written first.
+//!
+//! # Example
+//! ```
+//! use msd::{
+//!     de::Options,
+//!     merge::Document,
+//! };
+//!
+//! let base = Document::parse(b"#TITLE:Foo;\n#ARTIST:Bar;\n", Options::new()).unwrap();
+//! let ours = Document::parse(b"#TITLE:Baz;\n#ARTIST:Bar;\n", Options::new()).unwrap();
+//! let theirs = Document::parse(b"#TITLE:Foo;\n#ARTIST:Qux;\n", Options::new()).unwrap();
+//!
+//! let merged = msd::merge::merge(&base, &ours, &theirs);
+//!
+//! assert!(merged.is_clean());
+//! assert_eq!(merged.bytes(), b"#TITLE:Baz;\n#ARTIST:Qux;\n");
+//! ```
+//!
+//! [`diff()`]: crate::diff::diff()
+
+use crate::{
+    cst,
+    de::{Options, ParsedTag, ParsedTags, Position, Result},
+    diff::{self, pair},
+};
+use alloc::{vec, vec::Vec};
+
+/// The marker beginning a conflict, followed by the version from `ours`.
+const OURS_MARKER: &[u8] = b"<<<<<<< ours";
+/// The marker separating the two versions of a conflict.
+const SEPARATOR_MARKER: &[u8] = b"=======";
+/// The marker ending a conflict, preceded by the version from `theirs`.
+const THEIRS_MARKER: &[u8] = b">>>>>>> theirs";
+
+/// An MSD document, read for merging.
+///
+/// Each tag is kept exactly as it appears in the input, so that merged tags can be written back
+/// out unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    leading: Vec<u8>,
+    line_ending: &'static [u8],
+    tags: Vec<diff::Tag>,
+    sources: Vec<cst::Tag>,
+}
+
+impl Document {
+    /// Parses a document according to `options`.
+    pub fn parse(input: &[u8], options: Options) -> Result<Self> {
+        // The whole document is parsed first so that any errors are reported as they would be
+        // when deserializing it.
+        for tag in ParsedTags::with_options(input, options) {
+            tag?;
+        }
+
+        let document = cst::Document::parse(input);
+        let mut position = advance(Position::new(0, 0), document.leading());
+        let mut tags = Vec::with_capacity(document.len());
+        for source in document.tags() {
+            let tag = ParsedTag::read(source.as_bytes(), position)?;
+            tags.push(diff::Tag::from_parsed(tag));
+            position = advance(position, source.as_bytes());
+        }
+
+        Ok(Self {
+            leading: document.leading().to_vec(),
+            line_ending: document.line_ending(),
+            tags,
+            sources: document.tags().to_vec(),
+        })
+    }
+}
+
+/// Returns the position following `bytes`, when they begin at `position`.
+fn advance(mut position: Position, bytes: &[u8]) -> Position {
+    for &byte in bytes {
+        position = if byte == b'\n' {
+            position.increment_line()
+        } else {
+            position.increment_column()
+        };
+    }
+    position
+}
+
+/// A tag whose versions could not be merged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    position: Position,
+    name: Vec<u8>,
+}
+
+impl Conflict {
+    /// Returns the position of the conflict's opening marker within the merged document.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the name of the conflicting tag.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+}
+
+/// The result of a [`merge()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Merged {
+    bytes: Vec<u8>,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merged {
+    /// Returns the merged document, including the markers of any conflicts.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the merged document, including the markers of any conflicts.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the conflicts within the merged document, in order.
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Returns whether the documents were merged without any conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// An element of the merged document.
+enum Item<'a> {
+    Tag(&'a cst::Tag),
+    Conflict {
+        name: &'a [u8],
+        ours: Option<&'a cst::Tag>,
+        theirs: Option<&'a cst::Tag>,
+    },
+}
+
+/// Writes the elements of the merged document.
+struct Writer {
+    bytes: Vec<u8>,
+    line_ending: &'static [u8],
+    conflicts: Vec<Conflict>,
+    /// Whether the last bytes written were a tag, which must be ended before anything follows it.
+    after_tag: bool,
+}
+
+impl Writer {
+    fn end_line(&mut self) {
+        if !self.bytes.is_empty() && !self.bytes.ends_with(b"\n") {
+            self.bytes.extend_from_slice(self.line_ending);
+        }
+    }
+
+    fn line(&mut self, line: &[u8]) {
+        self.bytes.extend_from_slice(line);
+        self.bytes.extend_from_slice(self.line_ending);
+    }
+
+    fn tag(&mut self, tag: &cst::Tag) {
+        if self.after_tag {
+            self.end_line();
+        }
+        self.bytes.extend_from_slice(tag.as_bytes());
+        self.after_tag = true;
+    }
+
+    fn conflict(&mut self, name: &[u8], ours: Option<&cst::Tag>, theirs: Option<&cst::Tag>) {
+        self.end_line();
+        self.conflicts.push(Conflict {
+            position: advance(Position::new(0, 0), &self.bytes),
+            name: name.to_vec(),
+        });
+        self.line(OURS_MARKER);
+        if let Some(ours) = ours {
+            self.bytes.extend_from_slice(ours.as_bytes());
+            self.end_line();
+        }
+        self.line(SEPARATOR_MARKER);
+        if let Some(theirs) = theirs {
+            self.bytes.extend_from_slice(theirs.as_bytes());
+            self.end_line();
+        }
+        self.line(THEIRS_MARKER);
+        self.after_tag = false;
+    }
+
+    fn item(&mut self, item: &Item) {
+        match *item {
+            Item::Tag(tag) => self.tag(tag),
+            Item::Conflict { name, ours, theirs } => self.conflict(name, ours, theirs),
+        }
+    }
+}
+
+/// Merges the changes made to `base` in both `ours` and `theirs`.
+///
+/// See the [module documentation](self) for how each tag is merged. The merged document uses the
+/// content preceding the first tag and the line endings of `ours`, unless only `theirs` changed
+/// the content preceding the first tag.
+pub fn merge(base: &Document, ours: &Document, theirs: &Document) -> Merged {
+    let base_ours = pair(&base.tags, &ours.tags);
+    let base_theirs = pair(&base.tags, &theirs.tags);
+
+    // Tags added on both sides are paired with each other, first with tags that are the same and
+    // then with tags sharing a name the base does not use.
+    let mut ours_added = vec![None; ours.tags.len()];
+    let mut theirs_added = vec![None; theirs.tags.len()];
+    let in_base = |name: &[u8]| base.tags.iter().any(|tag| tag.name() == name);
+    for exact in [true, false] {
+        let same = |ours: &diff::Tag, theirs: &diff::Tag| {
+            if exact {
+                ours.same_as(theirs)
+            } else {
+                ours.name() == theirs.name() && !in_base(ours.name())
+            }
+        };
+        for j in (0..ours.tags.len()).filter(|&j| base_ours.new[j].is_none()) {
+            if ours_added[j].is_some() {
+                continue;
+            }
+            if let Some(k) = (0..theirs.tags.len()).find(|&k| {
+                base_theirs.new[k].is_none()
+                    && theirs_added[k].is_none()
+                    && same(&ours.tags[j], &theirs.tags[k])
+            }) {
+                ours_added[j] = Some(k);
+                theirs_added[k] = Some(j);
+            }
+        }
+    }
+
+    let mut items = Vec::with_capacity(ours.tags.len());
+    for (j, source) in ours.sources.iter().enumerate() {
+        let name = ours.tags[j].name();
+        let item = match base_ours.new[j] {
+            Some(i) => {
+                let ours_changed = !base.tags[i].same_as(&ours.tags[j]);
+                match base_theirs.old[i] {
+                    Some(k) if !base.tags[i].same_as(&theirs.tags[k]) => {
+                        if !ours_changed || ours.tags[j].same_as(&theirs.tags[k]) {
+                            Some(Item::Tag(&theirs.sources[k]))
+                        } else {
+                            Some(Item::Conflict {
+                                name,
+                                ours: Some(source),
+                                theirs: Some(&theirs.sources[k]),
+                            })
+                        }
+                    }
+                    Some(_) => Some(Item::Tag(source)),
+                    None if ours_changed => Some(Item::Conflict {
+                        name,
+                        ours: Some(source),
+                        theirs: None,
+                    }),
+                    // Removed in `theirs`.
+                    None => None,
+                }
+            }
+            None => match ours_added[j] {
+                Some(k) if !ours.tags[j].same_as(&theirs.tags[k]) => Some(Item::Conflict {
+                    name,
+                    ours: Some(source),
+                    theirs: Some(&theirs.sources[k]),
+                }),
+                _ => Some(Item::Tag(source)),
+            },
+        };
+        items.push(item);
+    }
+
+    // The tags of `theirs` that are not already merged are inserted after the tag preceding them
+    // in `theirs`, with `following[j]` holding the tags inserted after the tag of `ours` at `j - 1`.
+    let theirs_in_ours = (0..theirs.tags.len())
+        .map(|k| match base_theirs.new[k] {
+            Some(i) => base_ours.old[i],
+            None => theirs_added[k],
+        })
+        .collect::<Vec<_>>();
+    let mut following = (0..=ours.tags.len())
+        .map(|_| Vec::new())
+        .collect::<Vec<_>>();
+    for (k, source) in theirs.sources.iter().enumerate() {
+        let item = match base_theirs.new[k] {
+            Some(i) if base_ours.old[i].is_none() && !base.tags[i].same_as(&theirs.tags[k]) => {
+                Item::Conflict {
+                    name: theirs.tags[k].name(),
+                    ours: None,
+                    theirs: Some(source),
+                }
+            }
+            None if theirs_added[k].is_none() => Item::Tag(source),
+            _ => continue,
+        };
+        let mut index = (0..k)
+            .rev()
+            .find_map(|k| theirs_in_ours[k])
+            .map_or(0, |j| j + 1);
+        // Tags added at the same place in both documents are written with those of `ours` first.
+        while index < ours.tags.len()
+            && base_ours.new[index].is_none()
+            && ours_added[index].is_none()
+        {
+            index += 1;
+        }
+        following[index].push(item);
+    }
+
+    let ours_changed_leading = ours.leading != base.leading;
+    let mut writer = Writer {
+        bytes: if ours_changed_leading {
+            ours.leading.clone()
+        } else {
+            theirs.leading.clone()
+        },
+        line_ending: ours.line_ending,
+        conflicts: Vec::new(),
+        after_tag: false,
+    };
+    for (j, following) in following.iter().enumerate() {
+        if j > 0 {
+            if let Some(item) = &items[j - 1] {
+                writer.item(item);
+            }
+        }
+        for item in following {
+            writer.item(item);
+        }
+    }
+
+    Merged {
+        bytes: writer.bytes,
+        conflicts: writer.conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, Conflict, Document};
+    use crate::de::{Options, Position};
+    use alloc::vec;
+    use claims::{assert_err, assert_ok};
+
+    fn document(input: &[u8]) -> Document {
+        assert_ok!(Document::parse(input, Options::new()))
+    }
+
+    fn merged(base: &[u8], ours: &[u8], theirs: &[u8]) -> (Vec<u8>, Vec<Conflict>) {
+        let merged = merge(&document(base), &document(ours), &document(theirs));
+        (merged.bytes().to_vec(), merged.conflicts().to_vec())
+    }
+
+    #[test]
+    fn unchanged() {
+        let input = b"// header\n#TITLE:Foo;\n#ARTIST:Bar; // comment\n";
+
+        assert_eq!(merged(input, input, input), (input.to_vec(), vec![]));
+    }
+
+    #[test]
+    fn changes_to_different_tags() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Baz;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n#ARTIST:Qux;\n"
+            ),
+            (b"#TITLE:Baz;\n#ARTIST:Qux;\n".to_vec(), vec![])
+        );
+    }
+
+    #[test]
+    fn changes_to_adjacent_charts() {
+        let base = b"#NOTES:\n     Easy:\n     0000\n;\n#NOTES:\n     Hard:\n     0000\n;\n";
+        let ours = b"#NOTES:\n     Easy:\n     1000\n;\n#NOTES:\n     Hard:\n     0000\n;\n";
+        let theirs = b"#NOTES:\n     Easy:\n     0000\n;\n#NOTES:\n     Hard:\n     0001\n;\n";
+
+        assert_eq!(
+            merged(base, ours, theirs),
+            (
+                b"#NOTES:\n     Easy:\n     1000\n;\n#NOTES:\n     Hard:\n     0001\n;\n".to_vec(),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn same_change_on_both_sides() {
+        assert_eq!(
+            merged(b"#TITLE:Foo;\n", b"#TITLE:Bar;\n", b"#TITLE:  Bar;\n"),
+            (b"#TITLE:  Bar;\n".to_vec(), vec![])
+        );
+    }
+
+    #[test]
+    fn formatting_change_is_not_a_conflict() {
+        assert_eq!(
+            merged(b"#TITLE:Foo;\n", b"#TITLE:  Foo  ;\n", b"#TITLE:Bar;\n"),
+            (b"#TITLE:Bar;\n".to_vec(), vec![])
+        );
+    }
+
+    #[test]
+    fn removed_on_one_side() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n",
+                b"#TITLE:Baz;\n#ARTIST:Bar;\n"
+            ),
+            (b"#TITLE:Baz;\n".to_vec(), vec![])
+        );
+    }
+
+    #[test]
+    fn added_on_both_sides() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n#SUBTITLE:Baz;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n#CREDIT:Qux;"
+            ),
+            (
+                b"#TITLE:Foo;\n#SUBTITLE:Baz;\n#ARTIST:Bar;\n#CREDIT:Qux;".to_vec(),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn same_tag_added_on_both_sides() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n",
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#ARTIST:Bar;\n#TITLE:Foo;\n"
+            ),
+            (b"#TITLE:Foo;\n#ARTIST:Bar;\n".to_vec(), vec![])
+        );
+    }
+
+    #[test]
+    fn charts_added_on_both_sides() {
+        assert_eq!(
+            merged(
+                b"#NOTES:Easy:0000;\n",
+                b"#NOTES:Easy:0000;\n#NOTES:Hard:1000;\n",
+                b"#NOTES:Easy:0000;\n#NOTES:Medium:0100;\n"
+            ),
+            (
+                b"#NOTES:Easy:0000;\n#NOTES:Hard:1000;\n#NOTES:Medium:0100;\n".to_vec(),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn conflicting_changes() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Baz;\n#ARTIST:Bar;\n",
+                b"#TITLE:Qux;\n#ARTIST:Quux;\n"
+            ),
+            (
+                b"<<<<<<< ours\n#TITLE:Baz;\n=======\n#TITLE:Qux;\n>>>>>>> theirs\n#ARTIST:Quux;\n"
+                    .to_vec(),
+                vec![Conflict {
+                    position: Position::new(0, 0),
+                    name: b"TITLE".to_vec(),
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn changed_and_removed() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n",
+                b"#TITLE:Foo;\n#ARTIST:Baz;"
+            ),
+            (
+                b"#TITLE:Foo;\n<<<<<<< ours\n=======\n#ARTIST:Baz;\n>>>>>>> theirs\n".to_vec(),
+                vec![Conflict {
+                    position: Position::new(1, 0),
+                    name: b"ARTIST".to_vec(),
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn conflicting_additions() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n",
+                b"#TITLE:Foo;\n#ARTIST:Bar;\n",
+                b"#TITLE:Foo;\n#ARTIST:Baz;\n"
+            ),
+            (
+                b"#TITLE:Foo;\n<<<<<<< ours\n#ARTIST:Bar;\n=======\n#ARTIST:Baz;\n>>>>>>> theirs\n"
+                    .to_vec(),
+                vec![Conflict {
+                    position: Position::new(1, 0),
+                    name: b"ARTIST".to_vec(),
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn line_endings_of_ours() {
+        assert_eq!(
+            merged(b"#TITLE:Foo;\n", b"#TITLE:Bar;\r\n", b"#TITLE:Baz;\n").0,
+            b"<<<<<<< ours\r\n#TITLE:Bar;\r\n=======\r\n#TITLE:Baz;\n>>>>>>> theirs\r\n"
+        );
+    }
+
+    #[test]
+    fn leading_changed_in_theirs() {
+        assert_eq!(
+            merged(
+                b"#TITLE:Foo;\n",
+                b"#TITLE:Foo;\n",
+                b"// header\n#TITLE:Foo;\n"
+            )
+            .0,
+            b"// header\n#TITLE:Foo;\n"
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_err!(Document::parse(b"foo\n#TITLE:Foo;\n", Options::new()));
+    }
+}
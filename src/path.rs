@@ -0,0 +1,66 @@
+use alloc::string::String;
+use core::{fmt, fmt::Display};
+
+/// A single step along the path to the location of an error.
+///
+/// This is shared by [`de::Error`] and [`ser::Error`], and is re-exported as `PathSegment` from
+/// both modules.
+///
+/// [`de::Error`]: crate::de::Error
+/// [`ser::Error`]: crate::ser::Error
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// A tag, identified by its name. This is the name of either a `struct` field or a top-level
+    /// `map` key.
+    Tag(String),
+    /// The zero-indexed position of a tag within a sequence of tags.
+    Element(usize),
+    /// The zero-indexed position of a `;`-separated parameter list within a tag.
+    ParameterList(usize),
+    /// The zero-indexed position of a `:`-separated parameter within a parameter list.
+    Parameter(usize),
+    /// The key of a `map` entry within a parameter list.
+    Key(String),
+}
+
+impl Display for Segment {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Segment::Tag(name) => write!(formatter, "tag `{}`", name),
+            Segment::Element(index) => write!(formatter, "element {}", index),
+            Segment::ParameterList(index) => write!(formatter, "parameter list {}", index),
+            Segment::Parameter(index) => write!(formatter, "parameter {}", index),
+            Segment::Key(key) => write!(formatter, "key `{}`", key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Segment;
+
+    #[test]
+    fn display_tag() {
+        assert_eq!(format!("{}", Segment::Tag("foo".to_owned())), "tag `foo`");
+    }
+
+    #[test]
+    fn display_element() {
+        assert_eq!(format!("{}", Segment::Element(1)), "element 1");
+    }
+
+    #[test]
+    fn display_parameter_list() {
+        assert_eq!(format!("{}", Segment::ParameterList(2)), "parameter list 2");
+    }
+
+    #[test]
+    fn display_parameter() {
+        assert_eq!(format!("{}", Segment::Parameter(3)), "parameter 3");
+    }
+
+    #[test]
+    fn display_key() {
+        assert_eq!(format!("{}", Segment::Key("bar".to_owned())), "key `bar`");
+    }
+}
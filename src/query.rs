@@ -0,0 +1,343 @@
+//! Selecting tags from MSD documents.
+//!
+//! [`select()`] streams through a document, returning the tags matched by any of a list of
+//! [`Selector`]s. Only the names of tags are read until one matches, so no type needs to be
+//! deserialized and the values of unselected tags are never unescaped.
+//!
+//! # Example
+//! ```
+//! use msd::{de::Options, query::Selector};
+//!
+//! let input = b"#TITLE:Foo;\n#ARTIST:Bar;\n#NOTES:dance-single:desc:Beginner:1:0,0,0,0,0:\n0000\n;\n";
+//! let selectors = [Selector::new("title"), Selector::new("NOTES").parameter(2)];
+//!
+//! let selections = msd::query::select(input.as_slice(), &selectors, Options::new())
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//!
+//! assert_eq!(selections.len(), 2);
+//! assert_eq!(selections[0].parameters(), [b"Foo".to_vec()]);
+//! assert_eq!(selections[1].parameters(), [b"Beginner".to_vec()]);
+//! ```
+
+use crate::{
+    de::{Options, ParsedTag, ParsedTags, Position, Result},
+    io::Read,
+};
+use alloc::{
+    collections::VecDeque,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Returns whether `name` matches the glob `pattern`, ignoring ASCII case.
+///
+/// Within the pattern, `*` matches any sequence of bytes and `?` matches any single byte.
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    let mut pattern_index = 0;
+    let mut name_index = 0;
+    // The position of the most recent `*` and the position in `name` it was last tried against,
+    // which are returned to when the rest of the pattern fails to match.
+    let mut backtrack = None;
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some(b'*') => {
+                backtrack = Some((pattern_index, name_index));
+                pattern_index += 1;
+                continue;
+            }
+            Some(b'?') => {
+                pattern_index += 1;
+                name_index += 1;
+                continue;
+            }
+            Some(byte) if byte.eq_ignore_ascii_case(&name[name_index]) => {
+                pattern_index += 1;
+                name_index += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((star_index, star_name_index)) => {
+                pattern_index = star_index + 1;
+                name_index = star_name_index + 1;
+                backtrack = Some((star_index, name_index));
+            }
+            None => return false,
+        }
+    }
+    pattern[pattern_index..].iter().all(|&byte| byte == b'*')
+}
+
+/// Describes which tags to select, and which of their parameters.
+///
+/// Tags are selected by their names, which are matched against a glob pattern ignoring ASCII case.
+/// Within the pattern, `*` matches any sequence of bytes and `?` matches any single byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    pattern: String,
+    parameter: Option<usize>,
+}
+
+impl Selector {
+    /// Creates a selector for every parameter of the tags whose names match `pattern`.
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            parameter: None,
+        }
+    }
+
+    /// Selects only the parameter at the zero-indexed `index`.
+    ///
+    /// Parameters are indexed in the order they appear after the tag's name, across all of the
+    /// tag's parameter lists. Tags without a parameter at `index` are not selected.
+    #[must_use]
+    pub fn parameter(mut self, index: usize) -> Self {
+        self.parameter = Some(index);
+        self
+    }
+
+    /// Returns whether the selector matches a tag named `name`.
+    pub fn matches(&self, name: &[u8]) -> bool {
+        glob_matches(self.pattern.as_bytes(), name)
+    }
+
+    fn select(&self, tag: &ParsedTag) -> Option<Selection> {
+        if !self.matches(&tag.name) {
+            return None;
+        }
+        let parameters = tag.parameter_lists.iter().flatten();
+        Some(Selection {
+            position: tag.position,
+            name: tag.name.clone(),
+            parameters: match self.parameter {
+                Some(index) => vec![parameters.clone().nth(index)?.clone()],
+                None => parameters.cloned().collect(),
+            },
+        })
+    }
+}
+
+/// A tag selected by a [`Selector`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    position: Position,
+    name: Vec<u8>,
+    parameters: Vec<Vec<u8>>,
+}
+
+impl Selection {
+    /// Returns the position of the tag's `#`.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the tag's name.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// Returns the selected parameters, unescaped and stripped of comments.
+    ///
+    /// Surrounding whitespace is retained.
+    pub fn parameters(&self) -> &[Vec<u8>] {
+        &self.parameters
+    }
+}
+
+/// An iterator over the tags selected from a document.
+///
+/// This `struct` is created by [`select()`]. A tag matched by more than one selector is returned
+/// once for each of them, in the order of the selectors. Iteration ends after the first error.
+pub struct Selections<'a, R> {
+    tags: ParsedTags<R>,
+    selectors: &'a [Selector],
+    pending: VecDeque<Selection>,
+}
+
+impl<R> Iterator for Selections<'_, R>
+where
+    R: Read,
+{
+    type Item = Result<Selection>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(selection) = self.pending.pop_front() {
+                return Some(Ok(selection));
+            }
+            let selectors = self.selectors;
+            let tag = match self
+                .tags
+                .next_matching(|name| selectors.iter().any(|selector| selector.matches(name)))?
+            {
+                Ok(tag) => tag,
+                Err(error) => return Some(Err(error)),
+            };
+            self.pending.extend(
+                selectors
+                    .iter()
+                    .filter_map(|selector| selector.select(&tag)),
+            );
+        }
+    }
+}
+
+/// Selects tags from the MSD document read from `reader`.
+///
+/// The document is parsed according to `options` as the selections are iterated.
+pub fn select<R>(reader: R, selectors: &[Selector], options: Options) -> Selections<'_, R>
+where
+    R: Read,
+{
+    Selections {
+        tags: ParsedTags::with_options(reader, options),
+        selectors,
+        pending: VecDeque::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_matches, select, Selection, Selector};
+    use crate::de::{ErrorKind, Options, Position};
+    use alloc::{vec, vec::Vec};
+    use claims::{assert_err, assert_matches, assert_none, assert_ok_eq, assert_some};
+
+    #[test]
+    fn glob_literal() {
+        assert!(glob_matches(b"TITLE", b"TITLE"));
+        assert!(!glob_matches(b"TITLE", b"SUBTITLE"));
+        assert!(!glob_matches(b"TITLE", b"TITLETRANSLIT"));
+    }
+
+    #[test]
+    fn glob_ignores_case() {
+        assert!(glob_matches(b"title", b"TiTlE"));
+    }
+
+    #[test]
+    fn glob_star() {
+        assert!(glob_matches(b"*TITLE*", b"SUBTITLETRANSLIT"));
+        assert!(glob_matches(b"TITLE*", b"TITLE"));
+        assert!(glob_matches(b"*", b""));
+        assert!(glob_matches(b"A*B*C", b"AXBYBZC"));
+        assert!(!glob_matches(b"A*B*C", b"AXBYBZ"));
+    }
+
+    #[test]
+    fn glob_question_mark() {
+        assert!(glob_matches(b"B?M?", b"BPMS"));
+        assert!(!glob_matches(b"B?M?", b"BPM"));
+    }
+
+    #[test]
+    fn select_tags() {
+        let selectors = [Selector::new("TITLE"), Selector::new("BPMS")];
+        let mut selections = select(
+            b"#TITLE:Foo;\n#ARTIST:Bar;\n#BPMS:\n   0:120;\n   4:60;\n".as_slice(),
+            &selectors,
+            Options::new(),
+        );
+
+        assert_ok_eq!(
+            assert_some!(selections.next()),
+            Selection {
+                position: Position::new(0, 0),
+                name: b"TITLE".to_vec(),
+                parameters: vec![b"Foo".to_vec()],
+            }
+        );
+        assert_ok_eq!(
+            assert_some!(selections.next()),
+            Selection {
+                position: Position::new(2, 0),
+                name: b"BPMS".to_vec(),
+                parameters: vec![
+                    b"\n   0".to_vec(),
+                    b"120".to_vec(),
+                    b"\n   4".to_vec(),
+                    b"60".to_vec()
+                ],
+            }
+        );
+        assert_none!(selections.next());
+    }
+
+    #[test]
+    fn select_parameter() {
+        let selectors = [Selector::new("NOTES").parameter(2)];
+
+        assert_ok_eq!(
+            select(
+                b"#NOTES:dance-single:desc:Easy:3;\n#NOTES:dance-single;\n".as_slice(),
+                &selectors,
+                Options::new()
+            )
+            .collect::<Result<Vec<_>, _>>(),
+            vec![Selection {
+                position: Position::new(0, 0),
+                name: b"NOTES".to_vec(),
+                parameters: vec![b"Easy".to_vec()],
+            }]
+        );
+    }
+
+    #[test]
+    fn select_by_each_selector() {
+        let selectors = [Selector::new("*"), Selector::new("TITLE").parameter(0)];
+
+        assert_ok_eq!(
+            select(b"#TITLE:Foo:Bar;\n".as_slice(), &selectors, Options::new())
+                .map(|selection| selection.map(|selection| selection.parameters().len()))
+                .collect::<Result<Vec<_>, _>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn select_unescapes() {
+        let selectors = [Selector::new("TITLE")];
+
+        assert_ok_eq!(
+            select(
+                b"#TITLE:F\\:oo // comment\n;\n".as_slice(),
+                &selectors,
+                Options::new()
+            )
+            .map(|selection| selection.map(|selection| selection.parameters().to_vec()))
+            .collect::<Result<Vec<_>, _>>(),
+            vec![vec![b"F:oo \n".to_vec()]]
+        );
+    }
+
+    #[test]
+    fn select_error() {
+        let selectors = [Selector::new("TITLE")];
+        let mut selections = select(b"foo\n#TITLE:Foo;\n".as_slice(), &selectors, Options::new());
+
+        assert_matches!(
+            assert_err!(assert_some!(selections.next())).kind(),
+            ErrorKind::ExpectedTag
+        );
+        assert_none!(selections.next());
+    }
+
+    #[test]
+    fn select_lenient() {
+        let selectors = [Selector::new("TITLE")];
+
+        assert_eq!(
+            select(
+                b"foo\n#TITLE:Foo;\n".as_slice(),
+                &selectors,
+                Options::lenient()
+            )
+            .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+use serde::{de, de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The name of the newtype struct used to signal a [`RawValue`] to the serializer and
+/// deserializer.
+pub(crate) const TOKEN: &str = "$msd::private::RawValue";
+
+/// The exact source bytes of an MSD value, captured without any processing.
+///
+/// When deserialized, a `RawValue` captures its input verbatim: comments, whitespace, and escape
+/// sequences are all preserved. When serialized, the captured bytes are written back out exactly
+/// as they are, without escaping. This allows carrying a value through a round trip untouched, or
+/// deferring its parsing until later.
+///
+/// What a `RawValue` captures depends on where it is located:
+///
+/// - As a `struct` field, it captures all of the tag's parameters, including the `:` separators
+///   between them.
+/// - As an element of a `seq` field, it captures all of the parameters of one repeated tag.
+/// - At the top level, or as an element of a top-level `seq`, it captures an entire tag, including
+///   its name but excluding the leading `#` and trailing `;`.
+/// - As an element of a `tuple`, or as a `map` key or value, it captures a single parameter.
+///
+/// Since the bytes are written without escaping, it is up to the user to ensure that a `RawValue`
+/// created through [`RawValue::new()`] is valid MSD in the place it is written.
+///
+/// # Example
+/// ```
+/// use msd::RawValue;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// #[serde(rename_all = "UPPERCASE")]
+/// struct Chart {
+///     title: String,
+///     notes: RawValue,
+/// }
+///
+/// let input = b"#TITLE:foo;\n#NOTES:\n0000\n0100 // comment\n:single\\:escaped;\n";
+/// let chart: Chart = msd::from_bytes(input).unwrap();
+///
+/// assert_eq!(chart.notes.as_bytes(), b"\n0000\n0100 // comment\n:single\\:escaped");
+/// assert_eq!(msd::to_bytes(&chart).unwrap(), input);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RawValue {
+    bytes: Box<[u8]>,
+}
+
+impl RawValue {
+    /// Creates a `RawValue` from bytes that are already in MSD format.
+    pub fn new<B>(bytes: B) -> Self
+    where
+        B: Into<Box<[u8]>>,
+    {
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Returns the captured bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the captured bytes, consuming the `RawValue`.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes.into_vec()
+    }
+}
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &Bytes(&self.bytes))
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("raw MSD bytes")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawValue::new(v))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawValue::new(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawValue::new(v.as_bytes()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawValue::new(v.into_bytes()))
+    }
+
+    // Used by deserializers other than this crate's.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawValue;
+
+    #[test]
+    fn new() {
+        assert_eq!(RawValue::new(&b"foo:bar"[..]).as_bytes(), b"foo:bar");
+    }
+
+    #[test]
+    fn into_bytes() {
+        assert_eq!(RawValue::new(b"foo".to_vec()).into_bytes(), b"foo");
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(RawValue::default().as_bytes(), b"");
+    }
+}
@@ -0,0 +1,323 @@
+//! The byte-level scanner shared by the deserializer and the lexer.
+//!
+//! Both the deserializer's parser and the [`Lexer`] decide what each byte of the input means
+//! through a [`Scanner`], so that they always agree on where tags begin, where parameters are
+//! separated, and which bytes are escaped or commented out.
+//!
+//! [`Lexer`]: crate::lex::Lexer
+
+/// The meaning of a single byte of input, as determined by a [`Scanner`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Class {
+    /// A `#` beginning a tag.
+    TagStart,
+    /// A `#` within a tag that does not begin a new tag.
+    NumberSign,
+    /// A `:` separating values.
+    Colon,
+    /// A `;` terminating a parameter list.
+    Semicolon,
+    /// A `\` escaping the following byte.
+    Escape,
+    /// A byte escaped by the preceding `\`.
+    Escaped,
+    /// A `/` that may begin a comment.
+    ///
+    /// If the following byte is not classified as [`Class::CommentStart`], the `/` was plain text
+    /// after all, or content if it precedes the first tag.
+    Slash,
+    /// The second `/` of the `//` beginning a comment.
+    CommentStart,
+    /// A byte within a comment. The line ending concluding a comment is not part of it.
+    Comment,
+    /// Any other byte preceding the first tag.
+    Content,
+    /// Any other byte within a tag, including whitespace and line endings.
+    Text,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    None,
+    MaybeEnteringComment,
+    InComment,
+    Escaping,
+}
+
+/// Classifies input one byte at a time.
+///
+/// A tag begins at a `#` either before any other tag, at the start of a line, or following the
+/// `;` of the previous tag with only whitespace, line endings, and comments in between. Any other
+/// `#` is treated as an unescaped part of a value.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Scanner {
+    state: State,
+    in_tag: bool,
+    // Whether only whitespace, line endings, and comments have followed the most recent `;`.
+    end_of_values: bool,
+    starting_new_line: bool,
+}
+
+impl Scanner {
+    /// Creates a scanner positioned at the start of the input, before the first tag.
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::None,
+            in_tag: false,
+            end_of_values: false,
+            starting_new_line: false,
+        }
+    }
+
+    /// Creates a scanner positioned directly after a tag's `#`.
+    pub(crate) fn within_tag() -> Self {
+        Self {
+            in_tag: true,
+            ..Self::new()
+        }
+    }
+
+    /// Returns whether the current tag would be terminated if the input ended here.
+    ///
+    /// This is the case when only whitespace, line endings, and comments have followed its most
+    /// recent `;`.
+    pub(crate) fn end_of_values(&self) -> bool {
+        // A pending `/` that never begins a comment is part of a value.
+        self.end_of_values && !matches!(self.state, State::MaybeEnteringComment)
+    }
+
+    /// Classifies the next byte of input.
+    pub(crate) fn next(&mut self, byte: u8) -> Class {
+        let class = match self.state {
+            State::Escaping => {
+                self.state = State::None;
+                Class::Escaped
+            }
+            State::InComment => {
+                if matches!(byte, b'\n') {
+                    self.state = State::None;
+                    self.other(byte)
+                } else {
+                    Class::Comment
+                }
+            }
+            State::MaybeEnteringComment if matches!(byte, b'/') => {
+                self.state = State::InComment;
+                Class::CommentStart
+            }
+            State::MaybeEnteringComment => {
+                // The previous `/` did not begin a comment.
+                self.state = State::None;
+                self.end_of_values = false;
+                self.classify(byte)
+            }
+            State::None => self.classify(byte),
+        };
+        self.starting_new_line = matches!(byte, b'\n');
+        class
+    }
+
+    fn classify(&mut self, byte: u8) -> Class {
+        match byte {
+            b'#' if !self.in_tag || self.starting_new_line || self.end_of_values => {
+                self.in_tag = true;
+                self.end_of_values = false;
+                Class::TagStart
+            }
+            b'/' => {
+                self.state = State::MaybeEnteringComment;
+                Class::Slash
+            }
+            _ if !self.in_tag => Class::Content,
+            b'#' => {
+                self.end_of_values = false;
+                Class::NumberSign
+            }
+            b':' => {
+                self.end_of_values = false;
+                Class::Colon
+            }
+            b';' => {
+                self.end_of_values = true;
+                Class::Semicolon
+            }
+            b'\\' => {
+                self.state = State::Escaping;
+                self.end_of_values = false;
+                Class::Escape
+            }
+            _ => self.other(byte),
+        }
+    }
+
+    fn other(&mut self, byte: u8) -> Class {
+        if !self.in_tag {
+            Class::Content
+        } else {
+            if !byte.is_ascii_whitespace() {
+                self.end_of_values = false;
+            }
+            Class::Text
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Class, Scanner};
+    use alloc::vec::Vec;
+
+    fn classes(scanner: Scanner, bytes: &[u8]) -> Vec<Class> {
+        let mut scanner = scanner;
+        bytes.iter().map(|byte| scanner.next(*byte)).collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(classes(Scanner::new(), b""), []);
+    }
+
+    #[test]
+    fn tag() {
+        assert_eq!(
+            classes(Scanner::new(), b"#a:b;"),
+            [
+                Class::TagStart,
+                Class::Text,
+                Class::Colon,
+                Class::Text,
+                Class::Semicolon
+            ]
+        );
+    }
+
+    #[test]
+    fn content_before_first_tag() {
+        assert_eq!(
+            classes(Scanner::new(), b"a:\\;#"),
+            [
+                Class::Content,
+                Class::Content,
+                Class::Content,
+                Class::Content,
+                Class::TagStart
+            ]
+        );
+    }
+
+    #[test]
+    fn number_sign_within_tag() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a#b"),
+            [Class::Text, Class::NumberSign, Class::Text]
+        );
+    }
+
+    #[test]
+    fn tag_after_semicolon() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a; #"),
+            [Class::Text, Class::Semicolon, Class::Text, Class::TagStart]
+        );
+    }
+
+    #[test]
+    fn tag_at_start_of_line() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a\n#"),
+            [Class::Text, Class::Text, Class::TagStart]
+        );
+    }
+
+    #[test]
+    fn number_sign_after_content_following_semicolon() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a;b #"),
+            [
+                Class::Text,
+                Class::Semicolon,
+                Class::Text,
+                Class::Text,
+                Class::NumberSign
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"\\;\\\\:"),
+            [
+                Class::Escape,
+                Class::Escaped,
+                Class::Escape,
+                Class::Escaped,
+                Class::Colon
+            ]
+        );
+    }
+
+    #[test]
+    fn comment() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a;//;#\n#"),
+            [
+                Class::Text,
+                Class::Semicolon,
+                Class::Slash,
+                Class::CommentStart,
+                Class::Comment,
+                Class::Comment,
+                Class::Text,
+                Class::TagStart
+            ]
+        );
+    }
+
+    #[test]
+    fn comment_before_first_tag() {
+        assert_eq!(
+            classes(Scanner::new(), b"//#\n#"),
+            [
+                Class::Slash,
+                Class::CommentStart,
+                Class::Comment,
+                Class::Content,
+                Class::TagStart
+            ]
+        );
+    }
+
+    #[test]
+    fn single_slash_ends_values() {
+        assert_eq!(
+            classes(Scanner::within_tag(), b"a;/#"),
+            [
+                Class::Text,
+                Class::Semicolon,
+                Class::Slash,
+                Class::NumberSign
+            ]
+        );
+    }
+
+    #[test]
+    fn end_of_values() {
+        let mut scanner = Scanner::within_tag();
+
+        assert!(!scanner.end_of_values());
+        scanner.next(b';');
+        assert!(scanner.end_of_values());
+        scanner.next(b' ');
+        assert!(scanner.end_of_values());
+        scanner.next(b'/');
+        assert!(!scanner.end_of_values());
+        scanner.next(b'/');
+        assert!(scanner.end_of_values());
+        scanner.next(b'a');
+        assert!(scanner.end_of_values());
+        scanner.next(b'\n');
+        scanner.next(b'a');
+        assert!(!scanner.end_of_values());
+    }
+}
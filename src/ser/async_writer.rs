@@ -0,0 +1,102 @@
+use futures_util::io::AsyncWrite;
+use std::{
+    io,
+    io::Write,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The number of bytes gathered before attempting to write them.
+const CHUNK_SIZE: usize = 1024;
+
+/// Writes to an asynchronous writer from within synchronous serialization.
+///
+/// Output is gathered into chunks, each of which is written as soon as it fills. If the writer is
+/// not ready to accept a chunk, writing fails with an error of kind
+/// [`io::ErrorKind::WouldBlock`], abandoning serialization until the writer is ready again.
+/// Serialization is then restarted from the beginning, skipping the `written` bytes that were
+/// already accepted by the writer.
+pub(super) struct AsyncWriter<'a, 'b, W> {
+    writer: &'a mut W,
+    context: &'a mut Context<'b>,
+    /// The number of bytes accepted by the writer, across every attempt at serialization.
+    written: &'a mut usize,
+    /// The number of bytes serialized during this attempt.
+    serialized: usize,
+    buffer: Vec<u8>,
+    blocked: bool,
+}
+
+impl<'a, 'b, W> AsyncWriter<'a, 'b, W> {
+    pub(super) fn new(
+        writer: &'a mut W,
+        context: &'a mut Context<'b>,
+        written: &'a mut usize,
+    ) -> Self {
+        Self {
+            writer,
+            context,
+            written,
+            serialized: 0,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            blocked: false,
+        }
+    }
+
+    /// Returns whether serialization was abandoned because the writer was not ready.
+    pub(super) fn blocked(&self) -> bool {
+        self.blocked
+    }
+
+    /// Returns the output that has not been written yet.
+    pub(super) fn into_remaining(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+impl<W> Write for AsyncWriter<'_, '_, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Output accepted by the writer during a previous attempt is not written again.
+        let skipped = (*self.written)
+            .saturating_sub(self.serialized)
+            .min(buf.len());
+        self.serialized += buf.len();
+        self.buffer.extend_from_slice(&buf[skipped..]);
+        while self.buffer.len() >= CHUNK_SIZE {
+            match poll_write(self.writer, self.context, &self.buffer) {
+                Poll::Ready(Ok(len)) => {
+                    self.buffer.drain(..len);
+                    *self.written += len;
+                }
+                Poll::Ready(Err(error)) => return Err(error),
+                Poll::Pending => {
+                    self.blocked = true;
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Attempts to write `buffer`, treating a writer that accepts none of it as an error.
+pub(super) fn poll_write<W>(
+    writer: &mut W,
+    context: &mut Context,
+    buffer: &[u8],
+) -> Poll<io::Result<usize>>
+where
+    W: AsyncWrite + Unpin,
+{
+    match Pin::new(writer).poll_write(context, buffer) {
+        Poll::Ready(Ok(0)) => Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+        poll => poll,
+    }
+}
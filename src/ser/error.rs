@@ -1,12 +1,196 @@
+use crate::ser::PathSegment;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, fmt::Display};
 use serde::ser;
-use std::{fmt, fmt::Display};
 
-/// An error that may occur during serialization.
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error {
-    UnsupportedType,
+/// The kind of an [`Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // IO-related errors.
     Io,
+
+    // User-provided errors (provided through `serde::ser::Error` trait methods).
     Custom(String),
+
+    // Unrepresentable type errors.
+    CannotSerializeOptionInTuple,
+    CannotSerializeSeqInTuple,
+    CannotSerializeMapInTuple,
+    CannotSerializeStructInTuple,
+    CannotSerializeNestedStruct,
+    CannotSerializeOptionInSeq,
+    CannotSerializeNestedSeq,
+    CannotSerializeMapInSeq,
+    CannotSerializeStructInSeq,
+    CannotSerializeOptionInMapKey,
+    CannotSerializeSeqInMapKey,
+    CannotSerializeMapInMapKey,
+    CannotSerializeStructInMapKey,
+    CannotSerializeOptionInMapValue,
+    CannotSerializeSeqInMapValue,
+    CannotSerializeMapInMapValue,
+    CannotSerializeStructInMapValue,
+    MustSerializeRawValueAsBytes,
+    MustSerializeSectionHeaderAsStruct,
+}
+
+impl Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Io => formatter.write_str("error during I/O operations"),
+            Kind::Custom(msg) => formatter.write_str(msg),
+            Kind::CannotSerializeOptionInTuple => {
+                formatter.write_str("cannot serialize option inside tuple")
+            }
+            Kind::CannotSerializeSeqInTuple => {
+                formatter.write_str("cannot serialize seq inside tuple")
+            }
+            Kind::CannotSerializeMapInTuple => {
+                formatter.write_str("cannot serialize map inside tuple")
+            }
+            Kind::CannotSerializeStructInTuple => {
+                formatter.write_str("cannot serialize struct inside tuple")
+            }
+            Kind::CannotSerializeNestedStruct => {
+                formatter.write_str("cannot serialize nested struct")
+            }
+            Kind::CannotSerializeOptionInSeq => {
+                formatter.write_str("cannot serialize option inside seq")
+            }
+            Kind::CannotSerializeNestedSeq => formatter.write_str("cannot serialize nested seq"),
+            Kind::CannotSerializeMapInSeq => formatter.write_str("cannot serialize map inside seq"),
+            Kind::CannotSerializeStructInSeq => {
+                formatter.write_str("cannot serialize struct inside seq")
+            }
+            Kind::CannotSerializeOptionInMapKey => {
+                formatter.write_str("cannot serialize option inside map key")
+            }
+            Kind::CannotSerializeSeqInMapKey => {
+                formatter.write_str("cannot serialize seq inside map key")
+            }
+            Kind::CannotSerializeMapInMapKey => {
+                formatter.write_str("cannot serialize map inside map key")
+            }
+            Kind::CannotSerializeStructInMapKey => {
+                formatter.write_str("cannot serialize struct inside map key")
+            }
+            Kind::CannotSerializeOptionInMapValue => {
+                formatter.write_str("cannot serialize option inside map value")
+            }
+            Kind::CannotSerializeSeqInMapValue => {
+                formatter.write_str("cannot serialize seq inside map value")
+            }
+            Kind::CannotSerializeMapInMapValue => {
+                formatter.write_str("cannot serialize map inside map value")
+            }
+            Kind::CannotSerializeStructInMapValue => {
+                formatter.write_str("cannot serialize struct inside map value")
+            }
+            Kind::MustSerializeRawValueAsBytes => {
+                formatter.write_str("must serialize raw value as bytes")
+            }
+            Kind::MustSerializeSectionHeaderAsStruct => {
+                formatter.write_str("must serialize section header as struct")
+            }
+        }
+    }
+}
+
+/// An error that may occur during serialization.
+///
+/// Before version 0.5, this was an enum of what is now returned by [`kind()`]. Its `Io` and
+/// `Custom` variants are now [`Kind::Io`] and [`Kind::Custom`], while `UnsupportedType` is split
+/// into the remaining kinds, each naming the shape that could not be serialized.
+///
+/// [`kind()`]: Error::kind()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    kind: Kind,
+    type_name: Option<&'static str>,
+    path: Vec<PathSegment>,
+}
+
+impl Error {
+    pub(super) fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            type_name: None,
+            path: Vec::new(),
+        }
+    }
+
+    /// Creates an error for a value of the named type that cannot be represented where it was
+    /// serialized.
+    pub(super) fn unsupported(kind: Kind, type_name: &'static str) -> Self {
+        Self {
+            kind,
+            type_name: Some(type_name),
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the name of the type that could not be serialized, as provided to serde.
+    ///
+    /// Only named types, such as `struct`s and `enum`s, have names. This is `None` for all other
+    /// types, and for errors that are not caused by an unrepresentable type.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    /// Returns the path from the top level of the output to the value at which the error
+    /// occurred.
+    ///
+    /// The path begins with the outermost segment. It is empty if the error occurred at the top
+    /// level.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::{ErrorKind, PathSegment};
+    /// use serde_derive::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Bpm {
+    ///     beat: f32,
+    ///     bpm: f32,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// #[serde(rename_all = "UPPERCASE")]
+    /// struct Song {
+    ///     title: String,
+    ///     bpms: Bpm,
+    /// }
+    ///
+    /// let error = msd::to_bytes(&Song {
+    ///     title: "foo".to_owned(),
+    ///     bpms: Bpm {
+    ///         beat: 0.0,
+    ///         bpm: 120.0,
+    ///     },
+    /// })
+    /// .unwrap_err();
+    ///
+    /// assert_eq!(error.kind(), &ErrorKind::CannotSerializeNestedStruct);
+    /// assert_eq!(error.type_name(), Some("Bpm"));
+    /// assert_eq!(error.path(), [PathSegment::Tag("BPMS".to_owned())]);
+    /// ```
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Prepends a segment to the error's path, as the error propagates outward.
+    pub(super) fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
 }
 
 impl ser::Error for Error {
@@ -14,21 +198,26 @@ impl ser::Error for Error {
     where
         T: Display,
     {
-        Self::Custom(msg.to_string())
+        Self::new(Kind::Custom(msg.to_string()))
     }
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::UnsupportedType => "unsupported Rust type".fmt(formatter),
-            Self::Io => "error during I/O operations".fmt(formatter),
-            Self::Custom(message) => message.fmt(formatter),
+        self.kind.fmt(formatter)?;
+        if let Some(type_name) = self.type_name {
+            write!(formatter, " (type `{}`)", type_name)?;
         }
+        for (index, segment) in self.path.iter().enumerate() {
+            formatter.write_str(if index == 0 { " in " } else { ", " })?;
+            segment.fmt(formatter)?;
+        }
+        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+// This is `std::error::Error` when `std` is available.
+impl ser::StdError for Error {}
 
 /// An alias for a [`Result`] with the error type [`Error`].
 ///
@@ -37,20 +226,62 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
-    use super::Error;
+    use super::{Error, Kind};
+    use crate::ser::PathSegment;
     use serde::ser::Error as SerdeError;
 
+    #[test]
+    fn path() {
+        assert_eq!(
+            Error::new(Kind::CannotSerializeSeqInTuple)
+                .within(PathSegment::Parameter(1))
+                .within(PathSegment::Tag("foo".to_owned()))
+                .path(),
+            [
+                PathSegment::Tag("foo".to_owned()),
+                PathSegment::Parameter(1)
+            ]
+        );
+    }
+
     #[test]
     fn display_unsupported_type_error() {
         assert_eq!(
-            format!("{}", Error::UnsupportedType),
-            "unsupported Rust type"
+            format!("{}", Error::new(Kind::CannotSerializeSeqInTuple)),
+            "cannot serialize seq inside tuple"
+        );
+    }
+
+    #[test]
+    fn display_type_name() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::unsupported(Kind::CannotSerializeNestedStruct, "Foo")
+            ),
+            "cannot serialize nested struct (type `Foo`)"
+        );
+    }
+
+    #[test]
+    fn display_with_path() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::unsupported(Kind::CannotSerializeStructInTuple, "Bar")
+                    .within(PathSegment::Parameter(2))
+                    .within(PathSegment::Tag("foo".to_owned()))
+            ),
+            "cannot serialize struct inside tuple (type `Bar`) in tag `foo`, parameter 2"
         );
     }
 
     #[test]
     fn display_io_error() {
-        assert_eq!(format!("{}", Error::Io), "error during I/O operations");
+        assert_eq!(
+            format!("{}", Error::new(Kind::Io)),
+            "error during I/O operations"
+        );
     }
 
     #[test]
@@ -3,22 +3,43 @@ pub(super) mod tag;
 
 mod value;
 
-use crate::ser::{Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{error, path, Error, Options, PathSegment, Result, WriteExt};
+use alloc::{string::String, vec::Vec};
 use serde::{ser::SerializeMap, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
     written_field: bool,
+    index: usize,
+
+    key: Option<String>,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(super) fn new(writer: &'a mut W) -> Self {
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(super) fn with_options(writer: &'a mut W, options: Options) -> Self {
         Self {
             writer,
+            options,
             written_field: false,
+            index: 0,
+
+            key: None,
         }
     }
+
+    /// Returns the index of the parameter list of the entry most recently begun.
+    fn begin_entry(&mut self) -> usize {
+        let index = self.index;
+        self.index += 1;
+        index
+    }
 }
 
 impl<'a, W> SerializeMap for Serializer<'a, W>
@@ -33,14 +54,32 @@ where
         T: ?Sized + Serialize,
     {
         self.written_field = true;
-        key.serialize(key::Serializer::new(self.writer))
+        let index = self.begin_entry();
+        // The key is buffered so that its text is available for the paths of value errors.
+        let mut written = Vec::new();
+        key.serialize(key::Serializer::with_options(&mut written, self.options))
+            .map_err(|error| error.within(PathSegment::ParameterList(index)))?;
+        self.key = Some(path::key(&written));
+        self.writer
+            .write_all(&written)
+            .map_err(|_| Error::new(error::Kind::Io))
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(value::Serializer::new(self.writer))
+        let key = self
+            .key
+            .take()
+            .expect("call to `serialize_value()` not preceeded by call to `serialize_key()`");
+        value
+            .serialize(value::Serializer::with_options(self.writer, self.options))
+            .map_err(|error| {
+                error
+                    .within(PathSegment::Key(key))
+                    .within(PathSegment::ParameterList(self.index - 1))
+            })
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -1,14 +1,20 @@
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{error, number, raw, tuple, Error, Options, Result, WriteExt};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
     }
 }
 
@@ -27,97 +33,94 @@ where
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        if v {
-            self.writer.write_parameter_unescaped(b"true")?;
-        } else {
-            self.writer.write_parameter_unescaped(b"false")?;
-        }
+        self.writer
+            .write_parameter_unescaped(self.options.bool_format.format(v))?;
         self.writer.close_tag()
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
@@ -143,14 +146,14 @@ where
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeOptionInMapValue))
     }
 
     fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeOptionInMapValue))
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
@@ -172,10 +175,15 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == raw::TOKEN {
+            let bytes = value.serialize(raw::Serializer)?;
+            self.writer.write_parameter_unescaped(&bytes)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
@@ -194,11 +202,11 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeSeqInMapValue))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_tuple_struct(
@@ -206,7 +214,7 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_tuple_variant(
@@ -217,32 +225,38 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         self.writer.write_parameter_escaped(variant.as_bytes())?;
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeMapInMapValue))
     }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Err(Error::UnsupportedType)
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeStructInMapValue,
+            name,
+        ))
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         _variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::UnsupportedType)
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeStructInMapValue,
+            name,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Serializer;
-    use crate::ser::Error;
+    use crate::ser::{error, Error};
     use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeTupleStruct, SerializeTupleVariant},
@@ -612,7 +626,7 @@ mod tests {
 
         assert_err_eq!(
             Option::<()>::None.serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeOptionInMapValue)
         );
     }
 
@@ -622,7 +636,7 @@ mod tests {
 
         assert_err_eq!(
             Some(42).serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeOptionInMapValue)
         );
     }
 
@@ -842,7 +856,7 @@ mod tests {
 
         assert_err_eq!(
             Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeSeqInMapValue)
         );
     }
 
@@ -852,7 +866,7 @@ mod tests {
 
         assert_err_eq!(
             HashMap::<(), ()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeMapInMapValue)
         );
     }
 
@@ -868,7 +882,7 @@ mod tests {
 
         assert_err_eq!(
             Struct::default().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeStructInMapValue, "Struct")
         );
     }
 
@@ -883,7 +897,7 @@ mod tests {
 
         assert_err_eq!(
             Struct::Variant { foo: 0, bar: false }.serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeStructInMapValue, "Struct")
         );
     }
 }
@@ -17,19 +17,35 @@
 //!
 //! [`Serialize`]: serde::Serialize
 
+#[cfg(feature = "async")]
+mod async_writer;
 mod error;
-mod escaped;
+pub(crate) mod escaped;
 mod map;
+mod number;
+mod options;
+mod path;
+mod raw;
+mod sections;
 mod seq;
 mod r#struct;
 mod tuple;
-mod write;
-
-pub use error::{Error, Result};
-
+pub(crate) mod write;
+
+pub use crate::path::Segment as PathSegment;
+pub use error::{Error, Kind as ErrorKind, Result};
+pub use options::{BoolFormat, FloatFormat, IntegerFormat, Options};
+
+use crate::io::Write;
+use alloc::{borrow::ToOwned, vec::Vec};
+#[cfg(feature = "async")]
+use async_writer::AsyncWriter;
+#[cfg(feature = "async")]
+use core::{pin::Pin, task::Poll};
 use escaped::Escaped;
-use serde::{ser, Serialize};
-use std::io::Write;
+#[cfg(feature = "async")]
+use futures_util::{future, io::AsyncWrite};
+use serde::{ser, ser::SerializeSeq, Serialize};
 use write::WriteExt;
 
 /// Serializes data into MSD format.
@@ -56,11 +72,108 @@ use write::WriteExt;
 #[derive(Debug)]
 pub struct Serializer<W> {
     writer: W,
+    options: Options,
 }
 
 impl<W> Serializer<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    /// Creates a serializer that writes values as configured by the given [`Options`].
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::{IntegerFormat, Options};
+    /// use serde::Serialize;
+    ///
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     Options::new().integer_format(IntegerFormat::ZeroPadded(3)),
+    /// );
+    /// 7.serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(serialized, b"#007;\n");
+    /// ```
+    pub fn with_options(writer: W, options: Options) -> Self {
+        Self { writer, options }
+    }
+}
+
+impl<W> Serializer<W>
+where
+    W: Write,
+{
+    /// Serializes a document split into sections, writing a tag named `delimiter` before each
+    /// section.
+    ///
+    /// The header is written first, and must be a struct, or `()` if the document has no header.
+    /// Each section is then written in the same way as an element of a sequence of structs stored
+    /// in a field named `delimiter`: an empty delimiter tag, as in `#NOTEDATA:;`, followed by the
+    /// section's fields. This is the counterpart of [`Deserializer::deserialize_sections()`].
+    ///
+    /// # Example
+    /// ```
+    /// use serde_derive::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Song {
+    ///     #[serde(rename = "TITLE")]
+    ///     title: String,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Chart {
+    ///     #[serde(rename = "METER")]
+    ///     meter: u8,
+    /// }
+    ///
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::new(&mut serialized);
+    /// serializer
+    ///     .serialize_sections(
+    ///         &Song {
+    ///             title: "Foo".to_owned(),
+    ///         },
+    ///         "NOTEDATA",
+    ///         &[Chart { meter: 3 }, Chart { meter: 9 }],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     serialized,
+    ///     b"#TITLE:Foo;\n#NOTEDATA:;\n#METER:3;\n#NOTEDATA:;\n#METER:9;\n"
+    /// );
+    /// ```
+    ///
+    /// [`Deserializer::deserialize_sections()`]: crate::Deserializer::deserialize_sections()
+    pub fn serialize_sections<H, I>(
+        &mut self,
+        header: &H,
+        delimiter: &str,
+        sections: I,
+    ) -> Result<()>
+    where
+        H: ?Sized + Serialize,
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        header.serialize(sections::Header::with_options(
+            &mut self.writer,
+            self.options,
+        ))?;
+        let mut serializer = seq::Serializer::with_options(
+            &mut self.writer,
+            Escaped::new(delimiter.as_bytes()).collect::<Vec<_>>(),
+            self.options,
+        );
+        for section in sections {
+            serializer
+                .serialize_element(&section)
+                .map_err(|error| error.within(PathSegment::Tag(delimiter.to_owned())))?;
+        }
+        Ok(())
     }
 }
 
@@ -79,97 +192,94 @@ where
     type SerializeStructVariant = r#struct::Serializer<'a, W>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        if v {
-            self.writer.write_tag_name_unescaped(b"true")?;
-        } else {
-            self.writer.write_tag_name_unescaped(b"false")?;
-        }
+        self.writer
+            .write_tag_name_unescaped(self.options.bool_format.format(v))?;
         self.writer.close_tag()
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+            .write_tag_name_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
@@ -222,10 +332,15 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == raw::TOKEN {
+            let bytes = value.serialize(raw::Serializer)?;
+            self.writer.write_tag_name_unescaped(&bytes)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
@@ -239,18 +354,25 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(r#struct::field::Serializer::new(
+        value.serialize(r#struct::field::Serializer::with_options(
             &mut self.writer,
             Escaped::new(variant.as_bytes()).collect::<Vec<_>>(),
+            self.options,
         ))
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Ok(seq::tag::Serializer::new(&mut self.writer))
+        Ok(seq::tag::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Ok(tuple::tag::Serializer::new(&mut self.writer))
+        Ok(tuple::tag::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_tuple_struct(
@@ -258,7 +380,10 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Ok(tuple::tag::Serializer::new(&mut self.writer))
+        Ok(tuple::tag::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_tuple_variant(
@@ -269,15 +394,24 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         self.writer.write_tag_name_escaped(variant.as_bytes())?;
-        Ok(tuple::Serializer::new(&mut self.writer))
+        Ok(tuple::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Ok(map::tag::Serializer::new(&mut self.writer))
+        Ok(map::tag::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Ok(r#struct::Serializer::new(&mut self.writer))
+        Ok(r#struct::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_struct_variant(
@@ -289,7 +423,10 @@ where
     ) -> Result<Self::SerializeStructVariant> {
         self.writer.write_tag_name_escaped(variant.as_bytes())?;
         self.writer.close_tag()?;
-        Ok(r#struct::Serializer::new(&mut self.writer))
+        Ok(r#struct::Serializer::with_options(
+            &mut self.writer,
+            self.options,
+        ))
     }
 }
 
@@ -304,6 +441,62 @@ where
     Ok(())
 }
 
+/// Serialize the given `value` into the given asynchronous `writer` in MSD format.
+///
+/// Output is written to `writer` in chunks as the value is serialized, so no more than a single
+/// chunk is held in memory at once. Since serialization itself is synchronous, it cannot be
+/// suspended part way through: whenever `writer` is not ready for the next chunk, serialization is
+/// abandoned and restarted from the beginning once `writer` is ready, skipping the output that
+/// was already written. `value` must therefore serialize the same way each time. As with
+/// [`to_writer()`], output written before an error occurs is not undone.
+///
+/// # Example
+/// ```
+/// # async fn example() {
+/// let mut serialized = Vec::new();
+/// msd::ser::to_async_writer(&mut serialized, &42).await.unwrap();
+///
+/// assert_eq!(serialized, b"#42;\n");
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn to_async_writer<W, T>(mut writer: W, value: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    let mut written = 0;
+    // The output following the last full chunk, once serialization has completed.
+    let mut remaining = None;
+    future::poll_fn(|context| {
+        let buffer = match &mut remaining {
+            Some(buffer) => buffer,
+            None => {
+                let mut async_writer = AsyncWriter::new(&mut writer, context, &mut written);
+                match to_writer(&mut async_writer, value) {
+                    Ok(()) => remaining.insert(async_writer.into_remaining()),
+                    // The writer has registered to wake this task once it is ready.
+                    Err(_) if async_writer.blocked() => return Poll::Pending,
+                    Err(error) => return Poll::Ready(Err(error)),
+                }
+            }
+        };
+        while !buffer.is_empty() {
+            match async_writer::poll_write(&mut writer, context, buffer) {
+                Poll::Ready(Ok(len)) => {
+                    buffer.drain(..len);
+                }
+                Poll::Ready(Err(_)) => return Poll::Ready(Err(Error::new(error::Kind::Io))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Pin::new(&mut writer)
+            .poll_flush(context)
+            .map_err(|_| Error::new(error::Kind::Io))
+    })
+    .await
+}
+
 /// Serialize the given `value` into a byte buffer.
 pub fn to_bytes<T>(value: &T) -> Result<Vec<u8>>
 where
@@ -314,17 +507,35 @@ where
     Ok(bytes)
 }
 
+/// Serialize the given `header` followed by `sections` into a byte buffer, writing a tag named
+/// `delimiter` before each section.
+///
+/// See [`Serializer::serialize_sections()`] for details.
+pub fn to_bytes_sections<H, I>(header: &H, delimiter: &str, sections: I) -> Result<Vec<u8>>
+where
+    H: ?Sized + Serialize,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut bytes = Vec::with_capacity(128);
+    Serializer::new(&mut bytes).serialize_sections(header, delimiter, sections)?;
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Serializer;
-    use claims::assert_ok;
+    use super::{
+        error, BoolFormat, Error, FloatFormat, IntegerFormat, Options, PathSegment, Serializer,
+    };
+    use crate::{RawValue, UnrecognizedTags};
+    use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeMap, SerializeTupleStruct, SerializeTupleVariant},
         Serialize,
     };
     use serde_bytes::Bytes;
-    use serde_derive::Serialize;
-    use std::collections::HashMap;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn r#true() {
@@ -1136,4 +1347,679 @@ mod tests {
         .serialize(&mut Serializer::new(&mut output)));
         assert_eq!(output, b"#Variant;\n#bar:42;\n#qux:test\\:test;\n");
     }
+
+    #[test]
+    fn options_fixed_floats() {
+        #[derive(Serialize)]
+        struct Song {
+            offset: f64,
+            bpms: Vec<(f32, f64)>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            offset: -0.5,
+            bpms: vec![(0.0, 120.0), (16.0, 1e-7)],
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            Options::new().float_format(FloatFormat::Fixed(3))
+        )));
+
+        assert_eq!(
+            output,
+            b"#offset:-0.500;\n#bpms:0.000:120.000;\n#bpms:16.000:0.000;\n"
+        );
+    }
+
+    #[test]
+    fn options_no_exponent_floats() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![1e-7, 1e21].serialize(&mut Serializer::with_options(
+            &mut output,
+            Options::new().float_format(FloatFormat::NoExponent)
+        )));
+
+        assert_eq!(output, b"#0.0000001;\n#1000000000000000000000;\n");
+    }
+
+    #[test]
+    fn options_zero_padded_integers() {
+        let mut map = HashMap::new();
+        map.insert(7u8, (1i32, -2i64));
+        let mut output = Vec::new();
+
+        assert_ok!(map.serialize(&mut Serializer::with_options(
+            &mut output,
+            Options::new().integer_format(IntegerFormat::ZeroPadded(3))
+        )));
+
+        assert_eq!(output, b"#007:001:-02;\n");
+    }
+
+    #[test]
+    fn options_bool_format() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            selectable: bool,
+            flags: (bool, bool),
+            seen: Vec<bool>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            selectable: true,
+            flags: (false, true),
+            seen: vec![false],
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            Options::new().bool_format(BoolFormat::YesNo)
+        )));
+
+        assert_eq!(output, b"#SELECTABLE:YES;\n#FLAGS:NO:YES;\n#SEEN:NO;\n");
+    }
+
+    #[test]
+    fn bool_format_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!((true, false).serialize(&mut Serializer::with_options(
+            &mut output,
+            Options::new().bool_format(BoolFormat::OneZero)
+        )));
+
+        assert_eq!(output, b"#1:0;\n");
+        let mut deserializer = crate::Deserializer::with_options(
+            output.as_slice(),
+            crate::de::Options::new().allow_extended_bools(true),
+        );
+        assert_eq!(
+            <(bool, bool) as serde::Deserialize>::deserialize(&mut deserializer).unwrap(),
+            (true, false)
+        );
+    }
+
+    #[test]
+    fn raw_value_root() {
+        let mut output = Vec::new();
+
+        assert_ok!(RawValue::new(&b"foo\\#:bar // comment\n"[..])
+            .serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#foo\\#:bar // comment\n;\n");
+    }
+
+    #[test]
+    fn raw_value_struct_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            title: String,
+            notes: RawValue,
+            bpms: Vec<(f64, RawValue)>,
+        }
+        let input =
+            b"#TITLE:foo;\n#NOTES:\n  0000\\;\n  1000 // comment\n:bar;\n#BPMS:0.0: 120.000 ;\n";
+
+        let chart: Chart = crate::from_bytes(input).unwrap();
+        let mut output = Vec::new();
+        assert_ok!(chart.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn raw_value_root_seq_round_trip() {
+        let input = b"#foo:bar;\n#baz\\:qux;\n";
+
+        let tags: Vec<RawValue> = crate::from_bytes(input).unwrap();
+        let mut output = Vec::new();
+        assert_ok!(tags.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn unrecognized_tags() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            title: "foo".to_owned(),
+            unrecognized: vec![
+                RawValue::new(&b"ARTIST:bar\\:baz"[..]),
+                RawValue::new(&b"EMPTY"[..])
+            ]
+            .into_iter()
+            .collect(),
+        }
+        .serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#TITLE:foo;\n#ARTIST:bar\\:baz;\n#EMPTY;\n");
+    }
+
+    #[test]
+    fn unrecognized_tags_seq_of_structs_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            notes: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            charts: Vec<Chart>,
+        }
+        let input =
+            b"#TITLE:foo;\n#CHARTS:;\n#NOTES:bar;\n#METER:1;\n#CHARTS:;\n#METER:2;\n#NOTES:baz;\n";
+
+        let song: Song = crate::from_bytes(input).unwrap();
+        let mut output = Vec::new();
+        assert_ok!(song.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#CHARTS:;\n#NOTES:bar;\n#METER:1;\n#CHARTS:;\n#NOTES:baz;\n#METER:2;\n"
+        );
+    }
+
+    #[test]
+    fn unrecognized_tags_trailing_comments_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+            #[serde(rename = "$msd::unrecognized")]
+            unrecognized: UnrecognizedTags,
+        }
+        let input =
+            b"#TITLE:foo;\n#ARTIST:bar; // note\n#GENRE:baz;\t// other\n// last\n#CREDIT:qux // x\n";
+
+        let song: Song = crate::from_bytes(input).unwrap();
+        let mut output = Vec::new();
+        assert_ok!(song.serialize(&mut Serializer::new(&mut output)));
+        let round_tripped: Song = crate::from_bytes(&output).unwrap();
+        let mut round_tripped_output = Vec::new();
+        assert_ok!(round_tripped.serialize(&mut Serializer::new(&mut round_tripped_output)));
+
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#ARTIST:bar;\n#GENRE:baz;\n#CREDIT:qux ;\n"
+        );
+        assert_eq!(round_tripped, song);
+        assert_eq!(round_tripped_output, output);
+    }
+
+    #[test]
+    fn nan_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!(f64::NAN.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#nan;\n");
+        assert!(crate::from_bytes::<f64>(&output).unwrap().is_nan());
+    }
+
+    #[test]
+    fn infinity_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            (f32::INFINITY, f32::NEG_INFINITY).serialize(&mut Serializer::with_options(
+                &mut output,
+                Options::new().float_format(FloatFormat::Fixed(3))
+            ))
+        );
+
+        assert_eq!(output, b"#inf:-inf;\n");
+        assert_eq!(
+            crate::from_bytes::<(f32, f32)>(&output).unwrap(),
+            (f32::INFINITY, f32::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field() {
+        #[derive(Serialize)]
+        struct Inner {
+            foo: u8,
+        }
+        #[derive(Serialize)]
+        struct Outer {
+            bar: u8,
+            inner: Inner,
+        }
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Outer {
+                bar: 1,
+                inner: Inner { foo: 2 },
+            }
+            .serialize(&mut Serializer::new(&mut output)),
+            Error::unsupported(error::Kind::CannotSerializeNestedStruct, "Inner")
+                .within(PathSegment::Tag("inner".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_parameter() {
+        #[derive(Serialize)]
+        struct Foo {
+            bpms: (u8, Option<u8>),
+        }
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Foo { bpms: (1, None) }.serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeOptionInTuple)
+                .within(PathSegment::Parameter(1))
+                .within(PathSegment::Tag("bpms".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_seq() {
+        #[derive(Serialize)]
+        struct Foo {
+            notes: Vec<Vec<u8>>,
+        }
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Foo {
+                notes: vec![vec![1]],
+            }
+            .serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeNestedSeq)
+                .within(PathSegment::Element(0))
+                .within(PathSegment::Tag("notes".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_struct_field_map() {
+        #[derive(Serialize)]
+        struct Foo {
+            map: BTreeMap<&'static str, Vec<u8>>,
+        }
+        let mut map = BTreeMap::new();
+        map.insert("a", vec![]);
+        map.insert("b", vec![1]);
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Foo { map }.serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeSeqInMapValue)
+                .within(PathSegment::Key("a".to_owned()))
+                .within(PathSegment::ParameterList(0))
+                .within(PathSegment::Tag("map".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_root_seq() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            vec![Some(1), None].serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeOptionInSeq).within(PathSegment::Element(0))
+        );
+    }
+
+    #[test]
+    fn error_path_root_map() {
+        let mut map = BTreeMap::new();
+        map.insert("f:oo", Some(1));
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            map.serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeOptionInMapValue)
+                .within(PathSegment::Tag("f:oo".to_owned()))
+        );
+    }
+
+    #[test]
+    fn error_path_root_tuple() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            (1, vec![2]).serialize(&mut Serializer::new(&mut output)),
+            Error::new(error::Kind::CannotSerializeSeqInTuple).within(PathSegment::Parameter(1))
+        );
+    }
+
+    #[test]
+    fn error_path_display() {
+        #[derive(Serialize)]
+        struct Foo {
+            bpms: (u8, Vec<u8>),
+        }
+        let mut output = Vec::new();
+
+        assert_eq!(
+            Foo { bpms: (1, vec![]) }
+                .serialize(&mut Serializer::new(&mut output))
+                .unwrap_err()
+                .to_string(),
+            "cannot serialize seq inside tuple in tag `bpms`, parameter 1"
+        );
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    struct Chart {
+        stepstype: &'static str,
+        meter: u8,
+    }
+
+    #[test]
+    fn sections() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: &'static str,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Serializer::new(&mut output).serialize_sections(
+            &Song { title: "foo" },
+            "NOTEDATA",
+            &[
+                Chart {
+                    stepstype: "dance-single",
+                    meter: 3,
+                },
+                Chart {
+                    stepstype: "dance-double",
+                    meter: 9,
+                },
+            ],
+        ));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n#NOTEDATA:;\n#STEPSTYPE:dance-double;\n#METER:9;\n"
+        );
+    }
+
+    #[test]
+    fn sections_without_header() {
+        let mut output = Vec::new();
+
+        assert_ok!(Serializer::new(&mut output).serialize_sections(
+            &(),
+            "NOTEDATA",
+            &[Chart {
+                stepstype: "dance-single",
+                meter: 3,
+            }],
+        ));
+        assert_eq!(
+            output,
+            b"#NOTEDATA:;\n#STEPSTYPE:dance-single;\n#METER:3;\n"
+        );
+    }
+
+    #[test]
+    fn sections_escaped_delimiter() {
+        let mut output = Vec::new();
+
+        assert_ok!(Serializer::new(&mut output).serialize_sections(
+            &(),
+            "A:B",
+            &[Chart {
+                stepstype: "dance-single",
+                meter: 3,
+            }],
+        ));
+        assert_eq!(output, b"#A\\:B:;\n#STEPSTYPE:dance-single;\n#METER:3;\n");
+    }
+
+    #[test]
+    fn sections_error_path() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Serializer::new(&mut output).serialize_sections(&(), "NOTEDATA", &[vec![vec![1]]]),
+            Error::new(error::Kind::CannotSerializeNestedSeq)
+                .within(PathSegment::Element(0))
+                .within(PathSegment::Tag("NOTEDATA".to_owned()))
+        );
+    }
+
+    #[test]
+    fn sections_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            title: String,
+        }
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Chart {
+            meter: u8,
+        }
+        let header = Song {
+            title: "foo".to_owned(),
+        };
+        let sections = vec![Chart { meter: 3 }, Chart { meter: 9 }];
+
+        let serialized = assert_ok!(crate::ser::to_bytes_sections(
+            &header, "NOTEDATA", &sections
+        ));
+
+        assert_eq!(
+            assert_ok!(crate::de::from_bytes_sections::<Song, Chart>(
+                &serialized,
+                "NOTEDATA"
+            )),
+            (header, sections)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use crate::{
+            block_on::block_on,
+            ser::{error, to_async_writer, to_bytes, Error, PathSegment},
+        };
+        use claims::{assert_err_eq, assert_ok};
+        use futures_util::{io::AsyncWrite, task::noop_waker};
+        use serde_derive::Serialize;
+        use std::{
+            future::Future,
+            io,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        /// Writes at most `chunk_size` bytes at a time, returning `Poll::Pending` before each chunk.
+        struct ChunkedWriter {
+            bytes: Vec<u8>,
+            chunk_size: usize,
+            pending: bool,
+        }
+
+        impl ChunkedWriter {
+            fn new(chunk_size: usize) -> Self {
+                Self {
+                    bytes: Vec::new(),
+                    chunk_size,
+                    pending: true,
+                }
+            }
+        }
+
+        impl AsyncWrite for ChunkedWriter {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _context: &mut Context,
+                buffer: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                if self.pending {
+                    self.pending = false;
+                    return Poll::Pending;
+                }
+                self.pending = true;
+                let len = self.chunk_size.min(buffer.len());
+                self.bytes.extend_from_slice(&buffer[..len]);
+                Poll::Ready(Ok(len))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        /// Records the length of each write.
+        #[derive(Default)]
+        struct RecordingWriter {
+            bytes: Vec<u8>,
+            writes: Vec<usize>,
+        }
+
+        impl AsyncWrite for RecordingWriter {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _context: &mut Context,
+                buffer: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.bytes.extend_from_slice(buffer);
+                self.writes.push(buffer.len());
+                Poll::Ready(Ok(buffer.len()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        struct FailingWriter;
+
+        impl AsyncWrite for FailingWriter {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _context: &mut Context,
+                _buffer: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "failed")))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "failed")))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "failed")))
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Song {
+            title: &'static str,
+            bpm: f64,
+        }
+
+        #[test]
+        fn vec() {
+            let mut output = Vec::new();
+
+            assert_ok!(block_on(to_async_writer(&mut output, &42)));
+
+            assert_eq!(output, b"#42;\n");
+        }
+
+        #[test]
+        fn chunked() {
+            let mut writer = ChunkedWriter::new(3);
+
+            assert_ok!(block_on(to_async_writer(
+                &mut writer,
+                &Song {
+                    title: "foo",
+                    bpm: 120.0,
+                }
+            )));
+
+            assert_eq!(writer.bytes, b"#title:foo;\n#bpm:120.0;\n");
+        }
+
+        #[test]
+        fn writes_while_serializing() {
+            let value = (0..1000).collect::<Vec<u64>>();
+            let mut writer = RecordingWriter::default();
+
+            assert_ok!(block_on(to_async_writer(&mut writer, &value)));
+
+            assert_eq!(writer.bytes, assert_ok!(to_bytes(&value)));
+            // The output is written in several chunks, rather than all at once.
+            assert!(writer.writes.len() > 1);
+            assert!(writer.writes.iter().all(|len| *len < writer.bytes.len()));
+        }
+
+        #[test]
+        fn chunked_while_serializing() {
+            let value = (0..1000).collect::<Vec<u64>>();
+            let mut writer = ChunkedWriter::new(100);
+
+            assert_ok!(block_on(to_async_writer(&mut writer, &value)));
+
+            assert_eq!(writer.bytes, assert_ok!(to_bytes(&value)));
+        }
+
+        #[test]
+        fn pending_while_serializing() {
+            let value = (0..1000).collect::<Vec<u64>>();
+            let mut writer = ChunkedWriter::new(100);
+            let waker = noop_waker();
+            let mut context = Context::from_waker(&waker);
+            let mut future = Box::pin(to_async_writer(&mut writer, &value));
+
+            // The writer is not ready for the first chunk, which suspends serialization.
+            assert!(future.as_mut().poll(&mut context).is_pending());
+            assert_ok!(block_on(future));
+
+            assert_eq!(writer.bytes, assert_ok!(to_bytes(&value)));
+        }
+
+        #[test]
+        fn io_error() {
+            assert_err_eq!(
+                block_on(to_async_writer(FailingWriter, &42)),
+                Error::new(error::Kind::Io)
+            );
+        }
+
+        #[test]
+        fn unsupported_type() {
+            let mut output = Vec::new();
+
+            assert_err_eq!(
+                block_on(to_async_writer(&mut output, &vec![vec![1]])),
+                Error::new(error::Kind::CannotSerializeNestedSeq).within(PathSegment::Element(0))
+            );
+            assert_eq!(output, b"");
+        }
+    }
 }
@@ -0,0 +1,207 @@
+use crate::ser::{FloatFormat, IntegerFormat, Options};
+use alloc::string::String;
+use core::fmt::{Display, Write};
+use num_traits::Float;
+
+/// A buffer for formatting numbers as configured by [`Options`].
+///
+/// The default formats are written without allocating.
+pub(in crate::ser) struct Buffer {
+    options: Options,
+
+    itoa: itoa::Buffer,
+    ryu: ryu::Buffer,
+    string: String,
+}
+
+impl Buffer {
+    pub(in crate::ser) fn new(options: Options) -> Self {
+        Self {
+            options,
+
+            itoa: itoa::Buffer::new(),
+            ryu: ryu::Buffer::new(),
+            string: String::new(),
+        }
+    }
+
+    pub(in crate::ser) fn format_integer<I>(&mut self, value: I) -> &str
+    where
+        I: itoa::Integer + Display,
+    {
+        match self.options.integer_format {
+            IntegerFormat::Plain => self.itoa.format(value),
+            IntegerFormat::ZeroPadded(width) => {
+                self.string.clear();
+                // Writing to a `String` can't fail.
+                let _ = write!(self.string, "{:0width$}", value, width = width);
+                &self.string
+            }
+        }
+    }
+
+    pub(in crate::ser) fn format_float<F>(&mut self, value: F) -> &str
+    where
+        F: ryu::Float + Float + Display,
+    {
+        // Non-finite values are written the way the deserializer reads them.
+        if value.is_nan() {
+            return "nan";
+        }
+        if value.is_infinite() {
+            return if value.is_sign_negative() {
+                "-inf"
+            } else {
+                "inf"
+            };
+        }
+
+        match self.options.float_format {
+            FloatFormat::Shortest => self.ryu.format_finite(value),
+            FloatFormat::Fixed(decimals) => {
+                self.string.clear();
+                // Writing to a `String` can't fail.
+                let _ = write!(self.string, "{:.*}", decimals, value);
+                &self.string
+            }
+            FloatFormat::NoExponent => {
+                self.string.clear();
+                // Writing to a `String` can't fail.
+                let _ = write!(self.string, "{}", value);
+                &self.string
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Buffer;
+    use crate::ser::{FloatFormat, IntegerFormat, Options};
+
+    #[test]
+    fn integer_plain() {
+        let mut buffer = Buffer::new(Options::new());
+
+        assert_eq!(buffer.format_integer(42u8), "42");
+    }
+
+    #[test]
+    fn integer_plain_negative() {
+        let mut buffer = Buffer::new(Options::new());
+
+        assert_eq!(buffer.format_integer(-42i64), "-42");
+    }
+
+    #[test]
+    fn integer_zero_padded() {
+        let mut buffer = Buffer::new(Options::new().integer_format(IntegerFormat::ZeroPadded(4)));
+
+        assert_eq!(buffer.format_integer(42u32), "0042");
+    }
+
+    #[test]
+    fn integer_zero_padded_negative() {
+        let mut buffer = Buffer::new(Options::new().integer_format(IntegerFormat::ZeroPadded(4)));
+
+        assert_eq!(buffer.format_integer(-42i32), "-042");
+    }
+
+    #[test]
+    fn integer_zero_padded_wider_than_width() {
+        let mut buffer = Buffer::new(Options::new().integer_format(IntegerFormat::ZeroPadded(2)));
+
+        assert_eq!(buffer.format_integer(12345u64), "12345");
+    }
+
+    #[test]
+    fn integer_zero_padded_reused() {
+        let mut buffer = Buffer::new(Options::new().integer_format(IntegerFormat::ZeroPadded(3)));
+
+        assert_eq!(buffer.format_integer(1u8), "001");
+        assert_eq!(buffer.format_integer(2u8), "002");
+    }
+
+    #[test]
+    fn float_shortest() {
+        let mut buffer = Buffer::new(Options::new());
+
+        assert_eq!(buffer.format_float(120f64), "120.0");
+    }
+
+    #[test]
+    fn float_shortest_exponent() {
+        let mut buffer = Buffer::new(Options::new());
+
+        assert_eq!(buffer.format_float(1e-7f64), "1e-7");
+    }
+
+    #[test]
+    fn float_fixed() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::Fixed(3)));
+
+        assert_eq!(buffer.format_float(120f64), "120.000");
+    }
+
+    #[test]
+    fn float_fixed_rounds() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::Fixed(3)));
+
+        assert_eq!(buffer.format_float(0.12345f32), "0.123");
+    }
+
+    #[test]
+    fn float_fixed_no_exponent() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::Fixed(2)));
+
+        assert_eq!(buffer.format_float(1e20f64), "100000000000000000000.00");
+    }
+
+    #[test]
+    fn float_fixed_zero_decimals() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::Fixed(0)));
+
+        assert_eq!(buffer.format_float(1.5f64), "2");
+    }
+
+    #[test]
+    fn float_no_exponent() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::NoExponent));
+
+        assert_eq!(buffer.format_float(1e-7f64), "0.0000001");
+    }
+
+    #[test]
+    fn float_no_exponent_whole() {
+        let mut buffer = Buffer::new(Options::new().float_format(FloatFormat::NoExponent));
+
+        assert_eq!(buffer.format_float(120f32), "120");
+    }
+
+    #[test]
+    fn float_nan() {
+        for options in [
+            Options::new(),
+            Options::new().float_format(FloatFormat::Fixed(3)),
+            Options::new().float_format(FloatFormat::NoExponent),
+        ] {
+            let mut buffer = Buffer::new(options);
+
+            assert_eq!(buffer.format_float(f64::NAN), "nan");
+        }
+    }
+
+    #[test]
+    fn float_infinity() {
+        for options in [
+            Options::new(),
+            Options::new().float_format(FloatFormat::Fixed(3)),
+            Options::new().float_format(FloatFormat::NoExponent),
+        ] {
+            let mut buffer = Buffer::new(options);
+
+            assert_eq!(buffer.format_float(f32::INFINITY), "inf");
+            assert_eq!(buffer.format_float(f32::NEG_INFINITY), "-inf");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+/// How `bool` values are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolFormat {
+    /// `true` and `false`.
+    TrueFalse,
+    /// `YES` and `NO`, as used by tags such as `#SELECTABLE` in `.sm` files.
+    YesNo,
+    /// `ON` and `OFF`.
+    OnOff,
+    /// `1` and `0`.
+    OneZero,
+}
+
+impl BoolFormat {
+    pub(in crate::ser) fn format(self, value: bool) -> &'static [u8] {
+        match (self, value) {
+            (BoolFormat::TrueFalse, true) => b"true",
+            (BoolFormat::TrueFalse, false) => b"false",
+            (BoolFormat::YesNo, true) => b"YES",
+            (BoolFormat::YesNo, false) => b"NO",
+            (BoolFormat::OnOff, true) => b"ON",
+            (BoolFormat::OnOff, false) => b"OFF",
+            (BoolFormat::OneZero, true) => b"1",
+            (BoolFormat::OneZero, false) => b"0",
+        }
+    }
+}
+
+/// How floating point numbers are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest representation that reads back as the same value, such as `120.0` or `1e-7`.
+    Shortest,
+    /// A fixed number of digits after the decimal point, such as `120.000` for `Fixed(3)`.
+    ///
+    /// Exponents are never written.
+    Fixed(usize),
+    /// The shortest representation that reads back as the same value, without using an exponent,
+    /// such as `120` or `0.0000001`.
+    NoExponent,
+}
+
+/// How integers are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerFormat {
+    /// Only as many digits as are necessary, such as `42`.
+    Plain,
+    /// Padded with leading zeros to at least the given width, such as `0042` for `ZeroPadded(4)`.
+    ///
+    /// The width includes the sign of negative integers, so `-42` is written as `-042`.
+    ZeroPadded(usize),
+}
+
+/// Options controlling how values are written as MSD.
+///
+/// Regardless of the options, non-finite floating point numbers are always written as `nan`,
+/// `inf`, and `-inf`, which can be read back by the [`Deserializer`].
+///
+/// # Example
+/// ```
+/// use msd::ser::{FloatFormat, Options};
+/// use serde::Serialize;
+///
+/// let mut serialized = Vec::new();
+/// let mut serializer = msd::Serializer::with_options(
+///     &mut serialized,
+///     Options::new().float_format(FloatFormat::Fixed(3)),
+/// );
+/// 120.0.serialize(&mut serializer).unwrap();
+///
+/// assert_eq!(serialized, b"#120.000;\n");
+/// ```
+///
+/// [`Deserializer`]: crate::Deserializer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub(in crate::ser) bool_format: BoolFormat,
+    pub(in crate::ser) float_format: FloatFormat,
+    pub(in crate::ser) integer_format: IntegerFormat,
+}
+
+impl Options {
+    /// Returns the default options.
+    ///
+    /// `bool` values are written using [`BoolFormat::TrueFalse`], floating point numbers are
+    /// written using [`FloatFormat::Shortest`], and integers are written using
+    /// [`IntegerFormat::Plain`].
+    pub fn new() -> Self {
+        Self {
+            bool_format: BoolFormat::TrueFalse,
+            float_format: FloatFormat::Shortest,
+            integer_format: IntegerFormat::Plain,
+        }
+    }
+
+    /// Sets how `bool` values are written.
+    ///
+    /// Spellings other than `true` and `false` can only be read back when
+    /// [`de::Options::allow_extended_bools()`] is enabled.
+    ///
+    /// [`de::Options::allow_extended_bools()`]: crate::de::Options::allow_extended_bools()
+    pub fn bool_format(mut self, format: BoolFormat) -> Self {
+        self.bool_format = format;
+        self
+    }
+
+    /// Sets how floating point numbers are written.
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Sets how integers are written.
+    pub fn integer_format(mut self, format: IntegerFormat) -> Self {
+        self.integer_format = format;
+        self
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoolFormat, FloatFormat, IntegerFormat, Options};
+
+    #[test]
+    fn bool_formats() {
+        assert_eq!(BoolFormat::TrueFalse.format(true), b"true");
+        assert_eq!(BoolFormat::TrueFalse.format(false), b"false");
+        assert_eq!(BoolFormat::YesNo.format(true), b"YES");
+        assert_eq!(BoolFormat::YesNo.format(false), b"NO");
+        assert_eq!(BoolFormat::OnOff.format(true), b"ON");
+        assert_eq!(BoolFormat::OnOff.format(false), b"OFF");
+        assert_eq!(BoolFormat::OneZero.format(true), b"1");
+        assert_eq!(BoolFormat::OneZero.format(false), b"0");
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(Options::default(), Options::new());
+    }
+
+    #[test]
+    fn new() {
+        let options = Options::new();
+
+        assert_eq!(options.bool_format, BoolFormat::TrueFalse);
+        assert_eq!(options.float_format, FloatFormat::Shortest);
+        assert_eq!(options.integer_format, IntegerFormat::Plain);
+    }
+
+    #[test]
+    fn setters() {
+        let options = Options::new()
+            .bool_format(BoolFormat::YesNo)
+            .float_format(FloatFormat::Fixed(3))
+            .integer_format(IntegerFormat::ZeroPadded(4));
+
+        assert_eq!(options.bool_format, BoolFormat::YesNo);
+        assert_eq!(options.float_format, FloatFormat::Fixed(3));
+        assert_eq!(options.integer_format, IntegerFormat::ZeroPadded(4));
+    }
+}
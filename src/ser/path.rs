@@ -0,0 +1,47 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+
+/// Returns the text of the map key serialized as `written`, for use as a [`PathSegment`].
+///
+/// The leading `#` or indentation written before the key is skipped and escape sequences are
+/// removed. Only the first value of a compound key is used.
+///
+/// [`PathSegment`]: crate::ser::PathSegment
+pub(in crate::ser) fn key(written: &[u8]) -> String {
+    let mut text = Vec::new();
+    let mut bytes = written
+        .iter()
+        .skip_while(|&&byte| byte == b'#' || byte == b' ');
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'\\' => text.extend(bytes.next()),
+            b':' => break,
+            _ => text.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&text).trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::key;
+
+    #[test]
+    fn key_tag_name() {
+        assert_eq!(key(b"#foo"), "foo");
+    }
+
+    #[test]
+    fn key_indented() {
+        assert_eq!(key(b"   foo"), "foo");
+    }
+
+    #[test]
+    fn key_escaped() {
+        assert_eq!(key(b"#f\\:o\\#o"), "f:o#o");
+    }
+
+    #[test]
+    fn key_compound() {
+        assert_eq!(key(b"   foo:bar"), "foo");
+    }
+}
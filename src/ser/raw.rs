@@ -0,0 +1,192 @@
+pub(super) use crate::raw::TOKEN;
+
+use crate::ser::{error, Error, Result};
+use alloc::vec::Vec;
+use serde::{ser, ser::Impossible, Serialize};
+
+/// Captures the bytes of a [`RawValue`] so that they can be written without escaping.
+///
+/// [`RawValue`]: crate::RawValue
+pub(super) struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(v.to_vec())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::new(error::Kind::MustSerializeRawValueAsBytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use crate::ser::{error, Error};
+    use claims::{assert_err_eq, assert_ok_eq};
+    use serde::Serialize;
+    use serde_bytes::Bytes;
+
+    #[test]
+    fn bytes() {
+        assert_ok_eq!(Bytes::new(b"foo\\:bar").serialize(Serializer), b"foo\\:bar");
+    }
+
+    #[test]
+    fn str() {
+        assert_err_eq!(
+            "foo".serialize(Serializer),
+            Error::new(error::Kind::MustSerializeRawValueAsBytes)
+        );
+    }
+}
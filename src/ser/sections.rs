@@ -0,0 +1,257 @@
+use crate::io::Write;
+use crate::ser::{error, r#struct, Error, Options, Result};
+use serde::{ser, ser::Impossible, Serialize};
+
+/// Serializes the header preceding the first section of a document.
+///
+/// The header is written as a struct in the same way as an entire document is. A unit header is
+/// not written at all.
+pub(super) struct Header<'a, W> {
+    writer: &'a mut W,
+    options: Options,
+}
+
+impl<'a, W> Header<'a, W> {
+    pub(super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
+    }
+}
+
+fn not_struct() -> Error {
+    Error::new(error::Kind::MustSerializeSectionHeaderAsStruct)
+}
+
+impl<'a, W> ser::Serializer for Header<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = r#struct::Serializer<'a, W>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(not_struct())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_struct())
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        // An empty header, for documents made up only of sections.
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Err(Error::unsupported(
+            error::Kind::MustSerializeSectionHeaderAsStruct,
+            name,
+        ))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::unsupported(
+            error::Kind::MustSerializeSectionHeaderAsStruct,
+            name,
+        ))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_struct())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_struct())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::unsupported(
+            error::Kind::MustSerializeSectionHeaderAsStruct,
+            name,
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::unsupported(
+            error::Kind::MustSerializeSectionHeaderAsStruct,
+            name,
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(not_struct())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(r#struct::Serializer::with_options(
+            self.writer,
+            self.options,
+        ))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::unsupported(
+            error::Kind::MustSerializeSectionHeaderAsStruct,
+            name,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Header;
+    use crate::ser::{error, Error, Options};
+    use claims::{assert_err_eq, assert_ok};
+    use serde::Serialize;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn r#struct() {
+        #[derive(Serialize)]
+        struct Song {
+            title: &'static str,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(
+            Song { title: "foo" }.serialize(Header::with_options(&mut output, Options::new()))
+        );
+        assert_eq!(output, b"#title:foo;\n");
+    }
+
+    #[test]
+    fn unit() {
+        let mut output = Vec::new();
+
+        assert_ok!(().serialize(Header::with_options(&mut output, Options::new())));
+        assert_eq!(output, b"");
+    }
+
+    #[test]
+    fn not_struct() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            "foo".serialize(Header::with_options(&mut output, Options::new())),
+            Error::new(error::Kind::MustSerializeSectionHeaderAsStruct)
+        );
+    }
+
+    #[test]
+    fn named_not_struct() {
+        #[derive(Serialize)]
+        struct Tuple(u8, u8);
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Tuple(1, 2).serialize(Header::with_options(&mut output, Options::new())),
+            Error::unsupported(error::Kind::MustSerializeSectionHeaderAsStruct, "Tuple")
+        );
+    }
+}
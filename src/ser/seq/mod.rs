@@ -2,20 +2,34 @@ pub(super) mod tag;
 
 mod element;
 
-use crate::ser::{Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{Error, Options, PathSegment, Result, WriteExt};
+use alloc::vec::Vec;
 use serde::{ser::SerializeSeq, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
+    index: usize,
 
     escaped_field_name: Vec<u8>,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(super) fn new(writer: &'a mut W, escaped_field_name: Vec<u8>) -> Self {
+        Self::with_options(writer, escaped_field_name, Options::new())
+    }
+
+    pub(super) fn with_options(
+        writer: &'a mut W,
+        escaped_field_name: Vec<u8>,
+        options: Options,
+    ) -> Self {
         Self {
             writer,
+            options,
+            index: 0,
 
             escaped_field_name,
         }
@@ -33,9 +47,13 @@ where
     where
         T: ?Sized + Serialize,
     {
+        let index = self.index;
+        self.index += 1;
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        value.serialize(element::Serializer::new(self.writer))
+        value
+            .serialize(element::Serializer::with_options(self.writer, self.options))
+            .map_err(|error| error.within(PathSegment::Element(index)))
     }
 
     fn end(self) -> Result<Self::Ok> {
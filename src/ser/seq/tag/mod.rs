@@ -1,16 +1,27 @@
 mod element;
 
-use crate::ser::{Error, Result};
+use crate::io::Write;
+use crate::ser::{Error, Options, PathSegment, Result};
 use serde::{ser::SerializeSeq, Serialize};
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
+    index: usize,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self {
+            writer,
+            options,
+            index: 0,
+        }
     }
 }
 
@@ -25,7 +36,11 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        let index = self.index;
+        self.index += 1;
+        value
+            .serialize(element::Serializer::with_options(self.writer, self.options))
+            .map_err(|error| error.within(PathSegment::Element(index)))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -1,17 +1,29 @@
-use crate::ser::{map, seq, tuple, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{error, map, number, raw, seq, tuple, Error, Options, Result, WriteExt};
+use alloc::vec::Vec;
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 
     escaped_field_name: Vec<u8>,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W, escaped_field_name: Vec<u8>) -> Self {
+        Self::with_options(writer, escaped_field_name, Options::new())
+    }
+
+    pub(in super::super) fn with_options(
+        writer: &'a mut W,
+        escaped_field_name: Vec<u8>,
+        options: Options,
+    ) -> Self {
         Self {
             writer,
+            options,
             escaped_field_name,
         }
     }
@@ -34,47 +46,44 @@ where
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        if v {
-            self.writer.write_parameter_unescaped(b"true")?;
-        } else {
-            self.writer.write_parameter_unescaped(b"false")?;
-        }
+        self.writer
+            .write_parameter_unescaped(self.options.bool_format.format(v))?;
         self.writer.close_tag()
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
@@ -82,45 +91,45 @@ where
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
@@ -128,27 +137,27 @@ where
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())?;
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())?;
         self.writer.close_tag()
     }
 
@@ -213,10 +222,17 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == raw::TOKEN {
+            let bytes = value.serialize(raw::Serializer)?;
+            self.writer
+                .write_tag_name_unescaped(&self.escaped_field_name)?;
+            self.writer.write_parameter_unescaped(&bytes)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
@@ -232,17 +248,25 @@ where
     {
         self.escaped_field_name
             .write_parameter_escaped(variant.as_bytes())?;
-        value.serialize(Serializer::new(self.writer, self.escaped_field_name))
+        value.serialize(Serializer::with_options(
+            self.writer,
+            self.escaped_field_name,
+            self.options,
+        ))
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Ok(seq::Serializer::new(self.writer, self.escaped_field_name))
+        Ok(seq::Serializer::with_options(
+            self.writer,
+            self.escaped_field_name,
+            self.options,
+        ))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_tuple_struct(
@@ -252,7 +276,7 @@ where
     ) -> Result<Self::SerializeTupleStruct> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_tuple_variant(
@@ -265,35 +289,41 @@ where
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
         self.writer.write_parameter_escaped(variant.as_bytes())?;
-        Ok(tuple::Serializer::new(self.writer))
+        Ok(tuple::Serializer::with_options(self.writer, self.options))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
         self.writer.write_parameter_unescaped(b"\n")?;
-        Ok(map::Serializer::new(self.writer))
+        Ok(map::Serializer::with_options(self.writer, self.options))
     }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Err(Error::UnsupportedType)
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeNestedStruct,
+            name,
+        ))
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         _variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::UnsupportedType)
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeNestedStruct,
+            name,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Serializer;
-    use crate::ser::Error;
+    use crate::ser::{error, Error};
     use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeMap, SerializeTupleStruct, SerializeTupleVariant},
@@ -1155,7 +1185,7 @@ mod tests {
 
         assert_err_eq!(
             Struct::default().serialize(Serializer::new(&mut output, b"foo".to_vec())),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeNestedStruct, "Struct")
         );
     }
 
@@ -1171,7 +1201,7 @@ mod tests {
         assert_err_eq!(
             Struct::Variant { foo: 0, bar: false }
                 .serialize(Serializer::new(&mut output, b"foo".to_vec())),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeNestedStruct, "Struct")
         );
     }
 }
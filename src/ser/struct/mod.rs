@@ -1,19 +1,27 @@
 pub(super) mod field;
 
-use crate::ser::{Error, Escaped, Result};
+use crate::io::Write;
+use crate::ser::{self, Error, Escaped, Options, PathSegment, Result};
+use crate::unrecognized;
+use alloc::{borrow::ToOwned, vec::Vec};
 use serde::{
     ser::{SerializeStruct, SerializeStructVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
     }
 }
 
@@ -28,10 +36,20 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(field::Serializer::new(
-            self.writer,
-            Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
-        ))
+        if key == unrecognized::FIELD {
+            // Unrecognized tags are written as entire tags, as they would be at the top level.
+            return value.serialize(&mut ser::Serializer::with_options(
+                &mut *self.writer,
+                self.options,
+            ));
+        }
+        value
+            .serialize(field::Serializer::with_options(
+                self.writer,
+                Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
+                self.options,
+            ))
+            .map_err(|error| error.within(PathSegment::Tag(key.to_owned())))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -50,10 +68,13 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(field::Serializer::new(
-            self.writer,
-            Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
-        ))
+        value
+            .serialize(field::Serializer::with_options(
+                self.writer,
+                Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
+                self.options,
+            ))
+            .map_err(|error| error.within(PathSegment::Tag(key.to_owned())))
     }
 
     fn end(self) -> Result<Self::Ok> {
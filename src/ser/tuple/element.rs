@@ -1,14 +1,20 @@
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{error, number, raw, tuple, Error, Options, Result, WriteExt};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
     }
 }
 
@@ -27,85 +33,82 @@ where
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        if v {
-            self.writer.write_parameter_unescaped(b"true")
-        } else {
-            self.writer.write_parameter_unescaped(b"false")
-        }
+        self.writer
+            .write_parameter_unescaped(self.options.bool_format.format(v))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     #[cfg(has_i128)]
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     #[cfg(has_i128)]
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
-        let mut buffer = itoa::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_integer(v).as_bytes())
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        let mut buffer = ryu::Buffer::new();
+        let mut buffer = number::Buffer::new(self.options);
         self.writer
-            .write_parameter_unescaped(buffer.format(v).as_bytes())
+            .write_parameter_unescaped(buffer.format_float(v).as_bytes())
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok> {
@@ -123,14 +126,14 @@ where
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeOptionInTuple))
     }
 
     fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeOptionInTuple))
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
@@ -150,10 +153,14 @@ where
         self.writer.write_parameter_escaped(variant.as_bytes())
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == raw::TOKEN {
+            let bytes = value.serialize(raw::Serializer)?;
+            return self.writer.write_parameter_unescaped(&bytes);
+        }
         value.serialize(self)
     }
 
@@ -172,11 +179,14 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeSeqInTuple))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Ok(tuple::nested::Serializer::new(self.writer))
+        Ok(tuple::nested::Serializer::with_options(
+            self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_tuple_struct(
@@ -184,7 +194,10 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Ok(tuple::nested::Serializer::new(self.writer))
+        Ok(tuple::nested::Serializer::with_options(
+            self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_tuple_variant(
@@ -195,32 +208,41 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         self.writer.write_parameter_escaped(variant.as_bytes())?;
-        Ok(tuple::nested::Serializer::new(self.writer))
+        Ok(tuple::nested::Serializer::with_options(
+            self.writer,
+            self.options,
+        ))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Err(Error::UnsupportedType)
+        Err(Error::new(error::Kind::CannotSerializeMapInTuple))
     }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Err(Error::UnsupportedType)
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeStructInTuple,
+            name,
+        ))
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         _variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::UnsupportedType)
+        Err(Error::unsupported(
+            error::Kind::CannotSerializeStructInTuple,
+            name,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Serializer;
-    use crate::ser::Error;
+    use crate::ser::{error, Error};
     use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeTupleStruct, SerializeTupleVariant},
@@ -590,7 +612,7 @@ mod tests {
 
         assert_err_eq!(
             Option::<()>::None.serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeOptionInTuple)
         );
     }
 
@@ -600,7 +622,7 @@ mod tests {
 
         assert_err_eq!(
             Some(42).serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeOptionInTuple)
         );
     }
 
@@ -820,7 +842,7 @@ mod tests {
 
         assert_err_eq!(
             Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeSeqInTuple)
         );
     }
 
@@ -830,7 +852,7 @@ mod tests {
 
         assert_err_eq!(
             HashMap::<(), ()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::new(error::Kind::CannotSerializeMapInTuple)
         );
     }
 
@@ -846,7 +868,7 @@ mod tests {
 
         assert_err_eq!(
             Struct::default().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeStructInTuple, "Struct")
         );
     }
 
@@ -861,7 +883,7 @@ mod tests {
 
         assert_err_eq!(
             Struct::Variant { foo: 0, bar: false }.serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+            Error::unsupported(error::Kind::CannotSerializeStructInTuple, "Struct")
         );
     }
 }
@@ -1,20 +1,27 @@
 use super::element;
-use crate::ser::{map::key, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{map::key, Error, Options, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
     written_key: bool,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
         Self {
             writer,
+            options,
             written_key: false,
         }
     }
@@ -32,10 +39,10 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_key {
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         } else {
             self.written_key = true;
-            value.serialize(key::Serializer::new(self.writer))
+            value.serialize(key::Serializer::with_options(self.writer, self.options))
         }
     }
 
@@ -60,10 +67,10 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_key {
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         } else {
             self.written_key = true;
-            value.serialize(key::Serializer::new(self.writer))
+            value.serialize(key::Serializer::with_options(self.writer, self.options))
         }
     }
 
@@ -88,10 +95,10 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_key {
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         } else {
             self.written_key = true;
-            value.serialize(key::Serializer::new(self.writer))
+            value.serialize(key::Serializer::with_options(self.writer, self.options))
         }
     }
 
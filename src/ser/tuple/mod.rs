@@ -3,20 +3,47 @@ pub(super) mod key;
 pub(super) mod nested;
 pub(super) mod tag;
 
-use crate::ser::{Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{Error, Options, PathSegment, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
+    index: usize,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self {
+            writer,
+            options,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: Write,
+{
+    fn serialize_parameter<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let index = self.index;
+        self.index += 1;
+        value
+            .serialize(element::Serializer::with_options(self.writer, self.options))
+            .map_err(|error| error.within(PathSegment::Parameter(index)))
     }
 }
 
@@ -31,7 +58,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -50,7 +77,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -69,7 +96,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
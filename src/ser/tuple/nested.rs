@@ -1,18 +1,24 @@
 use super::element;
-use crate::ser::{Error, Result};
+use crate::io::Write;
+use crate::ser::{Error, Options, Result};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
     }
 }
 
@@ -27,7 +33,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        value.serialize(element::Serializer::with_options(self.writer, self.options))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -46,7 +52,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        value.serialize(element::Serializer::with_options(self.writer, self.options))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -65,7 +71,7 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        value.serialize(element::Serializer::with_options(self.writer, self.options))
     }
 
     fn end(self) -> Result<Self::Ok> {
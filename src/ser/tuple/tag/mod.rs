@@ -2,29 +2,59 @@ pub(in super::super) mod nested;
 
 mod element;
 
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{tuple, Error, Options, PathSegment, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 
-    written_first: bool,
+    index: usize,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
         Self {
             writer,
+            options,
 
-            written_first: false,
+            index: 0,
         }
     }
 }
 
+impl<'a, W> Serializer<'a, W>
+where
+    W: Write,
+{
+    /// Serializes an element, which is written as the tag's name if it is the first.
+    fn serialize_parameter<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let index = self.index;
+        self.index += 1;
+        if index == 0 {
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
+        } else {
+            value.serialize(tuple::element::Serializer::with_options(
+                self.writer,
+                self.options,
+            ))
+        }
+        .map_err(|error| error.within(PathSegment::Parameter(index)))
+    }
+}
+
 impl<'a, W> SerializeTuple for Serializer<'a, W>
 where
     W: Write,
@@ -36,16 +66,11 @@ where
     where
         T: ?Sized + Serialize,
     {
-        if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
-        } else {
-            self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
-        }
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        if !self.written_first {
+        if self.index == 0 {
             self.writer.write_tag_name_unescaped(b"")?;
         }
         self.writer.close_tag()
@@ -63,16 +88,11 @@ where
     where
         T: ?Sized + Serialize,
     {
-        if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
-        } else {
-            self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
-        }
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        if !self.written_first {
+        if self.index == 0 {
             self.writer.write_tag_name_unescaped(b"")?;
         }
         self.writer.close_tag()
@@ -90,16 +110,11 @@ where
     where
         T: ?Sized + Serialize,
     {
-        if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
-        } else {
-            self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
-        }
+        self.serialize_parameter(value)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        if !self.written_first {
+        if self.index == 0 {
             self.writer.write_tag_name_unescaped(b"")?;
         }
         self.writer.close_tag()
@@ -1,21 +1,28 @@
 use super::element;
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::io::Write;
+use crate::ser::{tuple, Error, Options, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub(in super::super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
 
     written_first: bool,
 }
 
 impl<'a, W> Serializer<'a, W> {
+    #[cfg(test)]
     pub(in super::super::super) fn new(writer: &'a mut W) -> Self {
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(in super::super::super) fn with_options(writer: &'a mut W, options: Options) -> Self {
         Self {
             writer,
+            options,
 
             written_first: false,
         }
@@ -34,10 +41,13 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
+            value.serialize(tuple::element::Serializer::with_options(
+                self.writer,
+                self.options,
+            ))
         } else {
             self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         }
     }
 
@@ -61,10 +71,13 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
+            value.serialize(tuple::element::Serializer::with_options(
+                self.writer,
+                self.options,
+            ))
         } else {
             self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         }
     }
 
@@ -88,10 +101,13 @@ where
         T: ?Sized + Serialize,
     {
         if self.written_first {
-            value.serialize(tuple::element::Serializer::new(self.writer))
+            value.serialize(tuple::element::Serializer::with_options(
+                self.writer,
+                self.options,
+            ))
         } else {
             self.written_first = true;
-            value.serialize(element::Serializer::new(self.writer))
+            value.serialize(element::Serializer::with_options(self.writer, self.options))
         }
     }
 
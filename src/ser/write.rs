@@ -1,5 +1,10 @@
-use crate::ser::{Error, Escaped, Result};
-use std::io::Write;
+use crate::io::Write;
+use crate::ser::{error, Error, Escaped, Result};
+use alloc::vec::Vec;
+
+/// The indentation preceding each parameter list written on a line of its own, such as the
+/// entries of a map.
+pub(crate) const INDENT: &[u8] = b"   ";
 
 pub(super) trait WriteExt {
     fn write_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()>;
@@ -16,8 +21,10 @@ where
     W: Write,
 {
     fn write_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()> {
-        self.write_all(b"#").or(Err(Error::Io))?;
-        self.write_all(tag_name).or(Err(Error::Io))
+        self.write_all(b"#")
+            .map_err(|_| Error::new(error::Kind::Io))?;
+        self.write_all(tag_name)
+            .map_err(|_| Error::new(error::Kind::Io))
     }
 
     fn write_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()> {
@@ -25,8 +32,10 @@ where
     }
 
     fn write_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()> {
-        self.write_all(b":").or(Err(Error::Io))?;
-        self.write_all(parameter).or(Err(Error::Io))
+        self.write_all(b":")
+            .map_err(|_| Error::new(error::Kind::Io))?;
+        self.write_all(parameter)
+            .map_err(|_| Error::new(error::Kind::Io))
     }
 
     fn write_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()> {
@@ -34,8 +43,10 @@ where
     }
 
     fn write_key_unescaped(&mut self, value: &[u8]) -> Result<()> {
-        self.write_all(b"   ").or(Err(Error::Io))?;
-        self.write_all(value).or(Err(Error::Io))
+        self.write_all(INDENT)
+            .map_err(|_| Error::new(error::Kind::Io))?;
+        self.write_all(value)
+            .map_err(|_| Error::new(error::Kind::Io))
     }
 
     fn write_key_escaped(&mut self, value: &[u8]) -> Result<()> {
@@ -43,15 +54,18 @@ where
     }
 
     fn close_tag(&mut self) -> Result<()> {
-        self.write_all(b";\n").or(Err(Error::Io))
+        self.write_all(b";\n")
+            .map_err(|_| Error::new(error::Kind::Io))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::WriteExt;
+    #[cfg(not(feature = "std"))]
+    use crate::io;
+    use crate::io::Write;
     use claims::{assert_err, assert_ok};
-    use std::{io, io::Write};
 
     #[test]
     fn write_tag_name_unescaped_regular() {
@@ -163,13 +177,21 @@ mod tests {
 
     struct FailingWriter;
 
+    #[cfg(feature = "std")]
     impl Write for FailingWriter {
-        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
-            Err(io::Error::new(io::ErrorKind::Other, "failed"))
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "failed"))
         }
 
-        fn flush(&mut self) -> io::Result<()> {
-            Err(io::Error::new(io::ErrorKind::Other, "failed"))
+        fn flush(&mut self) -> std::io::Result<()> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "failed"))
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    impl Write for FailingWriter {
+        fn write_all(&mut self, _buf: &[u8]) -> Result<(), io::Error> {
+            Err(io::Error)
         }
     }
 
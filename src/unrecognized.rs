@@ -0,0 +1,164 @@
+use crate::RawValue;
+use alloc::{vec, vec::Vec};
+use core::slice;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The name a `struct` field must be given to collect the struct's [`UnrecognizedTags`].
+pub(crate) const FIELD: &str = "$msd::unrecognized";
+
+/// Tags that did not match any field of a `struct`.
+///
+/// A `struct` field of this type collects every tag that is not one of the struct's other fields,
+/// allowing a file to be read into a typed `struct`, edited, and written back out without losing
+/// the tags the `struct` does not model. The field must be renamed to `$msd::unrecognized`.
+///
+/// Each tag is captured verbatim as a [`RawValue`], from its name up to the `;` ending its final
+/// parameter list, in the order the tags appeared in the input. Comments following that `;` are
+/// not captured, nor is a comment ending a tag that is missing its final `;`. When serialized, the tags are
+/// written in that same order at the position of the field within the `struct`, so the field is
+/// usually declared last.
+///
+/// Within a sequence of structs, a struct collects every tag up to the tag beginning the next
+/// struct in the sequence. This includes any tags following the final struct in the sequence,
+/// which are therefore collected even when they would otherwise match a field of an enclosing
+/// `struct`.
+///
+/// # Example
+/// ```
+/// use msd::UnrecognizedTags;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// #[serde(rename_all = "UPPERCASE")]
+/// struct Song {
+///     title: String,
+///     #[serde(rename = "$msd::unrecognized")]
+///     unrecognized: UnrecognizedTags,
+/// }
+///
+/// let mut song: Song =
+///     msd::from_bytes(b"#TITLE:foo;\n#ARTIST:bar;\n#BPMS:0.000=120.000;\n").unwrap();
+/// song.title = "baz".to_owned();
+///
+/// assert_eq!(
+///     msd::to_bytes(&song).unwrap(),
+///     b"#TITLE:baz;\n#ARTIST:bar;\n#BPMS:0.000=120.000;\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnrecognizedTags {
+    tags: Vec<RawValue>,
+}
+
+impl UnrecognizedTags {
+    /// Creates an empty collection of tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a tag, given as its verbatim contents without the leading `#` and trailing `;`.
+    pub fn push(&mut self, tag: RawValue) {
+        self.tags.push(tag);
+    }
+
+    /// Returns an iterator over the tags, in order.
+    pub fn iter(&self) -> slice::Iter<'_, RawValue> {
+        self.tags.iter()
+    }
+
+    /// Returns the number of tags.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Returns whether there are no tags.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+impl FromIterator<RawValue> for UnrecognizedTags {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = RawValue>,
+    {
+        Self {
+            tags: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for UnrecognizedTags {
+    type Item = RawValue;
+    type IntoIter = vec::IntoIter<RawValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UnrecognizedTags {
+    type Item = &'a RawValue;
+    type IntoIter = slice::Iter<'a, RawValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.iter()
+    }
+}
+
+impl Serialize for UnrecognizedTags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.tags.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnrecognizedTags {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(|tags| Self { tags })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnrecognizedTags;
+    use crate::RawValue;
+
+    #[test]
+    fn new() {
+        let tags = UnrecognizedTags::new();
+
+        assert!(tags.is_empty());
+        assert_eq!(tags.len(), 0);
+    }
+
+    #[test]
+    fn push() {
+        let mut tags = UnrecognizedTags::new();
+
+        tags.push(RawValue::new(&b"foo:bar"[..]));
+
+        assert_eq!(tags.len(), 1);
+        assert_eq!(
+            tags.iter().collect::<Vec<_>>(),
+            vec![&RawValue::new(&b"foo:bar"[..])]
+        );
+    }
+
+    #[test]
+    fn from_iter_preserves_order() {
+        let tags: UnrecognizedTags = vec![RawValue::new(&b"foo"[..]), RawValue::new(&b"bar"[..])]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            tags.into_iter().collect::<Vec<_>>(),
+            vec![RawValue::new(&b"foo"[..]), RawValue::new(&b"bar"[..])]
+        );
+    }
+}